@@ -4,38 +4,18 @@ extern crate rtsp;
 extern crate tokio;
 
 use bytes::BytesMut;
-use futures::{future, Future, Stream};
-use rtsp::protocol::Connection;
-use rtsp::{Request, Response, Service};
+use futures::{future, Future};
+use rtsp::{Request, Response, Server, Service};
 use std::io;
 use std::net::SocketAddr;
-use tokio::net::TcpListener;
 
 fn main() {
     let address = "0.0.0.0:10500".parse::<SocketAddr>().unwrap();
-    let listener = TcpListener::bind(&address).unwrap();
 
-    let server = listener.incoming().for_each(move |socket| {
-        let (connection, handler, mut handle) = Connection::new(socket, Some(Application));
+    // A new instance of the application service is created for every accepted connection. The
+    // server takes care of spawning the connection and request handler tasks.
 
-        // This will handle all reading and writing for the connection.
-
-        tokio::spawn(connection);
-
-        // This is a separate task that processes requests.
-
-        tokio::spawn(handler.unwrap());
-
-        // Only shutdown when the client has finished. The shutdown will be fired when both the
-        // connection and handler spawned above have finished.
-
-        tokio::spawn(handle.take_shutdown().unwrap().then(|_| {
-            std::mem::drop(handle);
-            Ok(())
-        }));
-
-        Ok(())
-    });
+    let server = Server::bind(&address).serve(|| Ok(Application)).unwrap();
 
     tokio::run(server.map_err(|_| ()));
 }
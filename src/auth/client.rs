@@ -0,0 +1,262 @@
+use bytes::BytesMut;
+use futures::future::{self, Either};
+use futures::Future;
+use std::sync::{Arc, Mutex};
+
+use auth::digest::{compute_response, generate_nonce, DigestParameters};
+use header::types::{
+    Authorization, BasicCredentials, Challenge, Credentials, DigestAlgorithm, DigestChallenge,
+    DigestCredentials, QualityOfProtection, WWWAuthenticate,
+};
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use protocol::{ConnectionHandle, OperationError, RequestOptions};
+use request::Request;
+use response::Response;
+use status::StatusCode;
+
+/// A wrapper around a [`ConnectionHandle`] that authenticates the requests sent through it.
+///
+/// When a request is answered with `401 Unauthorized`, the challenge with the strongest supported
+/// scheme is chosen and the request is sent once more with credentials computed for it. The
+/// challenge is remembered afterwards, so that subsequent requests are sent with credentials right
+/// away using an increasing nonce count.
+///
+/// Clones of the authenticator share the remembered challenge.
+#[derive(Clone)]
+pub struct ClientAuthenticator {
+    handle: ConnectionHandle,
+    state: Arc<Mutex<AuthenticationState>>,
+}
+
+impl ClientAuthenticator {
+    /// Constructs a new authenticator sending requests through the given handle on behalf of the
+    /// given user.
+    pub fn new<U, P>(handle: ConnectionHandle, username: U, password: P) -> Self
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        ClientAuthenticator {
+            handle,
+            state: Arc::new(Mutex::new(AuthenticationState {
+                challenge: None,
+                nonce_count: 0,
+                password: password.into(),
+                username: username.into(),
+            })),
+        }
+    }
+
+    pub fn handle(&self) -> &ConnectionHandle {
+        &self.handle
+    }
+
+    pub fn handle_mut(&mut self) -> &mut ConnectionHandle {
+        &mut self.handle
+    }
+
+    pub fn into_handle(self) -> ConnectionHandle {
+        self.handle
+    }
+
+    pub fn send_request<R, B>(
+        &mut self,
+        request: R,
+    ) -> impl Future<Item = Response<BytesMut>, Error = OperationError>
+    where
+        R: Into<Request<B>>,
+        B: AsRef<[u8]>,
+    {
+        self.send_authorized_request(request, None)
+    }
+
+    pub fn send_request_with_options<R, B>(
+        &mut self,
+        request: R,
+        options: RequestOptions,
+    ) -> impl Future<Item = Response<BytesMut>, Error = OperationError>
+    where
+        R: Into<Request<B>>,
+        B: AsRef<[u8]>,
+    {
+        self.send_authorized_request(request, Some(options))
+    }
+
+    /// Sends the given request, retrying it once with new credentials if it is answered with
+    /// `401 Unauthorized`. If the retried request is rejected as well, its response is returned.
+    /// The default request options of the handle are used if no options are given.
+    fn send_authorized_request<R, B>(
+        &mut self,
+        request: R,
+        options: Option<RequestOptions>,
+    ) -> impl Future<Item = Response<BytesMut>, Error = OperationError>
+    where
+        R: Into<Request<B>>,
+        B: AsRef<[u8]>,
+    {
+        let mut request = request.into().map(|body| BytesMut::from(body.as_ref()));
+        let mut handle = self.handle.clone();
+        let state = self.state.clone();
+        let sent_challenge = {
+            let mut state = state.lock().expect("locking `state` should not error");
+            state.authorize(&mut request);
+            state.challenge.clone()
+        };
+
+        send_request(&mut self.handle, request.clone(), options).and_then(move |response| {
+            if response.status_code() != StatusCode::Unauthorized {
+                return Either::A(future::ok(response));
+            }
+
+            let header_values = response
+                .headers()
+                .get_all(HeaderName::WWWAuthenticate)
+                .iter()
+                .cloned()
+                .collect::<Vec<HeaderValue>>();
+            let challenge = match WWWAuthenticate::try_from_header_raw(&header_values) {
+                Ok(challenges) => select_challenge(challenges),
+                Err(_) => None,
+            };
+            let challenge = match challenge {
+                Some(challenge) => challenge,
+                None => return Either::A(future::ok(response)),
+            };
+
+            // Retrying only makes sense if the credentials will differ from the rejected
+            // ones. Basic credentials never change, and digest credentials only change with
+            // a new nonce, so a rejection otherwise means the credentials are wrong.
+
+            let retry = match (&sent_challenge, &challenge) {
+                (None, _) => true,
+                (Some(Challenge::Digest(sent)), Challenge::Digest(received)) => {
+                    sent.nonce() != received.nonce()
+                }
+                (Some(Challenge::Basic(_)), Challenge::Digest(_)) => true,
+                _ => false,
+            };
+
+            if !retry {
+                return Either::A(future::ok(response));
+            }
+
+            {
+                let mut state = state.lock().expect("locking `state` should not error");
+                state.set_challenge(challenge);
+                state.authorize(&mut request);
+            }
+
+            Either::B(send_request(&mut handle, request, options))
+        })
+    }
+}
+
+struct AuthenticationState {
+    challenge: Option<Challenge>,
+    nonce_count: u32,
+    password: String,
+    username: String,
+}
+
+impl AuthenticationState {
+    /// Adds an `"Authorization"` header for the remembered challenge to the given request. Nothing
+    /// is added if no challenge has been received yet or if the credentials cannot be represented.
+    fn authorize(&mut self, request: &mut Request<BytesMut>) {
+        let credentials = match self.challenge {
+            Some(Challenge::Basic(_)) => {
+                BasicCredentials::new(self.username.as_str(), self.password.as_str())
+                    .map(Credentials::Basic)
+            }
+            Some(Challenge::Digest(ref challenge)) => {
+                self.nonce_count = self.nonce_count.wrapping_add(1);
+                digest_credentials(
+                    challenge,
+                    &self.username,
+                    &self.password,
+                    request,
+                    self.nonce_count,
+                )
+                .map(Credentials::Digest)
+            }
+            None => return,
+        };
+
+        if let Ok(credentials) = credentials {
+            let authorization = Authorization(credentials).to_header_raw().remove(0);
+            request
+                .headers_mut()
+                .insert(HeaderName::Authorization, authorization);
+        }
+    }
+
+    fn set_challenge(&mut self, challenge: Challenge) {
+        self.challenge = Some(challenge);
+        self.nonce_count = 0;
+    }
+}
+
+fn digest_credentials(
+    challenge: &DigestChallenge,
+    username: &str,
+    password: &str,
+    request: &Request<BytesMut>,
+    nonce_count: u32,
+) -> Result<DigestCredentials, InvalidTypedHeader> {
+    let cnonce = generate_nonce();
+    let qop = if challenge.qop().contains(&QualityOfProtection::Auth) {
+        Some((QualityOfProtection::Auth, cnonce.as_str(), nonce_count))
+    } else if challenge.qop().contains(&QualityOfProtection::AuthInt) {
+        Some((QualityOfProtection::AuthInt, cnonce.as_str(), nonce_count))
+    } else {
+        None
+    };
+    let uri = request.uri().as_str();
+    let response = compute_response(&DigestParameters {
+        algorithm: challenge.algorithm(),
+        body: request.body(),
+        method: request.method().as_str(),
+        nonce: challenge.nonce(),
+        password,
+        qop,
+        realm: challenge.realm(),
+        uri,
+        username,
+    });
+    let mut credentials = DigestCredentials::new(
+        username,
+        challenge.realm(),
+        challenge.nonce(),
+        uri,
+        &response,
+    )?;
+    *credentials.algorithm_mut() = challenge.algorithm();
+    credentials.set_opaque(challenge.opaque())?;
+    credentials.set_qop(qop)?;
+    Ok(credentials)
+}
+
+fn send_request(
+    handle: &mut ConnectionHandle,
+    request: Request<BytesMut>,
+    options: Option<RequestOptions>,
+) -> impl Future<Item = Response<BytesMut>, Error = OperationError> {
+    match options {
+        Some(options) => Either::A(handle.send_request_with_options(request, options)),
+        None => Either::B(handle.send_request(request)),
+    }
+}
+
+/// Chooses the challenge with the strongest supported scheme, preferring digest with SHA-256 over
+/// digest with MD5 over basic.
+fn select_challenge(challenges: WWWAuthenticate) -> Option<Challenge> {
+    challenges
+        .0
+        .into_iter()
+        .max_by_key(|challenge| match challenge {
+            Challenge::Basic(_) => 0,
+            Challenge::Digest(challenge) => match challenge.algorithm() {
+                DigestAlgorithm::MD5 => 1,
+                DigestAlgorithm::SHA256 => 2,
+            },
+        })
+}
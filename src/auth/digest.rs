@@ -0,0 +1,163 @@
+use md5;
+use rand::{thread_rng, Rng};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+use header::types::{DigestAlgorithm, QualityOfProtection};
+
+/// The number of random bytes used when generating server and client nonces.
+const NONCE_LENGTH: usize = 16;
+
+/// The parameters that are combined to compute the response of digest credentials.
+pub(crate) struct DigestParameters<'a> {
+    pub algorithm: DigestAlgorithm,
+    pub body: &'a [u8],
+    pub method: &'a str,
+    pub nonce: &'a str,
+    pub password: &'a str,
+    pub qop: Option<(QualityOfProtection, &'a str, u32)>,
+    pub realm: &'a str,
+    pub uri: &'a str,
+    pub username: &'a str,
+}
+
+/// Computes the response of digest credentials as described by
+/// [RFC7616](https://tools.ietf.org/html/rfc7616#section-3.4.1). If no quality of protection is
+/// given, the response is computed as described by
+/// [RFC2069](https://tools.ietf.org/html/rfc2069#section-2.1.2) for backwards compatibility.
+pub(crate) fn compute_response(parameters: &DigestParameters) -> String {
+    let algorithm = parameters.algorithm;
+    let a1 = format!(
+        "{}:{}:{}",
+        parameters.username, parameters.realm, parameters.password
+    );
+    let a2 = match parameters.qop {
+        Some((QualityOfProtection::AuthInt, _, _)) => format!(
+            "{}:{}:{}",
+            parameters.method,
+            parameters.uri,
+            hash(algorithm, parameters.body)
+        ),
+        _ => format!("{}:{}", parameters.method, parameters.uri),
+    };
+    let ha1 = hash(algorithm, a1.as_bytes());
+    let ha2 = hash(algorithm, a2.as_bytes());
+
+    let data = match parameters.qop {
+        Some((qop, cnonce, nonce_count)) => format!(
+            "{}:{:08x}:{}:{}:{}",
+            parameters.nonce, nonce_count, cnonce, qop, ha2
+        ),
+        None => format!("{}:{}", parameters.nonce, ha2),
+    };
+
+    hash(algorithm, format!("{}:{}", ha1, data).as_bytes())
+}
+
+/// Compares two strings in time dependent only on their lengths, so that comparing a secret does
+/// not reveal how much of it was guessed correctly.
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.bytes()
+        .zip(b.bytes())
+        .fold(0, |result, (a, b)| result | (a ^ b))
+        == 0
+}
+
+/// Generates a random hexadecimal nonce suitable for use as either a server or a client nonce.
+pub(crate) fn generate_nonce() -> String {
+    let mut rng = thread_rng();
+    let mut bytes = [0; NONCE_LENGTH];
+    rng.fill(&mut bytes);
+    to_hex(&bytes)
+}
+
+/// Hashes the given data with the given algorithm and returns the lowercase hexadecimal digest.
+pub(crate) fn hash(algorithm: DigestAlgorithm, data: &[u8]) -> String {
+    match algorithm {
+        DigestAlgorithm::MD5 => to_hex(&md5::compute(data).0),
+        DigestAlgorithm::SHA256 => to_hex(&Sha256::digest(data)),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+
+    for byte in bytes {
+        write!(hex, "{:02x}", byte).expect("writing to a string should not error");
+    }
+
+    hex
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parameters(algorithm: DigestAlgorithm) -> DigestParameters<'static> {
+        DigestParameters {
+            algorithm,
+            body: b"",
+            method: "GET",
+            nonce: "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v",
+            password: "Circle of Life",
+            qop: Some((
+                QualityOfProtection::Auth,
+                "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+                1,
+            )),
+            realm: "http-auth@example.org",
+            uri: "/dir/index.html",
+            username: "Mufasa",
+        }
+    }
+
+    #[test]
+    fn test_compute_response() {
+        // Test vectors from [RFC7616](https://tools.ietf.org/html/rfc7616#section-3.9.1).
+
+        assert_eq!(
+            compute_response(&parameters(DigestAlgorithm::MD5)),
+            "8ca523f5e9506fed4657c9700eebdbec"
+        );
+        assert_eq!(
+            compute_response(&parameters(DigestAlgorithm::SHA256)),
+            "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1"
+        );
+
+        // Test vector from [RFC2069](https://tools.ietf.org/html/rfc2069#section-2.4).
+
+        let parameters = DigestParameters {
+            method: "GET",
+            nonce: "dcd98b7102dd2f0e8b11d0f600bfb0c093",
+            password: "CircleOfLife",
+            qop: None,
+            realm: "testrealm@host.com",
+            uri: "/dir/index.html",
+            ..parameters(DigestAlgorithm::MD5)
+        };
+
+        assert_eq!(
+            compute_response(&parameters),
+            "1949323746fe6a43ef61f9606e7febea"
+        );
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq("", ""));
+        assert!(constant_time_eq("password", "password"));
+        assert!(!constant_time_eq("password", "passwore"));
+        assert!(!constant_time_eq("password", "pass"));
+    }
+
+    #[test]
+    fn test_generate_nonce() {
+        let nonce = generate_nonce();
+        assert_eq!(nonce.len(), NONCE_LENGTH * 2);
+        assert_ne!(nonce, generate_nonce());
+    }
+}
@@ -0,0 +1,20 @@
+//! RTSP Authentication
+//!
+//! This module contains the client and server sides of the `"Basic"` and `"Digest"` authentication
+//! schemes as described by [RFC7826](https://tools.ietf.org/html/rfc7826#section-19.1). The
+//! typed headers carrying the challenges and credentials can be found in [`header::types`].
+//!
+//! On the server side, [`AuthenticationService`] wraps another service and answers any request
+//! lacking valid credentials with a challenge. Passwords are looked up through a
+//! [`CredentialStore`]. On the client side, [`ClientAuthenticator`] wraps a `ConnectionHandle` and
+//! answers challenges by retrying the request with computed credentials.
+
+mod client;
+mod digest;
+mod server;
+
+pub use self::client::ClientAuthenticator;
+pub use self::server::{
+    AuthenticationService, AuthenticationServiceBuilder, AuthenticationServiceBuilderError,
+    CredentialStore, DEFAULT_NONCE_LIFETIME_DURATION,
+};
@@ -0,0 +1,615 @@
+use bytes::BytesMut;
+use futures::{future, Future};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use auth::digest::{compute_response, constant_time_eq, generate_nonce, DigestParameters};
+use header::types::{
+    Authorization, BasicChallenge, Challenge, Credentials, DigestAlgorithm, DigestChallenge,
+    DigestCredentials, QualityOfProtection, WWWAuthenticate,
+};
+use header::{HeaderMap, HeaderName, HeaderValue, TypedHeader};
+use protocol::Service;
+use request::Request;
+use response::Response;
+use status::StatusCode;
+
+pub const DEFAULT_NONCE_LIFETIME_DURATION: Duration = Duration::from_secs(300);
+
+/// A source of user passwords used to verify the credentials of incoming requests.
+pub trait CredentialStore {
+    /// Returns the password of the given user within the given realm, or `None` if no such user
+    /// exists.
+    fn password(&self, realm: &str, username: &str) -> Option<String>;
+}
+
+/// A credential store mapping usernames to passwords regardless of the realm.
+impl CredentialStore for HashMap<String, String> {
+    fn password(&self, _: &str, username: &str) -> Option<String> {
+        self.get(username).cloned()
+    }
+}
+
+/// A service wrapper that only forwards requests carrying valid credentials to the inner service.
+/// All other requests are answered with `401 Unauthorized` and a `"WWW-Authenticate"` header
+/// containing a challenge for each of the enabled authentication schemes.
+///
+/// Digest challenges always require the `"auth"` quality of protection, so that every use of a
+/// nonce carries a nonce count. Digest credentials without a quality of protection are rejected,
+/// since they could otherwise be replayed until their nonce expires.
+///
+/// Issued nonces are shared between clones of the service, so a single instance can be cloned for
+/// each connection.
+pub struct AuthenticationService<S, C> {
+    allow_basic: bool,
+    credential_store: Arc<C>,
+    digest_algorithms: Vec<DigestAlgorithm>,
+    nonces: Arc<Mutex<NonceStore>>,
+    realm: String,
+    service: S,
+}
+
+impl AuthenticationService<(), ()> {
+    pub fn builder() -> AuthenticationServiceBuilder {
+        AuthenticationServiceBuilder::new()
+    }
+}
+
+impl<S, C> AuthenticationService<S, C>
+where
+    C: CredentialStore,
+{
+    fn challenge(&self, stale: bool) -> Response<BytesMut> {
+        let mut nonces = self
+            .nonces
+            .lock()
+            .expect("locking `nonces` should not error");
+        let mut challenges = self
+            .digest_algorithms
+            .iter()
+            .map(|&algorithm| {
+                let mut challenge = DigestChallenge::new(self.realm.as_str(), nonces.issue())
+                    .expect("digest challenge should not be invalid");
+                *challenge.algorithm_mut() = algorithm;
+                *challenge.qop_mut() = vec![QualityOfProtection::Auth];
+                *challenge.stale_mut() = stale;
+                Challenge::Digest(challenge)
+            })
+            .collect::<WWWAuthenticate>();
+
+        if self.allow_basic {
+            challenges.push(Challenge::Basic(
+                BasicChallenge::new(self.realm.as_str())
+                    .expect("basic challenge should not be invalid"),
+            ));
+        }
+
+        Response::typed_builder()
+            .status_code(StatusCode::Unauthorized)
+            .header(challenges)
+            .build(BytesMut::new())
+            .expect("unauthorized response should not be invalid")
+            .into()
+    }
+
+    fn verify(&self, request: &Request<BytesMut>) -> Verification {
+        let header_values = request
+            .headers()
+            .get_all(HeaderName::Authorization)
+            .iter()
+            .cloned()
+            .collect::<Vec<HeaderValue>>();
+
+        match Authorization::try_from_header_raw(&header_values) {
+            Ok(Authorization(Credentials::Basic(credentials))) => {
+                if !self.allow_basic {
+                    return Verification::Rejected;
+                }
+
+                match self
+                    .credential_store
+                    .password(&self.realm, credentials.username())
+                {
+                    Some(ref password) if constant_time_eq(password, credentials.password()) => {
+                        Verification::Accepted
+                    }
+                    _ => Verification::Rejected,
+                }
+            }
+            Ok(Authorization(Credentials::Digest(credentials))) => {
+                self.verify_digest(request, &credentials)
+            }
+            Err(_) => Verification::Rejected,
+        }
+    }
+
+    fn verify_digest(
+        &self,
+        request: &Request<BytesMut>,
+        credentials: &DigestCredentials,
+    ) -> Verification {
+        if credentials.realm() != self.realm
+            || credentials.uri() != request.uri().as_str()
+            || !self.digest_algorithms.contains(&credentials.algorithm())
+        {
+            return Verification::Rejected;
+        }
+
+        let password = match self
+            .credential_store
+            .password(&self.realm, credentials.username())
+        {
+            Some(password) => password,
+            None => return Verification::Rejected,
+        };
+
+        // Every challenge specifies a quality of protection, so credentials without one are
+        // rejected. Otherwise, there would be no nonce count to detect replayed requests with.
+
+        let (qop, cnonce, nonce_count) = match credentials.qop() {
+            Some(qop) => (
+                qop,
+                credentials
+                    .cnonce()
+                    .expect("`cnonce` should be set with `qop`"),
+                credentials
+                    .nonce_count()
+                    .expect("`nc` should be set with `qop`"),
+            ),
+            None => return Verification::Rejected,
+        };
+        let response = compute_response(&DigestParameters {
+            algorithm: credentials.algorithm(),
+            body: request.body(),
+            method: request.method().as_str(),
+            nonce: credentials.nonce(),
+            password: &password,
+            qop: Some((qop, cnonce, nonce_count)),
+            realm: &self.realm,
+            uri: credentials.uri(),
+            username: credentials.username(),
+        });
+
+        if !constant_time_eq(&response, credentials.response()) {
+            return Verification::Rejected;
+        }
+
+        // The credentials are correct at this point, so the only remaining question is whether
+        // the nonce can still be used. If it cannot, the client is told that its nonce is stale so
+        // that it can retry with a new one without prompting the user.
+
+        let mut nonces = self
+            .nonces
+            .lock()
+            .expect("locking `nonces` should not error");
+
+        if nonces.use_nonce(credentials.nonce(), nonce_count) {
+            Verification::Accepted
+        } else {
+            Verification::Stale
+        }
+    }
+}
+
+impl<S, C> Clone for AuthenticationService<S, C>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        AuthenticationService {
+            allow_basic: self.allow_basic,
+            credential_store: self.credential_store.clone(),
+            digest_algorithms: self.digest_algorithms.clone(),
+            nonces: self.nonces.clone(),
+            realm: self.realm.clone(),
+            service: self.service.clone(),
+        }
+    }
+}
+
+impl<S, C> Service for AuthenticationService<S, C>
+where
+    S: Service<Request = Request<BytesMut>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Into<Response<BytesMut, HeaderMap>> + 'static,
+    C: CredentialStore,
+{
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = S::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        match self.verify(&request) {
+            Verification::Accepted => Box::new(self.service.call(request).map(Into::into)),
+            Verification::Rejected => Box::new(future::ok(self.challenge(false))),
+            Verification::Stale => Box::new(future::ok(self.challenge(true))),
+        }
+    }
+}
+
+pub struct AuthenticationServiceBuilder {
+    allow_basic: bool,
+    digest_algorithms: Vec<DigestAlgorithm>,
+    nonce_lifetime_duration: Duration,
+    realm: String,
+}
+
+impl AuthenticationServiceBuilder {
+    pub fn new() -> Self {
+        AuthenticationServiceBuilder::default()
+    }
+
+    /// Sets whether the `"Basic"` authentication scheme is accepted. Since basic credentials
+    /// contain the password in plain text, this is disabled by default.
+    pub fn allow_basic(&mut self, allow_basic: bool) -> &mut Self {
+        self.allow_basic = allow_basic;
+        self
+    }
+
+    /// Consumes the builder and wraps the given service.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the realm contains control characters or if no
+    /// authentication scheme is enabled.
+    pub fn build<S, C>(
+        &self,
+        service: S,
+        credential_store: C,
+    ) -> Result<AuthenticationService<S, C>, AuthenticationServiceBuilderError>
+    where
+        C: CredentialStore,
+    {
+        if BasicChallenge::new(self.realm.as_str()).is_err() {
+            return Err(AuthenticationServiceBuilderError::InvalidRealm);
+        }
+
+        if !self.allow_basic && self.digest_algorithms.is_empty() {
+            return Err(AuthenticationServiceBuilderError::NoAuthenticationSchemes);
+        }
+
+        if self.nonce_lifetime_duration.as_secs() == 0 {
+            return Err(AuthenticationServiceBuilderError::InvalidNonceLifetimeDuration);
+        }
+
+        Ok(AuthenticationService {
+            allow_basic: self.allow_basic,
+            credential_store: Arc::new(credential_store),
+            digest_algorithms: self.digest_algorithms.clone(),
+            nonces: Arc::new(Mutex::new(NonceStore::new(self.nonce_lifetime_duration))),
+            realm: self.realm.clone(),
+            service,
+        })
+    }
+
+    /// Sets the algorithms offered for the `"Digest"` authentication scheme in order of
+    /// preference. Passing an empty list disables the scheme.
+    pub fn digest_algorithms(&mut self, algorithms: Vec<DigestAlgorithm>) -> &mut Self {
+        self.digest_algorithms = algorithms;
+        self
+    }
+
+    /// Sets how long an issued nonce may be used before clients must retry with a new one.
+    pub fn nonce_lifetime_duration(&mut self, duration: Duration) -> &mut Self {
+        self.nonce_lifetime_duration = duration;
+        self
+    }
+
+    pub fn realm<T>(&mut self, realm: T) -> &mut Self
+    where
+        T: Into<String>,
+    {
+        self.realm = realm.into();
+        self
+    }
+}
+
+impl Default for AuthenticationServiceBuilder {
+    fn default() -> Self {
+        AuthenticationServiceBuilder {
+            allow_basic: false,
+            digest_algorithms: vec![DigestAlgorithm::SHA256, DigestAlgorithm::MD5],
+            nonce_lifetime_duration: DEFAULT_NONCE_LIFETIME_DURATION,
+            realm: "rtsp".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum AuthenticationServiceBuilderError {
+    InvalidNonceLifetimeDuration,
+    InvalidRealm,
+    NoAuthenticationSchemes,
+}
+
+impl fmt::Display for AuthenticationServiceBuilderError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for AuthenticationServiceBuilderError {
+    fn description(&self) -> &str {
+        use self::AuthenticationServiceBuilderError::*;
+
+        match self {
+            InvalidNonceLifetimeDuration => "invalid nonce lifetime duration",
+            InvalidRealm => "invalid realm",
+            NoAuthenticationSchemes => "no authentication schemes",
+        }
+    }
+}
+
+/// The outcome of verifying the credentials of a request.
+enum Verification {
+    Accepted,
+    Rejected,
+    Stale,
+}
+
+/// Keeps track of the nonces that have been issued along with the highest nonce count seen for
+/// each, so that replayed requests can be detected.
+struct NonceStore {
+    lifetime_duration: Duration,
+    nonces: HashMap<String, NonceState>,
+}
+
+struct NonceState {
+    expire_time: Instant,
+    nonce_count: u32,
+}
+
+impl NonceStore {
+    pub fn new(lifetime_duration: Duration) -> Self {
+        NonceStore {
+            lifetime_duration,
+            nonces: HashMap::new(),
+        }
+    }
+
+    /// Generates and records a new nonce. Any expired nonces are removed in the process.
+    pub fn issue(&mut self) -> String {
+        let now = Instant::now();
+        self.nonces.retain(|_, state| state.expire_time > now);
+
+        let nonce = generate_nonce();
+        self.nonces.insert(
+            nonce.clone(),
+            NonceState {
+                expire_time: now + self.lifetime_duration,
+                nonce_count: 0,
+            },
+        );
+        nonce
+    }
+
+    /// Records a use of the given nonce, returning whether the use is allowed. A nonce can only be
+    /// used if it was issued, has not expired, and the nonce count is higher than any previously
+    /// seen for the nonce.
+    pub fn use_nonce(&mut self, nonce: &str, nonce_count: u32) -> bool {
+        let now = Instant::now();
+
+        match self.nonces.get_mut(nonce) {
+            Some(ref mut state) if state.expire_time > now && nonce_count > state.nonce_count => {
+                state.nonce_count = nonce_count;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use protocol::EmptyService;
+
+    fn service() -> AuthenticationService<EmptyService, HashMap<String, String>> {
+        let mut credential_store = HashMap::new();
+        credential_store.insert("user".to_string(), "pass".to_string());
+
+        AuthenticationService::builder()
+            .allow_basic(true)
+            .build(EmptyService, credential_store)
+            .unwrap()
+    }
+
+    fn request(authorization: Option<Authorization>) -> Request<BytesMut> {
+        let mut request = Request::options("rtsp://example.com/media")
+            .build(BytesMut::new())
+            .unwrap();
+
+        if let Some(authorization) = authorization {
+            request.headers_mut().insert(
+                HeaderName::Authorization,
+                authorization.to_header_raw().remove(0),
+            );
+        }
+
+        request
+    }
+
+    fn digest_authorization(
+        challenge: &DigestChallenge,
+        password: &str,
+        nonce_count: u32,
+    ) -> Authorization {
+        let parameters = DigestParameters {
+            algorithm: challenge.algorithm(),
+            body: b"",
+            method: "OPTIONS",
+            nonce: challenge.nonce(),
+            password,
+            qop: Some((QualityOfProtection::Auth, "0a4f113b", nonce_count)),
+            realm: challenge.realm(),
+            uri: "rtsp://example.com/media",
+            username: "user",
+        };
+        let mut credentials = DigestCredentials::new(
+            "user",
+            challenge.realm(),
+            challenge.nonce(),
+            "rtsp://example.com/media",
+            &compute_response(&parameters),
+        )
+        .unwrap();
+        *credentials.algorithm_mut() = challenge.algorithm();
+        credentials
+            .set_qop(Some((QualityOfProtection::Auth, "0a4f113b", nonce_count)))
+            .unwrap();
+        Authorization(Credentials::Digest(credentials))
+    }
+
+    fn digest_challenge(response: &Response<BytesMut>) -> DigestChallenge {
+        let header_values = response
+            .headers()
+            .get_all(HeaderName::WWWAuthenticate)
+            .iter()
+            .cloned()
+            .collect::<Vec<HeaderValue>>();
+
+        match WWWAuthenticate::try_from_header_raw(&header_values).unwrap()[0] {
+            Challenge::Digest(ref challenge) => challenge.clone(),
+            _ => panic!("expected digest challenge"),
+        }
+    }
+
+    #[test]
+    fn test_challenge() {
+        let service = service();
+        let response = service.challenge(false);
+        let header_values = response
+            .headers()
+            .get_all(HeaderName::WWWAuthenticate)
+            .iter()
+            .cloned()
+            .collect::<Vec<HeaderValue>>();
+        let challenges = WWWAuthenticate::try_from_header_raw(&header_values).unwrap();
+
+        assert_eq!(response.status_code(), StatusCode::Unauthorized);
+        assert_eq!(challenges.len(), 3);
+        assert_eq!(
+            challenges[2],
+            Challenge::Basic(BasicChallenge::new("rtsp").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_verify_basic() {
+        use header::types::BasicCredentials;
+
+        let service = service();
+        let valid = BasicCredentials::new("user", "pass").unwrap();
+        let invalid = BasicCredentials::new("user", "wrong").unwrap();
+
+        match service.verify(&request(Some(Authorization(Credentials::Basic(valid))))) {
+            Verification::Accepted => (),
+            _ => panic!("expected valid credentials to be accepted"),
+        }
+
+        match service.verify(&request(Some(Authorization(Credentials::Basic(invalid))))) {
+            Verification::Rejected => (),
+            _ => panic!("expected invalid credentials to be rejected"),
+        }
+
+        match service.verify(&request(None)) {
+            Verification::Rejected => (),
+            _ => panic!("expected missing credentials to be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_verify_digest() {
+        let service = service();
+        let challenge = digest_challenge(&service.challenge(false));
+        assert_eq!(challenge.algorithm(), DigestAlgorithm::SHA256);
+
+        match service.verify(&request(Some(digest_authorization(&challenge, "wrong", 1)))) {
+            Verification::Rejected => (),
+            _ => panic!("expected invalid credentials to be rejected"),
+        }
+
+        match service.verify(&request(Some(digest_authorization(&challenge, "pass", 1)))) {
+            Verification::Accepted => (),
+            _ => panic!("expected valid credentials to be accepted"),
+        }
+
+        match service.verify(&request(Some(digest_authorization(&challenge, "pass", 1)))) {
+            Verification::Stale => (),
+            _ => panic!("expected replayed nonce count to be stale"),
+        }
+
+        match service.verify(&request(Some(digest_authorization(&challenge, "pass", 2)))) {
+            Verification::Accepted => (),
+            _ => panic!("expected increased nonce count to be accepted"),
+        }
+
+        let unknown = DigestChallenge::new("rtsp", "unknown").unwrap();
+
+        match service.verify(&request(Some(digest_authorization(&unknown, "pass", 1)))) {
+            Verification::Stale => (),
+            _ => panic!("expected unknown nonce to be stale"),
+        }
+    }
+
+    #[test]
+    fn test_verify_digest_without_qop() {
+        let mut service = service();
+        let challenge = digest_challenge(&service.challenge(false));
+        let parameters = DigestParameters {
+            algorithm: challenge.algorithm(),
+            body: b"",
+            method: "OPTIONS",
+            nonce: challenge.nonce(),
+            password: "pass",
+            qop: None,
+            realm: challenge.realm(),
+            uri: "rtsp://example.com/media",
+            username: "user",
+        };
+        let mut credentials = DigestCredentials::new(
+            "user",
+            challenge.realm(),
+            challenge.nonce(),
+            "rtsp://example.com/media",
+            &compute_response(&parameters),
+        )
+        .unwrap();
+        *credentials.algorithm_mut() = challenge.algorithm();
+        let authorization = Authorization(Credentials::Digest(credentials));
+
+        // Without a nonce count, the same credentials could be replayed until the nonce expires,
+        // so they are never accepted.
+
+        for _ in 0..2 {
+            let response = service
+                .call(request(Some(authorization.clone())))
+                .wait()
+                .unwrap();
+            assert_eq!(response.status_code(), StatusCode::Unauthorized);
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        assert_eq!(
+            AuthenticationService::builder()
+                .realm("invalid\r\nrealm")
+                .build(EmptyService, HashMap::new())
+                .err(),
+            Some(AuthenticationServiceBuilderError::InvalidRealm)
+        );
+        assert_eq!(
+            AuthenticationService::builder()
+                .digest_algorithms(vec![])
+                .build(EmptyService, HashMap::new())
+                .err(),
+            Some(AuthenticationServiceBuilderError::NoAuthenticationSchemes)
+        );
+    }
+}
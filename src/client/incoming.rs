@@ -0,0 +1,236 @@
+//! Server-Initiated Requests
+//!
+//! An RTSP 2.0 server may send requests to a client, such as a `PLAY_NOTIFY` request when the end
+//! of a stream has been reached, a `REDIRECT` request asking the client to connect elsewhere, or a
+//! `TEARDOWN` request along with a `"Terminate-Reason"` header when it terminates a session.
+//!
+//! A client handles these requests with a [`Service`]. The [`DefaultService`] is used unless
+//! another one is given, and answers them with default responses. Alternatively,
+//! [`incoming_requests`] creates a service that passes the requests to a [`Stream`] so that they
+//! can be handled alongside the rest of the application.
+//!
+//! Whichever service is used, a `TEARDOWN` request from the server ends the [`ClientSession`] it
+//! belongs to before the request is passed to the service.
+//!
+//! [`ClientSession`]: struct.ClientSession.html
+//! [`Service`]: ../protocol/service/trait.Service.html
+//! [`Stream`]: https://docs.rs/futures/0.1/futures/stream/trait.Stream.html
+
+use bytes::BytesMut;
+use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::sync::oneshot;
+use futures::{future, Future, Poll, Stream};
+use std::io;
+
+use header::types::Public;
+use header::{HeaderName, TypedHeader};
+use method::Method;
+use protocol::Service;
+use request::Request;
+use response::Response;
+use status::StatusCode;
+
+use super::session::SessionRegistry;
+
+/// The methods of requests sent by a server that are answered successfully by default.
+pub const DEFAULT_SUPPORTED_METHODS: &'static [Method] = &[
+    Method::Options,
+    Method::PlayNotify,
+    Method::Redirect,
+    Method::Teardown,
+];
+
+/// Answers requests sent by a server with default responses.
+///
+/// Requests whose method is in [`DEFAULT_SUPPORTED_METHODS`] are answered with `200 OK`, where the
+/// response to an `OPTIONS` request includes those methods in a `"Public"` header. Any other
+/// request is answered with `501 Not Implemented`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultService;
+
+impl Service for DefaultService {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        Box::new(future::ok(default_response(&request)))
+    }
+}
+
+/// Creates a service that passes requests sent by a server to the returned stream.
+///
+/// Requests that are dropped without being responded to, or that are received after the stream has
+/// been dropped, are answered as they would be by the [`DefaultService`].
+pub fn incoming_requests() -> (IncomingRequestService, IncomingRequests) {
+    let (tx_request, rx_request) = unbounded();
+    (
+        IncomingRequestService { tx_request },
+        IncomingRequests { rx_request },
+    )
+}
+
+/// The service created by [`incoming_requests`].
+pub struct IncomingRequestService {
+    tx_request: UnboundedSender<IncomingRequest>,
+}
+
+impl Service for IncomingRequestService {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let response = default_response(&request);
+        let (tx_response, rx_response) = oneshot::channel();
+        let request = IncomingRequest {
+            request,
+            tx_response,
+        };
+
+        if self.tx_request.unbounded_send(request).is_err() {
+            return Box::new(future::ok(response));
+        }
+
+        Box::new(rx_response.or_else(move |_| Ok(response)))
+    }
+}
+
+/// The stream created by [`incoming_requests`]. It ends once the connection no longer accepts
+/// requests.
+#[must_use = "streams do nothing unless polled"]
+pub struct IncomingRequests {
+    rx_request: UnboundedReceiver<IncomingRequest>,
+}
+
+impl Stream for IncomingRequests {
+    type Item = IncomingRequest;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.rx_request.poll()
+    }
+}
+
+/// A request sent by a server that is waiting for a response.
+#[derive(Debug)]
+pub struct IncomingRequest {
+    request: Request<BytesMut>,
+    tx_response: oneshot::Sender<Response<BytesMut>>,
+}
+
+impl IncomingRequest {
+    pub fn request(&self) -> &Request<BytesMut> {
+        &self.request
+    }
+
+    /// Sends the given response to the server. The `"CSeq"` header is set automatically.
+    pub fn respond(self, response: Response<BytesMut>) {
+        self.tx_response.send(response).ok();
+    }
+}
+
+/// Wraps the service answering requests sent by a server, passing each request to the sessions of
+/// the connection before it is answered.
+pub(crate) struct SessionRoutingService<S> {
+    service: S,
+    sessions: SessionRegistry,
+}
+
+impl<S> SessionRoutingService<S> {
+    pub(crate) fn new(service: S, sessions: SessionRegistry) -> Self {
+        SessionRoutingService { service, sessions }
+    }
+}
+
+impl<S> Service for SessionRoutingService<S>
+where
+    S: Service<Request = Request<BytesMut>>,
+{
+    type Request = Request<BytesMut>;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        self.sessions.handle_server_request(&request);
+        self.service.call(request)
+    }
+}
+
+/// Returns the response the [`DefaultService`] gives to the request.
+fn default_response(request: &Request<BytesMut>) -> Response<BytesMut> {
+    let mut builder = Response::builder();
+
+    if *request.method() == Method::Options {
+        let public = DEFAULT_SUPPORTED_METHODS
+            .iter()
+            .cloned()
+            .collect::<Public>();
+
+        for value in public.to_header_raw() {
+            builder.header(HeaderName::Public, value);
+        }
+    } else if !DEFAULT_SUPPORTED_METHODS.contains(request.method()) {
+        builder.status_code(StatusCode::NotImplemented);
+    }
+
+    builder
+        .build(BytesMut::new())
+        .expect("default response should not be invalid")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn request(method: Method) -> Request<BytesMut> {
+        Request::builder()
+            .method(method)
+            .uri("rtsp://example.com/")
+            .build(BytesMut::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_default_service() {
+        let mut service = DefaultService;
+
+        for method in vec![Method::PlayNotify, Method::Redirect, Method::Teardown] {
+            let response = service.call(request(method)).wait().unwrap();
+            assert_eq!(response.status_code(), StatusCode::OK);
+        }
+
+        let response = service.call(request(Method::Options)).wait().unwrap();
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert!(response.headers().contains_key(HeaderName::Public));
+
+        let response = service.call(request(Method::SetParameter)).wait().unwrap();
+        assert_eq!(response.status_code(), StatusCode::NotImplemented);
+    }
+
+    #[test]
+    fn test_incoming_requests() {
+        let (mut service, requests) = incoming_requests();
+
+        let response = service.call(request(Method::SetParameter));
+        let mut requests = requests.wait();
+        let incoming = requests.next().unwrap().unwrap();
+        assert_eq!(incoming.request().method(), &Method::SetParameter);
+        incoming.respond(Response::builder().build(BytesMut::new()).unwrap());
+        assert_eq!(response.wait().unwrap().status_code(), StatusCode::OK);
+
+        let response = service.call(request(Method::PlayNotify));
+        drop(requests.next().unwrap().unwrap());
+        assert_eq!(response.wait().unwrap().status_code(), StatusCode::OK);
+
+        drop(requests);
+        let response = service.call(request(Method::GetParameter));
+        assert_eq!(
+            response.wait().unwrap().status_code(),
+            StatusCode::NotImplemented
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Client Keep-Alive
+//!
+//! This module contains a [`KeepAlive`] task that prevents a session from timing out while it is
+//! idle. A request is sent with the session ID at a fraction of the session's timeout. The first
+//! request is an `OPTIONS` request, and if the `"Public"` header of its response lists
+//! `GET_PARAMETER`, that method is used for every following request instead. The outcome of each
+//! request is reported through the stream of [`KeepAliveEvent`]s.
+//!
+//! Requests are never sent more often than once every [`MIN_KEEP_ALIVE_PERIOD`], even if the
+//! server announces a timeout of zero seconds.
+
+use bytes::BytesMut;
+use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::sync::oneshot;
+use futures::{Future, Poll, Stream};
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tokio_executor::{DefaultExecutor, Executor, SpawnError};
+use tokio_timer::Interval;
+
+use header::types::{Public, Session as SessionHeader};
+use header::{HeaderName, TypedHeader};
+use method::Method;
+use protocol::OperationError;
+use request::Request;
+use response::Response;
+use session::SessionID;
+use status::StatusCode;
+use uri::RequestURI;
+
+use super::Client;
+
+/// The default fraction of the session timeout after which a keep-alive request is sent.
+pub const DEFAULT_KEEP_ALIVE_FRACTION: f64 = 0.5;
+
+/// The minimum duration between two keep-alive requests.
+pub const MIN_KEEP_ALIVE_PERIOD: Duration = Duration::from_millis(100);
+
+/// A task that periodically refreshes a session.
+///
+/// The task runs until this value is dropped or the connection is closed. Each keep-alive request
+/// results in an event which can be retrieved by polling this as a stream, though it is not
+/// necessary to do so.
+#[must_use = "the keep-alive task stops once this is dropped"]
+pub struct KeepAlive {
+    rx_event: UnboundedReceiver<KeepAliveEvent>,
+    #[allow(dead_code)]
+    tx_stop: oneshot::Sender<()>,
+}
+
+impl KeepAlive {
+    pub(crate) fn spawn(
+        client: Client,
+        uri: RequestURI,
+        session_id: SessionID,
+        timeout_duration: Duration,
+        fraction: f64,
+    ) -> Result<Self, SpawnError> {
+        assert!(
+            fraction > 0.0 && fraction <= 1.0,
+            "keep-alive fraction should be in the range (0, 1]"
+        );
+
+        let (tx_event, rx_event) = unbounded();
+        let (tx_stop, rx_stop) = oneshot::channel();
+        let period = period(timeout_duration, fraction);
+
+        let task = Interval::new(Instant::now() + period, period)
+            .map_err(|_| ())
+            .fold(Method::Options, move |method, _| {
+                refresh(client.clone(), &uri, &session_id, method, tx_event.clone())
+            })
+            .map(|_| ())
+            .select(rx_stop.then(|_| Ok(())))
+            .then(|_| Ok(()));
+
+        DefaultExecutor::current().spawn(Box::new(task))?;
+        Ok(KeepAlive { rx_event, tx_stop })
+    }
+}
+
+impl Stream for KeepAlive {
+    type Item = KeepAliveEvent;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.rx_event.poll()
+    }
+}
+
+/// The outcome of a single keep-alive request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KeepAliveEvent {
+    /// The session was refreshed by a request with the given method.
+    Refreshed(Method),
+
+    /// A request with the given method failed to refresh the session.
+    Failed(Method, KeepAliveError),
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeepAliveError {
+    Operation(OperationError),
+    UnsuccessfulResponse(StatusCode),
+}
+
+impl fmt::Display for KeepAliveError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for KeepAliveError {
+    fn description(&self) -> &str {
+        use self::KeepAliveError::*;
+
+        match self {
+            Operation(_) => "keep-alive request operation failed",
+            UnsuccessfulResponse(_) => "keep-alive request received an unsuccessful response",
+        }
+    }
+}
+
+/// Sends a single keep-alive request and reports its outcome, resolving to the method that should
+/// be used for the next request. The task is stopped if the connection has been closed.
+fn refresh(
+    mut client: Client,
+    uri: &RequestURI,
+    session_id: &SessionID,
+    method: Method,
+    tx_event: UnboundedSender<KeepAliveEvent>,
+) -> impl Future<Item = Method, Error = ()> {
+    let mut request = Request::builder()
+        .method(method.clone())
+        .uri(uri.as_str())
+        .build(BytesMut::new())
+        .expect("keep-alive request should not be invalid");
+    let session =
+        SessionHeader::without_timeout(session_id.as_str()).expect("session ID should be valid");
+    request
+        .headers_mut()
+        .insert(HeaderName::Session, session.to_header_raw().remove(0));
+
+    client.send_request(request).then(move |result| {
+        let error = match result {
+            Ok(response) => {
+                let status_code = response.status_code();
+
+                if status_code.is_success() {
+                    let next_method = next_method(&method, &response);
+                    tx_event
+                        .unbounded_send(KeepAliveEvent::Refreshed(method))
+                        .ok();
+                    return Ok(next_method);
+                }
+
+                KeepAliveError::UnsuccessfulResponse(status_code)
+            }
+            Err(error) => KeepAliveError::Operation(error),
+        };
+
+        tx_event
+            .unbounded_send(KeepAliveEvent::Failed(method.clone(), error))
+            .ok();
+
+        if error == KeepAliveError::Operation(OperationError::Closed) {
+            Err(())
+        } else {
+            Ok(method)
+        }
+    })
+}
+
+/// Determines which method to use for the next keep-alive request. `GET_PARAMETER` is preferred if
+/// the server has indicated that it supports it in response to an `OPTIONS` request.
+fn next_method(method: &Method, response: &Response<BytesMut>) -> Method {
+    if *method != Method::Options {
+        return method.clone();
+    }
+
+    let public = response
+        .headers()
+        .get_all(HeaderName::Public)
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    match Public::try_from_header_raw(&public) {
+        Ok(ref public) if public.contains(&Method::GetParameter) => Method::GetParameter,
+        _ => Method::Options,
+    }
+}
+
+/// Returns the duration between two keep-alive requests, which is the given fraction of the session
+/// timeout but no less than [`MIN_KEEP_ALIVE_PERIOD`].
+fn period(timeout_duration: Duration, fraction: f64) -> Duration {
+    scale(timeout_duration, fraction).max(MIN_KEEP_ALIVE_PERIOD)
+}
+
+/// Multiplies the duration by the given fraction.
+fn scale(duration: Duration, fraction: f64) -> Duration {
+    let seconds = (duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9) * fraction;
+    Duration::new(seconds.trunc() as u64, (seconds.fract() * 1e9) as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+
+    use header::HeaderValue;
+
+    fn response(public: Option<&str>) -> Response<BytesMut> {
+        let mut response = Response::builder().build(BytesMut::new()).unwrap();
+
+        if let Some(public) = public {
+            response
+                .headers_mut()
+                .insert(HeaderName::Public, HeaderValue::try_from(public).unwrap());
+        }
+
+        response
+    }
+
+    #[test]
+    fn test_next_method() {
+        let response_with_get_parameter = response(Some("OPTIONS, GET_PARAMETER, PLAY"));
+
+        assert_eq!(
+            next_method(&Method::Options, &response_with_get_parameter),
+            Method::GetParameter
+        );
+        assert_eq!(
+            next_method(&Method::Options, &response(Some("OPTIONS, PLAY"))),
+            Method::Options
+        );
+        assert_eq!(
+            next_method(&Method::Options, &response(None)),
+            Method::Options
+        );
+        assert_eq!(
+            next_method(&Method::GetParameter, &response(None)),
+            Method::GetParameter
+        );
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(scale(Duration::from_secs(60), 0.5), Duration::from_secs(30));
+        assert_eq!(
+            scale(Duration::from_secs(1), 0.25),
+            Duration::from_millis(250)
+        );
+    }
+
+    #[test]
+    fn test_period() {
+        assert_eq!(
+            period(Duration::from_secs(60), 0.5),
+            Duration::from_secs(30)
+        );
+        assert_eq!(period(Duration::from_secs(0), 0.5), MIN_KEEP_ALIVE_PERIOD);
+        assert_eq!(period(Duration::from_secs(1), 1e-10), MIN_KEEP_ALIVE_PERIOD);
+    }
+}
@@ -0,0 +1,461 @@
+mod incoming;
+mod keep_alive;
+mod redirect;
+mod session;
+
+pub use self::incoming::{
+    incoming_requests, DefaultService, IncomingRequest, IncomingRequestService, IncomingRequests,
+    DEFAULT_SUPPORTED_METHODS,
+};
+pub use self::keep_alive::{
+    KeepAlive, KeepAliveError, KeepAliveEvent, DEFAULT_KEEP_ALIVE_FRACTION, MIN_KEEP_ALIVE_PERIOD,
+};
+pub use self::redirect::RequestError;
+pub use self::session::{ClientSession, ClientSessionError, Track};
+
+use self::incoming::SessionRoutingService;
+use self::session::SessionRegistry;
+
+use bytes::BytesMut;
+use futures::future::{self, Either, Loop};
+use futures::sync::oneshot;
+use futures::Future;
+use std::convert::TryFrom;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+use std::vec;
+use tokio_executor::{DefaultExecutor, Executor, SpawnError};
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_tcp::TcpStream;
+
+use header::HeaderMap;
+use protocol::{Config, Connection, ConnectionHandle, OperationError, RequestOptions, Service};
+use request::Request;
+use response::Response;
+use session::SessionID;
+#[cfg(feature = "tls")]
+use tls::TlsConnector;
+use tunnel;
+use uri::{Host, InvalidRequestURI, RequestURI};
+
+/// The default port for the `"rtsp"` and `"rtspu"` schemes.
+pub const DEFAULT_RTSP_PORT: u16 = 554;
+
+/// The default port for the `"rtsps"` scheme.
+pub const DEFAULT_RTSPS_PORT: u16 = 322;
+
+/// A client for a single RTSP server.
+///
+/// Clones of a client share the same connection. A client is never switched to a different
+/// connection, so following a redirect to a different server results in a new client, see
+/// [`Client::send_request_with_options`].
+#[derive(Clone)]
+pub struct Client {
+    config: Config,
+    handle: ConnectionHandle,
+    sessions: SessionRegistry,
+    uri: Option<RequestURI>,
+}
+
+impl Client {
+    /// Connects to the given address. Requests sent by the server are answered by the
+    /// [`DefaultService`].
+    pub fn connect(address: SocketAddr) -> impl Future<Item = Client, Error = io::Error> {
+        Client::connect_with_service(address, DefaultService)
+    }
+
+    /// Connects to the given address. Requests sent by the server are answered by the given
+    /// service.
+    pub fn connect_with_service<S>(
+        address: SocketAddr,
+        service: S,
+    ) -> impl Future<Item = Client, Error = io::Error>
+    where
+        S: Service<Request = Request<BytesMut>> + Send + 'static,
+        S::Future: Send + 'static,
+        S::Response: Into<Response<BytesMut, HeaderMap>>,
+    {
+        TcpStream::connect(&address)
+            .map(|tcp_stream| Client::new(tcp_stream, None, Config::default(), service))
+    }
+
+    /// Connects to the host given in the URI.
+    ///
+    /// If the URI does not specify a port, the default port for its scheme is used, which is
+    /// `554` for `"rtsp"` and `322` for `"rtsps"`. A domain host is resolved on a separate thread
+    /// once the returned future is first polled, so that the executor is not blocked, and each
+    /// resolved address is tried in order until a connection succeeds. If none succeed, the error
+    /// of the last attempt is returned.
+    ///
+    /// The URI is remembered by the client, so that request URIs can be given relative to it using
+    /// [`Client::resolve_uri`]. Requests sent by the server are answered by the [`DefaultService`].
+    pub fn connect_url(uri: RequestURI) -> impl Future<Item = Client, Error = io::Error> {
+        Client::connect_url_with_service(uri, DefaultService)
+    }
+
+    /// Connects to the host given in the URI as with [`Client::connect_url`]. Requests sent by the
+    /// server are answered by the given service.
+    ///
+    /// For `"rtsps"` URIs, a TLS handshake is performed using a connector that trusts the
+    /// platform's root certificates, which requires the `tls` feature. Without it, an error is
+    /// returned. Use [`Client::connect_url_with_connector`] to trust other certificates.
+    pub fn connect_url_with_service<S>(
+        uri: RequestURI,
+        service: S,
+    ) -> impl Future<Item = Client, Error = io::Error>
+    where
+        S: Service<Request = Request<BytesMut>> + Send + 'static,
+        S::Future: Send + 'static,
+        S::Response: Into<Response<BytesMut, HeaderMap>>,
+    {
+        Client::connect_url_with_config(uri, Config::default(), service)
+    }
+
+    /// Connects to the host given in the URI as with [`Client::connect_url_with_service`], using
+    /// the given configuration for the connection. For example, the configuration determines
+    /// whether RTSP 1.0 is used.
+    ///
+    /// The configuration is also used for any new connection that is made when following a
+    /// redirect.
+    pub fn connect_url_with_config<S>(
+        uri: RequestURI,
+        config: Config,
+        service: S,
+    ) -> impl Future<Item = Client, Error = io::Error>
+    where
+        S: Service<Request = Request<BytesMut>> + Send + 'static,
+        S::Future: Send + 'static,
+        S::Response: Into<Response<BytesMut, HeaderMap>>,
+    {
+        if uri.scheme() == "rtsps" {
+            return Either::A(connect_tls(uri, config, service));
+        }
+
+        Either::B(
+            connect_tcp(&uri)
+                .map(move |tcp_stream| Client::new(tcp_stream, Some(uri), config, service)),
+        )
+    }
+
+    /// Connects to the host given in the URI as with [`Client::connect_url`], performing a TLS
+    /// handshake using the given connector regardless of the URI's scheme. The server's
+    /// certificate must be valid for the host of the URI.
+    ///
+    /// If a redirect is followed to a different server, the new connection does not use this
+    /// connector.
+    #[cfg(feature = "tls")]
+    pub fn connect_url_with_connector<S>(
+        uri: RequestURI,
+        connector: TlsConnector,
+        service: S,
+    ) -> impl Future<Item = Client, Error = io::Error>
+    where
+        S: Service<Request = Request<BytesMut>> + Send + 'static,
+        S::Future: Send + 'static,
+        S::Response: Into<Response<BytesMut, HeaderMap>>,
+    {
+        connect_tls_with_connector(uri, connector, Config::default(), service)
+    }
+
+    /// Connects to the server through an RTSP over HTTP tunnel to the HTTP server at the given
+    /// address, using the path of the URI as the target of the HTTP requests. The requests are
+    /// addressed to the host of the URI on the port of the address. See the [`tunnel`] module for
+    /// details. Requests sent by the server are answered by the [`DefaultService`].
+    ///
+    /// The URI is remembered by the client as with [`Client::connect_url`].
+    ///
+    /// [`tunnel`]: ../tunnel/index.html
+    pub fn connect_tunnel(
+        address: SocketAddr,
+        uri: RequestURI,
+    ) -> impl Future<Item = Client, Error = io::Error> {
+        let host = match address.port() {
+            80 => uri.host().to_string(),
+            port => format!("{}:{}", uri.host(), port),
+        };
+
+        tunnel::connect(&address, &host, uri.path())
+            .map(move |tunnel| Client::new(tunnel, Some(uri), Config::default(), DefaultService))
+    }
+
+    /// Starts a task that keeps the session with the given ID alive by sending a request for the
+    /// given URI once every `fraction` of the session timeout. See [`KeepAlive`] for details.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the task could not be spawned on the default executor, such as
+    /// when this is called outside of a runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fraction` is not in the range `(0, 1]`.
+    pub fn keep_alive(
+        &self,
+        uri: RequestURI,
+        session_id: SessionID,
+        timeout_duration: Duration,
+        fraction: f64,
+    ) -> Result<KeepAlive, SpawnError> {
+        KeepAlive::spawn(self.clone(), uri, session_id, timeout_duration, fraction)
+    }
+
+    fn new<T, S>(transport: T, uri: Option<RequestURI>, config: Config, service: S) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+        S: Service<Request = Request<BytesMut>> + Send + 'static,
+        S::Future: Send + 'static,
+        S::Response: Into<Response<BytesMut, HeaderMap>>,
+    {
+        let mut executor = DefaultExecutor::current();
+        let sessions = SessionRegistry::default();
+        let service = SessionRoutingService::new(service, sessions.clone());
+        let (connection, handler, handle) =
+            Connection::with_config(transport, Some(service), config.clone());
+
+        executor.spawn(Box::new(connection)).unwrap();
+
+        if let Some(handler) = handler {
+            executor.spawn(Box::new(handler)).unwrap();
+        }
+
+        Client {
+            config,
+            handle,
+            sessions,
+            uri,
+        }
+    }
+
+    /// The sessions that have been set up over the connection.
+    fn sessions(&self) -> SessionRegistry {
+        self.sessions.clone()
+    }
+
+    /// Resolves a URI reference against the URI the client was connected with, returning an
+    /// absolute URI that can be used as a request URI.
+    ///
+    /// If the client was not connected using [`Client::connect_url`], only absolute URIs can be
+    /// resolved.
+    pub fn resolve_uri(&self, reference: &str) -> Result<RequestURI, InvalidRequestURI> {
+        match self.uri {
+            Some(ref uri) => uri.join(reference),
+            None => RequestURI::try_from(reference),
+        }
+    }
+
+    pub fn send_request<R, B>(
+        &mut self,
+        request: R,
+    ) -> impl Future<Item = Response<BytesMut>, Error = OperationError>
+    where
+        R: Into<Request<B>>,
+        B: AsRef<[u8]>,
+    {
+        self.handle.send_request(request)
+    }
+
+    /// Sends a request using the given options.
+    ///
+    /// If the options specify a [`RedirectPolicy`] other than `RedirectPolicy::None`, redirection
+    /// responses are followed. See [`RequestError`] for the ways in which following redirects can
+    /// fail.
+    ///
+    /// # Return Value
+    ///
+    /// The final response is returned along with the client it was received by. This is a clone of
+    /// this client unless a redirect required connecting to a different server, in which case it is
+    /// a new client for that server. Either way, this client and its clones remain connected to the
+    /// server they were connected to, so sessions set up through them are unaffected.
+    pub fn send_request_with_options<R, B>(
+        &mut self,
+        request: R,
+        options: RequestOptions,
+    ) -> impl Future<Item = (Response<BytesMut>, Client), Error = RequestError>
+    where
+        R: Into<Request<B>>,
+        B: AsRef<[u8]>,
+    {
+        let request = request.into().map(|body| BytesMut::from(body.as_ref()));
+        redirect::send_request(self.clone(), request, options)
+    }
+
+    /// The URI the client was connected with, if it was connected using [`Client::connect_url`].
+    pub fn uri(&self) -> Option<&RequestURI> {
+        self.uri.as_ref()
+    }
+}
+
+/// Returns the port that is used for the given scheme when a URI does not specify one.
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "rtsp" | "rtspu" => Some(DEFAULT_RTSP_PORT),
+        "rtsps" => Some(DEFAULT_RTSPS_PORT),
+        _ => None,
+    }
+}
+
+/// Connects to the host given in the URI, trying each resolved address in order until a connection
+/// succeeds.
+fn connect_tcp(uri: &RequestURI) -> impl Future<Item = TcpStream, Error = io::Error> {
+    resolve(uri).and_then(|addresses| {
+        let initial_error = io::Error::new(
+            io::ErrorKind::NotFound,
+            "request URI host did not resolve to any addresses",
+        );
+
+        future::loop_fn(
+            (addresses.into_iter(), initial_error),
+            |(mut addresses, error): (vec::IntoIter<SocketAddr>, io::Error)| {
+                let address = match addresses.next() {
+                    Some(address) => address,
+                    None => return Either::A(future::err(error)),
+                };
+
+                Either::B(
+                    TcpStream::connect(&address).then(move |result| match result {
+                        Ok(tcp_stream) => Ok(Loop::Break(tcp_stream)),
+                        Err(error) => Ok(Loop::Continue((addresses, error))),
+                    }),
+                )
+            },
+        )
+    })
+}
+
+/// Connects to the host given in the URI using a connector that trusts the platform's root
+/// certificates.
+#[cfg(feature = "tls")]
+fn connect_tls<S>(
+    uri: RequestURI,
+    config: Config,
+    service: S,
+) -> impl Future<Item = Client, Error = io::Error>
+where
+    S: Service<Request = Request<BytesMut>> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Into<Response<BytesMut, HeaderMap>>,
+{
+    match TlsConnector::new() {
+        Ok(connector) => Either::A(connect_tls_with_connector(uri, connector, config, service)),
+        Err(error) => Either::B(future::err(error)),
+    }
+}
+
+/// Connects to the host given in the URI, performing a TLS handshake using the given connector.
+#[cfg(feature = "tls")]
+fn connect_tls_with_connector<S>(
+    uri: RequestURI,
+    connector: TlsConnector,
+    config: Config,
+    service: S,
+) -> impl Future<Item = Client, Error = io::Error>
+where
+    S: Service<Request = Request<BytesMut>> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Into<Response<BytesMut, HeaderMap>>,
+{
+    let domain = uri.host_str().to_string();
+
+    connect_tcp(&uri)
+        .and_then(move |tcp_stream| connector.connect(&domain, tcp_stream))
+        .map(move |tls_stream| Client::new(tls_stream, Some(uri), config, service))
+}
+
+#[cfg(not(feature = "tls"))]
+fn connect_tls<S>(_: RequestURI, _: Config, _: S) -> impl Future<Item = Client, Error = io::Error> {
+    future::err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "connecting to an `rtsps` URI requires the `tls` feature",
+    ))
+}
+
+/// Resolves the host of the URI to the socket addresses that should be tried, in order.
+///
+/// Resolving a domain blocks on the system resolver, so it is done on a separate thread that is
+/// spawned once the returned future is first polled. IP addresses are returned without spawning a
+/// thread.
+fn resolve(uri: &RequestURI) -> impl Future<Item = Vec<SocketAddr>, Error = io::Error> {
+    let host_and_port = match uri.with_default_port(|url| default_port(url.scheme()).ok_or(())) {
+        Ok(host_and_port) => host_and_port,
+        Err(error) => return Either::A(future::err(error)),
+    };
+
+    if let Host::Domain(_) = host_and_port.host {
+        let host_and_port = host_and_port.to_owned();
+
+        return Either::B(future::lazy(move || {
+            let (tx_addresses, rx_addresses) = oneshot::channel();
+            let result = thread::Builder::new()
+                .name("rtsp-resolver".to_string())
+                .spawn(move || {
+                    let addresses = host_and_port
+                        .to_socket_addrs()
+                        .map(|addresses| addresses.collect());
+                    let _ = tx_addresses.send(addresses);
+                });
+
+            if let Err(error) = result {
+                return Either::A(future::err(error));
+            }
+
+            Either::B(rx_addresses.then(|result| match result {
+                Ok(result) => result,
+                Err(_) => Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "resolver thread ended without a result",
+                )),
+            }))
+        }));
+    }
+
+    Either::A(future::result(
+        host_and_port
+            .to_socket_addrs()
+            .map(|addresses| addresses.collect()),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        fn check_bounds<T: Send + Send>() {}
+
+        check_bounds::<Client>();
+    }
+
+    #[test]
+    fn test_resolve_default_port() {
+        let uri = RequestURI::try_from("rtsp://127.0.0.1/movie").unwrap();
+        assert_eq!(
+            resolve(&uri).wait().unwrap(),
+            vec!["127.0.0.1:554".parse::<SocketAddr>().unwrap()]
+        );
+
+        let uri = RequestURI::try_from("rtsps://127.0.0.1/movie").unwrap();
+        assert_eq!(
+            resolve(&uri).wait().unwrap(),
+            vec!["127.0.0.1:322".parse::<SocketAddr>().unwrap()]
+        );
+
+        let uri = RequestURI::try_from("rtsp://127.0.0.1:8554/movie").unwrap();
+        assert_eq!(
+            resolve(&uri).wait().unwrap(),
+            vec!["127.0.0.1:8554".parse::<SocketAddr>().unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_resolve_domain() {
+        let uri = RequestURI::try_from("rtsp://localhost/movie").unwrap();
+        let addresses = resolve(&uri).wait().unwrap();
+
+        assert!(!addresses.is_empty());
+        assert!(addresses.iter().all(|address| address.port() == 554));
+        assert!(addresses.iter().all(|address| address.ip().is_loopback()));
+    }
+}
@@ -0,0 +1,336 @@
+//! Client Redirects
+//!
+//! This module contains the logic for following redirection responses when sending a request with
+//! a [`RedirectPolicy`] other than `RedirectPolicy::None`. The responses that are followed are
+//! `301 Moved Permanently`, `302 Found` and `303 See Other`, for which the request is resent to the
+//! URI given in the `"Location"` header, and `305 Use Proxy`, for which the request is resent
+//! unchanged through the proxy given in the `"Location"` header. The method and body of the request
+//! are kept as they are.
+//!
+//! If the new target has a different authority than the current connection, a new client is
+//! connected to it and the `"Session"` header is removed from the request, since session IDs are
+//! only meaningful to the server that created them. The original client is left untouched, and the
+//! new client is returned along with the final response. The `"CSeq"` header is always rewritten
+//! by the connection the request is sent on. Requests sent by the server on a new connection are
+//! answered by the [`DefaultService`].
+
+use bytes::BytesMut;
+use futures::future::{self, Either, Loop};
+use futures::Future;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use header::HeaderName;
+use protocol::{OperationError, RedirectPolicy, RequestOptions};
+use request::Request;
+use response::Response;
+use status::StatusCode;
+use uri::{RequestURI, RequestURIField};
+
+use super::{default_port, Client, DefaultService};
+
+/// The state of a request whose redirects are being followed.
+struct Redirecting {
+    client: Client,
+    redirects: usize,
+    request: Request<BytesMut>,
+    visited: Vec<String>,
+}
+
+/// Where a request should be resent after a redirection response.
+#[derive(Debug, Eq, PartialEq)]
+struct Redirect {
+    /// The URI to connect to if the request cannot be sent on the current connection.
+    connect_uri: Option<RequestURI>,
+
+    /// The URI the request should be sent to, if it changed.
+    request_uri: Option<RequestURI>,
+}
+
+pub(super) fn send_request(
+    client: Client,
+    request: Request<BytesMut>,
+    options: RequestOptions,
+) -> impl Future<Item = (Response<BytesMut>, Client), Error = RequestError> {
+    let visited = match *request.uri() {
+        RequestURIField::URI(ref uri) => vec![uri.as_str().to_string()],
+        RequestURIField::Any => Vec::new(),
+    };
+    let state = Redirecting {
+        client,
+        redirects: 0,
+        request,
+        visited,
+    };
+
+    future::loop_fn(state, move |mut state| {
+        let request = state.request.clone();
+        let future = state
+            .client
+            .handle
+            .send_request_with_options(request, options);
+
+        future
+            .map_err(RequestError::Operation)
+            .and_then(move |response| follow(state, response, options.redirect_policy()))
+    })
+}
+
+/// Determines whether the response should be followed, connecting to a new server if necessary.
+fn follow(
+    mut state: Redirecting,
+    response: Response<BytesMut>,
+    policy: RedirectPolicy,
+) -> impl Future<Item = Loop<(Response<BytesMut>, Client), Redirecting>, Error = RequestError> {
+    let max_redirects = match policy {
+        RedirectPolicy::None => {
+            return Either::A(future::ok(Loop::Break((response, state.client))))
+        }
+        RedirectPolicy::Limited(max_redirects) => max_redirects,
+    };
+
+    let redirect = match redirect(state.client.uri(), &state.request, &response) {
+        Ok(Some(redirect)) => redirect,
+        Ok(None) => return Either::A(future::ok(Loop::Break((response, state.client)))),
+        Err(error) => return Either::A(future::err(error)),
+    };
+
+    if state.redirects >= max_redirects {
+        return Either::A(future::err(RequestError::TooManyRedirects));
+    }
+
+    let target = redirect
+        .request_uri
+        .as_ref()
+        .or(redirect.connect_uri.as_ref())
+        .expect("redirect should have a target")
+        .as_str()
+        .to_string();
+
+    if state.visited.contains(&target) {
+        return Either::A(future::err(RequestError::RedirectLoop));
+    }
+
+    state.redirects += 1;
+    state.visited.push(target);
+
+    if let Some(uri) = redirect.request_uri {
+        *state.request.uri_mut() = RequestURIField::URI(uri);
+    }
+
+    match redirect.connect_uri {
+        Some(uri) => {
+            state.request.headers_mut().remove(HeaderName::Session);
+
+            Either::B(
+                Client::connect_url_with_config(uri, state.client.config.clone(), DefaultService)
+                    .map_err(|error| RequestError::Connect(error.kind()))
+                    .map(move |client| {
+                        state.client = client;
+                        Loop::Continue(state)
+                    }),
+            )
+        }
+        None => Either::A(future::ok(Loop::Continue(state))),
+    }
+}
+
+/// Returns where the request should be resent if the response is a redirect that can be followed.
+fn redirect(
+    client_uri: Option<&RequestURI>,
+    request: &Request<BytesMut>,
+    response: &Response<BytesMut>,
+) -> Result<Option<Redirect>, RequestError> {
+    let is_proxy = match response.status_code() {
+        StatusCode::MovedPermanently | StatusCode::Found | StatusCode::SeeOther => false,
+        StatusCode::UseProxy => true,
+        _ => return Ok(None),
+    };
+
+    let location = match response.headers().get(HeaderName::Location) {
+        Some(location) => location.as_str(),
+        None => return Ok(None),
+    };
+
+    let request_uri = match *request.uri() {
+        RequestURIField::URI(ref uri) => Some(uri),
+        RequestURIField::Any => None,
+    };
+    let location = match request_uri.or(client_uri) {
+        Some(base) => base.join(location),
+        None => RequestURI::try_from(location),
+    }
+    .map_err(|_| RequestError::InvalidLocation)?;
+
+    // The connection is reused if the location has the same authority as the URI the client
+    // connected with or, if the client was connected using an address, as the original request.
+
+    let connect_uri = match client_uri.or(request_uri) {
+        Some(uri) if !is_proxy && authority(uri) == authority(&location) => None,
+        _ => Some(location.clone()),
+    };
+    let request_uri = if is_proxy { None } else { Some(location) };
+
+    Ok(Some(Redirect {
+        connect_uri,
+        request_uri,
+    }))
+}
+
+/// Returns the scheme, host and port of the URI, using the default port of the scheme if none is
+/// given.
+fn authority(uri: &RequestURI) -> (&str, &str, Option<u16>) {
+    let port = uri.port().or_else(|| default_port(uri.scheme()));
+    (uri.scheme(), uri.host_str(), port)
+}
+
+/// A possible error value when sending a request with [`Client::send_request_with_options`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RequestError {
+    /// Connecting to the server a request was redirected to failed.
+    Connect(io::ErrorKind),
+
+    /// A redirection response had a `"Location"` header that was not a valid URI.
+    InvalidLocation,
+
+    /// Sending the request, or a redirected request, failed.
+    Operation(OperationError),
+
+    /// A redirect led back to a URI that had already been visited by the request.
+    RedirectLoop,
+
+    /// More redirects were received than the [`RedirectPolicy`] allows.
+    TooManyRedirects,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for RequestError {
+    fn description(&self) -> &str {
+        use self::RequestError::*;
+
+        match self {
+            Connect(_) => "failed to connect to redirected server",
+            InvalidLocation => "invalid redirect location",
+            Operation(_) => "request operation failed",
+            RedirectLoop => "redirect loop",
+            TooManyRedirects => "too many redirects",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use header::HeaderValue;
+
+    fn request(uri: &str) -> Request<BytesMut> {
+        Request::builder()
+            .method(::method::Method::Describe)
+            .uri(uri)
+            .build(BytesMut::new())
+            .unwrap()
+    }
+
+    fn response(status_code: StatusCode, location: &str) -> Response<BytesMut> {
+        let mut response = Response::builder()
+            .status_code(status_code)
+            .build(BytesMut::new())
+            .unwrap();
+        response.headers_mut().insert(
+            HeaderName::Location,
+            HeaderValue::try_from(location).unwrap(),
+        );
+        response
+    }
+
+    fn uri(uri: &str) -> RequestURI {
+        RequestURI::try_from(uri).unwrap()
+    }
+
+    #[test]
+    fn test_redirect_same_authority() {
+        let result = redirect(
+            None,
+            &request("rtsp://example.com/movie"),
+            &response(StatusCode::Found, "/other"),
+        );
+
+        assert_eq!(
+            result,
+            Ok(Some(Redirect {
+                connect_uri: None,
+                request_uri: Some(uri("rtsp://example.com/other")),
+            }))
+        );
+
+        let result = redirect(
+            None,
+            &request("rtsp://example.com/movie"),
+            &response(StatusCode::MovedPermanently, "rtsp://example.com:554/other"),
+        );
+
+        assert_eq!(
+            result,
+            Ok(Some(Redirect {
+                connect_uri: None,
+                request_uri: Some(uri("rtsp://example.com:554/other")),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_redirect_different_authority() {
+        let client_uri = uri("rtsp://example.com/");
+        let result = redirect(
+            Some(&client_uri),
+            &request("*"),
+            &response(StatusCode::SeeOther, "rtsp://example.org/movie"),
+        );
+
+        assert_eq!(
+            result,
+            Ok(Some(Redirect {
+                connect_uri: Some(uri("rtsp://example.org/movie")),
+                request_uri: Some(uri("rtsp://example.org/movie")),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_redirect_use_proxy() {
+        let result = redirect(
+            None,
+            &request("rtsp://example.com/movie"),
+            &response(StatusCode::UseProxy, "rtsp://proxy.example.com/"),
+        );
+
+        assert_eq!(
+            result,
+            Ok(Some(Redirect {
+                connect_uri: Some(uri("rtsp://proxy.example.com/")),
+                request_uri: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_redirect_not_followed() {
+        let result = redirect(
+            None,
+            &request("rtsp://example.com/movie"),
+            &response(StatusCode::NotModified, "/other"),
+        );
+        assert_eq!(result, Ok(None));
+
+        let result = redirect(None, &request("*"), &response(StatusCode::Found, "/other"));
+        assert_eq!(result, Err(RequestError::InvalidLocation));
+    }
+}
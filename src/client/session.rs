@@ -0,0 +1,832 @@
+//! Client Session
+//!
+//! This module contains a [`ClientSession`] that drives the usual sequence of requests needed to
+//! play a presentation: a `DESCRIBE` request to retrieve its session description, a `SETUP` request
+//! for each of its media streams, `PLAY` and `PAUSE` requests to control playback, and finally a
+//! `TEARDOWN` request. The session ID and the control URIs are tracked automatically, and the
+//! [`StateMachine`] is used to reject requests that are not valid in the current state before they
+//! are sent.
+//!
+//! The `SETUP` and `PLAY` requests can also be pipelined using [`ClientSession::setup_and_play`],
+//! in which case they are all sent at once with a `"Pipelined-Requests"` header rather than waiting
+//! for the session ID to be known.
+//!
+//! A session can also be used to publish a presentation to a server. In that case the session is
+//! created with an `ANNOUNCE` request carrying the session description, its media streams are set
+//! up with the `"mode=record"` transport parameter, and a `RECORD` request starts the recording.
+//!
+//! Sessions that have been set up are tracked by their client, so that a `TEARDOWN` request sent
+//! by the server ends the session it belongs to.
+
+use bytes::BytesMut;
+use futures::future::{self, Either, Loop};
+use futures::Future;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+use tokio_executor::SpawnError;
+
+use header::types::{PipelinedRequests, Range, Session as SessionHeader, Transport, TransportSpec};
+use header::{HeaderName, HeaderValue, TypedHeader};
+use method::Method;
+use protocol::OperationError;
+use request::Request;
+use response::Response;
+use sdp::{self, MediaDescription, ParseError, SessionDescription};
+use session::{SessionID, DEFAULT_SESSION_TIMEOUT};
+use state::{SessionState, StateMachine};
+use status::StatusCode;
+use uri::RequestURI;
+
+use super::{Client, KeepAlive};
+
+/// A media stream of a presentation along with the control URI used to set it up.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Track {
+    control_uri: RequestURI,
+    media_description: MediaDescription,
+    transport: Option<TransportSpec>,
+}
+
+impl Track {
+    pub fn control_uri(&self) -> &RequestURI {
+        &self.control_uri
+    }
+
+    pub fn media_description(&self) -> &MediaDescription {
+        &self.media_description
+    }
+
+    /// Returns the transport selected by the server, if the track has been set up.
+    pub fn transport(&self) -> Option<&TransportSpec> {
+        self.transport.as_ref()
+    }
+}
+
+/// The part of a session that changes as requests are made.
+struct Inner {
+    id: Option<SessionID>,
+    state_machine: StateMachine,
+    timeout_duration: Option<Duration>,
+    tracks: Vec<Track>,
+}
+
+/// A session with a server for a single presentation.
+///
+/// A session is created using [`ClientSession::describe`], after which its tracks can be set up
+/// and played, or using [`ClientSession::announce`], after which its tracks can be set up and
+/// recorded. If the session has been set up, a `TEARDOWN` request is sent once it is dropped
+/// unless [`ClientSession::teardown`] was used.
+pub struct ClientSession {
+    aggregate_uri: RequestURI,
+    client: Client,
+    description: SessionDescription,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl ClientSession {
+    /// Sends an `ANNOUNCE` request with the given session description for the given presentation
+    /// and constructs a session for publishing it to the server.
+    ///
+    /// The control URIs of the presentation and its tracks are resolved against the given URI. A
+    /// track without an `a=control` attribute is only allowed if it is the only track of the
+    /// presentation, in which case the aggregate control URI is used.
+    pub fn announce(
+        mut client: Client,
+        uri: RequestURI,
+        description: SessionDescription,
+    ) -> impl Future<Item = ClientSession, Error = ClientSessionError> {
+        let (aggregate_uri, tracks) = match resolve_tracks(&description, &uri) {
+            Ok(result) => result,
+            Err(error) => return Either::A(future::err(error)),
+        };
+
+        let request = Request::builder()
+            .method(Method::Announce)
+            .uri(uri.as_str())
+            .header(HeaderName::ContentType, "application/sdp")
+            .build(BytesMut::from(description.to_string()))
+            .expect("announce request should not be invalid");
+
+        Either::B(
+            client
+                .send_request(request)
+                .map_err(ClientSessionError::Operation)
+                .and_then(move |response| {
+                    if !response.status_code().is_success() {
+                        return Err(ClientSessionError::UnsuccessfulResponse(
+                            response.status_code(),
+                        ));
+                    }
+
+                    Ok(ClientSession::new(
+                        client,
+                        aggregate_uri,
+                        description,
+                        tracks,
+                    ))
+                }),
+        )
+    }
+
+    /// Sends a `DESCRIBE` request for the given presentation and constructs a session from the
+    /// session description in the response.
+    ///
+    /// The control URIs of the presentation and its tracks are resolved as described by
+    /// [`sdp::base_uri`]. A track without an `a=control` attribute is only allowed if it is the
+    /// only track of the presentation, in which case the aggregate control URI is used.
+    pub fn describe(
+        mut client: Client,
+        uri: RequestURI,
+    ) -> impl Future<Item = ClientSession, Error = ClientSessionError> {
+        let request = Request::builder()
+            .method(Method::Describe)
+            .uri(uri.as_str())
+            .header(HeaderName::Accept, "application/sdp")
+            .build(BytesMut::new())
+            .expect("describe request should not be invalid");
+
+        client
+            .send_request(request)
+            .map_err(ClientSessionError::Operation)
+            .and_then(move |response| {
+                if !response.status_code().is_success() {
+                    return Err(ClientSessionError::UnsuccessfulResponse(
+                        response.status_code(),
+                    ));
+                }
+
+                let description = SessionDescription::try_from(&response.body()[..])
+                    .map_err(ClientSessionError::InvalidDescription)?;
+                let base_uri = sdp::base_uri(&uri, response.headers())
+                    .map_err(|_| ClientSessionError::InvalidControlURI)?;
+                let (aggregate_uri, tracks) = resolve_tracks(&description, &base_uri)?;
+
+                Ok(ClientSession::new(
+                    client,
+                    aggregate_uri,
+                    description,
+                    tracks,
+                ))
+            })
+    }
+
+    fn new(
+        client: Client,
+        aggregate_uri: RequestURI,
+        description: SessionDescription,
+        tracks: Vec<Track>,
+    ) -> Self {
+        ClientSession {
+            aggregate_uri,
+            client,
+            description,
+            inner: Arc::new(Mutex::new(Inner {
+                id: None,
+                state_machine: StateMachine::new(),
+                timeout_duration: None,
+                tracks,
+            })),
+        }
+    }
+
+    /// Returns the URI used for requests that apply to the presentation as a whole.
+    pub fn aggregate_uri(&self) -> &RequestURI {
+        &self.aggregate_uri
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn description(&self) -> &SessionDescription {
+        &self.description
+    }
+
+    /// Returns the ID of the session, if it has been set up.
+    pub fn id(&self) -> Option<SessionID> {
+        self.lock().id.clone()
+    }
+
+    /// Starts a task that keeps the session alive, sending a request for the aggregate URI once
+    /// every `fraction` of the session timeout. Returns `None` if the session has not been set up.
+    /// See [`KeepAlive`] for details.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the task could not be spawned, see [`Client::keep_alive`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fraction` is not in the range `(0, 1]`.
+    pub fn keep_alive(&self, fraction: f64) -> Option<Result<KeepAlive, SpawnError>> {
+        let (id, timeout_duration) = {
+            let inner = self.lock();
+            (inner.id.clone()?, inner.timeout_duration?)
+        };
+
+        Some(
+            self.client
+                .keep_alive(self.aggregate_uri.clone(), id, timeout_duration, fraction),
+        )
+    }
+
+    /// Sends a `PAUSE` request for the presentation.
+    pub fn pause(&mut self) -> impl Future<Item = Response<BytesMut>, Error = ClientSessionError> {
+        let uri = self.aggregate_uri.clone();
+        send_request(
+            &mut self.client,
+            &self.inner,
+            Method::Pause,
+            &uri,
+            Vec::new(),
+        )
+    }
+
+    /// Sends a `PLAY` request for the presentation with the given optional `"Range"` and
+    /// `"Scale"` headers.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the scale is not finite, or if the server did not respond
+    /// successfully. The response is returned so that headers such as `"RTP-Info"` can be
+    /// inspected.
+    pub fn play(
+        &mut self,
+        range: Option<Range>,
+        scale: Option<f64>,
+    ) -> impl Future<Item = Response<BytesMut>, Error = ClientSessionError> {
+        let headers = match play_headers(range, scale) {
+            Ok(headers) => headers,
+            Err(error) => return Either::A(future::err(error)),
+        };
+
+        let uri = self.aggregate_uri.clone();
+        Either::B(send_request(
+            &mut self.client,
+            &self.inner,
+            Method::Play,
+            &uri,
+            headers,
+        ))
+    }
+
+    /// Sends a `RECORD` request for the presentation with the given optional `"Range"` header.
+    ///
+    /// The tracks should have been set up with transports that use the `"mode=record"` parameter.
+    pub fn record(
+        &mut self,
+        range: Option<Range>,
+    ) -> impl Future<Item = Response<BytesMut>, Error = ClientSessionError> {
+        let headers = range
+            .map(|range| {
+                range
+                    .to_header_raw()
+                    .into_iter()
+                    .map(|value| (HeaderName::Range, value))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let uri = self.aggregate_uri.clone();
+        send_request(&mut self.client, &self.inner, Method::Record, &uri, headers)
+    }
+
+    /// Sends a `SETUP` request for every track that has not been set up yet, one after the other.
+    /// The given function is called with the media description of each track to determine the
+    /// transports that are offered to the server, and the transport selected by the server is
+    /// stored with the track.
+    ///
+    /// The session ID is taken from the response to the first request and included in all of the
+    /// following requests.
+    pub fn setup<F>(&mut self, transport: F) -> impl Future<Item = (), Error = ClientSessionError>
+    where
+        F: FnMut(&MediaDescription) -> Transport + Send + 'static,
+    {
+        let client = self.client.clone();
+        let inner = self.inner.clone();
+        let sessions = self.client.sessions();
+
+        future::loop_fn(
+            (client, inner, transport, 0),
+            move |(mut client, inner, mut transport, index)| {
+                let track = {
+                    let inner = lock(&inner);
+
+                    match inner.tracks.get(index) {
+                        Some(track) if track.transport.is_some() => None,
+                        Some(track) => Some(track.clone()),
+                        None => return Either::A(future::ok(Loop::Break(()))),
+                    }
+                };
+
+                let track = match track {
+                    Some(track) => track,
+                    None => {
+                        return Either::A(future::ok(Loop::Continue((
+                            client,
+                            inner,
+                            transport,
+                            index + 1,
+                        ))))
+                    }
+                };
+
+                let headers = transport(&track.media_description)
+                    .to_header_raw()
+                    .into_iter()
+                    .map(|value| (HeaderName::Transport, value))
+                    .collect();
+                let future = send_request(
+                    &mut client,
+                    &inner,
+                    Method::Setup,
+                    &track.control_uri,
+                    headers,
+                );
+
+                let sessions = sessions.clone();
+
+                Either::B(future.and_then(move |response| {
+                    handle_setup_response(&inner, &sessions, index, &response)?;
+                    Ok(Loop::Continue((client, inner, transport, index + 1)))
+                }))
+            },
+        )
+    }
+
+    /// Sends a `SETUP` request for every track that has not been set up yet followed by a `PLAY`
+    /// request for the presentation, without waiting for any of the responses. The transports are
+    /// determined as with [`ClientSession::setup`] and the `"Range"` and `"Scale"` headers as with
+    /// [`ClientSession::play`].
+    ///
+    /// All requests carry the same randomly generated `"Pipelined-Requests"` header, so that the
+    /// server executes them in the session created by the first `SETUP` request. If that request
+    /// fails, the server fails the rest of the requests as well.
+    ///
+    /// # Return Value
+    ///
+    /// The response to the `PLAY` request is returned. An error will be returned if any of the
+    /// requests is unsuccessful.
+    pub fn setup_and_play<F>(
+        &mut self,
+        mut transport: F,
+        range: Option<Range>,
+        scale: Option<f64>,
+    ) -> impl Future<Item = Response<BytesMut>, Error = ClientSessionError>
+    where
+        F: FnMut(&MediaDescription) -> Transport,
+    {
+        let tracks = {
+            let inner = self.lock();
+
+            // The `PLAY` request is only valid once the `SETUP` requests have succeeded, so it is
+            // validated against the state that follows them.
+
+            let mut state_machine = inner.state_machine.clone();
+
+            if let Err(error) = state_machine.validate_request(&Method::Setup) {
+                return Either::A(future::err(ClientSessionError::MethodNotValidInThisState(
+                    error.0,
+                )));
+            }
+
+            state_machine.handle_response(&Method::Setup, StatusCode::OK);
+
+            if let Err(error) = state_machine.validate_request(&Method::Play) {
+                return Either::A(future::err(ClientSessionError::MethodNotValidInThisState(
+                    error.0,
+                )));
+            }
+
+            inner
+                .tracks
+                .iter()
+                .cloned()
+                .enumerate()
+                .filter(|(_, track)| track.transport.is_none())
+                .collect::<Vec<_>>()
+        };
+
+        let mut play_headers = match play_headers(range, scale) {
+            Ok(headers) => headers,
+            Err(error) => return Either::A(future::err(error)),
+        };
+
+        let pipeline = PipelinedRequests::random().to_header_raw().remove(0);
+        let mut setups = Vec::with_capacity(tracks.len());
+
+        for (index, track) in tracks {
+            let mut headers = transport(&track.media_description)
+                .to_header_raw()
+                .into_iter()
+                .map(|value| (HeaderName::Transport, value))
+                .collect::<Vec<_>>();
+            headers.push((HeaderName::PipelinedRequests, pipeline.clone()));
+
+            let inner = self.inner.clone();
+            let sessions = self.client.sessions();
+
+            setups.push(
+                send_unvalidated_request(
+                    &mut self.client,
+                    &self.inner,
+                    Method::Setup,
+                    &track.control_uri,
+                    headers,
+                )
+                .and_then(move |response| {
+                    let response = handle_response(&inner, &Method::Setup, response)?;
+                    handle_setup_response(&inner, &sessions, index, &response)?;
+                    Ok(response)
+                }),
+            );
+        }
+
+        play_headers.push((HeaderName::PipelinedRequests, pipeline));
+
+        let uri = self.aggregate_uri.clone();
+        let play = send_unvalidated_request(
+            &mut self.client,
+            &self.inner,
+            Method::Play,
+            &uri,
+            play_headers,
+        );
+        let inner = self.inner.clone();
+
+        // The state transition of the `PLAY` request is only applied once those of the `SETUP`
+        // requests have been.
+
+        Either::B(
+            future::join_all(setups)
+                .join(play)
+                .and_then(move |(_, response)| handle_response(&inner, &Method::Play, response)),
+        )
+    }
+
+    /// Returns the current state of the session.
+    pub fn state(&self) -> SessionState {
+        self.lock().state_machine.state()
+    }
+
+    /// Sends a `TEARDOWN` request for the presentation, ending the session.
+    pub fn teardown(self) -> impl Future<Item = Response<BytesMut>, Error = ClientSessionError> {
+        let mut client = self.client.clone();
+        let inner = self.inner.clone();
+        let uri = self.aggregate_uri.clone();
+
+        // The session is dropped afterwards, so it must not send a second `TEARDOWN` request.
+
+        let future = send_request(&mut client, &inner, Method::Teardown, &uri, Vec::new());
+        lock(&inner).id = None;
+        future
+    }
+
+    /// Returns the timeout of the session as announced by the server, or the default timeout if
+    /// the server did not announce one. If the session has not been set up, `None` is returned.
+    pub fn timeout_duration(&self) -> Option<Duration> {
+        self.lock().timeout_duration
+    }
+
+    pub fn tracks(&self) -> Vec<Track> {
+        self.lock().tracks.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<Inner> {
+        lock(&self.inner)
+    }
+}
+
+impl Drop for ClientSession {
+    fn drop(&mut self) {
+        if self.lock().id.is_none() {
+            return;
+        }
+
+        // The request is queued for sending as soon as it is created, so the response future does
+        // not need to be polled.
+
+        let uri = self.aggregate_uri.clone();
+        let _ = send_request(
+            &mut self.client,
+            &self.inner,
+            Method::Teardown,
+            &uri,
+            Vec::new(),
+        );
+    }
+}
+
+/// Sends a request with the given method to the given URI as part of the session, applying the
+/// state transition once a successful response has been received.
+///
+/// # Return Value
+///
+/// An error will be returned without sending the request if the method is not valid in the
+/// current state. An unsuccessful response is also returned as an error.
+fn send_request(
+    client: &mut Client,
+    inner: &Arc<Mutex<Inner>>,
+    method: Method,
+    uri: &RequestURI,
+    headers: Vec<(HeaderName, HeaderValue)>,
+) -> impl Future<Item = Response<BytesMut>, Error = ClientSessionError> {
+    if let Err(error) = lock(inner).state_machine.validate_request(&method) {
+        return Either::A(future::err(ClientSessionError::MethodNotValidInThisState(
+            error.0,
+        )));
+    }
+
+    let inner = inner.clone();
+
+    Either::B(
+        send_unvalidated_request(client, &inner, method.clone(), uri, headers)
+            .and_then(move |response| handle_response(&inner, &method, response)),
+    )
+}
+
+/// Sends a request with the given method to the given URI as part of the session without checking
+/// whether the method is valid in the current state. No state transition is applied, see
+/// [`handle_response`].
+fn send_unvalidated_request(
+    client: &mut Client,
+    inner: &Arc<Mutex<Inner>>,
+    method: Method,
+    uri: &RequestURI,
+    headers: Vec<(HeaderName, HeaderValue)>,
+) -> impl Future<Item = Response<BytesMut>, Error = ClientSessionError> {
+    let id = lock(inner).id.clone();
+    let mut request = Request::builder()
+        .method(method.clone())
+        .uri(uri.as_str())
+        .build(BytesMut::new())
+        .expect("session request should not be invalid");
+
+    if let Some(id) = id {
+        let session =
+            SessionHeader::without_timeout(id.as_str()).expect("session ID should be valid");
+        request
+            .headers_mut()
+            .insert(HeaderName::Session, session.to_header_raw().remove(0));
+    }
+
+    for (name, value) in headers {
+        request.headers_mut().append(name, value);
+    }
+
+    client
+        .send_request(request)
+        .map_err(ClientSessionError::Operation)
+}
+
+/// Applies the state transition for a response to a request with the given method.
+///
+/// # Return Value
+///
+/// An unsuccessful response is returned as an error.
+fn handle_response(
+    inner: &Arc<Mutex<Inner>>,
+    method: &Method,
+    response: Response<BytesMut>,
+) -> Result<Response<BytesMut>, ClientSessionError> {
+    let status_code = response.status_code();
+
+    if !status_code.is_success() {
+        return Err(ClientSessionError::UnsuccessfulResponse(status_code));
+    }
+
+    let mut inner = lock(inner);
+    inner.state_machine.handle_response(method, status_code);
+
+    if *method == Method::Teardown {
+        inner.id = None;
+    }
+
+    Ok(response)
+}
+
+/// Returns the `"Range"` and `"Scale"` headers of a `PLAY` request.
+///
+/// # Return Value
+///
+/// An error will be returned if the scale is not finite.
+fn play_headers(
+    range: Option<Range>,
+    scale: Option<f64>,
+) -> Result<Vec<(HeaderName, HeaderValue)>, ClientSessionError> {
+    let mut headers = Vec::new();
+
+    if let Some(range) = range {
+        headers.extend(
+            range
+                .to_header_raw()
+                .into_iter()
+                .map(|value| (HeaderName::Range, value)),
+        );
+    }
+
+    if let Some(scale) = scale {
+        let value = if scale.is_finite() {
+            HeaderValue::try_from(scale.to_string().as_str()).ok()
+        } else {
+            None
+        };
+
+        match value {
+            Some(value) => headers.push((HeaderName::Scale, value)),
+            None => return Err(ClientSessionError::InvalidScale),
+        }
+    }
+
+    Ok(headers)
+}
+
+/// Resolves the aggregate control URI of the given presentation and the control URIs of its tracks
+/// against the given base URI.
+fn resolve_tracks(
+    description: &SessionDescription,
+    base_uri: &RequestURI,
+) -> Result<(RequestURI, Vec<Track>), ClientSessionError> {
+    let aggregate_uri = description
+        .control_uri(base_uri)
+        .map_err(|_| ClientSessionError::InvalidControlURI)?;
+    let media_descriptions = description.media_descriptions();
+    let mut tracks = Vec::with_capacity(media_descriptions.len());
+
+    for media_description in media_descriptions.iter() {
+        let control_uri = match media_description.control_uri(base_uri) {
+            Ok(Some(control_uri)) => control_uri,
+            Ok(None) if media_descriptions.len() == 1 => aggregate_uri.clone(),
+            _ => return Err(ClientSessionError::InvalidControlURI),
+        };
+
+        tracks.push(Track {
+            control_uri,
+            media_description: media_description.clone(),
+            transport: None,
+        });
+    }
+
+    Ok((aggregate_uri, tracks))
+}
+
+/// Records the session ID and the transport selected by the server from a successful `SETUP`
+/// response for the track with the given index. The session is registered with the given sessions
+/// once its ID is known.
+fn handle_setup_response(
+    inner: &Arc<Mutex<Inner>>,
+    sessions: &SessionRegistry,
+    index: usize,
+    response: &Response<BytesMut>,
+) -> Result<(), ClientSessionError> {
+    let transport = Transport::try_from_header_raw(
+        &response
+            .headers()
+            .get_all(HeaderName::Transport)
+            .iter()
+            .cloned()
+            .collect::<Vec<HeaderValue>>(),
+    )
+    .map_err(|_| ClientSessionError::InvalidResponse)?
+    .0
+    .into_iter()
+    .next()
+    .ok_or(ClientSessionError::InvalidResponse)?;
+
+    let header_values = response
+        .headers()
+        .get_all(HeaderName::Session)
+        .iter()
+        .cloned()
+        .collect::<Vec<HeaderValue>>();
+    let mut inner_guard = lock(inner);
+
+    if inner_guard.id.is_none() {
+        let session = SessionHeader::try_from_header_raw(&header_values)
+            .map_err(|_| ClientSessionError::InvalidResponse)?;
+        inner_guard.id = Some(session.id().clone());
+        inner_guard.timeout_duration = Some(session.timeout().unwrap_or(DEFAULT_SESSION_TIMEOUT));
+        sessions.register(session.id().clone(), inner);
+    }
+
+    inner_guard.tracks[index].transport = Some(transport);
+    Ok(())
+}
+
+fn lock(inner: &Arc<Mutex<Inner>>) -> MutexGuard<Inner> {
+    inner.lock().expect("locking `inner` should not error")
+}
+
+/// The sessions that have been set up over a connection, by their ID.
+///
+/// Requests sent by the server are passed to the registry before they are answered, so that the
+/// state transition of a `TEARDOWN` or `REDIRECT` request is applied to the session it belongs to.
+/// A session that has been torn down by the server no longer has an ID and thus does not send a
+/// `TEARDOWN` request of its own when it is dropped.
+#[derive(Clone, Default)]
+pub(crate) struct SessionRegistry {
+    sessions: Arc<Mutex<HashMap<SessionID, Weak<Mutex<Inner>>>>>,
+}
+
+impl SessionRegistry {
+    /// Applies the state transition for a request sent by the server to the session identified by
+    /// its `"Session"` header, if that session is registered.
+    pub(crate) fn handle_server_request(&self, request: &Request<BytesMut>) {
+        let method = request.method();
+
+        if *method != Method::Teardown && *method != Method::Redirect {
+            return;
+        }
+
+        let header_values = request
+            .headers()
+            .get_all(HeaderName::Session)
+            .iter()
+            .cloned()
+            .collect::<Vec<HeaderValue>>();
+        let id = match SessionHeader::try_from_header_raw(&header_values) {
+            Ok(session) => session.id().clone(),
+            Err(_) => return,
+        };
+
+        let inner = match self.lock().get(&id).and_then(|inner| inner.upgrade()) {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let mut inner = lock(&inner);
+
+        if inner.id.as_ref() != Some(&id) {
+            return;
+        }
+
+        if inner.state_machine.handle_server_request(method) == SessionState::Init {
+            inner.id = None;
+            self.lock().remove(&id);
+        }
+    }
+
+    /// Registers the session with the given ID, removing any sessions that no longer exist.
+    fn register(&self, id: SessionID, inner: &Arc<Mutex<Inner>>) {
+        let mut sessions = self.lock();
+        sessions.retain(|_, inner| inner.upgrade().is_some());
+        sessions.insert(id, Arc::downgrade(inner));
+    }
+
+    fn lock(&self) -> MutexGuard<HashMap<SessionID, Weak<Mutex<Inner>>>> {
+        self.sessions
+            .lock()
+            .expect("locking session registry should not error")
+    }
+}
+
+/// A possible error value when using a [`ClientSession`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ClientSessionError {
+    /// A control URI of the presentation could not be determined or was invalid.
+    InvalidControlURI,
+
+    /// The session description in the `DESCRIBE` response could not be parsed.
+    InvalidDescription(ParseError),
+
+    /// A response was missing a required header or contained an invalid one.
+    InvalidResponse,
+
+    /// The given scale was not a finite number.
+    InvalidScale,
+
+    /// The request is not valid in the current state of the session and was not sent.
+    MethodNotValidInThisState(SessionState),
+
+    /// The request could not be sent or its response could not be received.
+    Operation(OperationError),
+
+    /// The server responded with a status code that does not indicate success.
+    UnsuccessfulResponse(StatusCode),
+}
+
+impl fmt::Display for ClientSessionError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for ClientSessionError {
+    fn description(&self) -> &str {
+        use self::ClientSessionError::*;
+
+        match self {
+            InvalidControlURI => "invalid control URI",
+            InvalidDescription(_) => "invalid session description",
+            InvalidResponse => "invalid response",
+            InvalidScale => "invalid scale",
+            MethodNotValidInThisState(_) => "method not valid in this state",
+            Operation(error) => error.description(),
+            UnsuccessfulResponse(_) => "unsuccessful response",
+        }
+    }
+}
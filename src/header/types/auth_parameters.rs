@@ -0,0 +1,229 @@
+use header::{HeaderValue, InvalidTypedHeader};
+use syntax::{is_token, quote_string, split_unquoted, trim_whitespace, unquote_string};
+
+/// A single challenge or set of credentials as found in the authentication related headers. Only
+/// one of `token68` or `parameters` will be non-empty. The values of the parameters are stored
+/// unquoted and parameter names are normalized to lowercase.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct AuthItem {
+    pub scheme: String,
+    pub token68: Option<String>,
+    pub parameters: Vec<(String, String)>,
+}
+
+impl AuthItem {
+    /// Returns the value of the parameter with the given lowercase name. An error is returned if
+    /// the parameter was given more than once.
+    pub fn parameter(&self, name: &str) -> Result<Option<&str>, InvalidTypedHeader> {
+        let mut values = self
+            .parameters
+            .iter()
+            .filter(|&&(ref parameter, _)| parameter == name)
+            .map(|&(_, ref value)| value.as_str());
+        let value = values.next();
+
+        if values.next().is_some() {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the value of the parameter with the given lowercase name, failing if it is missing.
+    pub fn required_parameter(&self, name: &str) -> Result<&str, InvalidTypedHeader> {
+        self.parameter(name)?.ok_or(InvalidTypedHeader)
+    }
+}
+
+/// Determines whether the given value can be serialized as a parameter value. Since values are
+/// always serialized as either tokens or quoted strings, this only excludes control characters.
+pub(super) fn is_auth_parameter_value(value: &str) -> bool {
+    value.chars().all(|c| !c.is_control())
+}
+
+/// Parses all challenges or credentials contained in the given header values. Based on
+/// [RFC7235](https://tools.ietf.org/html/rfc7235#section-2.1), the syntax is:
+///
+/// ```text
+/// auth-scheme = token
+/// auth-param = token BWS "=" BWS ( token / quoted-string )
+/// token68 = 1*( ALPHA / DIGIT / "-" / "." / "_" / "~" / "+" / "/" ) *"="
+/// challenge = auth-scheme [ 1*SP ( token68 / #auth-param ) ]
+/// credentials = auth-scheme [ 1*SP ( token68 / #auth-param ) ]
+/// ```
+///
+/// Since both the list of challenges and the list of parameters are comma separated, a new item
+/// starts with each list element that begins with a token that is not followed by `'='`.
+pub(super) fn parse_auth_items(
+    header: &[HeaderValue],
+) -> Result<Vec<AuthItem>, InvalidTypedHeader> {
+    let mut items: Vec<AuthItem> = Vec::new();
+
+    for value in header {
+        for element in split_unquoted(value.as_str(), ',') {
+            let element = trim_whitespace(element);
+
+            if element.is_empty() {
+                continue;
+            }
+
+            let scheme_end = element
+                .find(|c| c == ' ' || c == '\t' || c == '=')
+                .unwrap_or(element.len());
+            let rest = trim_whitespace(&element[scheme_end..]);
+
+            if rest.starts_with('=') && !is_token68(element) {
+                let item = items.last_mut().ok_or(InvalidTypedHeader)?;
+
+                if item.token68.is_some() {
+                    return Err(InvalidTypedHeader);
+                }
+
+                item.parameters.push(parse_auth_parameter(element)?);
+                continue;
+            }
+
+            let scheme = &element[..scheme_end];
+
+            if !is_token(scheme) {
+                return Err(InvalidTypedHeader);
+            }
+
+            let mut item = AuthItem {
+                scheme: scheme.to_string(),
+                token68: None,
+                parameters: Vec::new(),
+            };
+
+            if is_token68(rest) {
+                item.token68 = Some(rest.to_string());
+            } else if !rest.is_empty() {
+                item.parameters.push(parse_auth_parameter(rest)?);
+            }
+
+            items.push(item);
+        }
+    }
+
+    if items.is_empty() {
+        return Err(InvalidTypedHeader);
+    }
+
+    Ok(items)
+}
+
+/// Serializes a scheme and its parameters. Parameters whose values are `None` are omitted, and
+/// the boolean of each pair specifies whether the value should be quoted.
+pub(super) fn write_auth_item(scheme: &str, parameters: &[(&str, Option<&str>, bool)]) -> String {
+    let mut value = scheme.to_string();
+    let mut first = true;
+
+    for &(name, parameter, quoted) in parameters {
+        if let Some(parameter) = parameter {
+            value.push_str(if first { " " } else { ", " });
+            value.push_str(name);
+            value.push('=');
+
+            if quoted {
+                value.push_str(&quote_string(parameter));
+            } else {
+                value.push_str(parameter);
+            }
+
+            first = false;
+        }
+    }
+
+    value
+}
+
+fn is_token68(value: &str) -> bool {
+    let value = value.trim_right_matches('=');
+
+    !value.is_empty()
+        && value.bytes().all(|b| match b {
+            b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' => true,
+            b'-' | b'.' | b'_' | b'~' | b'+' | b'/' => true,
+            _ => false,
+        })
+}
+
+fn parse_auth_parameter(value: &str) -> Result<(String, String), InvalidTypedHeader> {
+    let mut parts = value.splitn(2, '=');
+    let name = trim_whitespace(parts.next().unwrap());
+    let value = trim_whitespace(parts.next().ok_or(InvalidTypedHeader)?);
+
+    if !is_token(name) {
+        return Err(InvalidTypedHeader);
+    }
+
+    let value = if is_token(value) {
+        value.to_string()
+    } else {
+        unquote_string(value).ok_or(InvalidTypedHeader)?
+    };
+
+    Ok((name.to_lowercase(), value))
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn parse(values: &[&str]) -> Result<Vec<AuthItem>, InvalidTypedHeader> {
+        let header = values
+            .iter()
+            .map(|value| HeaderValue::try_from(*value).unwrap())
+            .collect::<Vec<HeaderValue>>();
+        parse_auth_items(&header)
+    }
+
+    #[test]
+    fn test_parse_auth_items() {
+        let items = parse(&[
+            r#"Digest realm="a, b", nonce = "n\"1", qop="auth,auth-int", Basic realm=r"#,
+            "Basic dXNlcjpwYXNz==",
+        ])
+        .unwrap();
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].scheme, "Digest");
+        assert_eq!(
+            items[0].parameters,
+            vec![
+                ("realm".to_string(), "a, b".to_string()),
+                ("nonce".to_string(), "n\"1".to_string()),
+                ("qop".to_string(), "auth,auth-int".to_string()),
+            ]
+        );
+        assert_eq!(items[1].parameter("realm"), Ok(Some("r")));
+        assert_eq!(items[2].token68, Some("dXNlcjpwYXNz==".to_string()));
+        assert!(items[2].parameters.is_empty());
+
+        assert!(parse(&[""]).is_err());
+        assert!(parse(&["realm=a"]).is_err());
+        assert!(parse(&["Basic abc, realm=a"]).is_err());
+        assert!(parse(&[r#"Digest realm="a"#]).is_err());
+        assert!(parse(&["Digest realm=a, realm=b"]).unwrap()[0]
+            .parameter("realm")
+            .is_err());
+    }
+
+    #[test]
+    fn test_write_auth_item() {
+        assert_eq!(write_auth_item("Basic", &[]), "Basic");
+        assert_eq!(
+            write_auth_item(
+                "Digest",
+                &[
+                    ("realm", Some("a\"b"), true),
+                    ("opaque", None, true),
+                    ("algorithm", Some("MD5"), false),
+                ]
+            ),
+            r#"Digest realm="a\"b", algorithm=MD5"#
+        );
+    }
+}
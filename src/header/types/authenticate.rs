@@ -0,0 +1,691 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+
+use header::types::auth_parameters::{
+    is_auth_parameter_value, parse_auth_items, write_auth_item, AuthItem,
+};
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+
+/// The `"WWW-Authenticate"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.58).
+///
+/// The header is a list of challenges, each indicating an authentication scheme and the
+/// parameters applicable to the request URI. Challenges using schemes other than `"Basic"` or
+/// `"Digest"` and digest challenges using unsupported algorithms are ignored when parsing.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct WWWAuthenticate(pub Vec<Challenge>);
+
+impl WWWAuthenticate {
+    /// Constructs a new header with no challenges by default.
+    pub fn new() -> Self {
+        WWWAuthenticate::default()
+    }
+}
+
+impl Deref for WWWAuthenticate {
+    type Target = Vec<Challenge>;
+
+    fn deref(&self) -> &Vec<Challenge> {
+        &self.0
+    }
+}
+
+impl DerefMut for WWWAuthenticate {
+    fn deref_mut(&mut self) -> &mut Vec<Challenge> {
+        &mut self.0
+    }
+}
+
+impl FromIterator<Challenge> for WWWAuthenticate {
+    fn from_iter<I>(iterator: I) -> Self
+    where
+        I: IntoIterator<Item = Challenge>,
+    {
+        WWWAuthenticate(Vec::from_iter(iterator))
+    }
+}
+
+impl TypedHeader for WWWAuthenticate {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::WWWAuthenticate
+    }
+
+    /// Converts the [`WWWAuthenticate`] type to raw header values. Each challenge is serialized
+    /// as a separate header value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let mut challenge = DigestChallenge::new("rtsp", "dcd98b7102dd2f0e").unwrap();
+    /// *challenge.qop_mut() = vec![QualityOfProtection::Auth];
+    ///
+    /// let typed_header = vec![
+    ///     Challenge::Digest(challenge),
+    ///     Challenge::Basic(BasicChallenge::new("rtsp").unwrap()),
+    /// ].into_iter().collect::<WWWAuthenticate>();
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         r#"Digest realm="rtsp", nonce="dcd98b7102dd2f0e", algorithm=MD5, qop="auth""#
+    ///     ).unwrap(),
+    ///     HeaderValue::try_from(r#"Basic realm="rtsp""#).unwrap(),
+    /// ];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        challenges_to_header_raw(&self.0)
+    }
+
+    /// Converts the raw header values to the [`WWWAuthenticate`] header type. Based on the syntax
+    /// provided by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the
+    /// following syntax:
+    ///
+    /// ```text
+    /// WWW-Authenticate = "WWW-Authenticate" HCOLON challenge *(COMMA challenge)
+    /// challenge = auth-scheme [ 1*SP ( token68 / #auth-param ) ]
+    /// auth-param = token BWS "=" BWS ( token / quoted-string )
+    /// ```
+    ///
+    /// The digest specific parameters are described by
+    /// [RFC7616](https://tools.ietf.org/html/rfc7616#section-3.3).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         r#"Digest realm="rtsp", nonce="dcd98b7102dd2f0e", algorithm=SHA-256, stale=TRUE"#
+    ///     ).unwrap(),
+    ///     HeaderValue::try_from(r#"Bearer realm="rtsp", Basic realm="rtsp""#).unwrap(),
+    /// ];
+    /// let typed_header = WWWAuthenticate::try_from_header_raw(&raw_header).unwrap();
+    ///
+    /// assert_eq!(typed_header.len(), 2);
+    ///
+    /// match typed_header[0] {
+    ///     Challenge::Digest(ref challenge) => {
+    ///         assert_eq!(challenge.algorithm(), DigestAlgorithm::SHA256);
+    ///         assert_eq!(challenge.nonce(), "dcd98b7102dd2f0e");
+    ///         assert!(challenge.stale());
+    ///     }
+    ///     _ => panic!("expected digest challenge"),
+    /// }
+    ///
+    /// assert_eq!(
+    ///     typed_header[1],
+    ///     Challenge::Basic(BasicChallenge::new("rtsp").unwrap())
+    /// );
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("Digest nonce=\"abc\"").unwrap()];
+    ///
+    /// assert!(WWWAuthenticate::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        challenges_from_header_raw(header).map(WWWAuthenticate)
+    }
+}
+
+/// The `"Proxy-Authenticate"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.40).
+///
+/// The syntax is identical to that of [`WWWAuthenticate`], except that the challenges apply to
+/// the proxy rather than the origin server.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ProxyAuthenticate(pub Vec<Challenge>);
+
+impl ProxyAuthenticate {
+    /// Constructs a new header with no challenges by default.
+    pub fn new() -> Self {
+        ProxyAuthenticate::default()
+    }
+}
+
+impl Deref for ProxyAuthenticate {
+    type Target = Vec<Challenge>;
+
+    fn deref(&self) -> &Vec<Challenge> {
+        &self.0
+    }
+}
+
+impl DerefMut for ProxyAuthenticate {
+    fn deref_mut(&mut self) -> &mut Vec<Challenge> {
+        &mut self.0
+    }
+}
+
+impl FromIterator<Challenge> for ProxyAuthenticate {
+    fn from_iter<I>(iterator: I) -> Self
+    where
+        I: IntoIterator<Item = Challenge>,
+    {
+        ProxyAuthenticate(Vec::from_iter(iterator))
+    }
+}
+
+impl TypedHeader for ProxyAuthenticate {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::ProxyAuthenticate
+    }
+
+    /// Converts the [`ProxyAuthenticate`] type to raw header values. Each challenge is serialized
+    /// as a separate header value.
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        challenges_to_header_raw(&self.0)
+    }
+
+    /// Converts the raw header values to the [`ProxyAuthenticate`] header type. The syntax is the
+    /// same as that of [`WWWAuthenticate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("Basic realm=proxy").unwrap()];
+    ///
+    /// assert_eq!(
+    ///     ProxyAuthenticate::try_from_header_raw(&raw_header).unwrap(),
+    ///     vec![Challenge::Basic(BasicChallenge::new("proxy").unwrap())]
+    ///         .into_iter()
+    ///         .collect()
+    /// );
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        challenges_from_header_raw(header).map(ProxyAuthenticate)
+    }
+}
+
+/// A single authentication challenge.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Challenge {
+    Basic(BasicChallenge),
+    Digest(DigestChallenge),
+}
+
+impl Challenge {
+    /// Returns the realm the challenge applies to.
+    pub fn realm(&self) -> &str {
+        match self {
+            Challenge::Basic(challenge) => challenge.realm(),
+            Challenge::Digest(challenge) => challenge.realm(),
+        }
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Challenge::Basic(challenge) => challenge.fmt(formatter),
+            Challenge::Digest(challenge) => challenge.fmt(formatter),
+        }
+    }
+}
+
+/// A challenge for the `"Basic"` authentication scheme as described by
+/// [RFC7617](https://tools.ietf.org/html/rfc7617#section-2).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BasicChallenge {
+    realm: String,
+}
+
+impl BasicChallenge {
+    /// Constructs a new basic challenge for the given realm.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the realm contains control characters.
+    pub fn new<S>(realm: S) -> Result<Self, InvalidTypedHeader>
+    where
+        S: Into<String>,
+    {
+        let realm = realm.into();
+
+        if !is_auth_parameter_value(&realm) {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(BasicChallenge { realm })
+    }
+
+    pub fn realm(&self) -> &str {
+        &self.realm
+    }
+}
+
+impl fmt::Display for BasicChallenge {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&write_auth_item(
+            "Basic",
+            &[("realm", Some(&self.realm), true)],
+        ))
+    }
+}
+
+impl<'a> TryFrom<&'a AuthItem> for BasicChallenge {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(item: &'a AuthItem) -> Result<Self, Self::Error> {
+        if item.token68.is_some() {
+            return Err(InvalidTypedHeader);
+        }
+
+        BasicChallenge::new(item.required_parameter("realm")?)
+    }
+}
+
+/// A challenge for the `"Digest"` authentication scheme as described by
+/// [RFC7616](https://tools.ietf.org/html/rfc7616#section-3.3).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DigestChallenge {
+    algorithm: DigestAlgorithm,
+    nonce: String,
+    opaque: Option<String>,
+    qop: Vec<QualityOfProtection>,
+    realm: String,
+    stale: bool,
+}
+
+impl DigestChallenge {
+    /// Constructs a new digest challenge for the given realm and nonce. The challenge will use the
+    /// MD5 algorithm, will not specify any quality of protection, and will not be stale by
+    /// default.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the realm or the nonce contain control characters.
+    pub fn new<R, N>(realm: R, nonce: N) -> Result<Self, InvalidTypedHeader>
+    where
+        R: Into<String>,
+        N: Into<String>,
+    {
+        let nonce = nonce.into();
+        let realm = realm.into();
+
+        if !is_auth_parameter_value(&nonce) || !is_auth_parameter_value(&realm) {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(DigestChallenge {
+            algorithm: DigestAlgorithm::MD5,
+            nonce,
+            opaque: None,
+            qop: Vec::new(),
+            realm,
+            stale: false,
+        })
+    }
+
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    pub fn algorithm_mut(&mut self) -> &mut DigestAlgorithm {
+        &mut self.algorithm
+    }
+
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    /// Returns the opaque data that must be returned unchanged in the credentials.
+    pub fn opaque(&self) -> Option<&str> {
+        self.opaque.as_ref().map(|opaque| opaque.as_str())
+    }
+
+    /// Returns the qualities of protection supported by the server. If empty, the server is
+    /// assumed to only support the legacy [RFC2069](https://tools.ietf.org/html/rfc2069)
+    /// computation of the response.
+    pub fn qop(&self) -> &[QualityOfProtection] {
+        &self.qop
+    }
+
+    pub fn qop_mut(&mut self) -> &mut Vec<QualityOfProtection> {
+        &mut self.qop
+    }
+
+    pub fn realm(&self) -> &str {
+        &self.realm
+    }
+
+    /// Sets the opaque data of the challenge.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the opaque data contains control characters.
+    pub fn set_opaque(&mut self, opaque: Option<&str>) -> Result<(), InvalidTypedHeader> {
+        if let Some(opaque) = opaque {
+            if !is_auth_parameter_value(opaque) {
+                return Err(InvalidTypedHeader);
+            }
+        }
+
+        self.opaque = opaque.map(|opaque| opaque.to_string());
+        Ok(())
+    }
+
+    /// Returns whether the previous request was rejected only because the nonce it used was
+    /// stale, meaning the credentials themselves were valid.
+    pub fn stale(&self) -> bool {
+        self.stale
+    }
+
+    pub fn stale_mut(&mut self) -> &mut bool {
+        &mut self.stale
+    }
+}
+
+impl fmt::Display for DigestChallenge {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let qop = self
+            .qop
+            .iter()
+            .map(|qop| qop.as_str())
+            .collect::<Vec<&str>>()
+            .join(",");
+        let qop = if qop.is_empty() {
+            None
+        } else {
+            Some(qop.as_str())
+        };
+        let stale = if self.stale { Some("true") } else { None };
+
+        formatter.write_str(&write_auth_item(
+            "Digest",
+            &[
+                ("realm", Some(&self.realm), true),
+                ("nonce", Some(&self.nonce), true),
+                ("opaque", self.opaque(), true),
+                ("algorithm", Some(self.algorithm.as_str()), false),
+                ("qop", qop, true),
+                ("stale", stale, false),
+            ],
+        ))
+    }
+}
+
+impl<'a> TryFrom<&'a AuthItem> for DigestChallenge {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(item: &'a AuthItem) -> Result<Self, Self::Error> {
+        if item.token68.is_some() {
+            return Err(InvalidTypedHeader);
+        }
+
+        let mut challenge = DigestChallenge::new(
+            item.required_parameter("realm")?,
+            item.required_parameter("nonce")?,
+        )?;
+
+        if let Some(algorithm) = item.parameter("algorithm")? {
+            challenge.algorithm = DigestAlgorithm::try_from(algorithm)?;
+        }
+
+        challenge.set_opaque(item.parameter("opaque")?)?;
+
+        if let Some(qop) = item.parameter("qop")? {
+            // Unknown qualities of protection are ignored so that the remaining ones can still be
+            // used.
+
+            challenge.qop = qop
+                .split(',')
+                .filter_map(|qop| QualityOfProtection::try_from(qop.trim()).ok())
+                .collect();
+        }
+
+        if let Some(stale) = item.parameter("stale")? {
+            challenge.stale = stale.eq_ignore_ascii_case("true");
+        }
+
+        Ok(challenge)
+    }
+}
+
+/// The hash algorithm used to compute digest credentials.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DigestAlgorithm {
+    MD5,
+    SHA256,
+}
+
+impl DigestAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        use self::DigestAlgorithm::*;
+
+        match self {
+            MD5 => "MD5",
+            SHA256 => "SHA-256",
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for DigestAlgorithm {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value.eq_ignore_ascii_case("MD5") {
+            Ok(DigestAlgorithm::MD5)
+        } else if value.eq_ignore_ascii_case("SHA-256") {
+            Ok(DigestAlgorithm::SHA256)
+        } else {
+            Err(InvalidTypedHeader)
+        }
+    }
+}
+
+/// The quality of protection applied to digest credentials.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum QualityOfProtection {
+    /// Authentication only.
+    Auth,
+
+    /// Authentication with integrity protection of the message body.
+    AuthInt,
+}
+
+impl QualityOfProtection {
+    pub fn as_str(&self) -> &'static str {
+        use self::QualityOfProtection::*;
+
+        match self {
+            Auth => "auth",
+            AuthInt => "auth-int",
+        }
+    }
+}
+
+impl fmt::Display for QualityOfProtection {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for QualityOfProtection {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value.eq_ignore_ascii_case("auth") {
+            Ok(QualityOfProtection::Auth)
+        } else if value.eq_ignore_ascii_case("auth-int") {
+            Ok(QualityOfProtection::AuthInt)
+        } else {
+            Err(InvalidTypedHeader)
+        }
+    }
+}
+
+fn challenges_from_header_raw(
+    header: &[HeaderValue],
+) -> Result<Vec<Challenge>, InvalidTypedHeader> {
+    let mut challenges = Vec::new();
+
+    for item in parse_auth_items(header)? {
+        if item.scheme.eq_ignore_ascii_case("Basic") {
+            challenges.push(Challenge::Basic(BasicChallenge::try_from(&item)?));
+        } else if item.scheme.eq_ignore_ascii_case("Digest") {
+            let algorithm = item.parameter("algorithm")?;
+
+            if algorithm.map_or(true, |algorithm| {
+                DigestAlgorithm::try_from(algorithm).is_ok()
+            }) {
+                challenges.push(Challenge::Digest(DigestChallenge::try_from(&item)?));
+            }
+        }
+    }
+
+    if challenges.is_empty() {
+        return Err(InvalidTypedHeader);
+    }
+
+    Ok(challenges)
+}
+
+fn challenges_to_header_raw(challenges: &[Challenge]) -> Vec<HeaderValue> {
+    // Unsafe Justification
+    //
+    // In order for this to be safe, we must ensure that each value contains no unprintable
+    // ASCII-US characters and that all linebreaks of the form `"\r\n"` are followed by a space or
+    // tab. The scheme and parameter names are fixed tokens, and all parameter values are checked
+    // for control characters on construction, so no linebreaks can be introduced.
+
+    challenges
+        .iter()
+        .map(|challenge| unsafe { HeaderValue::from_str_unchecked(challenge.to_string()) })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn header(values: &[&str]) -> Vec<HeaderValue> {
+        values
+            .iter()
+            .map(|value| HeaderValue::try_from(*value).unwrap())
+            .collect()
+    }
+
+    fn round_trip(values: &[&str]) -> WWWAuthenticate {
+        let raw_header = header(values);
+        let typed_header = WWWAuthenticate::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.to_header_raw(), raw_header);
+        typed_header
+    }
+
+    #[test]
+    fn test_www_authenticate() {
+        let typed_header = round_trip(&[r#"Basic realm="example""#]);
+        assert_eq!(
+            typed_header,
+            vec![Challenge::Basic(BasicChallenge::new("example").unwrap())]
+                .into_iter()
+                .collect()
+        );
+
+        let typed_header = round_trip(&[
+            "Digest realm=\"example\", nonce=\"abc\", opaque=\"xyz\", algorithm=SHA-256, \
+             qop=\"auth,auth-int\", stale=true",
+            r#"Basic realm="a \"quoted\" realm""#,
+        ]);
+        assert_eq!(typed_header.len(), 2);
+        assert_eq!(typed_header[1].realm(), r#"a "quoted" realm"#);
+
+        let mut challenge = DigestChallenge::new("example", "abc").unwrap();
+        *challenge.algorithm_mut() = DigestAlgorithm::SHA256;
+        *challenge.qop_mut() = vec![QualityOfProtection::Auth, QualityOfProtection::AuthInt];
+        *challenge.stale_mut() = true;
+        challenge.set_opaque(Some("xyz")).unwrap();
+        assert_eq!(typed_header[0], Challenge::Digest(challenge));
+
+        round_trip(&[r#"Digest realm="example", nonce="abc", algorithm=MD5"#]);
+    }
+
+    #[test]
+    fn test_www_authenticate_lenient() {
+        let raw_header = header(&[
+            "basic REALM=example, Bearer realm=example",
+            "DIGEST realm=example, nonce=abc, qop=\"auth, x-unknown\", stale=FALSE",
+            "Digest realm=example, nonce=abc, algorithm=SHA-512-256",
+        ]);
+        let typed_header = WWWAuthenticate::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.len(), 2);
+        assert_eq!(typed_header[0].realm(), "example");
+
+        match typed_header[1] {
+            Challenge::Digest(ref challenge) => {
+                assert_eq!(challenge.algorithm(), DigestAlgorithm::MD5);
+                assert_eq!(challenge.qop(), &[QualityOfProtection::Auth]);
+                assert!(!challenge.stale());
+            }
+            _ => panic!("expected a digest challenge"),
+        }
+    }
+
+    #[test]
+    fn test_proxy_authenticate() {
+        let raw_header = header(&[r#"Digest realm="example", nonce="abc", algorithm=MD5"#]);
+        let typed_header = ProxyAuthenticate::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.to_header_raw(), raw_header);
+        assert_eq!(typed_header[0].realm(), "example");
+    }
+
+    #[test]
+    fn test_authenticate_invalid() {
+        assert!(WWWAuthenticate::try_from_header_raw(&[]).is_err());
+        assert!(ProxyAuthenticate::try_from_header_raw(&[]).is_err());
+
+        for value in &[
+            "",
+            "Bearer realm=example",
+            "Basic",
+            "Basic abc",
+            "Basic realm=\"example",
+            "Basic realm=a, realm=b",
+            "Digest realm=example",
+            "Digest nonce=abc",
+            "Digest realm=example, nonce=abc, algorithm=SHA-512-256",
+            "Digest realm=example, nonce=abc, algorithm=MD5, algorithm=MD5",
+            "realm=example",
+        ] {
+            let raw_header = header(&[value]);
+            assert!(WWWAuthenticate::try_from_header_raw(&raw_header).is_err());
+            assert!(ProxyAuthenticate::try_from_header_raw(&raw_header).is_err());
+        }
+
+        assert!(BasicChallenge::new("a\r\nb").is_err());
+        assert!(DigestChallenge::new("example", "a\u{7f}").is_err());
+        assert!(DigestChallenge::new("example", "abc")
+            .unwrap()
+            .set_opaque(Some("\n"))
+            .is_err());
+    }
+}
@@ -0,0 +1,603 @@
+use base64;
+use std::convert::TryFrom;
+use std::fmt;
+
+use header::types::auth_parameters::{
+    is_auth_parameter_value, parse_auth_items, write_auth_item, AuthItem,
+};
+use header::types::{DigestAlgorithm, QualityOfProtection};
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+
+/// The `"Authorization"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.8).
+///
+/// The header contains the credentials of the user agent for the realm of the requested resource.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Authorization(pub Credentials);
+
+impl TypedHeader for Authorization {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::Authorization
+    }
+
+    /// Converts the [`Authorization`] type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let typed_header =
+    ///     Authorization(Credentials::Basic(BasicCredentials::new("user", "pass").unwrap()));
+    /// let raw_header = vec![HeaderValue::try_from("Basic dXNlcjpwYXNz").unwrap()];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        credentials_to_header_raw(&self.0)
+    }
+
+    /// Converts the raw header values to the [`Authorization`] header type. Based on the syntax
+    /// provided by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the
+    /// following syntax:
+    ///
+    /// ```text
+    /// Authorization = "Authorization" HCOLON credentials
+    /// credentials = auth-scheme [ 1*SP ( token68 / #auth-param ) ]
+    /// auth-param = token BWS "=" BWS ( token / quoted-string )
+    /// ```
+    ///
+    /// The digest specific parameters are described by
+    /// [RFC7616](https://tools.ietf.org/html/rfc7616#section-3.4).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("Basic dXNlcjpwYXNz").unwrap()];
+    ///
+    /// assert_eq!(
+    ///     Authorization::try_from_header_raw(&raw_header).unwrap(),
+    ///     Authorization(Credentials::Basic(BasicCredentials::new("user", "pass").unwrap()))
+    /// );
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         "Digest username=\"user\", realm=\"rtsp\", nonce=\"dcd98b7102dd2f0e\", \
+    ///          uri=\"rtsp://example.com/media\", response=\"6629fae49393a05397450978507c4ef1\", \
+    ///          qop=auth, nc=00000001, cnonce=\"0a4f113b\""
+    ///     ).unwrap(),
+    /// ];
+    /// let typed_header = Authorization::try_from_header_raw(&raw_header).unwrap();
+    ///
+    /// match typed_header.0 {
+    ///     Credentials::Digest(ref credentials) => {
+    ///         assert_eq!(credentials.username(), "user");
+    ///         assert_eq!(credentials.nonce_count(), Some(1));
+    ///         assert_eq!(credentials.qop(), Some(QualityOfProtection::Auth));
+    ///     }
+    ///     _ => panic!("expected digest credentials"),
+    /// }
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("Digest username=\"user\"").unwrap()];
+    ///
+    /// assert!(Authorization::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        credentials_from_header_raw(header).map(Authorization)
+    }
+}
+
+/// The `"Proxy-Authorization"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.42).
+///
+/// The syntax is identical to that of [`Authorization`], except that the credentials are meant
+/// for the proxy rather than the origin server.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ProxyAuthorization(pub Credentials);
+
+impl TypedHeader for ProxyAuthorization {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::ProxyAuthorization
+    }
+
+    /// Converts the [`ProxyAuthorization`] type to raw header values.
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        credentials_to_header_raw(&self.0)
+    }
+
+    /// Converts the raw header values to the [`ProxyAuthorization`] header type. The syntax is
+    /// the same as that of [`Authorization`].
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        credentials_from_header_raw(header).map(ProxyAuthorization)
+    }
+}
+
+/// The credentials of a user agent.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Credentials {
+    Basic(BasicCredentials),
+    Digest(DigestCredentials),
+}
+
+impl Credentials {
+    pub fn username(&self) -> &str {
+        match self {
+            Credentials::Basic(credentials) => credentials.username(),
+            Credentials::Digest(credentials) => credentials.username(),
+        }
+    }
+}
+
+impl fmt::Display for Credentials {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Credentials::Basic(credentials) => credentials.fmt(formatter),
+            Credentials::Digest(credentials) => credentials.fmt(formatter),
+        }
+    }
+}
+
+/// Credentials for the `"Basic"` authentication scheme as described by
+/// [RFC7617](https://tools.ietf.org/html/rfc7617#section-2).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BasicCredentials {
+    password: String,
+    username: String,
+}
+
+impl BasicCredentials {
+    /// Constructs new basic credentials.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if either the username or the password contain control
+    /// characters or if the username contains `':'`.
+    pub fn new<U, P>(username: U, password: P) -> Result<Self, InvalidTypedHeader>
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        let password = password.into();
+        let username = username.into();
+
+        if username.contains(':')
+            || !is_auth_parameter_value(&username)
+            || !is_auth_parameter_value(&password)
+        {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(BasicCredentials { password, username })
+    }
+
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+}
+
+impl fmt::Display for BasicCredentials {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let user_pass = format!("{}:{}", self.username, self.password);
+        write!(formatter, "Basic {}", base64::encode(user_pass.as_bytes()))
+    }
+}
+
+impl<'a> TryFrom<&'a AuthItem> for BasicCredentials {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(item: &'a AuthItem) -> Result<Self, Self::Error> {
+        let token68 = item.token68.as_ref().ok_or(InvalidTypedHeader)?;
+        let user_pass = base64::decode(token68).map_err(|_| InvalidTypedHeader)?;
+        let user_pass = String::from_utf8(user_pass).map_err(|_| InvalidTypedHeader)?;
+        let mut parts = user_pass.splitn(2, ':');
+        let username = parts.next().unwrap();
+        let password = parts.next().ok_or(InvalidTypedHeader)?;
+
+        BasicCredentials::new(username, password)
+    }
+}
+
+/// Credentials for the `"Digest"` authentication scheme as described by
+/// [RFC7616](https://tools.ietf.org/html/rfc7616#section-3.4).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DigestCredentials {
+    algorithm: DigestAlgorithm,
+    cnonce: Option<String>,
+    nonce: String,
+    nonce_count: Option<u32>,
+    opaque: Option<String>,
+    qop: Option<QualityOfProtection>,
+    realm: String,
+    response: String,
+    uri: String,
+    username: String,
+}
+
+impl DigestCredentials {
+    /// Constructs new digest credentials from the given parameters. The credentials will use the
+    /// MD5 algorithm and will not specify a quality of protection by default.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if any of the given values contain control characters or if the
+    /// response is not a lowercase hexadecimal string.
+    pub fn new(
+        username: &str,
+        realm: &str,
+        nonce: &str,
+        uri: &str,
+        response: &str,
+    ) -> Result<Self, InvalidTypedHeader> {
+        if !is_auth_parameter_value(username)
+            || !is_auth_parameter_value(realm)
+            || !is_auth_parameter_value(nonce)
+            || !is_auth_parameter_value(uri)
+            || response.is_empty()
+            || !response.bytes().all(|b| match b {
+                b'0'...b'9' | b'a'...b'f' => true,
+                _ => false,
+            })
+        {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(DigestCredentials {
+            algorithm: DigestAlgorithm::MD5,
+            cnonce: None,
+            nonce: nonce.to_string(),
+            nonce_count: None,
+            opaque: None,
+            qop: None,
+            realm: realm.to_string(),
+            response: response.to_string(),
+            uri: uri.to_string(),
+            username: username.to_string(),
+        })
+    }
+
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    pub fn algorithm_mut(&mut self) -> &mut DigestAlgorithm {
+        &mut self.algorithm
+    }
+
+    pub fn cnonce(&self) -> Option<&str> {
+        self.cnonce.as_ref().map(|cnonce| cnonce.as_str())
+    }
+
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    /// Returns the number of requests, including this one, that the client has sent using the
+    /// nonce of these credentials.
+    pub fn nonce_count(&self) -> Option<u32> {
+        self.nonce_count
+    }
+
+    pub fn opaque(&self) -> Option<&str> {
+        self.opaque.as_ref().map(|opaque| opaque.as_str())
+    }
+
+    pub fn qop(&self) -> Option<QualityOfProtection> {
+        self.qop
+    }
+
+    pub fn realm(&self) -> &str {
+        &self.realm
+    }
+
+    /// Returns the hexadecimal digest proving that the client knows the password.
+    pub fn response(&self) -> &str {
+        &self.response
+    }
+
+    /// Sets the quality of protection along with the client nonce and the nonce count that are
+    /// required with it. Passing `None` removes all three.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the client nonce contains control characters.
+    pub fn set_qop(
+        &mut self,
+        qop: Option<(QualityOfProtection, &str, u32)>,
+    ) -> Result<(), InvalidTypedHeader> {
+        match qop {
+            Some((qop, cnonce, nonce_count)) => {
+                if !is_auth_parameter_value(cnonce) {
+                    return Err(InvalidTypedHeader);
+                }
+
+                self.cnonce = Some(cnonce.to_string());
+                self.nonce_count = Some(nonce_count);
+                self.qop = Some(qop);
+            }
+            None => {
+                self.cnonce = None;
+                self.nonce_count = None;
+                self.qop = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the opaque data that was given in the challenge.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the opaque data contains control characters.
+    pub fn set_opaque(&mut self, opaque: Option<&str>) -> Result<(), InvalidTypedHeader> {
+        if let Some(opaque) = opaque {
+            if !is_auth_parameter_value(opaque) {
+                return Err(InvalidTypedHeader);
+            }
+        }
+
+        self.opaque = opaque.map(|opaque| opaque.to_string());
+        Ok(())
+    }
+
+    /// Returns the request URI as given by the client, which should match that of the request.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+}
+
+impl fmt::Display for DigestCredentials {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let nonce_count = self
+            .nonce_count
+            .map(|nonce_count| format!("{:08x}", nonce_count));
+
+        formatter.write_str(&write_auth_item(
+            "Digest",
+            &[
+                ("username", Some(&self.username), true),
+                ("realm", Some(&self.realm), true),
+                ("nonce", Some(&self.nonce), true),
+                ("uri", Some(&self.uri), true),
+                ("response", Some(&self.response), true),
+                ("algorithm", Some(self.algorithm.as_str()), false),
+                ("cnonce", self.cnonce(), true),
+                ("opaque", self.opaque(), true),
+                ("qop", self.qop.as_ref().map(|qop| qop.as_str()), false),
+                ("nc", nonce_count.as_ref().map(|nc| nc.as_str()), false),
+            ],
+        ))
+    }
+}
+
+impl<'a> TryFrom<&'a AuthItem> for DigestCredentials {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(item: &'a AuthItem) -> Result<Self, Self::Error> {
+        if item.token68.is_some() {
+            return Err(InvalidTypedHeader);
+        }
+
+        let mut credentials = DigestCredentials::new(
+            item.required_parameter("username")?,
+            item.required_parameter("realm")?,
+            item.required_parameter("nonce")?,
+            item.required_parameter("uri")?,
+            &item.required_parameter("response")?.to_lowercase(),
+        )?;
+
+        if let Some(algorithm) = item.parameter("algorithm")? {
+            credentials.algorithm = DigestAlgorithm::try_from(algorithm)?;
+        }
+
+        credentials.set_opaque(item.parameter("opaque")?)?;
+
+        if let Some(qop) = item.parameter("qop")? {
+            let qop = QualityOfProtection::try_from(qop)?;
+            let cnonce = item.required_parameter("cnonce")?;
+            let nonce_count = item.required_parameter("nc")?;
+
+            if nonce_count.len() != 8 {
+                return Err(InvalidTypedHeader);
+            }
+
+            let nonce_count =
+                u32::from_str_radix(nonce_count, 16).map_err(|_| InvalidTypedHeader)?;
+            credentials.set_qop(Some((qop, cnonce, nonce_count)))?;
+        }
+
+        Ok(credentials)
+    }
+}
+
+fn credentials_from_header_raw(header: &[HeaderValue]) -> Result<Credentials, InvalidTypedHeader> {
+    let items = parse_auth_items(header)?;
+
+    if items.len() != 1 {
+        return Err(InvalidTypedHeader);
+    }
+
+    let item = &items[0];
+
+    if item.scheme.eq_ignore_ascii_case("Basic") {
+        BasicCredentials::try_from(item).map(Credentials::Basic)
+    } else if item.scheme.eq_ignore_ascii_case("Digest") {
+        DigestCredentials::try_from(item).map(Credentials::Digest)
+    } else {
+        Err(InvalidTypedHeader)
+    }
+}
+
+fn credentials_to_header_raw(credentials: &Credentials) -> Vec<HeaderValue> {
+    // Unsafe Justification
+    //
+    // In order for this to be safe, we must ensure that the value contains no unprintable ASCII-US
+    // characters and that all linebreaks of the form `"\r\n"` are followed by a space or tab. Basic
+    // credentials are serialized using base64, and all digest parameter values are checked for
+    // control characters on construction, so no linebreaks can be introduced.
+
+    vec![unsafe { HeaderValue::from_str_unchecked(credentials.to_string()) }]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn header(values: &[&str]) -> Vec<HeaderValue> {
+        values
+            .iter()
+            .map(|value| HeaderValue::try_from(*value).unwrap())
+            .collect()
+    }
+
+    fn round_trip(value: &str) -> Credentials {
+        let raw_header = header(&[value]);
+        let typed_header = Authorization::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.to_header_raw(), raw_header);
+        typed_header.0
+    }
+
+    #[test]
+    fn test_authorization_basic() {
+        assert_eq!(
+            round_trip("Basic dXNlcjpwYXNz"),
+            Credentials::Basic(BasicCredentials::new("user", "pass").unwrap())
+        );
+
+        let credentials = round_trip("Basic dXNlcjpwYTpzcw==");
+        assert_eq!(credentials.username(), "user");
+
+        match credentials {
+            Credentials::Basic(ref credentials) => assert_eq!(credentials.password(), "pa:ss"),
+            _ => panic!("expected basic credentials"),
+        }
+    }
+
+    #[test]
+    fn test_authorization_digest() {
+        let credentials = round_trip(
+            "Digest username=\"user\", realm=\"example\", nonce=\"abc\", \
+             uri=\"rtsp://example.com/movie\", response=\"0123456789abcdef\", algorithm=MD5",
+        );
+        assert_eq!(
+            credentials,
+            Credentials::Digest(
+                DigestCredentials::new(
+                    "user",
+                    "example",
+                    "abc",
+                    "rtsp://example.com/movie",
+                    "0123456789abcdef"
+                )
+                .unwrap()
+            )
+        );
+
+        let credentials = round_trip(
+            "Digest username=\"user\", realm=\"example\", nonce=\"abc\", \
+             uri=\"rtsp://example.com/movie\", response=\"0123456789abcdef\", algorithm=SHA-256, \
+             cnonce=\"def\", opaque=\"xyz\", qop=auth-int, nc=0000000a",
+        );
+
+        match credentials {
+            Credentials::Digest(ref credentials) => {
+                assert_eq!(credentials.algorithm(), DigestAlgorithm::SHA256);
+                assert_eq!(credentials.cnonce(), Some("def"));
+                assert_eq!(credentials.nonce_count(), Some(10));
+                assert_eq!(credentials.opaque(), Some("xyz"));
+                assert_eq!(credentials.qop(), Some(QualityOfProtection::AuthInt));
+            }
+            _ => panic!("expected digest credentials"),
+        }
+
+        let raw_header = header(&[
+            "digest USERNAME=user, realm=example, nonce=abc, uri=\"rtsp://example.com/movie\", \
+             response=ABCDEF",
+        ]);
+        match Authorization::try_from_header_raw(&raw_header).unwrap().0 {
+            Credentials::Digest(ref credentials) => assert_eq!(credentials.response(), "abcdef"),
+            _ => panic!("expected digest credentials"),
+        }
+    }
+
+    #[test]
+    fn test_proxy_authorization() {
+        let raw_header = header(&["Basic dXNlcjpwYXNz"]);
+        let typed_header = ProxyAuthorization::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.to_header_raw(), raw_header);
+        assert_eq!(typed_header.0.username(), "user");
+    }
+
+    #[test]
+    fn test_authorization_invalid() {
+        assert!(Authorization::try_from_header_raw(&[]).is_err());
+        assert!(ProxyAuthorization::try_from_header_raw(&[]).is_err());
+
+        let digest = "Digest username=user, realm=example, nonce=abc, uri=movie";
+
+        for value in &[
+            "".to_string(),
+            "Bearer abc".to_string(),
+            "Basic".to_string(),
+            "Basic !!!".to_string(),
+            "Basic dXNlcg==".to_string(),
+            "Basic /zph".to_string(),
+            "Basic realm=example".to_string(),
+            "Basic dXNlcjpwYXNz, Basic dXNlcjpwYXNz".to_string(),
+            "Digest abc".to_string(),
+            "Digest username=user, realm=example, nonce=abc, response=abc".to_string(),
+            format!("{}, response=xyz", digest),
+            format!("{}, response=abc, algorithm=SHA-512-256", digest),
+            format!("{}, response=abc, qop=auth, nc=00000001", digest),
+            format!(
+                "{}, response=abc, qop=x-unknown, cnonce=def, nc=00000001",
+                digest
+            ),
+            format!("{}, response=abc, qop=auth, cnonce=def, nc=1", digest),
+            format!(
+                "{}, response=abc, qop=auth, cnonce=def, nc=0000000g",
+                digest
+            ),
+        ] {
+            let raw_header = header(&[value]);
+            assert!(Authorization::try_from_header_raw(&raw_header).is_err());
+            assert!(ProxyAuthorization::try_from_header_raw(&raw_header).is_err());
+        }
+
+        let raw_header = header(&["Basic dXNlcjpwYXNz", "Basic dXNlcjpwYXNz"]);
+        assert!(Authorization::try_from_header_raw(&raw_header).is_err());
+
+        assert!(BasicCredentials::new("us:er", "pass").is_err());
+        assert!(BasicCredentials::new("user", "pa\nss").is_err());
+        assert!(DigestCredentials::new("user", "example", "abc", "movie", "").is_err());
+        assert!(DigestCredentials::new("user", "example", "abc", "movie", "ABC").is_err());
+        assert!(
+            DigestCredentials::new("user", "example", "abc", "movie", "abc")
+                .unwrap()
+                .set_qop(Some((QualityOfProtection::Auth, "\r\n", 1)))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,543 @@
+use base64;
+use itertools::Itertools;
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+use std::fmt;
+
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use syntax::{is_token, split_unquoted, trim_whitespace};
+
+/// The `"Accept-Credentials"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.2).
+///
+/// A client sends this header to a proxy to indicate which servers it trusts when the proxy
+/// connects to them using TLS.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum AcceptCredentials {
+    /// The proxy may trust any server that it has verified itself.
+    Any,
+
+    /// The proxy may trust any server that it has verified itself, and should provide the
+    /// credentials of the servers it connects to in a `"Connection-Credentials"` header.
+    Proxy,
+
+    /// Only the servers whose credentials are listed are trusted. If the list is empty, the proxy
+    /// must provide the credentials of each server before it is trusted.
+    User(Vec<CredentialInfo>),
+
+    /// A decision that is not standardized. Any values following the decision are not retained.
+    Extension(CredentialToken),
+}
+
+impl TypedHeader for AcceptCredentials {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::AcceptCredentials
+    }
+
+    /// Converts the [`AcceptCredentials`] type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::{AcceptCredentials, CredentialInfo};
+    ///
+    /// let typed_header = AcceptCredentials::User(vec![
+    ///     CredentialInfo::sha256("rtsps://example.com/", b"certificate").unwrap(),
+    /// ]);
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         "User \"rtsps://example.com/\";sha-256;\
+    ///          A9Zt0Ig1wco/EozOrNHzGslBYwlrIPRFroQoW8CDLXI="
+    ///     ).unwrap()
+    /// ];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        use self::AcceptCredentials::*;
+
+        // Unsafe Justification
+        //
+        // In order for this to be safe, we must ensure that the value contains no unprintable
+        // ASCII-US characters and no linebreaks. The decisions are tokens, the URIs of the
+        // credential information are validated on construction, and the hashes are encoded using
+        // base64.
+
+        let value = match self {
+            Any => "Any".to_string(),
+            Proxy => "Proxy".to_string(),
+            User(credentials) if credentials.is_empty() => "User".to_string(),
+            User(credentials) => format!("User {}", credentials.iter().join(", ")),
+            Extension(decision) => decision.to_string(),
+        };
+
+        vec![unsafe { HeaderValue::from_str_unchecked(value) }]
+    }
+
+    /// Converts the raw header values to the [`AcceptCredentials`] header type. Based on the syntax
+    /// provided by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the
+    /// following syntax:
+    ///
+    /// ```text
+    /// Accept-Credentials = "Accept-Credentials" HCOLON cred-decision
+    /// cred-decision = ("User" [LWS cred-info])
+    ///               / "Proxy"
+    ///               / "Any"
+    ///               / (token [LWS 1*header-value])
+    /// cred-info = cred-info-data *(COMMA cred-info-data)
+    /// cred-info-data = DQUOTE absolute-URI DQUOTE SEMI hash-alg SEMI base64
+    /// hash-alg = "sha-256" / extension-alg
+    /// extension-alg = token
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::AcceptCredentials;
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         "User \"rtsps://example.com/\";sha-256;\
+    ///          A9Zt0Ig1wco/EozOrNHzGslBYwlrIPRFroQoW8CDLXI="
+    ///     ).unwrap()
+    /// ];
+    ///
+    /// match AcceptCredentials::try_from_header_raw(&raw_header).unwrap() {
+    ///     AcceptCredentials::User(credentials) => {
+    ///         assert_eq!(credentials[0].uri(), "rtsps://example.com/");
+    ///         assert!(credentials[0].matches(b"certificate"));
+    ///     }
+    ///     _ => panic!("expected user decision"),
+    /// }
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("Proxy \"rtsps://example.com/\"").unwrap()];
+    ///
+    /// assert!(AcceptCredentials::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        if header.len() != 1 {
+            return Err(InvalidTypedHeader);
+        }
+
+        let value = trim_whitespace(header[0].as_str());
+        let (decision, rest) = match value.find(|c| c == ' ' || c == '\t' || c == '\r') {
+            Some(index) => (&value[..index], trim_whitespace(&value[index..])),
+            None => (value, ""),
+        };
+
+        match decision.to_ascii_lowercase().as_str() {
+            "any" if rest.is_empty() => Ok(AcceptCredentials::Any),
+            "proxy" if rest.is_empty() => Ok(AcceptCredentials::Proxy),
+            "any" | "proxy" => Err(InvalidTypedHeader),
+            "user" if rest.is_empty() => Ok(AcceptCredentials::User(Vec::new())),
+            "user" => split_unquoted(rest, ',')
+                .into_iter()
+                .map(|credential| CredentialInfo::try_from(trim_whitespace(credential)))
+                .collect::<Result<Vec<_>, _>>()
+                .map(AcceptCredentials::User),
+            _ => Ok(AcceptCredentials::Extension(CredentialToken::try_from(
+                decision,
+            )?)),
+        }
+    }
+}
+
+/// The `"Connection-Credentials"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.12).
+///
+/// A proxy sends this header to a client to provide the certificate of a server it has connected
+/// to using TLS, so that the client can decide whether to trust it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ConnectionCredentials {
+    certificate: Vec<u8>,
+    uri: String,
+}
+
+impl ConnectionCredentials {
+    /// Constructs a new header with the URI of the server and its DER-encoded certificate.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the URI is not valid as described by [`CredentialInfo::new`].
+    pub fn new<S>(uri: S, certificate: Vec<u8>) -> Result<Self, InvalidTypedHeader>
+    where
+        S: Into<String>,
+    {
+        let uri = uri.into();
+
+        if !is_credential_uri(&uri) {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(ConnectionCredentials { certificate, uri })
+    }
+
+    /// Returns the DER-encoded certificate of the server.
+    pub fn certificate(&self) -> &[u8] {
+        &self.certificate
+    }
+
+    /// Returns the credential information that a client can send in an `"Accept-Credentials"`
+    /// header to indicate that it trusts this server.
+    pub fn credential_info(&self) -> CredentialInfo {
+        CredentialInfo::sha256(self.uri.as_str(), &self.certificate)
+            .expect("URI should have been validated")
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+}
+
+impl fmt::Display for ConnectionCredentials {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "\"{}\";{}",
+            self.uri,
+            base64::encode(&self.certificate)
+        )
+    }
+}
+
+impl TypedHeader for ConnectionCredentials {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::ConnectionCredentials
+    }
+
+    /// Converts the [`ConnectionCredentials`] type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::ConnectionCredentials;
+    ///
+    /// let typed_header =
+    ///     ConnectionCredentials::new("rtsps://example.com/", b"certificate".to_vec()).unwrap();
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from("\"rtsps://example.com/\";Y2VydGlmaWNhdGU=").unwrap()
+    /// ];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        // Unsafe Justification
+        //
+        // In order for this to be safe, we must ensure that the value contains no unprintable
+        // ASCII-US characters and no linebreaks. The URI is validated on construction and the
+        // certificate is encoded using base64.
+
+        vec![unsafe { HeaderValue::from_str_unchecked(self.to_string()) }]
+    }
+
+    /// Converts the raw header values to the [`ConnectionCredentials`] header type. Based on the
+    /// syntax provided by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header
+    /// has the following syntax:
+    ///
+    /// ```text
+    /// Connection-Credentials = "Connection-Credentials" HCOLON cred-chain
+    /// cred-chain = DQUOTE absolute-URI DQUOTE SEMI base64
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::ConnectionCredentials;
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from("\"rtsps://example.com/\"; Y2VydGlmaWNhdGU=").unwrap()
+    /// ];
+    /// let typed_header = ConnectionCredentials::try_from_header_raw(&raw_header).unwrap();
+    ///
+    /// assert_eq!(typed_header.uri(), "rtsps://example.com/");
+    /// assert_eq!(typed_header.certificate(), b"certificate");
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("rtsps://example.com/;Y2VydGlmaWNhdGU=").unwrap()];
+    ///
+    /// assert!(ConnectionCredentials::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        if header.len() != 1 {
+            return Err(InvalidTypedHeader);
+        }
+
+        let parts = split_unquoted(header[0].as_str(), ';');
+
+        if parts.len() != 2 {
+            return Err(InvalidTypedHeader);
+        }
+
+        let uri = parse_quoted_uri(trim_whitespace(parts[0]))?;
+        let certificate =
+            base64::decode(trim_whitespace(parts[1])).map_err(|_| InvalidTypedHeader)?;
+
+        ConnectionCredentials::new(uri, certificate)
+    }
+}
+
+/// The credentials of a single server that a client trusts, identified by the hash of its
+/// certificate.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CredentialInfo {
+    algorithm: HashAlgorithm,
+    hash: Vec<u8>,
+    uri: String,
+}
+
+impl CredentialInfo {
+    /// Constructs new credential information from the URI of the server and the hash of its
+    /// certificate computed with the given algorithm.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the URI is empty or contains whitespace, control characters,
+    /// or `'"'`.
+    pub fn new<S>(
+        uri: S,
+        algorithm: HashAlgorithm,
+        hash: Vec<u8>,
+    ) -> Result<Self, InvalidTypedHeader>
+    where
+        S: Into<String>,
+    {
+        let uri = uri.into();
+
+        if !is_credential_uri(&uri) {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(CredentialInfo {
+            algorithm,
+            hash,
+            uri,
+        })
+    }
+
+    /// Constructs new credential information for the server with the given DER-encoded
+    /// certificate, hashed using SHA-256.
+    pub fn sha256<S>(uri: S, certificate: &[u8]) -> Result<Self, InvalidTypedHeader>
+    where
+        S: Into<String>,
+    {
+        CredentialInfo::new(
+            uri,
+            HashAlgorithm::SHA256,
+            Sha256::digest(certificate).to_vec(),
+        )
+    }
+
+    pub fn algorithm(&self) -> &HashAlgorithm {
+        &self.algorithm
+    }
+
+    pub fn hash(&self) -> &[u8] {
+        &self.hash
+    }
+
+    /// Determines whether the given DER-encoded certificate has the hash of this credential
+    /// information. This is always false for hash algorithms that are not standardized.
+    pub fn matches(&self, certificate: &[u8]) -> bool {
+        match self.algorithm {
+            HashAlgorithm::SHA256 => Sha256::digest(certificate).as_slice() == &self.hash[..],
+            HashAlgorithm::Extension(_) => false,
+        }
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+}
+
+impl fmt::Display for CredentialInfo {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "\"{}\";{};{}",
+            self.uri,
+            self.algorithm,
+            base64::encode(&self.hash)
+        )
+    }
+}
+
+impl<'a> TryFrom<&'a str> for CredentialInfo {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let parts = split_unquoted(value, ';');
+
+        if parts.len() != 3 {
+            return Err(InvalidTypedHeader);
+        }
+
+        let uri = parse_quoted_uri(trim_whitespace(parts[0]))?;
+        let algorithm = HashAlgorithm::try_from(trim_whitespace(parts[1]))?;
+        let hash = base64::decode(trim_whitespace(parts[2])).map_err(|_| InvalidTypedHeader)?;
+
+        CredentialInfo::new(uri, algorithm, hash)
+    }
+}
+
+/// The algorithm used to hash a certificate in [`CredentialInfo`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum HashAlgorithm {
+    SHA256,
+
+    /// An algorithm that is not standardized.
+    Extension(CredentialToken),
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HashAlgorithm::SHA256 => "sha-256",
+            HashAlgorithm::Extension(algorithm) => algorithm.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for HashAlgorithm {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value.eq_ignore_ascii_case("sha-256") {
+            Ok(HashAlgorithm::SHA256)
+        } else {
+            Ok(HashAlgorithm::Extension(CredentialToken::try_from(value)?))
+        }
+    }
+}
+
+/// A generic token used by the extension variants of the credential headers.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CredentialToken(String);
+
+impl CredentialToken {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CredentialToken {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for CredentialToken {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if is_token(value) {
+            Ok(CredentialToken(value.to_string()))
+        } else {
+            Err(InvalidTypedHeader)
+        }
+    }
+}
+
+/// Determines whether the URI can be serialized within double quotes.
+fn is_credential_uri(uri: &str) -> bool {
+    !uri.is_empty() && uri.bytes().all(|b| b > b' ' && b < 0x7F && b != b'"')
+}
+
+/// Returns the URI within the double quotes of the given value.
+fn parse_quoted_uri(value: &str) -> Result<&str, InvalidTypedHeader> {
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return Err(InvalidTypedHeader);
+    }
+
+    Ok(&value[1..value.len() - 1])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw(value: &str) -> Vec<HeaderValue> {
+        vec![HeaderValue::try_from(value).unwrap()]
+    }
+
+    #[test]
+    fn test_accept_credentials() {
+        assert_eq!(
+            AcceptCredentials::try_from_header_raw(&raw("any")).unwrap(),
+            AcceptCredentials::Any
+        );
+        assert_eq!(
+            AcceptCredentials::try_from_header_raw(&raw("User")).unwrap(),
+            AcceptCredentials::User(Vec::new())
+        );
+        assert_eq!(
+            AcceptCredentials::try_from_header_raw(&raw("x-trust-all 1")).unwrap(),
+            AcceptCredentials::Extension(CredentialToken::try_from("x-trust-all").unwrap())
+        );
+
+        let typed_header = AcceptCredentials::User(vec![
+            CredentialInfo::sha256("rtsps://a.example.com/", b"a").unwrap(),
+            CredentialInfo::new(
+                "rtsps://b.example.com/",
+                HashAlgorithm::try_from("x-hash").unwrap(),
+                vec![1, 2, 3],
+            )
+            .unwrap(),
+        ]);
+        assert_eq!(
+            AcceptCredentials::try_from_header_raw(&typed_header.to_header_raw()).unwrap(),
+            typed_header
+        );
+    }
+
+    #[test]
+    fn test_accept_credentials_invalid() {
+        for value in &[
+            "",
+            "Any \"rtsps://example.com/\"",
+            "User \"rtsps://example.com/\";sha-256",
+            "User rtsps://example.com/;sha-256;AAAA",
+            "User \"rtsps://example.com/\";sha-256;not base64",
+        ] {
+            assert!(AcceptCredentials::try_from_header_raw(&raw(value)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_connection_credentials() {
+        let typed_header =
+            ConnectionCredentials::new("rtsps://example.com/", b"certificate".to_vec()).unwrap();
+        let credential_info = typed_header.credential_info();
+
+        assert_eq!(credential_info.uri(), "rtsps://example.com/");
+        assert!(credential_info.matches(b"certificate"));
+        assert!(!credential_info.matches(b"other"));
+        assert!(ConnectionCredentials::new("rtsps://example.com/\"", Vec::new()).is_err());
+    }
+}
@@ -1,9 +1,41 @@
+mod allow;
+mod auth_parameters;
+mod authenticate;
+mod authorization;
 mod content_length;
+mod credentials;
 mod cseq;
+mod notify_reason;
+mod pipelined_requests;
 mod public;
+mod range;
+mod rtp_info;
 mod session;
+mod terminate_reason;
+mod transport;
 
+pub use self::allow::Allow;
+pub use self::authenticate::{
+    BasicChallenge, Challenge, DigestAlgorithm, DigestChallenge, ProxyAuthenticate,
+    QualityOfProtection, WWWAuthenticate,
+};
+pub use self::authorization::{
+    Authorization, BasicCredentials, Credentials, DigestCredentials, ProxyAuthorization,
+};
 pub use self::content_length::{ContentLength, MAX_CONTENT_LENGTH};
+pub use self::credentials::{
+    AcceptCredentials, ConnectionCredentials, CredentialInfo, CredentialToken, HashAlgorithm,
+};
 pub use self::cseq::{CSeq, MAX_CSEQ};
+pub use self::notify_reason::{NotifyReason, ReasonToken};
+pub use self::pipelined_requests::{PipelinedRequests, MAX_PIPELINED_REQUESTS_ID};
 pub use self::public::Public;
+pub use self::range::{ExtensionRange, NPTTime, Range, SMPTETime, SMPTEType, TimeRange};
+pub use self::rtp_info::{RTPInfo, RTPInfoExtension, RTPInfoSSRC, RTPInfoStream};
 pub use self::session::Session;
+pub use self::terminate_reason::{TerminateReason, TerminationReason};
+pub use self::transport::{
+    ConnectionSetup, ConnectionType, ExtensionAddress, ExtensionParameter, HostPort,
+    LowerTransport, OtherTransportID, RTPProfile, Transport, TransportAddress, TransportID,
+    TransportMode, TransportParameter, TransportSpec, TransportToken,
+};
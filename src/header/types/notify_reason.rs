@@ -0,0 +1,206 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use syntax::{is_token, trim_whitespace};
+
+/// The `"Notify-Reason"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.32).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum NotifyReason {
+    /// The end of a media stream has been reached.
+    EndOfStream,
+
+    /// The properties of the media have changed.
+    MediaPropertiesUpdate,
+
+    /// The scale of the media has changed.
+    ScaleChange,
+
+    /// A reason that is not standardized.
+    Extension(ReasonToken),
+}
+
+impl NotifyReason {
+    pub fn as_str(&self) -> &str {
+        use self::NotifyReason::*;
+
+        match self {
+            EndOfStream => "end-of-stream",
+            MediaPropertiesUpdate => "media-properties-update",
+            ScaleChange => "scale-change",
+            Extension(reason) => reason.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for NotifyReason {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for NotifyReason {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "end-of-stream" => Ok(NotifyReason::EndOfStream),
+            "media-properties-update" => Ok(NotifyReason::MediaPropertiesUpdate),
+            "scale-change" => Ok(NotifyReason::ScaleChange),
+            _ => Ok(NotifyReason::Extension(ReasonToken::try_from(value)?)),
+        }
+    }
+}
+
+impl TypedHeader for NotifyReason {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::NotifyReason
+    }
+
+    /// Converts the [`NotifyReason`] type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::NotifyReason;
+    ///
+    /// let typed_header = NotifyReason::EndOfStream;
+    /// let raw_header = vec![HeaderValue::try_from("end-of-stream").unwrap()];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        // Unsafe Justification
+        //
+        // In order for this to be safe, we must ensure that the value contains no unprintable
+        // ASCII-US characters and no linebreaks. The standardized reasons are fixed tokens and
+        // extension reasons can only be constructed from valid tokens.
+
+        vec![unsafe { HeaderValue::from_str_unchecked(self.as_str()) }]
+    }
+
+    /// Converts the raw header values to the [`NotifyReason`] header type. Based on the syntax
+    /// provided by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the
+    /// following syntax:
+    ///
+    /// ```text
+    /// Notify-Reason = "Notify-Reason" HCOLON Notify-Reas-val
+    /// Notify-Reas-val = "end-of-stream"
+    ///                 / "media-properties-update"
+    ///                 / "scale-change"
+    ///                 / Notify-Reason-extension
+    /// Notify-Reason-extension = token
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::NotifyReason;
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("scale-change").unwrap()];
+    ///
+    /// assert_eq!(
+    ///     NotifyReason::try_from_header_raw(&raw_header).unwrap(),
+    ///     NotifyReason::ScaleChange
+    /// );
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("not a token").unwrap()];
+    ///
+    /// assert!(NotifyReason::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        if header.len() != 1 {
+            return Err(InvalidTypedHeader);
+        }
+
+        NotifyReason::try_from(trim_whitespace(header[0].as_str()))
+    }
+}
+
+/// A generic token used by the extension variants of the reason headers.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ReasonToken(String);
+
+impl ReasonToken {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ReasonToken {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ReasonToken {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if is_token(value) {
+            Ok(ReasonToken(value.to_string()))
+        } else {
+            Err(InvalidTypedHeader)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(value: &str) -> NotifyReason {
+        let raw_header = vec![HeaderValue::try_from(value).unwrap()];
+        let typed_header = NotifyReason::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.to_header_raw(), raw_header);
+        typed_header
+    }
+
+    #[test]
+    fn test_notify_reason() {
+        assert_eq!(round_trip("end-of-stream"), NotifyReason::EndOfStream);
+        assert_eq!(
+            round_trip("media-properties-update"),
+            NotifyReason::MediaPropertiesUpdate
+        );
+        assert_eq!(round_trip("scale-change"), NotifyReason::ScaleChange);
+        assert_eq!(
+            round_trip("x-custom"),
+            NotifyReason::Extension(ReasonToken::try_from("x-custom").unwrap())
+        );
+
+        let raw_header = vec![HeaderValue::try_from(" End-Of-Stream ").unwrap()];
+        assert_eq!(
+            NotifyReason::try_from_header_raw(&raw_header).unwrap(),
+            NotifyReason::EndOfStream
+        );
+    }
+
+    #[test]
+    fn test_notify_reason_invalid() {
+        assert!(NotifyReason::try_from_header_raw(&[]).is_err());
+
+        for value in &["", "not a token", "end-of-stream;x", "\u{e9}"] {
+            let raw_header = vec![HeaderValue::try_from(*value).unwrap()];
+            assert!(NotifyReason::try_from_header_raw(&raw_header).is_err());
+        }
+
+        let raw_header = vec![
+            HeaderValue::try_from("end-of-stream").unwrap(),
+            HeaderValue::try_from("scale-change").unwrap(),
+        ];
+        assert!(NotifyReason::try_from_header_raw(&raw_header).is_err());
+    }
+}
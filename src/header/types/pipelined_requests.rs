@@ -0,0 +1,171 @@
+use rand::{thread_rng, Rng};
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use syntax::trim_whitespace;
+
+pub const MAX_PIPELINED_REQUESTS_ID: u32 = 99_999_999;
+
+/// The `Pipelined-Requests` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.33).
+///
+/// All requests with the same identifier are executed in the context of the session created by the
+/// first successful `SETUP` request among them. This allows a client to send a `SETUP` request and
+/// the requests that depend on it, such as `PLAY`, before the session ID is known.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PipelinedRequests(u32);
+
+impl PipelinedRequests {
+    /// Returns a randomly generated identifier.
+    pub fn random() -> Self {
+        PipelinedRequests(thread_rng().gen_range(0, MAX_PIPELINED_REQUESTS_ID + 1))
+    }
+}
+
+impl Deref for PipelinedRequests {
+    type Target = u32;
+
+    fn deref(&self) -> &u32 {
+        &self.0
+    }
+}
+
+impl TryFrom<u32> for PipelinedRequests {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        if value > MAX_PIPELINED_REQUESTS_ID {
+            Err(InvalidTypedHeader)
+        } else {
+            Ok(PipelinedRequests(value))
+        }
+    }
+}
+
+impl TypedHeader for PipelinedRequests {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::PipelinedRequests
+    }
+
+    /// Converts the `PipelinedRequests` type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::PipelinedRequests;
+    ///
+    /// let typed_header = PipelinedRequests::try_from(7654).unwrap();
+    /// let raw_header = vec![HeaderValue::try_from("7654").unwrap()];
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        // Unsafe Justification
+        //
+        // In order for this to be safe, we must ensure that `value` contains no unprintable
+        // ASCII-US characters and that all linebreaks of the form `"\r\n"` are followed by a space
+        // or tab. Since [`PipelinedRequests`] serializes into a number, it satisfies the
+        // constraints.
+
+        vec![unsafe { HeaderValue::from_str_unchecked(self.0.to_string().as_str()) }]
+    }
+
+    /// Converts the raw header values to the `PipelinedRequests` header type. Based on the syntax
+    /// provided by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the
+    /// following syntax:
+    ///
+    /// ```text
+    /// DIGIT = %x30-39 ; any US-ASCII digit "0".."9"
+    /// Pipelined-Requests = "Pipelined-Requests" HCOLON startup-id
+    /// startup-id = 1*8DIGIT
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::PipelinedRequests;
+    ///
+    /// let typed_header = PipelinedRequests::try_from(7654).unwrap();
+    /// let raw_header = vec![HeaderValue::try_from("7654").unwrap()];
+    ///
+    /// assert_eq!(
+    ///     PipelinedRequests::try_from_header_raw(&raw_header).unwrap(),
+    ///     typed_header
+    /// );
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("123456789").unwrap()];
+    ///
+    /// assert!(PipelinedRequests::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        if header.len() != 1 {
+            return Err(InvalidTypedHeader);
+        }
+
+        let value = trim_whitespace(header[0].as_str());
+
+        if value.is_empty() || value.len() > 8 || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(InvalidTypedHeader);
+        }
+
+        value
+            .parse::<u32>()
+            .map_err(|_| InvalidTypedHeader)
+            .and_then(PipelinedRequests::try_from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(value: &str) -> PipelinedRequests {
+        let raw_header = vec![HeaderValue::try_from(value).unwrap()];
+        let typed_header = PipelinedRequests::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.to_header_raw(), raw_header);
+        typed_header
+    }
+
+    #[test]
+    fn test_pipelined_requests() {
+        assert_eq!(*round_trip("0"), 0);
+        assert_eq!(*round_trip("7654"), 7654);
+        assert_eq!(*round_trip("99999999"), MAX_PIPELINED_REQUESTS_ID);
+
+        for value in &[" 7654", "7654 ", "\t7654\t", "00007654"] {
+            let raw_header = vec![HeaderValue::try_from(*value).unwrap()];
+            assert_eq!(
+                *PipelinedRequests::try_from_header_raw(&raw_header).unwrap(),
+                7654
+            );
+        }
+    }
+
+    #[test]
+    fn test_pipelined_requests_invalid() {
+        assert!(PipelinedRequests::try_from_header_raw(&[]).is_err());
+        assert!(PipelinedRequests::try_from(MAX_PIPELINED_REQUESTS_ID + 1).is_err());
+
+        for value in &["", " ", "123456789", "-1", "+1", "12 34", "0x10", "\u{664}"] {
+            let raw_header = vec![HeaderValue::try_from(*value).unwrap()];
+            assert!(PipelinedRequests::try_from_header_raw(&raw_header).is_err());
+        }
+
+        let raw_header = vec![
+            HeaderValue::try_from("1").unwrap(),
+            HeaderValue::try_from("2").unwrap(),
+        ];
+        assert!(PipelinedRequests::try_from_header_raw(&raw_header).is_err());
+    }
+}
@@ -1,4 +1,3 @@
-use itertools::Itertools;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::iter::FromIterator;
@@ -49,7 +48,8 @@ impl TypedHeader for Public {
         &HeaderName::Public
     }
 
-    /// Converts the [`Public`] type to raw header values.
+    /// Converts the [`Public`] type to raw header values. The methods are listed in alphabetical
+    /// order.
     ///
     /// # Examples
     ///
@@ -62,13 +62,9 @@ impl TypedHeader for Public {
     /// use rtsp::header::types::Public;
     ///
     /// let typed_header = vec![Method::Play, Method::Setup].into_iter().collect::<Public>();
-    /// let raw_headers = vec![
-    ///     vec![HeaderValue::try_from("PLAY, SETUP").unwrap()],
-    ///     vec![HeaderValue::try_from("SETUP, PLAY").unwrap()]
-    /// ];
+    /// let raw_header = vec![HeaderValue::try_from("PLAY, SETUP").unwrap()];
     ///
-    /// assert!(typed_header.to_header_raw() == raw_headers[0] ||
-    ///         typed_header.to_header_raw() == raw_headers[1]);
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
     /// ```
     fn to_header_raw(&self) -> Vec<HeaderValue> {
         // Unsafe Justification
@@ -76,7 +72,15 @@ impl TypedHeader for Public {
         // Header values must be valid UTF-8, and since we know that the [`Method`] type
         // guarantees valid ASCII-US (with no newlines), it satisfies the constraints.
 
-        let value = self.iter().map(|method| method.as_str()).join(", ");
+        // The methods are sorted so that the serialized header does not depend on the iteration
+        // order of the set.
+
+        let mut methods = self
+            .iter()
+            .map(|method| method.as_str())
+            .collect::<Vec<_>>();
+        methods.sort();
+        let value = methods.join(", ");
         vec![unsafe { HeaderValue::from_str_unchecked(value) }]
     }
 
@@ -0,0 +1,987 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Timelike, Utc};
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use syntax::{is_token, trim_whitespace};
+
+/// The number of nanoseconds in a single second.
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// The `"Range"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.40).
+///
+/// Each variant represents one of the time formats that a range can be specified in. A variant
+/// without a range (e.g. `Range::NPT(None)`) corresponds to the format being given without a value,
+/// such as `"npt"`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Range {
+    /// An absolute range given in UTC time.
+    Clock(Option<TimeRange<DateTime<Utc>>>),
+
+    /// A range given in a time format that is not standardized.
+    Extension(ExtensionRange),
+
+    /// A range given in normal play time, relative to the beginning of the presentation.
+    NPT(Option<TimeRange<NPTTime>>),
+
+    /// A range given in SMPTE timecodes, relative to the beginning of the presentation.
+    SMPTE(SMPTEType, Option<TimeRange<SMPTETime>>),
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Range::Clock(range) => {
+                formatter.write_str("clock")?;
+
+                if let Some(range) = range {
+                    formatter.write_str("=")?;
+                    range.fmt_with(formatter, fmt_utc_time)?;
+                }
+
+                Ok(())
+            }
+            Range::Extension(range) => write!(formatter, "{}", range),
+            Range::NPT(range) => {
+                formatter.write_str("npt")?;
+
+                if let Some(range) = range {
+                    formatter.write_str("=")?;
+                    range.fmt_with(formatter, |formatter, time| write!(formatter, "{}", time))?;
+                }
+
+                Ok(())
+            }
+            Range::SMPTE(smpte_type, range) => {
+                write!(formatter, "{}", smpte_type)?;
+
+                if let Some(range) = range {
+                    formatter.write_str("=")?;
+                    range.fmt_with(formatter, |formatter, time| write!(formatter, "{}", time))?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Range {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let (format, range) = match value.find('=') {
+            Some(index) => (
+                trim_whitespace(&value[..index]),
+                Some(trim_whitespace(&value[index + 1..])),
+            ),
+            None => (trim_whitespace(value), None),
+        };
+        let lowercase_format = format.to_ascii_lowercase();
+
+        match lowercase_format.as_str() {
+            "clock" => Ok(Range::Clock(match range {
+                Some(range) => Some(TimeRange::parse(range, parse_utc_time)?),
+                None => None,
+            })),
+            "npt" => Ok(Range::NPT(match range {
+                Some(range) => Some(TimeRange::parse(range, |time| NPTTime::try_from(time))?),
+                None => None,
+            })),
+            _ if lowercase_format.starts_with("smpte") => {
+                let smpte_type = SMPTEType::try_from(format)?;
+                let range = match range {
+                    Some(range) => Some(TimeRange::parse(range, |time| {
+                        let time = SMPTETime::try_from(time)?;
+
+                        if time.is_valid_for(&smpte_type) {
+                            Ok(time)
+                        } else {
+                            Err(InvalidTypedHeader)
+                        }
+                    })?),
+                    None => None,
+                };
+
+                Ok(Range::SMPTE(smpte_type, range))
+            }
+            _ => Ok(Range::Extension(ExtensionRange::new(format, range)?)),
+        }
+    }
+}
+
+impl TypedHeader for Range {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::Range
+    }
+
+    /// Converts the [`Range`] type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    /// use std::time::Duration;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let typed_header = Range::NPT(Some(TimeRange::From(NPTTime::from(
+    ///     Duration::from_millis(10_500),
+    /// ))));
+    /// let raw_header = vec![HeaderValue::try_from("npt=10.5-").unwrap()];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    ///
+    /// let typed_header = Range::SMPTE(
+    ///     SMPTEType::SMPTE25,
+    ///     Some(TimeRange::FromTo(
+    ///         SMPTETime::new(10, 7, 33, 5, 1).unwrap(),
+    ///         SMPTETime::new(10, 7, 34, 0, 0).unwrap(),
+    ///     )),
+    /// );
+    /// let raw_header = vec![HeaderValue::try_from("smpte-25=10:07:33:05.01-10:07:34").unwrap()];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        // Unsafe Justification
+        //
+        // In order for this to be safe, we must ensure that the value contains no unprintable
+        // ASCII-US characters and no linebreaks. All of the standardized formats serialize to
+        // digits and a fixed set of punctuation. Extension formats can only be constructed after
+        // validating that the format name is a token and that the value consists of printable
+        // characters and quoted strings.
+
+        vec![unsafe { HeaderValue::from_str_unchecked(self.to_string()) }]
+    }
+
+    /// Converts the raw header values to the [`Range`] header type. Based on the syntax provided by
+    /// [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the following
+    /// syntax:
+    ///
+    /// ```text
+    /// Range = "Range" HCOLON ranges-spec
+    /// ranges-spec = npt-range / utc-range / smpte-range / range-ext
+    /// range-ext = extension-format ["=" range-value]
+    /// range-value = 1*(unreserved / quoted-string / ":")
+    /// extension-format = token
+    ///
+    /// npt-range = "npt" [EQUAL npt-range-spec]
+    /// npt-range-spec = ( npt-time "-" [ npt-time ] ) / ( "-" npt-time )
+    /// npt-time = "now" / npt-sec / npt-hhmmss / npt-hhmmss-comp
+    /// npt-sec = 1*19DIGIT [ "." 1*9DIGIT ]
+    /// npt-hhmmss = npt-hh ":" npt-mm ":" npt-ss [ "." 1*9DIGIT ]
+    /// npt-hh = 2*19DIGIT ; any positive number
+    /// npt-mm = 2*2DIGIT ; 0-59
+    /// npt-ss = 2*2DIGIT ; 0-59
+    /// npt-hhmmss-comp = npt-hh-comp ":" npt-mm-comp ":" npt-ss-comp [ "." 1*9DIGIT ]
+    /// npt-hh-comp = 1*19DIGIT
+    /// npt-mm-comp = 1*2DIGIT
+    /// npt-ss-comp = 1*2DIGIT
+    ///
+    /// smpte-range = smpte-type [EQUAL smpte-range-spec]
+    /// smpte-range-spec = ( smpte-time "-" [ smpte-time ] ) / ( "-" smpte-time )
+    /// smpte-type = "smpte" / "smpte-30-drop" / "smpte-25" / smpte-type-extension
+    /// smpte-type-extension = "smpte" token
+    /// smpte-time = 1*2DIGIT ":" 1*2DIGIT ":" 1*2DIGIT [ ":" 1*2DIGIT [ "." 1*2DIGIT ] ]
+    ///
+    /// utc-range = "clock" [EQUAL utc-range-spec]
+    /// utc-range-spec = ( utc-time "-" [ utc-time ] ) / ( "-" utc-time )
+    /// utc-time = utc-date "T" utc-clock "Z"
+    /// utc-date = 8DIGIT
+    /// utc-clock = 6DIGIT [ "." 1*9DIGIT ]
+    /// ```
+    ///
+    /// Format names are matched case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    /// use std::time::Duration;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("npt=00:01:02.5-now").unwrap()];
+    /// let typed_header = Range::try_from_header_raw(&raw_header).unwrap();
+    ///
+    /// assert_eq!(
+    ///     typed_header,
+    ///     Range::NPT(Some(TimeRange::FromTo(
+    ///         NPTTime::from(Duration::from_millis(62_500)),
+    ///         NPTTime::Now,
+    ///     )))
+    /// );
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from("clock=19961108T142300Z-19961108T143520.25Z").unwrap()
+    /// ];
+    /// let typed_header = Range::try_from_header_raw(&raw_header).unwrap();
+    ///
+    /// match typed_header {
+    ///     Range::Clock(Some(range)) => {
+    ///         assert_eq!(range.duration(), Some(Duration::from_millis(740_250)));
+    ///     }
+    ///     _ => panic!("expected clock range"),
+    /// }
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("npt=00:60:00-").unwrap()];
+    ///
+    /// assert!(Range::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        if header.len() != 1 {
+            return Err(InvalidTypedHeader);
+        }
+
+        Range::try_from(header[0].as_str())
+    }
+}
+
+/// A range of time in a given format. At least one end of the range is always present.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TimeRange<T> {
+    /// A range starting at the given time and continuing until the end of the presentation.
+    From(T),
+
+    /// A range starting at the first time and ending at the second time.
+    FromTo(T, T),
+
+    /// A range starting at the beginning of the presentation and ending at the given time.
+    To(T),
+}
+
+impl<T> TimeRange<T> {
+    /// Returns the end of the range, if present.
+    pub fn end(&self) -> Option<&T> {
+        match self {
+            TimeRange::From(_) => None,
+            TimeRange::FromTo(_, end) | TimeRange::To(end) => Some(end),
+        }
+    }
+
+    /// Converts the range into a range of another time format.
+    pub fn map<F, U>(self, mut f: F) -> TimeRange<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        match self {
+            TimeRange::From(start) => TimeRange::From(f(start)),
+            TimeRange::FromTo(start, end) => TimeRange::FromTo(f(start), f(end)),
+            TimeRange::To(end) => TimeRange::To(f(end)),
+        }
+    }
+
+    /// Returns the start of the range, if present.
+    pub fn start(&self) -> Option<&T> {
+        match self {
+            TimeRange::From(start) | TimeRange::FromTo(start, _) => Some(start),
+            TimeRange::To(_) => None,
+        }
+    }
+
+    fn fmt_with<F>(&self, formatter: &mut fmt::Formatter, mut f: F) -> fmt::Result
+    where
+        F: FnMut(&mut fmt::Formatter, &T) -> fmt::Result,
+    {
+        if let Some(start) = self.start() {
+            f(formatter, start)?;
+        }
+
+        formatter.write_str("-")?;
+
+        if let Some(end) = self.end() {
+            f(formatter, end)?;
+        }
+
+        Ok(())
+    }
+
+    fn parse<F>(value: &str, mut f: F) -> Result<Self, InvalidTypedHeader>
+    where
+        F: FnMut(&str) -> Result<T, InvalidTypedHeader>,
+    {
+        // None of the time formats contain a `'-'`, so the first one must be the range separator.
+
+        let index = value.find('-').ok_or(InvalidTypedHeader)?;
+        let start = &value[..index];
+        let end = &value[index + 1..];
+
+        match (start.is_empty(), end.is_empty()) {
+            (false, false) => Ok(TimeRange::FromTo(f(start)?, f(end)?)),
+            (false, true) => Ok(TimeRange::From(f(start)?)),
+            (true, false) => Ok(TimeRange::To(f(end)?)),
+            (true, true) => Err(InvalidTypedHeader),
+        }
+    }
+}
+
+impl TimeRange<DateTime<Utc>> {
+    /// Returns the duration of the range if both ends are present and the end is not before the
+    /// start.
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            TimeRange::FromTo(start, end) => end.signed_duration_since(*start).to_std().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl TimeRange<NPTTime> {
+    /// Returns the duration of the range if both ends are present, neither end is `"now"`, and the
+    /// end is not before the start.
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            TimeRange::FromTo(start, end) => {
+                let start = start.to_duration()?;
+                let end = end.to_duration()?;
+
+                if end >= start {
+                    Some(end - start)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A single point in normal play time.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NPTTime {
+    /// The current instant of a live presentation.
+    Now,
+
+    /// The given offset from the beginning of the presentation.
+    Time(Duration),
+}
+
+impl NPTTime {
+    /// Returns the offset from the beginning of the presentation, or `None` if the time is `"now"`.
+    pub fn to_duration(&self) -> Option<Duration> {
+        match self {
+            NPTTime::Now => None,
+            NPTTime::Time(duration) => Some(*duration),
+        }
+    }
+}
+
+impl From<Duration> for NPTTime {
+    fn from(value: Duration) -> Self {
+        NPTTime::Time(value)
+    }
+}
+
+impl fmt::Display for NPTTime {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NPTTime::Now => formatter.write_str("now"),
+            NPTTime::Time(duration) => {
+                write!(formatter, "{}", duration.as_secs())?;
+                fmt_fraction(formatter, duration.subsec_nanos())
+            }
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for NPTTime {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value == "now" {
+            return Ok(NPTTime::Now);
+        }
+
+        let (value, nanos) = split_fraction(value)?;
+        let parts = value.split(':').collect::<Vec<&str>>();
+
+        let seconds = match parts.len() {
+            1 => parse_digits(parts[0], 1, 19)?,
+            3 => {
+                let hours: u64 = parse_digits(parts[0], 1, 19)?;
+                let minutes: u64 = parse_digits(parts[1], 1, 2)?;
+                let seconds: u64 = parse_digits(parts[2], 1, 2)?;
+
+                if minutes > 59 || seconds > 59 {
+                    return Err(InvalidTypedHeader);
+                }
+
+                hours
+                    .checked_mul(3600)
+                    .and_then(|hours| hours.checked_add(minutes * 60 + seconds))
+                    .ok_or(InvalidTypedHeader)?
+            }
+            _ => return Err(InvalidTypedHeader),
+        };
+
+        Ok(NPTTime::Time(Duration::new(seconds, nanos)))
+    }
+}
+
+/// The type of SMPTE timecode being used, which determines the frame rate.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SMPTEType {
+    /// SMPTE timecodes at 30 frames per second.
+    SMPTE,
+
+    /// SMPTE timecodes at 25 frames per second.
+    SMPTE25,
+
+    /// SMPTE drop-frame timecodes at 29.97 frames per second.
+    SMPTE30Drop,
+
+    /// A non-standard SMPTE type. The frame rate of such a type is unknown.
+    Extension(String),
+}
+
+impl SMPTEType {
+    /// Returns the frame rate as a fraction of frames per second, or `None` if it is unknown.
+    fn frame_rate(&self) -> Option<(u64, u64)> {
+        match self {
+            SMPTEType::SMPTE => Some((30, 1)),
+            SMPTEType::SMPTE25 => Some((25, 1)),
+            SMPTEType::SMPTE30Drop => Some((30_000, 1001)),
+            SMPTEType::Extension(_) => None,
+        }
+    }
+
+    /// Returns the number of frame labels per second.
+    fn frames_per_second(&self) -> u8 {
+        match self {
+            SMPTEType::SMPTE25 => 25,
+            _ => 30,
+        }
+    }
+}
+
+impl fmt::Display for SMPTEType {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SMPTEType::SMPTE => formatter.write_str("smpte"),
+            SMPTEType::SMPTE25 => formatter.write_str("smpte-25"),
+            SMPTEType::SMPTE30Drop => formatter.write_str("smpte-30-drop"),
+            SMPTEType::Extension(smpte_type) => formatter.write_str(smpte_type),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SMPTEType {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let value = value.to_ascii_lowercase();
+
+        match value.as_str() {
+            "smpte" => Ok(SMPTEType::SMPTE),
+            "smpte-25" => Ok(SMPTEType::SMPTE25),
+            "smpte-30-drop" => Ok(SMPTEType::SMPTE30Drop),
+            _ if value.starts_with("smpte") && is_token(&value) => Ok(SMPTEType::Extension(value)),
+            _ => Err(InvalidTypedHeader),
+        }
+    }
+}
+
+/// A single SMPTE timecode of the form `hours:minutes:seconds:frames.subframes`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SMPTETime {
+    frames: u8,
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+    subframes: u8,
+}
+
+impl SMPTETime {
+    /// Constructs a new SMPTE timecode.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the hours are above 99, the minutes or seconds are above 59,
+    /// the frames are above 29, or the subframes are above 99.
+    pub fn new(
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+        frames: u8,
+        subframes: u8,
+    ) -> Result<Self, InvalidTypedHeader> {
+        if hours > 99 || minutes > 59 || seconds > 59 || frames > 29 || subframes > 99 {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(SMPTETime {
+            frames,
+            hours,
+            minutes,
+            seconds,
+            subframes,
+        })
+    }
+
+    /// Converts an offset from the beginning of the presentation into a timecode of the given
+    /// type. Any remainder smaller than a subframe is truncated.
+    ///
+    /// `None` will be returned if the frame rate of the type is unknown or if the offset is too
+    /// large to be represented.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use rtsp::header::types::{SMPTETime, SMPTEType};
+    ///
+    /// let time = SMPTETime::from_duration(Duration::from_secs(60), &SMPTEType::SMPTE30Drop);
+    /// assert_eq!(time, Some(SMPTETime::new(0, 0, 59, 28, 20).unwrap()));
+    /// ```
+    pub fn from_duration(duration: Duration, smpte_type: &SMPTEType) -> Option<Self> {
+        let (numerator, denominator) = smpte_type.frame_rate()?;
+        let nanos =
+            duration.as_secs() as u128 * NANOS_PER_SECOND as u128 + duration.subsec_nanos() as u128;
+        let total_subframes =
+            nanos * numerator as u128 * 100 / (denominator as u128 * NANOS_PER_SECOND as u128);
+        let subframes = (total_subframes % 100) as u8;
+        let mut frame_number = (total_subframes / 100) as u64;
+
+        if *smpte_type == SMPTEType::SMPTE30Drop {
+            // Frame labels 0 and 1 are skipped at the start of every minute that is not divisible
+            // by 10, so add them back to get the label of the frame.
+
+            let tens_of_minutes = frame_number / 17_982;
+            let remainder = frame_number % 17_982;
+            frame_number += 18 * tens_of_minutes;
+
+            if remainder > 1 {
+                frame_number += 2 * ((remainder - 2) / 1798);
+            }
+        }
+
+        let frames_per_second = smpte_type.frames_per_second() as u64;
+        let hours = frame_number / (frames_per_second * 3600);
+
+        if hours > 99 {
+            return None;
+        }
+
+        Some(SMPTETime {
+            frames: (frame_number % frames_per_second) as u8,
+            hours: hours as u8,
+            minutes: (frame_number / (frames_per_second * 60) % 60) as u8,
+            seconds: (frame_number / frames_per_second % 60) as u8,
+            subframes,
+        })
+    }
+
+    pub fn frames(&self) -> u8 {
+        self.frames
+    }
+
+    pub fn hours(&self) -> u8 {
+        self.hours
+    }
+
+    /// Returns whether this timecode is a valid label for the given type.
+    fn is_valid_for(&self, smpte_type: &SMPTEType) -> bool {
+        if self.frames >= smpte_type.frames_per_second() {
+            return false;
+        }
+
+        if *smpte_type == SMPTEType::SMPTE30Drop {
+            !(self.seconds == 0 && self.frames < 2 && self.minutes % 10 != 0)
+        } else {
+            true
+        }
+    }
+
+    pub fn minutes(&self) -> u8 {
+        self.minutes
+    }
+
+    pub fn seconds(&self) -> u8 {
+        self.seconds
+    }
+
+    pub fn subframes(&self) -> u8 {
+        self.subframes
+    }
+
+    /// Converts this timecode of the given type into an offset from the beginning of the
+    /// presentation.
+    ///
+    /// `None` will be returned if the frame rate of the type is unknown or if the timecode is not a
+    /// valid label for the type (e.g. a dropped frame).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use rtsp::header::types::{SMPTETime, SMPTEType};
+    ///
+    /// let time = SMPTETime::new(0, 0, 1, 12, 50).unwrap();
+    /// assert_eq!(time.to_duration(&SMPTEType::SMPTE25), Some(Duration::from_millis(1_500)));
+    /// ```
+    pub fn to_duration(&self, smpte_type: &SMPTEType) -> Option<Duration> {
+        let (numerator, denominator) = smpte_type.frame_rate()?;
+
+        if !self.is_valid_for(smpte_type) {
+            return None;
+        }
+
+        let frames_per_second = smpte_type.frames_per_second() as u64;
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let mut frame_number =
+            (total_minutes * 60 + self.seconds as u64) * frames_per_second + self.frames as u64;
+
+        if *smpte_type == SMPTEType::SMPTE30Drop {
+            frame_number -= 2 * (total_minutes - total_minutes / 10);
+        }
+
+        let total_subframes = frame_number as u128 * 100 + self.subframes as u128;
+        let nanos = total_subframes * denominator as u128 * NANOS_PER_SECOND as u128
+            / (numerator as u128 * 100);
+
+        Some(Duration::new(
+            (nanos / NANOS_PER_SECOND as u128) as u64,
+            (nanos % NANOS_PER_SECOND as u128) as u32,
+        ))
+    }
+}
+
+impl fmt::Display for SMPTETime {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{:02}:{:02}:{:02}",
+            self.hours, self.minutes, self.seconds
+        )?;
+
+        if self.frames != 0 || self.subframes != 0 {
+            write!(formatter, ":{:02}", self.frames)?;
+        }
+
+        if self.subframes != 0 {
+            write!(formatter, ".{:02}", self.subframes)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SMPTETime {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let parts = value.split(':').collect::<Vec<&str>>();
+
+        if parts.len() != 3 && parts.len() != 4 {
+            return Err(InvalidTypedHeader);
+        }
+
+        let hours = parse_digits(parts[0], 1, 2)?;
+        let minutes = parse_digits(parts[1], 1, 2)?;
+        let seconds = parse_digits(parts[2], 1, 2)?;
+        let (frames, subframes) = match parts.get(3) {
+            Some(frames) => {
+                let mut parts = frames.splitn(2, '.');
+                let frames = parse_digits(parts.next().unwrap(), 1, 2)?;
+                let subframes = match parts.next() {
+                    Some(subframes) => parse_digits(subframes, 1, 2)?,
+                    None => 0,
+                };
+
+                (frames, subframes)
+            }
+            None => (0, 0),
+        };
+
+        SMPTETime::new(hours, minutes, seconds, frames, subframes)
+    }
+}
+
+/// A range given in a time format that is not standardized. The value is kept as it would be
+/// serialized.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ExtensionRange {
+    format: String,
+    value: Option<String>,
+}
+
+impl ExtensionRange {
+    /// Constructs a new extension range.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the format is not a valid token or if the value does not
+    /// consist of unreserved characters, `':'`, and quoted strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::header::types::ExtensionRange;
+    ///
+    /// let range = ExtensionRange::new("x-frames", Some("10-20")).unwrap();
+    /// assert_eq!(range.format(), "x-frames");
+    /// assert_eq!(range.value(), Some("10-20"));
+    ///
+    /// assert!(ExtensionRange::new("x-frames", Some("10 - 20")).is_err());
+    /// ```
+    pub fn new(format: &str, value: Option<&str>) -> Result<Self, InvalidTypedHeader> {
+        if !is_token(format) {
+            return Err(InvalidTypedHeader);
+        }
+
+        if let Some(value) = value {
+            if !is_range_value(value) {
+                return Err(InvalidTypedHeader);
+            }
+        }
+
+        Ok(ExtensionRange {
+            format: format.to_string(),
+            value: value.map(|value| value.to_string()),
+        })
+    }
+
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_ref().map(|value| value.as_str())
+    }
+}
+
+impl fmt::Display for ExtensionRange {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            Some(ref value) => write!(formatter, "{}={}", self.format, value),
+            None => formatter.write_str(&self.format),
+        }
+    }
+}
+
+/// Writes a fraction of a second given in nanoseconds as a `'.'` followed by the significant
+/// decimal digits. Nothing is written if there is no fraction.
+fn fmt_fraction(formatter: &mut fmt::Formatter, nanos: u32) -> fmt::Result {
+    if nanos == 0 {
+        return Ok(());
+    }
+
+    let fraction = format!("{:09}", nanos);
+    write!(formatter, ".{}", fraction.trim_right_matches('0'))
+}
+
+/// Returns whether the value is a valid extension range value. Quoted strings may not contain any
+/// control characters, and unquoted text may only contain unreserved characters and `':'`.
+fn is_range_value(value: &str) -> bool {
+    let mut escaped = false;
+    let mut quoted = false;
+
+    for c in value.chars() {
+        if c.is_control() {
+            return false;
+        } else if escaped {
+            escaped = false;
+        } else if quoted {
+            match c {
+                '\\' => escaped = true,
+                '"' => quoted = false,
+                _ => (),
+            }
+        } else {
+            match c {
+                '"' => quoted = true,
+                'a'...'z' | 'A'...'Z' | '0'...'9' | '-' | '.' | '_' | '~' | ':' => (),
+                _ => return false,
+            }
+        }
+    }
+
+    !value.is_empty() && !quoted
+}
+
+/// Parses a number consisting of `min_digits` to `max_digits` decimal digits.
+fn parse_digits<T>(
+    value: &str,
+    min_digits: usize,
+    max_digits: usize,
+) -> Result<T, InvalidTypedHeader>
+where
+    T: ::std::str::FromStr,
+{
+    if value.len() < min_digits
+        || value.len() > max_digits
+        || !value.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(InvalidTypedHeader);
+    }
+
+    value.parse::<T>().map_err(|_| InvalidTypedHeader)
+}
+
+/// Splits an optional fraction of the form `"." 1*9DIGIT` off of the end of the value, returning
+/// the remaining value and the fraction in nanoseconds.
+fn split_fraction(value: &str) -> Result<(&str, u32), InvalidTypedHeader> {
+    match value.find('.') {
+        Some(index) => {
+            let fraction = &value[index + 1..];
+            let digits = parse_digits::<u32>(fraction, 1, 9)?;
+            let nanos = digits * 10u32.pow(9 - fraction.len() as u32);
+            Ok((&value[..index], nanos))
+        }
+        None => Ok((value, 0)),
+    }
+}
+
+/// Formats a UTC time as `YYYYMMDDThhmmss[.fraction]Z`.
+pub(crate) fn fmt_utc_time(formatter: &mut fmt::Formatter, time: &DateTime<Utc>) -> fmt::Result {
+    write!(formatter, "{}", time.format("%Y%m%dT%H%M%S"))?;
+    fmt_fraction(formatter, time.nanosecond() % NANOS_PER_SECOND as u32)?;
+    formatter.write_str("Z")
+}
+
+/// Parses a UTC time of the form `YYYYMMDDThhmmss[.fraction]Z`. Since the components are sliced by
+/// byte offset, any non-ASCII value is rejected up front.
+pub(crate) fn parse_utc_time(value: &str) -> Result<DateTime<Utc>, InvalidTypedHeader> {
+    if !value.is_ascii() || !value.ends_with('Z') || value.len() < 16 || value.as_bytes()[8] != b'T'
+    {
+        return Err(InvalidTypedHeader);
+    }
+
+    let date = &value[..8];
+    let (clock, nanos) = split_fraction(&value[9..value.len() - 1])?;
+
+    if clock.len() != 6 {
+        return Err(InvalidTypedHeader);
+    }
+
+    let year = parse_digits(&date[..4], 4, 4)?;
+    let month = parse_digits(&date[4..6], 2, 2)?;
+    let day = parse_digits(&date[6..], 2, 2)?;
+    let hour = parse_digits(&clock[..2], 2, 2)?;
+    let minute = parse_digits(&clock[2..4], 2, 2)?;
+    let second = parse_digits(&clock[4..], 2, 2)?;
+
+    NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_nano_opt(hour, minute, second, nanos))
+        .map(|date_time| Utc.from_utc_datetime(&date_time))
+        .ok_or(InvalidTypedHeader)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(value: &str) -> Range {
+        let raw_header = vec![HeaderValue::try_from(value).unwrap()];
+        let typed_header = Range::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(
+            Range::try_from_header_raw(&typed_header.to_header_raw()).unwrap(),
+            typed_header
+        );
+        typed_header
+    }
+
+    #[test]
+    fn test_range_npt() {
+        assert_eq!(round_trip("npt"), Range::NPT(None));
+        assert_eq!(
+            round_trip("npt=-34.123456789"),
+            Range::NPT(Some(TimeRange::To(NPTTime::Time(Duration::new(
+                34,
+                123_456_789
+            )))))
+        );
+        assert_eq!(round_trip("NPT = 1:02:03-now").to_string(), "npt=3723-now");
+        assert_eq!(round_trip("npt=now-").to_string(), "npt=now-");
+    }
+
+    #[test]
+    fn test_range_smpte() {
+        assert_eq!(
+            round_trip("smpte-30-drop=00:01:00:02-").to_string(),
+            "smpte-30-drop=00:01:00:02-"
+        );
+        assert_eq!(
+            round_trip("smpte=1:2:3-1:2:3:4.5").to_string(),
+            "smpte=01:02:03-01:02:03:04.05"
+        );
+    }
+
+    #[test]
+    fn test_range_clock() {
+        let typed_header = round_trip("clock=19961108T142300.25Z-");
+        assert_eq!(typed_header.to_string(), "clock=19961108T142300.25Z-");
+    }
+
+    #[test]
+    fn test_range_extension() {
+        let typed_header = round_trip("x-custom=\"a b\":1");
+        assert_eq!(
+            typed_header,
+            Range::Extension(ExtensionRange::new("x-custom", Some("\"a b\":1")).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_smpte_time_duration() {
+        for smpte_type in [SMPTEType::SMPTE, SMPTEType::SMPTE25, SMPTEType::SMPTE30Drop].iter() {
+            for seconds in [0, 1, 59, 60, 61, 599, 600, 3599, 3600, 35999].iter() {
+                let duration = Duration::from_secs(*seconds);
+                let time = SMPTETime::from_duration(duration, smpte_type).unwrap();
+                let round_trip = time.to_duration(smpte_type).unwrap();
+                let difference = if duration > round_trip {
+                    duration - round_trip
+                } else {
+                    round_trip - duration
+                };
+
+                assert!(difference < Duration::from_millis(1));
+            }
+        }
+
+        let time = SMPTETime::new(0, 1, 0, 0, 0).unwrap();
+        assert_eq!(time.to_duration(&SMPTEType::SMPTE30Drop), None);
+    }
+
+    #[test]
+    fn test_range_invalid() {
+        let invalid = [
+            "npt=-",
+            "npt=1-2-3",
+            "npt=00:00:60-",
+            "npt=1.1234567890-",
+            "smpte=00:00:00:30-",
+            "smpte-25=00:00:00:25-",
+            "smpte-30-drop=00:01:00:00-",
+            "clock=19961108T142300-",
+            "clock=19961332T142300Z-",
+            "x-custom=a b",
+            "clock=abc\u{e9}xyzT123456Z-",
+            "clock=1996110\u{e9}T142300Z-",
+            "clock=19961108T1423\u{e9}Z-",
+            "npt=\u{e9}-",
+            "smpte=\u{e9}-",
+        ];
+
+        for value in invalid.iter() {
+            let raw_header = vec![HeaderValue::try_from(*value).unwrap()];
+            assert!(
+                Range::try_from_header_raw(&raw_header).is_err(),
+                "expected {:?} to be invalid",
+                value
+            );
+        }
+    }
+}
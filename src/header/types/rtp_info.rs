@@ -0,0 +1,576 @@
+use itertools::Itertools;
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use syntax::{is_quoted_string, is_token, split_unquoted, trim_whitespace, trim_whitespace_left};
+use uri::Host;
+
+/// The `"RTP-Info"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.45).
+///
+/// The header is a list of stream entries, one for each stream that the request applied to.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RTPInfo(pub Vec<RTPInfoStream>);
+
+impl RTPInfo {
+    /// Constructs a new header with no stream entries by default.
+    pub fn new() -> Self {
+        RTPInfo::default()
+    }
+}
+
+impl Deref for RTPInfo {
+    type Target = Vec<RTPInfoStream>;
+
+    fn deref(&self) -> &Vec<RTPInfoStream> {
+        &self.0
+    }
+}
+
+impl DerefMut for RTPInfo {
+    fn deref_mut(&mut self) -> &mut Vec<RTPInfoStream> {
+        &mut self.0
+    }
+}
+
+impl FromIterator<RTPInfoStream> for RTPInfo {
+    fn from_iter<I>(iterator: I) -> Self
+    where
+        I: IntoIterator<Item = RTPInfoStream>,
+    {
+        RTPInfo(Vec::from_iter(iterator))
+    }
+}
+
+impl TypedHeader for RTPInfo {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::RTPInfo
+    }
+
+    /// Converts the [`RTPInfo`] type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let typed_header = vec![
+    ///     RTPInfoStream::new(
+    ///         "rtsp://example.com/foo/audio",
+    ///         vec![RTPInfoSSRC::new(0x0A13C760, Some(45102), Some(12345678))],
+    ///     ).unwrap(),
+    ///     RTPInfoStream::new(
+    ///         "rtsp://example.com/foo/video",
+    ///         vec![RTPInfoSSRC::new(0x9A9DE123, Some(30211), Some(29567112))],
+    ///     ).unwrap(),
+    /// ].into_iter().collect::<RTPInfo>();
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         "url=\"rtsp://example.com/foo/audio\" ssrc=0A13C760:seq=45102;rtptime=12345678, \
+    ///          url=\"rtsp://example.com/foo/video\" ssrc=9A9DE123:seq=30211;rtptime=29567112"
+    ///     ).unwrap()
+    /// ];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        // Unsafe Justification
+        //
+        // In order for this to be safe, we must ensure that the value contains no unprintable
+        // ASCII-US characters and no linebreaks. The stream URLs and extension parameters can only
+        // be constructed after validating that they do not contain any such characters, and the
+        // remaining parts are numbers and fixed punctuation.
+
+        let value = self.iter().map(|stream| stream.to_string()).join(", ");
+        vec![unsafe { HeaderValue::from_str_unchecked(value) }]
+    }
+
+    /// Converts the raw header values to the [`RTPInfo`] header type. Based on the syntax provided
+    /// by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the following
+    /// syntax:
+    ///
+    /// ```text
+    /// RTP-Info = "RTP-Info" HCOLON rtsp-info-spec *(COMMA rtsp-info-spec)
+    /// rtsp-info-spec = stream-url 1*ssrc-parameter
+    /// stream-url = "url" EQUAL DQ2URI
+    /// ssrc-parameter = LWS "ssrc" EQUAL ssrc HCOLON ri-parameter *(SEMI ri-parameter)
+    /// ri-parameter = ("seq" EQUAL 1*5DIGIT) / ("rtptime" EQUAL 1*10DIGIT) / generic-param
+    /// generic-param = token [ EQUAL gen-value ]
+    /// gen-value = token / host / quoted-string
+    /// DQ2URI = DQUOTE ( absolute-URI / relative-ref ) DQUOTE
+    /// ssrc = 8HEXDIG ; 32 bit hex value
+    /// ```
+    ///
+    /// As a leniency, an SSRC parameter without any `ri-parameter` (and without the trailing colon)
+    /// is also accepted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         "url=\"rtsp://example.com/foo/audio\" ssrc=0A13C760:seq=45102;rtptime=12345678,\r\n \
+    ///          url=\"video\" ssrc=9A9DE123 : seq=30211 ssrc=0000000A:rtptime=1;x-ext=\"a, b\""
+    ///     ).unwrap()
+    /// ];
+    /// let typed_header = RTPInfo::try_from_header_raw(&raw_header).unwrap();
+    ///
+    /// assert_eq!(typed_header.len(), 2);
+    /// assert_eq!(typed_header[0].url(), "rtsp://example.com/foo/audio");
+    /// assert_eq!(typed_header[0].ssrcs()[0].sequence_number(), Some(45102));
+    /// assert_eq!(typed_header[1].url(), "video");
+    /// assert_eq!(typed_header[1].ssrcs()[1].ssrc(), 10);
+    /// assert_eq!(typed_header[1].ssrcs()[1].extensions()[0].value(), Some("\"a, b\""));
+    ///
+    /// assert_eq!(
+    ///     RTPInfo::try_from_header_raw(&typed_header.to_header_raw()).unwrap(),
+    ///     typed_header
+    /// );
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from("url=\"rtsp://example.com/foo/audio\"").unwrap()
+    /// ];
+    ///
+    /// assert!(RTPInfo::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        if header.is_empty() {
+            return Err(InvalidTypedHeader);
+        }
+
+        let mut streams = Vec::new();
+
+        for value in header {
+            for stream in split_unquoted(value.as_str(), ',') {
+                streams.push(RTPInfoStream::try_from(trim_whitespace(stream))?);
+            }
+        }
+
+        Ok(RTPInfo(streams))
+    }
+}
+
+/// The RTP information of a single stream, identified by its URL.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RTPInfoStream {
+    ssrcs: Vec<RTPInfoSSRC>,
+    url: String,
+}
+
+impl RTPInfoStream {
+    /// Constructs a new stream entry.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the URL is empty or contains whitespace, control characters,
+    /// or `'"'`, or if no SSRC entries are given.
+    pub fn new<S>(url: S, ssrcs: Vec<RTPInfoSSRC>) -> Result<Self, InvalidTypedHeader>
+    where
+        S: Into<String>,
+    {
+        let url = url.into();
+
+        if url.is_empty()
+            || ssrcs.is_empty()
+            || !url.bytes().all(|b| b > b' ' && b < 0x7F && b != b'"')
+        {
+            return Err(InvalidTypedHeader);
+        }
+
+        Ok(RTPInfoStream { ssrcs, url })
+    }
+
+    /// Returns the SSRC entry for the given SSRC, if present.
+    pub fn ssrc(&self, ssrc: u32) -> Option<&RTPInfoSSRC> {
+        self.ssrcs.iter().find(|entry| entry.ssrc == ssrc)
+    }
+
+    pub fn ssrcs(&self) -> &[RTPInfoSSRC] {
+        &self.ssrcs
+    }
+
+    /// Returns the URL of the stream. This may be a relative URL, in which case it is relative to
+    /// the request URI.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl fmt::Display for RTPInfoStream {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "url=\"{}\"", self.url)?;
+
+        for ssrc in &self.ssrcs {
+            write!(formatter, " {}", ssrc)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for RTPInfoStream {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.get(..3) {
+            Some(name) if name.eq_ignore_ascii_case("url") => (),
+            _ => return Err(InvalidTypedHeader),
+        }
+
+        let value = trim_whitespace_left(&value[3..]);
+
+        if !value.starts_with('=') {
+            return Err(InvalidTypedHeader);
+        }
+
+        let value = trim_whitespace_left(&value[1..]);
+
+        if !value.starts_with('"') {
+            return Err(InvalidTypedHeader);
+        }
+
+        let end = value[1..].find('"').ok_or(InvalidTypedHeader)? + 1;
+        let url = &value[1..end];
+        let ssrcs = split_ssrc_parameters(&value[end + 1..])?
+            .into_iter()
+            .map(|parameter| RTPInfoSSRC::try_from(parameter.as_str()))
+            .collect::<Result<Vec<RTPInfoSSRC>, InvalidTypedHeader>>()?;
+
+        RTPInfoStream::new(url, ssrcs)
+    }
+}
+
+/// The RTP information of a single synchronization source of a stream.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RTPInfoSSRC {
+    extensions: Vec<RTPInfoExtension>,
+    rtp_time: Option<u32>,
+    sequence_number: Option<u16>,
+    ssrc: u32,
+}
+
+impl RTPInfoSSRC {
+    /// Constructs a new SSRC entry with the given sequence number and RTP timestamp.
+    pub fn new(ssrc: u32, sequence_number: Option<u16>, rtp_time: Option<u32>) -> Self {
+        RTPInfoSSRC {
+            extensions: Vec::new(),
+            rtp_time,
+            sequence_number,
+            ssrc,
+        }
+    }
+
+    pub fn extensions(&self) -> &Vec<RTPInfoExtension> {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Vec<RTPInfoExtension> {
+        &mut self.extensions
+    }
+
+    /// Returns the RTP timestamp corresponding to the start time given in the `"Range"` header.
+    pub fn rtp_time(&self) -> Option<u32> {
+        self.rtp_time
+    }
+
+    pub fn rtp_time_mut(&mut self) -> &mut Option<u32> {
+        &mut self.rtp_time
+    }
+
+    /// Returns the sequence number of the first packet that is sent after the request.
+    pub fn sequence_number(&self) -> Option<u16> {
+        self.sequence_number
+    }
+
+    pub fn sequence_number_mut(&mut self) -> &mut Option<u16> {
+        &mut self.sequence_number
+    }
+
+    pub fn ssrc(&self) -> u32 {
+        self.ssrc
+    }
+}
+
+impl fmt::Display for RTPInfoSSRC {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "ssrc={:08X}", self.ssrc)?;
+
+        let mut separator = ":";
+
+        if let Some(sequence_number) = self.sequence_number {
+            write!(formatter, "{}seq={}", separator, sequence_number)?;
+            separator = ";";
+        }
+
+        if let Some(rtp_time) = self.rtp_time {
+            write!(formatter, "{}rtptime={}", separator, rtp_time)?;
+            separator = ";";
+        }
+
+        for extension in &self.extensions {
+            write!(formatter, "{}{}", separator, extension)?;
+            separator = ";";
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for RTPInfoSSRC {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.get(..5) {
+            Some(name) if name.eq_ignore_ascii_case("ssrc=") => (),
+            _ => return Err(InvalidTypedHeader),
+        }
+
+        let ssrc = value.get(5..13).ok_or(InvalidTypedHeader)?;
+
+        if !ssrc.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(InvalidTypedHeader);
+        }
+
+        let mut entry = RTPInfoSSRC::new(
+            u32::from_str_radix(ssrc, 16).map_err(|_| InvalidTypedHeader)?,
+            None,
+            None,
+        );
+        let value = &value[13..];
+
+        if value.is_empty() {
+            return Ok(entry);
+        } else if !value.starts_with(':') {
+            return Err(InvalidTypedHeader);
+        }
+
+        for parameter in split_unquoted(&value[1..], ';') {
+            let (name, value) = match parameter.find('=') {
+                Some(index) => (&parameter[..index], Some(&parameter[index + 1..])),
+                None => (parameter, None),
+            };
+
+            match (name.to_ascii_lowercase().as_str(), value) {
+                ("seq", Some(value)) => {
+                    entry.sequence_number = Some(parse_digits(value, 5)?);
+                }
+                ("rtptime", Some(value)) => {
+                    entry.rtp_time = Some(parse_digits(value, 10)?);
+                }
+                ("seq", None) | ("rtptime", None) => return Err(InvalidTypedHeader),
+                _ => entry.extensions.push(RTPInfoExtension::new(name, value)?),
+            }
+        }
+
+        Ok(entry)
+    }
+}
+
+/// A non-standard parameter of an SSRC entry with an optional value. The value is stored as it
+/// would be serialized, so a quoted string value retains its surrounding quotes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RTPInfoExtension {
+    name: String,
+    value: Option<String>,
+}
+
+impl RTPInfoExtension {
+    /// Constructs a new extension parameter.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the name is not a valid token or if the value is not a token,
+    /// host, or quoted string.
+    pub fn new(name: &str, value: Option<&str>) -> Result<Self, InvalidTypedHeader> {
+        if !is_token(name) {
+            return Err(InvalidTypedHeader);
+        }
+
+        if let Some(value) = value {
+            if !is_token(value) && !is_quoted_string(value) && Host::parse(value).is_err() {
+                return Err(InvalidTypedHeader);
+            }
+        }
+
+        Ok(RTPInfoExtension {
+            name: name.to_string(),
+            value: value.map(|value| value.to_string()),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_ref().map(|value| value.as_str())
+    }
+}
+
+impl fmt::Display for RTPInfoExtension {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            Some(ref value) => write!(formatter, "{}={}", self.name, value),
+            None => formatter.write_str(&self.name),
+        }
+    }
+}
+
+/// Parses a number consisting of one to `max_digits` decimal digits.
+fn parse_digits<T>(value: &str, max_digits: usize) -> Result<T, InvalidTypedHeader>
+where
+    T: ::std::str::FromStr,
+{
+    if value.is_empty() || value.len() > max_digits || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(InvalidTypedHeader);
+    }
+
+    value.parse::<T>().map_err(|_| InvalidTypedHeader)
+}
+
+/// Splits the whitespace separated SSRC parameters that follow the stream URL. Since whitespace is
+/// also allowed around the `'='`, `':'`, and `';'` separators within a parameter, any such
+/// whitespace is removed first. At least one parameter must be present.
+fn split_ssrc_parameters(value: &str) -> Result<Vec<String>, InvalidTypedHeader> {
+    const SEPARATORS: &[char] = &['=', ':', ';'];
+
+    if !value.starts_with(|c: char| c.is_ascii_whitespace()) {
+        return Err(InvalidTypedHeader);
+    }
+
+    let mut parameters = Vec::new();
+    let mut parameter = String::new();
+    let mut chars = trim_whitespace(value).chars().peekable();
+    let mut escaped = false;
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                quoted = false;
+            }
+
+            parameter.push(c);
+        } else if c.is_ascii_whitespace() {
+            while chars.peek().map_or(false, |c| c.is_ascii_whitespace()) {
+                chars.next();
+            }
+
+            let separated = parameter.ends_with(SEPARATORS)
+                || chars.peek().map_or(false, |c| SEPARATORS.contains(c));
+
+            if !separated {
+                parameters.push(parameter);
+                parameter = String::new();
+            }
+        } else {
+            if c == '"' {
+                quoted = true;
+            }
+
+            parameter.push(c);
+        }
+    }
+
+    if quoted || parameter.is_empty() {
+        return Err(InvalidTypedHeader);
+    }
+
+    parameters.push(parameter);
+    Ok(parameters)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(value: &str) -> RTPInfo {
+        let raw_header = vec![HeaderValue::try_from(value).unwrap()];
+        let typed_header = RTPInfo::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.to_header_raw(), raw_header);
+        typed_header
+    }
+
+    fn parse(value: &str) -> Result<RTPInfo, InvalidTypedHeader> {
+        RTPInfo::try_from_header_raw(&[HeaderValue::try_from(value).unwrap()])
+    }
+
+    #[test]
+    fn test_rtp_info() {
+        let typed_header = round_trip(
+            "url=\"rtsp://example.com/foo/audio\" ssrc=0A13C760:seq=45102;rtptime=12345678, \
+             url=\"video\" ssrc=9A9DE123 ssrc=0000000A:rtptime=1;x-ext=\"a, b\";flag",
+        );
+        assert_eq!(typed_header.len(), 2);
+
+        let ssrc = typed_header[0].ssrc(0x0A13_C760).unwrap();
+        assert_eq!(ssrc.sequence_number(), Some(45102));
+        assert_eq!(ssrc.rtp_time(), Some(12_345_678));
+
+        let ssrcs = typed_header[1].ssrcs();
+        assert_eq!(ssrcs[0], RTPInfoSSRC::new(0x9A9D_E123, None, None));
+        assert_eq!(ssrcs[1].sequence_number(), None);
+        assert_eq!(ssrcs[1].rtp_time(), Some(1));
+        assert_eq!(ssrcs[1].extensions()[0].name(), "x-ext");
+        assert_eq!(ssrcs[1].extensions()[0].value(), Some("\"a, b\""));
+        assert_eq!(ssrcs[1].extensions()[1].name(), "flag");
+        assert_eq!(ssrcs[1].extensions()[1].value(), None);
+    }
+
+    #[test]
+    fn test_rtp_info_whitespace() {
+        let typed_header =
+            parse("URL = \"audio\"  SSRC = 0a13c760 : seq = 1 ; rtptime = 2").unwrap();
+        let ssrc = typed_header[0].ssrc(0x0A13_C760).unwrap();
+        assert_eq!(ssrc.sequence_number(), Some(1));
+        assert_eq!(ssrc.rtp_time(), Some(2));
+    }
+
+    #[test]
+    fn test_rtp_info_invalid() {
+        assert!(RTPInfo::try_from_header_raw(&[]).is_err());
+        assert!(parse("").is_err());
+        assert!(parse("url=\"audio\"").is_err());
+        assert!(parse("url=audio ssrc=0A13C760").is_err());
+        assert!(parse("url=\"audio ssrc=0A13C760").is_err());
+        assert!(parse("url=\"audio\"ssrc=0A13C760").is_err());
+        assert!(parse("url=\"audio\" ssrc=0A13C76").is_err());
+        assert!(parse("url=\"audio\" ssrc=0A13C76G").is_err());
+        assert!(parse("url=\"audio\" ssrc=0A13C760;seq=1").is_err());
+        assert!(parse("url=\"audio\" ssrc=0A13C760:seq").is_err());
+        assert!(parse("url=\"audio\" ssrc=0A13C760:seq=123456").is_err());
+        assert!(parse("url=\"audio\" ssrc=0A13C760:seq=65536").is_err());
+        assert!(parse("url=\"audio\" ssrc=0A13C760:rtptime=4294967296").is_err());
+        assert!(parse("url=\"audio\" ssrc=0A13C760:x-ext=\"a").is_err());
+    }
+
+    #[test]
+    fn test_rtp_info_non_ascii() {
+        assert!(parse("ab\u{e9}").is_err());
+        assert!(parse("\u{e9}").is_err());
+        assert!(parse("url=\"x\" ssrc=0000000\u{e9}:seq=1").is_err());
+        assert!(parse("url=\"x\" ssr\u{e9}=00000000").is_err());
+        assert!(parse("url=\"x\" ssrc=\u{e9}").is_err());
+        assert!(parse("url=\"\u{e9}\" ssrc=00000000").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use chrono::{DateTime, Utc};
+use std::convert::TryFrom;
+use std::fmt;
+
+use header::types::range::{fmt_utc_time, parse_utc_time};
+use header::types::{ExtensionParameter, ReasonToken};
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use syntax::{quote_string, split_unquoted, trim_whitespace, unquote_string};
+
+/// The `"Terminate-Reason"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.52).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TerminateReason {
+    extensions: Vec<ExtensionParameter>,
+    reason: TerminationReason,
+    time: Option<DateTime<Utc>>,
+    user_message: Option<String>,
+}
+
+impl TerminateReason {
+    /// Constructs a new header with the given reason and no parameters.
+    pub fn new(reason: TerminationReason) -> Self {
+        TerminateReason {
+            extensions: Vec::new(),
+            reason,
+            time: None,
+            user_message: None,
+        }
+    }
+
+    /// Returns the parameters that are not standardized.
+    pub fn extensions(&self) -> &[ExtensionParameter] {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Vec<ExtensionParameter> {
+        &mut self.extensions
+    }
+
+    pub fn reason(&self) -> &TerminationReason {
+        &self.reason
+    }
+
+    pub fn reason_mut(&mut self) -> &mut TerminationReason {
+        &mut self.reason
+    }
+
+    /// Sets the message intended for the user of the client.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the message contains control characters.
+    pub fn set_user_message(&mut self, message: Option<&str>) -> Result<(), InvalidTypedHeader> {
+        if let Some(message) = message {
+            if message.chars().any(|c| c.is_control()) {
+                return Err(InvalidTypedHeader);
+            }
+        }
+
+        self.user_message = message.map(|message| message.to_string());
+        Ok(())
+    }
+
+    /// Returns the time at which the session will be terminated, if given.
+    pub fn time(&self) -> Option<&DateTime<Utc>> {
+        self.time.as_ref()
+    }
+
+    pub fn time_mut(&mut self) -> &mut Option<DateTime<Utc>> {
+        &mut self.time
+    }
+
+    /// Returns the message intended for the user of the client, if given.
+    pub fn user_message(&self) -> Option<&str> {
+        self.user_message.as_ref().map(|message| message.as_str())
+    }
+}
+
+impl fmt::Display for TerminateReason {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.reason)?;
+
+        if let Some(ref time) = self.time {
+            formatter.write_str(";time=")?;
+            fmt_utc_time(formatter, time)?;
+        }
+
+        if let Some(ref message) = self.user_message {
+            write!(formatter, ";user-msg={}", quote_string(message))?;
+        }
+
+        for extension in self.extensions.iter() {
+            write!(formatter, ";{}", extension)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TypedHeader for TerminateReason {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::TerminateReason
+    }
+
+    /// Converts the [`TerminateReason`] type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::{TerminateReason, TerminationReason};
+    ///
+    /// let mut typed_header = TerminateReason::new(TerminationReason::ServerAdmin);
+    /// typed_header.set_user_message(Some("Maintenance")).unwrap();
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from("Server-Admin;user-msg=\"Maintenance\"").unwrap()
+    /// ];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        // Unsafe Justification
+        //
+        // In order for this to be safe, we must ensure that the value contains no unprintable
+        // ASCII-US characters and no linebreaks. The reason is a token, the time is formatted from
+        // digits, the user message is checked for control characters when it is set, and extension
+        // parameters are validated on construction.
+
+        vec![unsafe { HeaderValue::from_str_unchecked(self.to_string()) }]
+    }
+
+    /// Converts the raw header values to the [`TerminateReason`] header type. Based on the syntax
+    /// provided by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the
+    /// following syntax:
+    ///
+    /// ```text
+    /// Terminate-Reason = "Terminate-Reason" HCOLON TR-Reason *(SEMI TR-Parameter)
+    /// TR-Reason = "Session-Timeout" / "Server-Admin" / "Internal-Error" / token
+    /// TR-Parameter = TR-time / TR-user-msg / generic-param
+    /// TR-time = "time" EQUAL utc-time
+    /// TR-user-msg = "user-msg" EQUAL quoted-string
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::{TerminateReason, TerminationReason};
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from("Session-Timeout;time=19961108T142730.25Z").unwrap()
+    /// ];
+    /// let typed_header = TerminateReason::try_from_header_raw(&raw_header).unwrap();
+    ///
+    /// assert_eq!(typed_header.reason(), &TerminationReason::SessionTimeout);
+    /// assert!(typed_header.time().is_some());
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("Server-Admin;time=never").unwrap()];
+    ///
+    /// assert!(TerminateReason::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        if header.len() != 1 {
+            return Err(InvalidTypedHeader);
+        }
+
+        let mut parts = split_unquoted(header[0].as_str(), ';').into_iter();
+        let reason = parts.next().expect("split should return at least one part");
+        let mut terminate_reason =
+            TerminateReason::new(TerminationReason::try_from(trim_whitespace(reason))?);
+
+        for part in parts {
+            let (name, value) = match part.find('=') {
+                Some(index) => (
+                    trim_whitespace(&part[..index]),
+                    Some(trim_whitespace(&part[index + 1..])),
+                ),
+                None => (trim_whitespace(part), None),
+            };
+
+            match (name.to_ascii_lowercase().as_str(), value) {
+                ("time", Some(value)) => {
+                    terminate_reason.time = Some(parse_utc_time(value)?);
+                }
+                ("user-msg", Some(value)) => {
+                    let message = unquote_string(value).ok_or(InvalidTypedHeader)?;
+                    terminate_reason.set_user_message(Some(&message))?;
+                }
+                ("time", None) | ("user-msg", None) => return Err(InvalidTypedHeader),
+                _ => terminate_reason
+                    .extensions
+                    .push(ExtensionParameter::new(name, value)?),
+            }
+        }
+
+        Ok(terminate_reason)
+    }
+}
+
+/// The reason given by a server for terminating a session.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TerminationReason {
+    /// An error occurred on the server.
+    InternalError,
+
+    /// The session was terminated by an administrator.
+    ServerAdmin,
+
+    /// The session timed out.
+    SessionTimeout,
+
+    /// A reason that is not standardized.
+    Extension(ReasonToken),
+}
+
+impl TerminationReason {
+    pub fn as_str(&self) -> &str {
+        use self::TerminationReason::*;
+
+        match self {
+            InternalError => "Internal-Error",
+            ServerAdmin => "Server-Admin",
+            SessionTimeout => "Session-Timeout",
+            Extension(reason) => reason.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for TerminationReason {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TerminationReason {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "internal-error" => Ok(TerminationReason::InternalError),
+            "server-admin" => Ok(TerminationReason::ServerAdmin),
+            "session-timeout" => Ok(TerminationReason::SessionTimeout),
+            _ => Ok(TerminationReason::Extension(ReasonToken::try_from(value)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(value: &str) -> TerminateReason {
+        let raw_header = vec![HeaderValue::try_from(value).unwrap()];
+        let typed_header = TerminateReason::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(typed_header.to_header_raw(), raw_header);
+        typed_header
+    }
+
+    #[test]
+    fn test_terminate_reason() {
+        let typed_header = round_trip("Session-Timeout");
+        assert_eq!(typed_header.reason(), &TerminationReason::SessionTimeout);
+        assert_eq!(typed_header.time(), None);
+
+        let typed_header =
+            round_trip("Server-Admin;time=19961108T142730.25Z;user-msg=\"Going \\\"down\\\"\"");
+        assert_eq!(
+            typed_header.time().unwrap().to_rfc3339(),
+            "1996-11-08T14:27:30.250+00:00"
+        );
+        assert_eq!(typed_header.user_message(), Some("Going \"down\""));
+
+        let typed_header = round_trip("Moved;retry=10;now");
+        assert_eq!(
+            typed_header.reason(),
+            &TerminationReason::Extension(ReasonToken::try_from("Moved").unwrap())
+        );
+        assert_eq!(typed_header.extensions().len(), 2);
+        assert_eq!(typed_header.extensions()[0].value(), Some("10"));
+    }
+
+    #[test]
+    fn test_terminate_reason_invalid() {
+        for value in &[
+            "",
+            "Server-Admin;time",
+            "Server-Admin;user-msg=unquoted",
+            "Server-Admin;time=19961108",
+            "Session-Timeout;time=abc\u{e9}xyzT123456Z",
+        ] {
+            let raw_header = vec![HeaderValue::try_from(*value).unwrap()];
+            assert!(TerminateReason::try_from_header_raw(&raw_header).is_err());
+        }
+    }
+}
@@ -0,0 +1,1257 @@
+use itertools::Itertools;
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+
+use header::{HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use syntax::{is_quoted_string, is_token, split_unquoted, trim_whitespace};
+use uri::Host;
+
+/// The `"Transport"` typed header as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#section-18.54).
+///
+/// The header is a list of transport specifications ordered by preference.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Transport(pub Vec<TransportSpec>);
+
+impl Transport {
+    /// Constructs a new header with no transport specifications by default.
+    pub fn new() -> Self {
+        Transport::default()
+    }
+}
+
+impl Deref for Transport {
+    type Target = Vec<TransportSpec>;
+
+    fn deref(&self) -> &Vec<TransportSpec> {
+        &self.0
+    }
+}
+
+impl DerefMut for Transport {
+    fn deref_mut(&mut self) -> &mut Vec<TransportSpec> {
+        &mut self.0
+    }
+}
+
+impl FromIterator<TransportSpec> for Transport {
+    fn from_iter<I>(iterator: I) -> Self
+    where
+        I: IntoIterator<Item = TransportSpec>,
+    {
+        Transport(Vec::from_iter(iterator))
+    }
+}
+
+impl TypedHeader for Transport {
+    /// Returns the statically assigned `HeaderName` for this header.
+    fn header_name() -> &'static HeaderName {
+        &HeaderName::Transport
+    }
+
+    /// Converts the [`Transport`] type to raw header values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let typed_header = vec![
+    ///     TransportSpec::with_parameters(
+    ///         TransportID::RTP(RTPProfile::AVP, None),
+    ///         vec![
+    ///             TransportParameter::Multicast,
+    ///             TransportParameter::Mode(vec![TransportMode::Play]),
+    ///         ],
+    ///     ),
+    ///     TransportSpec::with_parameters(
+    ///         TransportID::RTP(RTPProfile::AVP, Some(LowerTransport::TCP)),
+    ///         vec![
+    ///             TransportParameter::Unicast,
+    ///             TransportParameter::Interleaved(0, Some(1)),
+    ///         ],
+    ///     ),
+    /// ].into_iter().collect::<Transport>();
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         "RTP/AVP;multicast;mode=\"PLAY\", RTP/AVP/TCP;unicast;interleaved=0-1"
+    ///     ).unwrap()
+    /// ];
+    ///
+    /// assert_eq!(typed_header.to_header_raw(), raw_header);
+    /// ```
+    fn to_header_raw(&self) -> Vec<HeaderValue> {
+        // Unsafe Justification
+        //
+        // In order for this to be safe, we must ensure that the value contains no unprintable
+        // ASCII-US characters and no linebreaks. All textual components of a transport
+        // specification are either statically defined or are wrapped in types that can only be
+        // constructed after validating that they consist of token characters, host names, or
+        // quoted strings, none of which allow control characters.
+
+        let value = self.iter().map(|spec| spec.to_string()).join(", ");
+        vec![unsafe { HeaderValue::from_str_unchecked(value) }]
+    }
+
+    /// Converts the raw header values to the [`Transport`] header type. Based on the syntax
+    /// provided by [RFC7826](https://tools.ietf.org/html/rfc7826#section-20), this header has the
+    /// following syntax:
+    ///
+    /// ```text
+    /// Transport = "Transport" HCOLON transport-spec *(COMMA transport-spec)
+    /// transport-spec = transport-id *tr-parameter
+    /// transport-id = trans-id-rtp / other-trans
+    /// trans-id-rtp = "RTP/" profile ["/" lower-transport]
+    ///                ; no LWS is allowed inside transport-id
+    /// other-trans = token *("/" token)
+    /// profile = "AVP" / "SAVP" / "AVPF" / "SAVPF" / token
+    /// lower-transport = "TCP" / "UDP" / token
+    /// tr-parameter = SEMI ( "unicast" / "multicast"
+    ///              / ( "interleaved" EQUAL channel [ "-" channel ] )
+    ///              / ( "ttl" EQUAL ttl )
+    ///              / ( "layers" EQUAL 1*DIGIT )
+    ///              / ( "ssrc" EQUAL ssrc *(SLASH ssrc) )
+    ///              / ( "mode" EQUAL mode-spec )
+    ///              / ( "dest_addr" EQUAL addr-list )
+    ///              / ( "src_addr" EQUAL addr-list )
+    ///              / ( "setup" EQUAL contrans-setup )
+    ///              / ( "connection" EQUAL contrans-con )
+    ///              / "RTCP-mux"
+    ///              / trn-par-extension )
+    /// channel = 1*3DIGIT
+    /// ssrc = 8HEXDIG ; 32 bit hex value
+    /// ttl = 1*3DIGIT ; 0 to 255
+    /// mode-spec = ( DQUOTE mode *(COMMA mode) DQUOTE ) / mode
+    /// mode = "PLAY" / "RECORD" / token
+    /// addr-list = quoted-addr *(SLASH quoted-addr)
+    /// quoted-addr = DQUOTE (host-port / extension-addr) DQUOTE
+    /// host-port = ( host [":" port] ) / ( ":" port )
+    /// extension-addr = 1*qdtext
+    /// contrans-setup = "active" / "passive" / "actpass"
+    /// contrans-con = "new" / "existing"
+    /// trn-par-extension = par-name [EQUAL (trn-par-value / quoted-string)]
+    /// par-name = token
+    /// ```
+    ///
+    /// RTSP 1.0 peers describe addresses and ports using the parameters of
+    /// [RFC2326](https://tools.ietf.org/html/rfc2326#section-12.39) instead of `"dest_addr"` and
+    /// `"src_addr"`, so the following parameters are recognized as well:
+    ///
+    /// ```text
+    /// tr-parameter =/ SEMI ( ( "destination" [EQUAL host] )
+    ///                / ( "source" EQUAL host )
+    ///                / ( "port" EQUAL port-range )
+    ///                / ( "client_port" EQUAL port-range )
+    ///                / ( "server_port" EQUAL port-range )
+    ///                / "append" )
+    /// port-range = port ["-" port]
+    /// ```
+    ///
+    /// Parameter names and the standardized parameter values are matched case-insensitively. All
+    /// header values are treated as a single comma separated list of transport specifications.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::*;
+    /// use rtsp::header::types::*;
+    ///
+    /// let raw_header = vec![
+    ///     HeaderValue::try_from(
+    ///         "RTP/AVP;unicast;dest_addr=\"192.0.2.5:3456\"/\r\n\t\"192.0.2.5:3457\";mode=\"PLAY\""
+    ///     ).unwrap()
+    /// ];
+    /// let typed_header = Transport::try_from_header_raw(&raw_header).unwrap();
+    /// let spec = &typed_header[0];
+    ///
+    /// assert_eq!(spec.id(), &TransportID::RTP(RTPProfile::AVP, None));
+    /// assert_eq!(spec.mode(), Some(&[TransportMode::Play][..]));
+    /// assert_eq!(
+    ///     spec.destination_addresses().unwrap()[1].to_string(),
+    ///     "192.0.2.5:3457"
+    /// );
+    ///
+    /// assert_eq!(
+    ///     Transport::try_from_header_raw(&typed_header.to_header_raw()).unwrap(),
+    ///     typed_header
+    /// );
+    ///
+    /// let raw_header = vec![HeaderValue::try_from("RTP/AVP;ttl=256").unwrap()];
+    ///
+    /// assert!(Transport::try_from_header_raw(&raw_header).is_err());
+    /// ```
+    fn try_from_header_raw(header: &[HeaderValue]) -> Result<Self, InvalidTypedHeader> {
+        if header.is_empty() {
+            return Err(InvalidTypedHeader);
+        }
+
+        let mut specs = Vec::new();
+
+        for value in header {
+            for spec in split_unquoted(value.as_str(), ',') {
+                specs.push(TransportSpec::try_from(spec)?);
+            }
+        }
+
+        Ok(Transport(specs))
+    }
+}
+
+/// A single transport specification consisting of a transport identifier and an ordered list of
+/// parameters.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TransportSpec {
+    id: TransportID,
+    parameters: Vec<TransportParameter>,
+}
+
+impl TransportSpec {
+    /// Constructs a new transport specification with no parameters.
+    pub fn new(id: TransportID) -> Self {
+        TransportSpec::with_parameters(id, Vec::new())
+    }
+
+    /// Constructs a new transport specification with the given parameters.
+    pub fn with_parameters(id: TransportID, parameters: Vec<TransportParameter>) -> Self {
+        TransportSpec { id, parameters }
+    }
+
+    /// Returns the ports given by the first `"client_port"` parameter, if present.
+    pub fn client_port(&self) -> Option<(u16, Option<u16>)> {
+        self.parameters
+            .iter()
+            .find_map(|parameter| match parameter {
+                TransportParameter::ClientPort(start, end) => Some((*start, *end)),
+                _ => None,
+            })
+    }
+
+    /// Returns the addresses given by the first `"dest_addr"` parameter, if present.
+    pub fn destination_addresses(&self) -> Option<&[TransportAddress]> {
+        self.parameters
+            .iter()
+            .find_map(|parameter| match parameter {
+                TransportParameter::DestinationAddresses(addresses) => Some(addresses.as_slice()),
+                _ => None,
+            })
+    }
+
+    pub fn id(&self) -> &TransportID {
+        &self.id
+    }
+
+    pub fn id_mut(&mut self) -> &mut TransportID {
+        &mut self.id
+    }
+
+    /// Returns the channels given by the first `"interleaved"` parameter, if present.
+    pub fn interleaved(&self) -> Option<(u8, Option<u8>)> {
+        self.parameters
+            .iter()
+            .find_map(|parameter| match parameter {
+                TransportParameter::Interleaved(start, end) => Some((*start, *end)),
+                _ => None,
+            })
+    }
+
+    /// Returns whether a `"multicast"` parameter is present.
+    pub fn is_multicast(&self) -> bool {
+        self.parameters.contains(&TransportParameter::Multicast)
+    }
+
+    /// Returns whether a `"unicast"` parameter is present.
+    pub fn is_unicast(&self) -> bool {
+        self.parameters.contains(&TransportParameter::Unicast)
+    }
+
+    /// Returns the modes given by the first `"mode"` parameter, if present.
+    pub fn mode(&self) -> Option<&[TransportMode]> {
+        self.parameters
+            .iter()
+            .find_map(|parameter| match parameter {
+                TransportParameter::Mode(modes) => Some(modes.as_slice()),
+                _ => None,
+            })
+    }
+
+    pub fn parameters(&self) -> &Vec<TransportParameter> {
+        &self.parameters
+    }
+
+    pub fn parameters_mut(&mut self) -> &mut Vec<TransportParameter> {
+        &mut self.parameters
+    }
+
+    /// Returns the ports given by the first `"server_port"` parameter, if present.
+    pub fn server_port(&self) -> Option<(u16, Option<u16>)> {
+        self.parameters
+            .iter()
+            .find_map(|parameter| match parameter {
+                TransportParameter::ServerPort(start, end) => Some((*start, *end)),
+                _ => None,
+            })
+    }
+
+    /// Returns the addresses given by the first `"src_addr"` parameter, if present.
+    pub fn source_addresses(&self) -> Option<&[TransportAddress]> {
+        self.parameters
+            .iter()
+            .find_map(|parameter| match parameter {
+                TransportParameter::SourceAddresses(addresses) => Some(addresses.as_slice()),
+                _ => None,
+            })
+    }
+
+    /// Returns the synchronization sources given by the first `"ssrc"` parameter, if present.
+    pub fn ssrc(&self) -> Option<&[u32]> {
+        self.parameters
+            .iter()
+            .find_map(|parameter| match parameter {
+                TransportParameter::SSRC(ssrcs) => Some(ssrcs.as_slice()),
+                _ => None,
+            })
+    }
+}
+
+impl fmt::Display for TransportSpec {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.id)?;
+
+        for parameter in &self.parameters {
+            if !parameter.is_empty() {
+                write!(formatter, ";{}", parameter)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TransportSpec {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut parts = split_unquoted(value, ';').into_iter();
+        let id = parts
+            .next()
+            .map(trim_whitespace)
+            .ok_or(InvalidTypedHeader)
+            .and_then(TransportID::try_from)?;
+        let parameters = parts
+            .map(|part| TransportParameter::try_from(trim_whitespace(part)))
+            .collect::<Result<Vec<TransportParameter>, InvalidTypedHeader>>()?;
+
+        Ok(TransportSpec { id, parameters })
+    }
+}
+
+/// The transport identifier of a transport specification which specifies the transport protocol
+/// (and, for RTP, the profile and lower transport) being used.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TransportID {
+    /// The RTP protocol using the given profile over the given lower transport. The absence of a
+    /// lower transport implies the default lower transport for the profile (typically UDP).
+    RTP(RTPProfile, Option<LowerTransport>),
+
+    /// A non-RTP transport protocol.
+    Other(OtherTransportID),
+}
+
+impl fmt::Display for TransportID {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransportID::RTP(profile, Some(lower_transport)) => {
+                write!(formatter, "RTP/{}/{}", profile, lower_transport)
+            }
+            TransportID::RTP(profile, None) => write!(formatter, "RTP/{}", profile),
+            TransportID::Other(id) => write!(formatter, "{}", id),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TransportID {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let parts = value.split('/').collect::<Vec<&str>>();
+
+        if parts[0] == "RTP" && (parts.len() == 2 || parts.len() == 3) {
+            let profile = RTPProfile::try_from(parts[1])?;
+            let lower_transport = match parts.get(2) {
+                Some(lower_transport) => Some(LowerTransport::try_from(*lower_transport)?),
+                None => None,
+            };
+
+            Ok(TransportID::RTP(profile, lower_transport))
+        } else {
+            Ok(TransportID::Other(OtherTransportID::try_from(value)?))
+        }
+    }
+}
+
+/// The RTP profile being used by an RTP transport identifier.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RTPProfile {
+    /// The RTP/AVP profile as described by [RFC3551](https://tools.ietf.org/html/rfc3551).
+    AVP,
+
+    /// The RTP/AVPF profile as described by [RFC4585](https://tools.ietf.org/html/rfc4585).
+    AVPF,
+
+    /// The RTP/SAVP profile as described by [RFC3711](https://tools.ietf.org/html/rfc3711).
+    SAVP,
+
+    /// The RTP/SAVPF profile as described by [RFC5124](https://tools.ietf.org/html/rfc5124).
+    SAVPF,
+
+    /// A non-standard profile.
+    Extension(TransportToken),
+}
+
+impl fmt::Display for RTPProfile {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RTPProfile::AVP => formatter.write_str("AVP"),
+            RTPProfile::AVPF => formatter.write_str("AVPF"),
+            RTPProfile::SAVP => formatter.write_str("SAVP"),
+            RTPProfile::SAVPF => formatter.write_str("SAVPF"),
+            RTPProfile::Extension(profile) => write!(formatter, "{}", profile),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for RTPProfile {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value {
+            "AVP" => Ok(RTPProfile::AVP),
+            "AVPF" => Ok(RTPProfile::AVPF),
+            "SAVP" => Ok(RTPProfile::SAVP),
+            "SAVPF" => Ok(RTPProfile::SAVPF),
+            _ => Ok(RTPProfile::Extension(TransportToken::try_from(value)?)),
+        }
+    }
+}
+
+/// The lower transport being used by an RTP transport identifier.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum LowerTransport {
+    TCP,
+    UDP,
+
+    /// A non-standard lower transport.
+    Extension(TransportToken),
+}
+
+impl fmt::Display for LowerTransport {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LowerTransport::TCP => formatter.write_str("TCP"),
+            LowerTransport::UDP => formatter.write_str("UDP"),
+            LowerTransport::Extension(lower_transport) => write!(formatter, "{}", lower_transport),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for LowerTransport {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value {
+            "TCP" => Ok(LowerTransport::TCP),
+            "UDP" => Ok(LowerTransport::UDP),
+            _ => Ok(LowerTransport::Extension(TransportToken::try_from(value)?)),
+        }
+    }
+}
+
+/// A non-RTP transport identifier consisting of one or more tokens separated by `'/'`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct OtherTransportID(String);
+
+impl OtherTransportID {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for OtherTransportID {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for OtherTransportID {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value.split('/').all(is_token) {
+            Ok(OtherTransportID(value.to_string()))
+        } else {
+            Err(InvalidTypedHeader)
+        }
+    }
+}
+
+/// A generic token used by the extension variants of the transport types.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TransportToken(String);
+
+impl TransportToken {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TransportToken {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TransportToken {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if is_token(value) {
+            Ok(TransportToken(value.to_string()))
+        } else {
+            Err(InvalidTypedHeader)
+        }
+    }
+}
+
+/// A single parameter of a transport specification.
+///
+/// Parameters with list values (e.g. [`TransportParameter::Mode`]) that are empty are omitted when
+/// serialized, since the syntax requires at least one value.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TransportParameter {
+    /// The RTSP 1.0 `"append"` parameter indicating that recorded media is appended to the
+    /// existing resource.
+    Append,
+
+    /// The RTSP 1.0 `"client_port"` parameter specifying a single port or a range of ports.
+    ClientPort(u16, Option<u16>),
+
+    /// The `"connection"` parameter indicating whether a new connection should be established.
+    Connection(ConnectionType),
+
+    /// The RTSP 1.0 `"destination"` parameter with an optional host.
+    Destination(Option<Host>),
+
+    /// The `"dest_addr"` parameter.
+    DestinationAddresses(Vec<TransportAddress>),
+
+    /// Any parameter that is not standardized.
+    Extension(ExtensionParameter),
+
+    /// The `"interleaved"` parameter specifying a single channel or a range of channels.
+    Interleaved(u8, Option<u8>),
+
+    /// The `"layers"` parameter specifying the number of multicast layers.
+    Layers(u32),
+
+    /// The `"mode"` parameter.
+    Mode(Vec<TransportMode>),
+
+    /// The `"multicast"` parameter.
+    Multicast,
+
+    /// The RTSP 1.0 `"port"` parameter specifying a single multicast port or a range of ports.
+    Port(u16, Option<u16>),
+
+    /// The `"RTCP-mux"` parameter indicating that RTP and RTCP are multiplexed.
+    RTCPMux,
+
+    /// The RTSP 1.0 `"server_port"` parameter specifying a single port or a range of ports.
+    ServerPort(u16, Option<u16>),
+
+    /// The `"setup"` parameter indicating the role in connection establishment.
+    Setup(ConnectionSetup),
+
+    /// The RTSP 1.0 `"source"` parameter.
+    Source(Host),
+
+    /// The `"src_addr"` parameter.
+    SourceAddresses(Vec<TransportAddress>),
+
+    /// The `"ssrc"` parameter.
+    SSRC(Vec<u32>),
+
+    /// The `"ttl"` parameter specifying the multicast time-to-live.
+    TTL(u8),
+
+    /// The `"unicast"` parameter.
+    Unicast,
+}
+
+impl TransportParameter {
+    fn is_empty(&self) -> bool {
+        match self {
+            TransportParameter::DestinationAddresses(addresses)
+            | TransportParameter::SourceAddresses(addresses) => addresses.is_empty(),
+            TransportParameter::Mode(modes) => modes.is_empty(),
+            TransportParameter::SSRC(ssrcs) => ssrcs.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for TransportParameter {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        use self::TransportParameter::*;
+
+        match self {
+            Append => formatter.write_str("append"),
+            ClientPort(start, end) => write_port_range(formatter, "client_port", *start, *end),
+            Connection(connection) => write!(formatter, "connection={}", connection),
+            Destination(Some(host)) => write!(formatter, "destination={}", host),
+            Destination(None) => formatter.write_str("destination"),
+            DestinationAddresses(addresses) => write!(
+                formatter,
+                "dest_addr={}",
+                addresses
+                    .iter()
+                    .map(|address| format!("\"{}\"", address))
+                    .join("/")
+            ),
+            Extension(extension) => write!(formatter, "{}", extension),
+            Interleaved(start, Some(end)) => write!(formatter, "interleaved={}-{}", start, end),
+            Interleaved(start, None) => write!(formatter, "interleaved={}", start),
+            Layers(layers) => write!(formatter, "layers={}", layers),
+            Mode(modes) => write!(formatter, "mode=\"{}\"", modes.iter().join(",")),
+            Multicast => formatter.write_str("multicast"),
+            Port(start, end) => write_port_range(formatter, "port", *start, *end),
+            RTCPMux => formatter.write_str("RTCP-mux"),
+            ServerPort(start, end) => write_port_range(formatter, "server_port", *start, *end),
+            Setup(setup) => write!(formatter, "setup={}", setup),
+            Source(host) => write!(formatter, "source={}", host),
+            SourceAddresses(addresses) => write!(
+                formatter,
+                "src_addr={}",
+                addresses
+                    .iter()
+                    .map(|address| format!("\"{}\"", address))
+                    .join("/")
+            ),
+            SSRC(ssrcs) => write!(
+                formatter,
+                "ssrc={}",
+                ssrcs.iter().map(|ssrc| format!("{:08X}", ssrc)).join("/")
+            ),
+            TTL(ttl) => write!(formatter, "ttl={}", ttl),
+            Unicast => formatter.write_str("unicast"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TransportParameter {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        use self::TransportParameter::*;
+
+        let (name, parameter_value) = match value.find('=') {
+            Some(index) => (
+                trim_whitespace(&value[..index]),
+                Some(trim_whitespace(&value[index + 1..])),
+            ),
+            None => (value, None),
+        };
+
+        let parameter = match (name.to_ascii_lowercase().as_str(), parameter_value) {
+            ("append", None) => Append,
+            ("client_port", Some(value)) => {
+                let (start, end) = parse_port_range(value)?;
+                ClientPort(start, end)
+            }
+            ("connection", Some(value)) => Connection(ConnectionType::try_from(value)?),
+            ("destination", Some(value)) => Destination(Some(parse_host(value)?)),
+            ("destination", None) => Destination(None),
+            ("dest_addr", Some(value)) => DestinationAddresses(parse_address_list(value)?),
+            ("interleaved", Some(value)) => {
+                let mut channels = value.splitn(2, '-');
+                let start = parse_digits(channels.next().unwrap(), 3)?;
+                let end = match channels.next() {
+                    Some(end) => Some(parse_digits(end, 3)?),
+                    None => None,
+                };
+
+                Interleaved(start, end)
+            }
+            ("layers", Some(value)) => Layers(parse_digits(value, 10)?),
+            ("mode", Some(value)) => {
+                let value = if value.starts_with('"') {
+                    if value.len() < 2 || !value.ends_with('"') {
+                        return Err(InvalidTypedHeader);
+                    }
+
+                    &value[1..value.len() - 1]
+                } else {
+                    value
+                };
+
+                Mode(
+                    value
+                        .split(',')
+                        .map(|mode| TransportMode::try_from(trim_whitespace(mode)))
+                        .collect::<Result<Vec<TransportMode>, InvalidTypedHeader>>()?,
+                )
+            }
+            ("multicast", None) => Multicast,
+            ("port", Some(value)) => {
+                let (start, end) = parse_port_range(value)?;
+                Port(start, end)
+            }
+            ("rtcp-mux", None) => RTCPMux,
+            ("server_port", Some(value)) => {
+                let (start, end) = parse_port_range(value)?;
+                ServerPort(start, end)
+            }
+            ("setup", Some(value)) => Setup(ConnectionSetup::try_from(value)?),
+            ("source", Some(value)) => Source(parse_host(value)?),
+            ("src_addr", Some(value)) => SourceAddresses(parse_address_list(value)?),
+            ("ssrc", Some(value)) => SSRC(
+                value
+                    .split('/')
+                    .map(|ssrc| {
+                        let ssrc = trim_whitespace(ssrc);
+
+                        if ssrc.len() != 8 || !ssrc.bytes().all(|b| b.is_ascii_hexdigit()) {
+                            return Err(InvalidTypedHeader);
+                        }
+
+                        u32::from_str_radix(ssrc, 16).map_err(|_| InvalidTypedHeader)
+                    })
+                    .collect::<Result<Vec<u32>, InvalidTypedHeader>>()?,
+            ),
+            ("ttl", Some(value)) => TTL(parse_digits(value, 3)?),
+            ("unicast", None) => Unicast,
+            ("client_port", None)
+            | ("connection", None)
+            | ("dest_addr", None)
+            | ("interleaved", None)
+            | ("layers", None)
+            | ("mode", None)
+            | ("port", None)
+            | ("server_port", None)
+            | ("setup", None)
+            | ("source", None)
+            | ("src_addr", None)
+            | ("ssrc", None)
+            | ("ttl", None)
+            | ("append", Some(_))
+            | ("multicast", Some(_))
+            | ("rtcp-mux", Some(_))
+            | ("unicast", Some(_)) => return Err(InvalidTypedHeader),
+            _ => Extension(ExtensionParameter::new(name, parameter_value)?),
+        };
+
+        Ok(parameter)
+    }
+}
+
+/// The value of the `"connection"` transport parameter.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConnectionType {
+    Existing,
+    New,
+}
+
+impl fmt::Display for ConnectionType {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConnectionType::Existing => formatter.write_str("existing"),
+            ConnectionType::New => formatter.write_str("new"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ConnectionType {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "existing" => Ok(ConnectionType::Existing),
+            "new" => Ok(ConnectionType::New),
+            _ => Err(InvalidTypedHeader),
+        }
+    }
+}
+
+/// The value of the `"setup"` transport parameter.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConnectionSetup {
+    Active,
+    ActPass,
+    Passive,
+}
+
+impl fmt::Display for ConnectionSetup {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConnectionSetup::Active => formatter.write_str("active"),
+            ConnectionSetup::ActPass => formatter.write_str("actpass"),
+            ConnectionSetup::Passive => formatter.write_str("passive"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ConnectionSetup {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "active" => Ok(ConnectionSetup::Active),
+            "actpass" => Ok(ConnectionSetup::ActPass),
+            "passive" => Ok(ConnectionSetup::Passive),
+            _ => Err(InvalidTypedHeader),
+        }
+    }
+}
+
+/// A single mode of the `"mode"` transport parameter.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TransportMode {
+    Play,
+    Record,
+
+    /// A non-standard mode.
+    Extension(TransportToken),
+}
+
+impl fmt::Display for TransportMode {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransportMode::Play => formatter.write_str("PLAY"),
+            TransportMode::Record => formatter.write_str("RECORD"),
+            TransportMode::Extension(mode) => write!(formatter, "{}", mode),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TransportMode {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_ascii_uppercase().as_str() {
+            "PLAY" => Ok(TransportMode::Play),
+            "RECORD" => Ok(TransportMode::Record),
+            _ => Ok(TransportMode::Extension(TransportToken::try_from(value)?)),
+        }
+    }
+}
+
+/// A single address of the `"dest_addr"` or `"src_addr"` transport parameters.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TransportAddress {
+    HostPort(HostPort),
+
+    /// An address of a format that is not a host and port.
+    Extension(ExtensionAddress),
+}
+
+impl fmt::Display for TransportAddress {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransportAddress::HostPort(host_port) => write!(formatter, "{}", host_port),
+            TransportAddress::Extension(address) => write!(formatter, "{}", address),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TransportAddress {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match HostPort::try_from(value) {
+            Ok(host_port) => Ok(TransportAddress::HostPort(host_port)),
+            Err(_) => Ok(TransportAddress::Extension(ExtensionAddress::try_from(
+                value,
+            )?)),
+        }
+    }
+}
+
+/// A host and port pair where at least one of the two is present.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct HostPort {
+    host: Option<Host>,
+    port: Option<u16>,
+}
+
+impl HostPort {
+    /// Constructs a new host and port pair.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the host is not a valid host or if neither a host nor a port is
+    /// given.
+    pub fn new(host: Option<&str>, port: Option<u16>) -> Result<Self, InvalidTypedHeader> {
+        let host = match host {
+            Some(host) => Some(Host::parse(host).map_err(|_| InvalidTypedHeader)?),
+            None if port.is_none() => return Err(InvalidTypedHeader),
+            None => None,
+        };
+
+        Ok(HostPort { host, port })
+    }
+
+    pub fn host(&self) -> Option<&Host> {
+        self.host.as_ref()
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+}
+
+impl fmt::Display for HostPort {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref host) = self.host {
+            write!(formatter, "{}", host)?;
+        }
+
+        if let Some(port) = self.port {
+            write!(formatter, ":{}", port)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for HostPort {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let port_index = if value.starts_with('[') {
+            let end = value.find(']').ok_or(InvalidTypedHeader)?;
+            Some(end + 1).filter(|&index| index < value.len())
+        } else {
+            value.rfind(':')
+        };
+
+        let (host, port) = match port_index {
+            Some(index) => {
+                if !value[index..].starts_with(':') {
+                    return Err(InvalidTypedHeader);
+                }
+
+                (&value[..index], Some(parse_digits(&value[index + 1..], 5)?))
+            }
+            None => (value, None),
+        };
+
+        HostPort::new(Some(host).filter(|host| !host.is_empty()), port)
+    }
+}
+
+/// An address that does not follow the host and port format. It may contain any quoted string
+/// text excluding escaped characters.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ExtensionAddress(String);
+
+impl ExtensionAddress {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ExtensionAddress {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ExtensionAddress {
+    type Error = InvalidTypedHeader;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if !value.is_empty()
+            && is_quoted_string(&format!("\"{}\"", value))
+            && !value.contains(|c| c == '"' || c == '\\')
+        {
+            Ok(ExtensionAddress(value.to_string()))
+        } else {
+            Err(InvalidTypedHeader)
+        }
+    }
+}
+
+/// A non-standard transport parameter with an optional value. The value is stored as it would be
+/// serialized, so a quoted string value retains its surrounding quotes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ExtensionParameter {
+    name: TransportToken,
+    value: Option<String>,
+}
+
+impl ExtensionParameter {
+    /// Constructs a new extension parameter.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the name is not a valid token or if the value is neither a
+    /// quoted string nor a valid unquoted parameter value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use rtsp::header::types::ExtensionParameter;
+    ///
+    /// let parameter = ExtensionParameter::new("client_port", Some("8000-8001")).unwrap();
+    /// assert_eq!(parameter.name(), "client_port");
+    /// assert_eq!(parameter.value(), Some("8000-8001"));
+    ///
+    /// assert!(ExtensionParameter::new("client_port", Some("8000;8001")).is_err());
+    /// ```
+    pub fn new(name: &str, value: Option<&str>) -> Result<Self, InvalidTypedHeader> {
+        let name = TransportToken::try_from(name)?;
+
+        if let Some(value) = value {
+            if !is_quoted_string(value) && !value.bytes().all(is_parameter_value_char) {
+                return Err(InvalidTypedHeader);
+            }
+        }
+
+        Ok(ExtensionParameter {
+            name,
+            value: value.map(|value| value.to_string()),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_ref().map(|value| value.as_str())
+    }
+}
+
+impl fmt::Display for ExtensionParameter {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            Some(ref value) => write!(formatter, "{}={}", self.name, value),
+            None => write!(formatter, "{}", self.name),
+        }
+    }
+}
+
+/// Returns whether the given byte may appear in an unquoted extension parameter value.
+fn is_parameter_value_char(b: u8) -> bool {
+    match b {
+        b'a'...b'z' | b'A'...b'Z' | b'0'...b'9' => true,
+        b'-' | b'.' | b'_' | b'~' | b'%' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*'
+        | b'+' | b'=' | b':' | b'@' | b'/' | b'?' => true,
+        _ => false,
+    }
+}
+
+/// Parses a list of quoted addresses separated by `'/'`.
+fn parse_address_list(value: &str) -> Result<Vec<TransportAddress>, InvalidTypedHeader> {
+    split_unquoted(value, '/')
+        .into_iter()
+        .map(|address| {
+            let address = trim_whitespace(address);
+
+            if address.len() < 2 || !address.starts_with('"') || !address.ends_with('"') {
+                return Err(InvalidTypedHeader);
+            }
+
+            TransportAddress::try_from(&address[1..address.len() - 1])
+        })
+        .collect()
+}
+
+/// Parses an unquoted host, as used by the RTSP 1.0 `"destination"` and `"source"` parameters.
+fn parse_host(value: &str) -> Result<Host, InvalidTypedHeader> {
+    if value.is_empty() || value.starts_with('"') {
+        return Err(InvalidTypedHeader);
+    }
+
+    Host::parse(value).map_err(|_| InvalidTypedHeader)
+}
+
+/// Parses a single port or a range of ports of the form `start-end`.
+fn parse_port_range(value: &str) -> Result<(u16, Option<u16>), InvalidTypedHeader> {
+    let mut ports = value.splitn(2, '-');
+    let start = parse_digits(ports.next().unwrap(), 5)?;
+    let end = match ports.next() {
+        Some(end) => Some(parse_digits(end, 5)?),
+        None => None,
+    };
+
+    Ok((start, end))
+}
+
+/// Writes a parameter whose value is a single port or a range of ports.
+fn write_port_range(
+    formatter: &mut fmt::Formatter,
+    name: &str,
+    start: u16,
+    end: Option<u16>,
+) -> fmt::Result {
+    match end {
+        Some(end) => write!(formatter, "{}={}-{}", name, start, end),
+        None => write!(formatter, "{}={}", name, start),
+    }
+}
+
+/// Parses a number consisting of one to `max_digits` decimal digits.
+fn parse_digits<T>(value: &str, max_digits: usize) -> Result<T, InvalidTypedHeader>
+where
+    T: ::std::str::FromStr,
+{
+    if value.is_empty() || value.len() > max_digits || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(InvalidTypedHeader);
+    }
+
+    value.parse::<T>().map_err(|_| InvalidTypedHeader)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(value: &str) -> Transport {
+        let raw_header = vec![HeaderValue::try_from(value).unwrap()];
+        let typed_header = Transport::try_from_header_raw(&raw_header).unwrap();
+        assert_eq!(
+            Transport::try_from_header_raw(&typed_header.to_header_raw()).unwrap(),
+            typed_header
+        );
+        typed_header
+    }
+
+    #[test]
+    fn test_transport_round_trip() {
+        let value = "RTP/AVP/TCP;unicast;interleaved=0-1;ssrc=0A13C760/000000FF;\
+                     mode=\"PLAY,RECORD\";setup=actpass;connection=new;RTCP-mux;layers=2, \
+                     RTP/SAVPF;multicast;ttl=127;dest_addr=\"[2001:db8::1]:4000\"/\":4001\";\
+                     src_addr=\"example.com\"/\"some address\";client_port=8000-8001;\
+                     x-note=\"a; b, c\", MPEG/TS/X-UDP;x-flag";
+        let typed_header = round_trip(value);
+
+        assert_eq!(typed_header.len(), 3);
+        assert_eq!(typed_header.to_header_raw()[0].as_str(), value);
+
+        let spec = &typed_header[0];
+        assert_eq!(
+            spec.id(),
+            &TransportID::RTP(RTPProfile::AVP, Some(LowerTransport::TCP))
+        );
+        assert!(spec.is_unicast());
+        assert_eq!(spec.interleaved(), Some((0, Some(1))));
+        assert_eq!(spec.ssrc(), Some(&[0x0A13_C760, 0xFF][..]));
+        assert_eq!(
+            spec.mode(),
+            Some(&[TransportMode::Play, TransportMode::Record][..])
+        );
+
+        let spec = &typed_header[1];
+        assert!(spec.is_multicast());
+        let addresses = spec.destination_addresses().unwrap();
+        match addresses[1] {
+            TransportAddress::HostPort(ref host_port) => {
+                assert_eq!(host_port.host(), None);
+                assert_eq!(host_port.port(), Some(4001));
+            }
+            _ => panic!("expected host and port"),
+        }
+        let addresses = spec.source_addresses().unwrap();
+        assert_eq!(
+            addresses[1],
+            TransportAddress::Extension(ExtensionAddress::try_from("some address").unwrap())
+        );
+
+        let spec = &typed_header[2];
+        assert_eq!(
+            spec.id(),
+            &TransportID::Other(OtherTransportID::try_from("MPEG/TS/X-UDP").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_transport_rtsp10_parameters() {
+        let value = "RTP/AVP;unicast;client_port=8000-8001;server_port=9000-9001;source=192.0.2.1;\
+                     destination;mode=\"RECORD\";append, \
+                     RTP/AVP;multicast;destination=224.2.0.1;port=3456-3457;ttl=16";
+        let typed_header = round_trip(value);
+
+        assert_eq!(typed_header.to_header_raw()[0].as_str(), value);
+
+        let spec = &typed_header[0];
+        assert_eq!(spec.client_port(), Some((8000, Some(8001))));
+        assert_eq!(spec.server_port(), Some((9000, Some(9001))));
+        assert!(spec
+            .parameters()
+            .contains(&TransportParameter::Destination(None)));
+        assert!(spec.parameters().contains(&TransportParameter::Append));
+        assert_eq!(spec.mode(), Some(&[TransportMode::Record][..]));
+
+        let spec = &typed_header[1];
+        assert_eq!(spec.client_port(), None);
+        assert!(spec
+            .parameters()
+            .contains(&TransportParameter::Port(3456, Some(3457))));
+        assert!(spec
+            .parameters()
+            .contains(&TransportParameter::Destination(Some(
+                Host::parse("224.2.0.1").unwrap()
+            ))));
+    }
+
+    #[test]
+    fn test_transport_normalization() {
+        let typed_header = round_trip("RTP/AVP ; Unicast ; MODE = play ; TTL=4");
+        assert_eq!(
+            typed_header.to_header_raw()[0].as_str(),
+            "RTP/AVP;unicast;mode=\"PLAY\";ttl=4"
+        );
+    }
+
+    #[test]
+    fn test_transport_invalid() {
+        let invalid = [
+            "",
+            "RTP/AVP;",
+            "RTP/AVP;interleaved=256",
+            "RTP/AVP;interleaved",
+            "RTP/AVP;unicast=1",
+            "RTP/AVP;ssrc=1234",
+            "RTP/AVP;dest_addr=192.0.2.5",
+            "RTP/AVP;client_port=70000",
+            "RTP/AVP;server_port",
+            "RTP/AVP;source=\"192.0.2.5\"",
+            "RTP/AVP;append=1",
+            "RTP/AVP;mode=\"PLAY",
+            "RTP/AVP;setup=maybe",
+            "RTP/AVP;x-ext=a b",
+        ];
+
+        for value in invalid.iter() {
+            let raw_header = vec![HeaderValue::try_from(*value).unwrap()];
+            assert!(
+                Transport::try_from_header_raw(&raw_header).is_err(),
+                "expected {:?} to be invalid",
+                value
+            );
+        }
+    }
+}
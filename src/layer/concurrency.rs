@@ -0,0 +1,118 @@
+use bytes::BytesMut;
+use futures::{future, Future};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use layer::Layer;
+use protocol::Service;
+use request::Request;
+use response::Response;
+use status::StatusCode;
+
+/// A layer that limits the number of requests that are being serviced at once.
+///
+/// The limit is shared by all services created by the same layer, so when used with a server it
+/// applies across all connections. Since a [`Service`] cannot signal that it is not ready to accept
+/// a request, requests above the limit are answered with `503 Service Unavailable` rather than
+/// being queued.
+#[derive(Clone, Debug)]
+pub struct ConcurrencyLimitLayer {
+    in_flight: Arc<AtomicUsize>,
+    max: usize,
+}
+
+impl ConcurrencyLimitLayer {
+    pub fn new(max: usize) -> Self {
+        ConcurrencyLimitLayer {
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            max,
+        }
+    }
+
+    /// Returns the number of requests that are currently being serviced.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+}
+
+impl<S> Layer<S> for ConcurrencyLimitLayer {
+    type Service = ConcurrencyLimit<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        ConcurrencyLimit {
+            in_flight: self.in_flight.clone(),
+            max: self.max,
+            service,
+        }
+    }
+}
+
+/// The service created by a [`ConcurrencyLimitLayer`].
+pub struct ConcurrencyLimit<S> {
+    in_flight: Arc<AtomicUsize>,
+    max: usize,
+    service: S,
+}
+
+impl<S> ConcurrencyLimit<S> {
+    /// Reserves a slot for a request, returning `None` if the limit has been reached.
+    fn acquire(&self) -> Option<Permit> {
+        let mut current = self.in_flight.load(Ordering::SeqCst);
+
+        loop {
+            if current >= self.max {
+                return None;
+            }
+
+            match self.in_flight.compare_exchange(
+                current,
+                current + 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return Some(Permit(self.in_flight.clone())),
+                Err(previous) => current = previous,
+            }
+        }
+    }
+}
+
+impl<S> Service for ConcurrencyLimit<S>
+where
+    S: Service<Request = Request<BytesMut>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Into<Response<BytesMut>>,
+{
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = S::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let permit = match self.acquire() {
+            Some(permit) => permit,
+            None => {
+                let response = Response::builder()
+                    .status_code(StatusCode::ServiceUnavailable)
+                    .build(BytesMut::new())
+                    .expect("service unavailable response should not be invalid");
+                return Box::new(future::ok(response));
+            }
+        };
+
+        Box::new(self.service.call(request).then(move |result| {
+            drop(permit);
+            result.map(Into::into)
+        }))
+    }
+}
+
+/// A reserved slot that is released once dropped.
+struct Permit(Arc<AtomicUsize>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,73 @@
+use bytes::BytesMut;
+use futures::Future;
+use std::time::Duration;
+use tokio_timer::Timeout;
+
+use layer::Layer;
+use protocol::Service;
+use request::Request;
+use response::Response;
+use status::StatusCode;
+
+/// A layer that responds with `503 Service Unavailable` if a request has not been serviced within
+/// a fixed duration. The future of the inner service is dropped once the deadline has passed.
+///
+/// The services created by this layer must be polled within the context of a timer, such as the
+/// one provided by the Tokio runtime.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DeadlineLayer {
+    duration: Duration,
+}
+
+impl DeadlineLayer {
+    pub fn new(duration: Duration) -> Self {
+        DeadlineLayer { duration }
+    }
+}
+
+impl<S> Layer<S> for DeadlineLayer {
+    type Service = Deadline<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        Deadline {
+            duration: self.duration,
+            service,
+        }
+    }
+}
+
+/// The service created by a [`DeadlineLayer`].
+pub struct Deadline<S> {
+    duration: Duration,
+    service: S,
+}
+
+impl<S> Service for Deadline<S>
+where
+    S: Service<Request = Request<BytesMut>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Into<Response<BytesMut>>,
+{
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = S::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let future = Timeout::new(self.service.call(request), self.duration);
+
+        Box::new(future.then(|result| {
+            match result {
+                Ok(response) => Ok(response.into()),
+                Err(error) => match error.into_inner() {
+                    Some(error) => Err(error),
+                    None => Ok(Response::builder()
+                        .status_code(StatusCode::ServiceUnavailable)
+                        .build(BytesMut::new())
+                        .expect("service unavailable response should not be invalid")),
+                },
+            }
+        }))
+    }
+}
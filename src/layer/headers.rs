@@ -0,0 +1,89 @@
+use bytes::BytesMut;
+use chrono::Utc;
+use futures::Future;
+use std::convert::TryFrom;
+
+use header::{HeaderName, HeaderValue};
+use layer::Layer;
+use protocol::Service;
+use request::Request;
+use response::Response;
+
+/// A layer that adds a `"Date"` header, and optionally a `"Server"` header, to responses that do
+/// not already have them.
+#[derive(Clone, Debug, Default)]
+pub struct ServerDateLayer {
+    server: Option<HeaderValue>,
+}
+
+impl ServerDateLayer {
+    /// Constructs a new layer that only adds the `"Date"` header.
+    pub fn new() -> Self {
+        ServerDateLayer::default()
+    }
+
+    /// Constructs a new layer that adds the `"Date"` header along with a `"Server"` header with the
+    /// given value.
+    pub fn with_server(server: HeaderValue) -> Self {
+        ServerDateLayer {
+            server: Some(server),
+        }
+    }
+}
+
+impl<S> Layer<S> for ServerDateLayer {
+    type Service = ServerDate<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        ServerDate {
+            server: self.server.clone(),
+            service,
+        }
+    }
+}
+
+/// The service created by a [`ServerDateLayer`].
+pub struct ServerDate<S> {
+    server: Option<HeaderValue>,
+    service: S,
+}
+
+impl<S> Service for ServerDate<S>
+where
+    S: Service<Request = Request<BytesMut>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Into<Response<BytesMut>>,
+{
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = S::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let server = self.server.clone();
+
+        Box::new(self.service.call(request).map(move |response| {
+            let mut response = response.into();
+
+            if !response.headers().contains_key(HeaderName::Date) {
+                response.headers_mut().insert(HeaderName::Date, date());
+            }
+
+            if let Some(server) = server {
+                if !response.headers().contains_key(HeaderName::Server) {
+                    response.headers_mut().insert(HeaderName::Server, server);
+                }
+            }
+
+            response
+        }))
+    }
+}
+
+/// Returns the current time formatted as an HTTP date, such as
+/// `"Tue, 15 Nov 1994 08:12:31 GMT"`.
+fn date() -> HeaderValue {
+    let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+    HeaderValue::try_from(date.as_str()).expect("formatted date should be a valid header value")
+}
@@ -0,0 +1,76 @@
+use bytes::BytesMut;
+use futures::Future;
+use std::time::Instant;
+
+use layer::Layer;
+use protocol::Service;
+use request::Request;
+use response::Response;
+
+/// A layer that logs every request along with the status code of its response and how long it
+/// took to service, using the `log` crate.
+///
+/// Successful requests are logged at the `info` level, while requests whose service failed are
+/// logged at the `warn` level.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct LoggingLayer;
+
+impl LoggingLayer {
+    pub fn new() -> Self {
+        LoggingLayer
+    }
+}
+
+impl<S> Layer<S> for LoggingLayer {
+    type Service = Logging<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        Logging { service }
+    }
+}
+
+/// The service created by a [`LoggingLayer`].
+pub struct Logging<S> {
+    service: S,
+}
+
+impl<S> Service for Logging<S>
+where
+    S: Service<Request = Request<BytesMut>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Into<Response<BytesMut>>,
+{
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = S::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let method = request.method().clone();
+        let uri = request.uri().clone();
+        let start = Instant::now();
+
+        Box::new(self.service.call(request).then(move |result| {
+            let elapsed = start.elapsed();
+
+            match result {
+                Ok(response) => {
+                    let response = response.into();
+                    info!(
+                        "{} {} {} {:?}",
+                        method,
+                        uri,
+                        response.status_code(),
+                        elapsed
+                    );
+                    Ok(response)
+                }
+                Err(error) => {
+                    warn!("{} {} failed after {:?}", method, uri, elapsed);
+                    Err(error)
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,262 @@
+//! Service Layers
+//!
+//! This module contains the [`Layer`] trait, which describes middleware that wraps a [`Service`] to
+//! add behavior that is independent of the service itself, such as logging or timeouts. Layers are
+//! composed using a [`ServiceBuilder`].
+//!
+//! [`Service`]: ../protocol/service/trait.Service.html
+
+mod concurrency;
+mod deadline;
+mod headers;
+mod logging;
+
+pub use self::concurrency::{ConcurrencyLimit, ConcurrencyLimitLayer};
+pub use self::deadline::{Deadline, DeadlineLayer};
+pub use self::headers::{ServerDate, ServerDateLayer};
+pub use self::logging::{Logging, LoggingLayer};
+
+use header::HeaderValue;
+use std::time::Duration;
+
+/// Wraps a service of type `S` to produce a new service.
+pub trait Layer<S> {
+    type Service;
+
+    fn layer(&self, service: S) -> Self::Service;
+}
+
+/// A layer that returns the service unchanged.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Identity;
+
+impl<S> Layer<S> for Identity {
+    type Service = S;
+
+    fn layer(&self, service: S) -> Self::Service {
+        service
+    }
+}
+
+/// Two layers that are applied one after the other. The `inner` layer wraps the service first and
+/// the `outer` layer wraps the result.
+#[derive(Clone, Debug)]
+pub struct Stack<Inner, Outer> {
+    inner: Inner,
+    outer: Outer,
+}
+
+impl<S, Inner, Outer> Layer<S> for Stack<Inner, Outer>
+where
+    Inner: Layer<S>,
+    Outer: Layer<Inner::Service>,
+{
+    type Service = Outer::Service;
+
+    fn layer(&self, service: S) -> Self::Service {
+        self.outer.layer(self.inner.layer(service))
+    }
+}
+
+/// A builder for composing layers around a service.
+///
+/// Layers are applied in the order in which they are added, so the first layer added is the
+/// outermost one and sees requests first and responses last.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate bytes;
+/// # extern crate futures;
+/// # extern crate rtsp;
+/// #
+/// use bytes::BytesMut;
+/// use futures::{future, Future};
+/// use rtsp::layer::ServiceBuilder;
+/// use rtsp::{Request, Response, Service};
+/// use std::io;
+/// use std::time::Duration;
+///
+/// struct Application;
+///
+/// impl Service for Application {
+///     type Request = Request<BytesMut>;
+///     type Response = Response<BytesMut>;
+///     type Error = io::Error;
+///     type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+///
+///     fn call(&mut self, _: Self::Request) -> Self::Future {
+///         Box::new(future::ok(Response::builder().build(BytesMut::new()).unwrap()))
+///     }
+/// }
+///
+/// # fn main() {
+/// let builder = ServiceBuilder::new()
+///     .logging()
+///     .server_date()
+///     .deadline(Duration::from_secs(10))
+///     .concurrency_limit(64);
+/// let service = builder.service(Application);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ServiceBuilder<L> {
+    layer: L,
+}
+
+impl ServiceBuilder<Identity> {
+    pub fn new() -> Self {
+        ServiceBuilder { layer: Identity }
+    }
+}
+
+impl Default for ServiceBuilder<Identity> {
+    fn default() -> Self {
+        ServiceBuilder::new()
+    }
+}
+
+impl<L> ServiceBuilder<L> {
+    /// Adds a layer that limits the number of requests that are being serviced at once across all
+    /// services built by this builder.
+    pub fn concurrency_limit(self, max: usize) -> ServiceBuilder<Stack<ConcurrencyLimitLayer, L>> {
+        self.layer(ConcurrencyLimitLayer::new(max))
+    }
+
+    /// Adds a layer that responds with `503 Service Unavailable` if a request has not been
+    /// serviced within the given duration.
+    pub fn deadline(self, duration: Duration) -> ServiceBuilder<Stack<DeadlineLayer, L>> {
+        self.layer(DeadlineLayer::new(duration))
+    }
+
+    /// Adds the given layer. It will wrap all layers that are added after it.
+    pub fn layer<T>(self, layer: T) -> ServiceBuilder<Stack<T, L>> {
+        ServiceBuilder {
+            layer: Stack {
+                inner: layer,
+                outer: self.layer,
+            },
+        }
+    }
+
+    /// Adds a layer that logs every request along with its response.
+    pub fn logging(self) -> ServiceBuilder<Stack<LoggingLayer, L>> {
+        self.layer(LoggingLayer::new())
+    }
+
+    /// Adds a layer that adds a `"Date"` header to responses that do not have one.
+    pub fn server_date(self) -> ServiceBuilder<Stack<ServerDateLayer, L>> {
+        self.layer(ServerDateLayer::new())
+    }
+
+    /// Adds a layer that adds a `"Date"` header and a `"Server"` header with the given value to
+    /// responses that do not have them.
+    pub fn server_date_with_server(
+        self,
+        server: HeaderValue,
+    ) -> ServiceBuilder<Stack<ServerDateLayer, L>> {
+        self.layer(ServerDateLayer::with_server(server))
+    }
+
+    /// Wraps the given service with all of the layers that have been added.
+    pub fn service<S>(&self, service: S) -> L::Service
+    where
+        L: Layer<S>,
+    {
+        self.layer.layer(service)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use bytes::BytesMut;
+    use futures::sync::oneshot;
+    use futures::{future, Future};
+    use std::convert::TryFrom;
+    use std::io;
+    use tokio::runtime::current_thread::Runtime;
+
+    use header::HeaderName;
+    use method::Method;
+    use protocol::Service;
+    use request::Request;
+    use response::Response;
+    use status::StatusCode;
+
+    /// Responds once the corresponding sender has been triggered, or immediately if there is none.
+    struct Application(Option<oneshot::Receiver<()>>);
+
+    impl Service for Application {
+        type Request = Request<BytesMut>;
+        type Response = Response<BytesMut>;
+        type Error = io::Error;
+        type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+        fn call(&mut self, _: Self::Request) -> Self::Future {
+            let response = Response::builder().build(BytesMut::new()).unwrap();
+
+            match self.0.take() {
+                Some(rx) => Box::new(rx.then(move |_| Ok(response))),
+                None => Box::new(future::ok(response)),
+            }
+        }
+    }
+
+    fn request() -> Request<BytesMut> {
+        Request::builder()
+            .method(Method::Options)
+            .uri("rtsp://example.com/")
+            .build(BytesMut::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_server_date_layer() {
+        let server = HeaderValue::try_from("rtsp-rs").unwrap();
+        let mut service = ServiceBuilder::new()
+            .server_date_with_server(server.clone())
+            .service(Application(None));
+        let response = service.call(request()).wait().unwrap();
+
+        assert_eq!(response.headers().get(HeaderName::Server), Some(&server));
+        assert!(response.headers().contains_key(HeaderName::Date));
+    }
+
+    #[test]
+    fn test_concurrency_limit_layer() {
+        let layer = ConcurrencyLimitLayer::new(1);
+        let (tx, rx) = oneshot::channel();
+        let mut first = layer.layer(Application(Some(rx)));
+        let mut second = layer.layer(Application(None));
+
+        let mut pending = first.call(request());
+        assert_eq!(layer.in_flight(), 1);
+
+        let response = second.call(request()).wait().unwrap();
+        assert_eq!(response.status_code(), StatusCode::ServiceUnavailable);
+
+        tx.send(()).unwrap();
+        assert!(pending.poll().unwrap().is_ready());
+        assert_eq!(layer.in_flight(), 0);
+
+        let response = second.call(request()).wait().unwrap();
+        assert_eq!(response.status_code(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_deadline_layer() {
+        let (_tx, rx) = oneshot::channel();
+        let mut service = ServiceBuilder::new()
+            .logging()
+            .deadline(Duration::from_millis(10))
+            .service(Application(Some(rx)));
+
+        let response = Runtime::new()
+            .unwrap()
+            .block_on(service.call(request()))
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::ServiceUnavailable);
+    }
+}
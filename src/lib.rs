@@ -3,6 +3,7 @@
 #![recursion_limit = "256"]
 
 extern crate ascii;
+extern crate base64;
 extern crate bytes;
 extern crate chrono;
 extern crate itertools;
@@ -13,28 +14,43 @@ extern crate fnv;
 extern crate futures;
 #[macro_use]
 extern crate lazy_static;
+#[macro_use]
+extern crate log;
+extern crate md5;
+#[cfg(feature = "tls")]
+extern crate native_tls;
 extern crate rand;
 extern crate regex;
+extern crate sha2;
 #[cfg(test)]
 extern crate tokio;
 extern crate tokio_executor;
 extern crate tokio_io;
 extern crate tokio_tcp;
 extern crate tokio_timer;
+#[cfg(feature = "tls")]
+extern crate tokio_tls;
 extern crate url;
 
 mod syntax;
 
+pub mod auth;
 pub mod client;
 pub mod header;
+pub mod layer;
 pub mod method;
 pub mod protocol;
 pub mod reason;
 pub mod request;
 pub mod response;
+pub mod sdp;
 pub mod server;
 pub mod session;
+pub mod state;
 pub mod status;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod tunnel;
 pub mod uri;
 pub mod version;
 
@@ -43,6 +43,12 @@ use syntax::is_token;
 #[derive(Clone, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum Method {
+    /// ANNOUNCE
+    /// [[RFC2326, Section 10.3](https://tools.ietf.org/html/rfc2326#section-10.3)]
+    ///
+    /// This method was removed in RTSP 2.0 and is only used with RTSP 1.0.
+    Announce,
+
     /// DESCRIBE
     /// [[RFC7826, Section 13.2](https://tools.ietf.org/html/rfc7826#section-13.2)]
     Describe,
@@ -67,6 +73,12 @@ pub enum Method {
     /// [[RFC7826, Section 13.5](https://tools.ietf.org/html/rfc7826#section-13.5)]
     PlayNotify,
 
+    /// RECORD
+    /// [[RFC2326, Section 10.11](https://tools.ietf.org/html/rfc2326#section-10.11)]
+    ///
+    /// This method was removed in RTSP 2.0 and is only used with RTSP 1.0.
+    Record,
+
     /// REDIRECT
     /// [[RFC7826, Section 13.10](https://tools.ietf.org/html/rfc7826#section-13.10)]
     Redirect,
@@ -108,12 +120,14 @@ impl Method {
         use self::Method::*;
 
         match *self {
+            Announce => "ANNOUNCE",
             Describe => "DESCRIBE",
             GetParameter => "GET_PARAMETER",
             Options => "OPTIONS",
             Pause => "PAUSE",
             Play => "PLAY",
             PlayNotify => "PLAY_NOTIFY",
+            Record => "RECORD",
             Redirect => "REDIRECT",
             SetParameter => "SET_PARAMETER",
             Setup => "SETUP",
@@ -139,19 +153,22 @@ impl Method {
     }
 
     /// Returns whether the given method name is valid. Based on
-    /// [[RFC7826, Section 20.1](https://tools.ietf.org/html/rfc7826#section-20.1)], a method name
-    /// follows the following rules:
+    /// [[RFC7826, Section 20.1](https://tools.ietf.org/html/rfc7826#section-20.1)] along with the
+    /// RTSP 1.0 methods from [[RFC2326](https://tools.ietf.org/html/rfc2326#section-6.1)], a
+    /// method name follows the following rules:
     ///
     /// ```text
     /// token = 1*(%x21 / %x23-27 / %x2A-2B / %x2D-2E / %x30-39
     ///       /  %x41-5A / %x5E-7A / %x7C / %x7E)
     ///          ; 1*<any CHAR except CTLs or tspecials>
-    /// Method = "DESCRIBE"
+    /// Method = "ANNOUNCE"
+    ///        / "DESCRIBE"
     ///        / "GET_PARAMETER"
     ///        / "OPTIONS"
     ///        / "PAUSE"
     ///        / "PLAY"
     ///        / "PLAY_NOTIFY"
+    ///        / "RECORD"
     ///        / "REDIRECT"
     ///        / "SETUP"
     ///        / "SET_PARAMETER"
@@ -262,6 +279,9 @@ impl<'a> TryFrom<&'a [u8]> for Method {
     /// let describe = Method::try_from(&b"describe"[..]).unwrap();
     /// assert_eq!(describe, Method::Describe);
     ///
+    /// let record = Method::try_from(&b"RECORD"[..]).unwrap();
+    /// assert_eq!(record, Method::Record);
+    ///
     /// let extension = Method::try_from(&b"Ext"[..]).unwrap();
     /// assert_eq!(extension.as_str(), "EXT");
     ///
@@ -283,11 +303,16 @@ impl<'a> TryFrom<&'a [u8]> for Method {
                 b"SETUP" => Ok(Setup),
                 _ => Method::extension(value.as_slice()),
             },
+            6 => match value.as_slice() {
+                b"RECORD" => Ok(Record),
+                _ => Method::extension(value.as_slice()),
+            },
             7 => match value.as_slice() {
                 b"OPTIONS" => Ok(Options),
                 _ => Method::extension(value.as_slice()),
             },
             8 => match value.as_slice() {
+                b"ANNOUNCE" => Ok(Announce),
                 b"DESCRIBE" => Ok(Describe),
                 b"REDIRECT" => Ok(Redirect),
                 b"TEARDOWN" => Ok(Teardown),
@@ -7,11 +7,13 @@ use std::{fmt, io};
 use tokio_io::codec::{Decoder, Encoder};
 
 use protocol::{
-    encode_request, encode_response, InvalidRequest, InvalidResponse, ParseResult, ParseState,
-    RequestDecoder, ResponseDecoder,
+    encode_interleaved_data, encode_request, encode_response, DecoderLimits, InterleavedData,
+    InvalidRequest, InvalidResponse, ParseResult, ParseState, RequestDecoder, ResponseDecoder,
+    Strictness, INTERLEAVED_DATA_HEADER_SIZE, INTERLEAVED_DATA_MAGIC,
 };
 use request::Request;
 use response::Response;
+use version::{Version, VersionPolicy};
 
 /// The minimum amount of bytes needed in the information line in order to differentiate between
 /// responses and requests.
@@ -34,6 +36,10 @@ pub type ResponseResult = Result<Response<BytesMut>, RecoverableInvalidResponse>
 /// two (as well as proxies).
 #[derive(Debug)]
 pub struct Codec {
+    /// The version that was negotiated with the peer. This is only set if the version policy is
+    /// [`VersionPolicy::Negotiate`] and a request or response has been decoded.
+    negotiated_version: Option<Version>,
+
     /// The request decoder that maintains partial parsing state.
     request_decoder: RequestDecoder,
 
@@ -58,12 +64,80 @@ impl Codec {
     /// * `tx_event` - The sink that will be sent any [`CodecEvent`] that occur.
     pub fn with_events(tx_event: UnboundedSender<CodecEvent>) -> Self {
         Codec {
+            negotiated_version: None,
             request_decoder: RequestDecoder::new(),
             response_decoder: ResponseDecoder::new(),
             tx_event: Some(tx_event),
         }
     }
 
+    /// Returns the limits enforced on the requests and responses being decoded.
+    pub fn decoder_limits(&self) -> DecoderLimits {
+        self.request_decoder.limits()
+    }
+
+    /// Sets the limits enforced on the requests and responses being decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The decoder limits to use.
+    pub fn set_decoder_limits(&mut self, limits: DecoderLimits) {
+        self.request_decoder.set_limits(limits);
+        self.response_decoder.set_limits(limits);
+    }
+
+    /// Sets how strictly the syntax of the requests and responses being decoded is followed.
+    ///
+    /// # Arguments
+    ///
+    /// * `strictness` - The strictness to use.
+    pub fn set_strictness(&mut self, strictness: Strictness) {
+        self.request_decoder.set_strictness(strictness);
+        self.response_decoder.set_strictness(strictness);
+    }
+
+    /// Returns how strictly the syntax of the requests and responses being decoded is followed.
+    pub fn strictness(&self) -> Strictness {
+        self.request_decoder.strictness()
+    }
+
+    /// Sets the policy that determines which versions are accepted when decoding and which version
+    /// is used when encoding. Any previously negotiated version is forgotten.
+    ///
+    /// # Arguments
+    ///
+    /// * `version_policy` - The version policy to use.
+    pub fn set_version_policy(&mut self, version_policy: VersionPolicy) {
+        self.negotiated_version = None;
+        self.request_decoder.set_version_policy(version_policy);
+        self.response_decoder.set_version_policy(version_policy);
+    }
+
+    /// Returns the version that requests and responses are encoded with. This is the negotiated
+    /// version if there is one, and otherwise the preferred version of the version policy.
+    pub fn version(&self) -> Version {
+        self.negotiated_version
+            .unwrap_or_else(|| self.version_policy().preferred_version())
+    }
+
+    /// Returns the policy that determines which versions are accepted when decoding and which
+    /// version is used when encoding.
+    pub fn version_policy(&self) -> VersionPolicy {
+        self.request_decoder.version_policy()
+    }
+
+    /// Records the version of a decoded request or response as the negotiated version if the
+    /// version policy is [`VersionPolicy::Negotiate`] and no version has been negotiated yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version of the decoded request or response.
+    fn negotiate_version(&mut self, version: Version) {
+        if self.version_policy() == VersionPolicy::Negotiate && self.negotiated_version.is_none() {
+            self.negotiated_version = Some(version);
+        }
+    }
+
     /// Sends a [`CodecEvent`] through the internal event sink.
     ///
     /// If an error is encountered while sending the codec event, then no more events will be sent
@@ -79,6 +153,48 @@ impl Codec {
             .and_then(|tx_event| tx_event.unbounded_send(event).ok().map(|_| tx_event));
     }
 
+    /// Decodes an interleaved binary data frame.
+    ///
+    /// This function assumes that the buffer starts with the `'$'` character. Since the frame
+    /// header contains the length of the payload, no partial parsing state needs to be maintained.
+    /// If the entire frame is present, a [`CodecEvent::DecodingEnded`] event will be sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The byte buffer containing the interleaved binary data frame to decode.
+    ///
+    /// # Return Value
+    ///
+    /// * If there was enough data provided to decode the entire frame, then
+    ///   `Ok(Some(Ok(`[`Message`]`)))` will be returned.
+    /// * If there was not enough data, then `Ok(None)` will be returned indicating that more data
+    ///   is needed.
+    fn decode_interleaved_data(
+        &mut self,
+        buffer: &mut BytesMut,
+    ) -> Result<Option<<Self as Decoder>::Item>, <Self as Decoder>::Error> {
+        debug_assert!(buffer.starts_with(&[INTERLEAVED_DATA_MAGIC]));
+
+        if buffer.len() < INTERLEAVED_DATA_HEADER_SIZE {
+            return Ok(None);
+        }
+
+        let channel = buffer[1];
+        let payload_size = ((buffer[2] as usize) << 8) | buffer[3] as usize;
+
+        if buffer.len() < INTERLEAVED_DATA_HEADER_SIZE + payload_size {
+            return Ok(None);
+        }
+
+        buffer.split_to(INTERLEAVED_DATA_HEADER_SIZE);
+        let payload = buffer.split_to(payload_size);
+        let data = InterleavedData::new(channel, payload)
+            .expect("payload size should not exceed maximum interleaved data size");
+
+        self.send_codec_event(CodecEvent::DecodingEnded);
+        Ok(Some(Ok(Message::InterleavedData(data))))
+    }
+
     /// Decodes a request.
     ///
     /// Using the internal request decoder, this function will attempt to make progress on decoding
@@ -110,6 +226,7 @@ impl Codec {
 
         match result {
             ParseResult::Complete(request) => {
+                self.negotiate_version(request.version());
                 self.send_codec_event(CodecEvent::DecodingEnded);
                 Ok(Some(Ok(Message::Request(request))))
             }
@@ -163,6 +280,7 @@ impl Codec {
 
         match result {
             ParseResult::Complete(response) => {
+                self.negotiate_version(response.version());
                 self.send_codec_event(CodecEvent::DecodingEnded);
                 Ok(Some(Ok(Message::Response(response))))
             }
@@ -193,9 +311,9 @@ impl Decoder for Codec {
     /// Decodes a message.
     ///
     /// Using the internal decoders, this function will attempt to make progress on decoding either
-    /// a request or response using the buffer. If neither of the decoders were active, this
-    /// function will send a [`CodecEvent::DecodingStarted`] event if the buffer is non-empty after
-    /// removing all preceding newlines.
+    /// a request, response, or interleaved binary data frame using the buffer. If neither of the
+    /// decoders were active, this function will send a [`CodecEvent::DecodingStarted`] event if
+    /// the buffer is non-empty after removing all preceding newlines.
     ///
     /// # Arguments
     ///
@@ -216,8 +334,9 @@ impl Decoder for Codec {
     fn decode(&mut self, buffer: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
         // Need to determine whether we are trying to decode a request or response. If either of the
         // internal decoder states are past their starting states, then we continue off of that.
-        // Otherwise, we check if the message starts with `"RTSP/"` which indicates that it is a
-        // response. If not, it is a request.
+        // Otherwise, we check if the message starts with `'$'` which indicates that it is an
+        // interleaved binary data frame or `"RTSP/"` which indicates that it is a response. If
+        // neither, it is a request.
 
         if self.request_decoder.parse_state() != ParseState::InfoLine {
             self.decode_request(buffer)
@@ -226,17 +345,27 @@ impl Decoder for Codec {
         } else {
             // Ignore any preceding newlines.
 
-            while buffer.starts_with(b"\r\n") {
-                buffer.split_to(2);
+            let strictness = self.strictness();
+
+            loop {
+                if buffer.starts_with(b"\r\n") {
+                    buffer.split_to(2);
+                } else if strictness == Strictness::Lenient && buffer.starts_with(b"\n") {
+                    buffer.split_to(1);
+                } else {
+                    break;
+                }
             }
 
             if !buffer.is_empty() {
                 self.send_codec_event(CodecEvent::DecodingStarted);
             }
 
-            if buffer.len() < MINIMUM_INFO_LINE_SIZE {
+            if buffer.starts_with(&[INTERLEAVED_DATA_MAGIC]) {
+                self.decode_interleaved_data(buffer)
+            } else if buffer.len() < MINIMUM_INFO_LINE_SIZE {
                 Ok(None)
-            } else if buffer.starts_with(b"RTSP/") {
+            } else if is_response(buffer, strictness) {
                 self.decode_response(buffer)
             } else {
                 self.decode_request(buffer)
@@ -269,9 +398,22 @@ impl Decoder for Codec {
     }
 }
 
+/// Returns whether or not the buffer starts with a response line rather than a request line. If the
+/// strictness is lenient, the version of the response line does not have to be in uppercase.
+fn is_response(buffer: &[u8], strictness: Strictness) -> bool {
+    match strictness {
+        Strictness::Strict => buffer.starts_with(b"RTSP/"),
+        Strictness::Lenient => {
+            buffer.len() >= MINIMUM_INFO_LINE_SIZE
+                && buffer[..MINIMUM_INFO_LINE_SIZE].eq_ignore_ascii_case(b"RTSP/")
+        }
+    }
+}
+
 impl Default for Codec {
     fn default() -> Self {
         Codec {
+            negotiated_version: None,
             request_decoder: RequestDecoder::new(),
             response_decoder: ResponseDecoder::new(),
             tx_event: None,
@@ -289,6 +431,9 @@ impl Encoder for Codec {
     /// message, a [`CodecEvent::EncodingStarted`] event will be sent. And after encoding has
     /// finished, an [`CodecEvent::EncodingEnded`] event will be sent.
     ///
+    /// Requests and responses are encoded with the version given by [`Codec::version`] regardless
+    /// of the version they were constructed with.
+    ///
     /// # Arguments
     ///
     /// * `message` - The message to be encoded.
@@ -302,8 +447,15 @@ impl Encoder for Codec {
         self.send_codec_event(CodecEvent::EncodingStarted);
 
         match message {
-            Message::Request(request) => encode_request(&request, buffer),
-            Message::Response(response) => encode_response(&response, buffer),
+            Message::InterleavedData(data) => encode_interleaved_data(&data, buffer),
+            Message::Request(mut request) => {
+                *request.version_mut() = self.version();
+                encode_request(&request, buffer)
+            }
+            Message::Response(mut response) => {
+                *response.version_mut() = self.version();
+                encode_response(&response, buffer)
+            }
         }
 
         self.send_codec_event(CodecEvent::EncodingEnded);
@@ -330,9 +482,10 @@ pub enum CodecEvent {
     EncodingEnded,
 }
 
-/// An abstract message type that is either a request or response.
+/// An abstract message type that is either a request, response, or interleaved binary data frame.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Message {
+    InterleavedData(InterleavedData),
     Request(Request<BytesMut>),
     Response(Response<BytesMut>),
 }
@@ -465,10 +618,14 @@ impl Error for DecodeError {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum IrrecoverableInvalidRequest {
+    HeaderLineTooLong,
+    HeadersTooLarge,
     InvalidContentLength,
     InvalidHeaderLine,
     InvalidRequestLine,
     InvalidVersion,
+    RequestLineTooLong,
+    TooManyHeaders,
     UnsupportedVersion,
 }
 
@@ -483,10 +640,14 @@ impl Error for IrrecoverableInvalidRequest {
         use self::IrrecoverableInvalidRequest::*;
 
         match self {
+            HeaderLineTooLong => "invalid RTSP request - header line too long",
+            HeadersTooLarge => "invalid RTSP request - headers too large",
             InvalidContentLength => "invalid RTSP request - invalid content length",
             InvalidHeaderLine => "invalid RTSP request - invalid header line",
             InvalidRequestLine => "invalid RTSP request - invalid request line",
             InvalidVersion => "invalid RTSP request - invalid version",
+            RequestLineTooLong => "invalid RTSP request - request line too long",
+            TooManyHeaders => "invalid RTSP request - too many headers",
             UnsupportedVersion => "invalid RTSP request - unsupported version",
         }
     }
@@ -499,10 +660,14 @@ impl TryFrom<InvalidRequest> for IrrecoverableInvalidRequest {
         use self::IrrecoverableInvalidRequest::*;
 
         match value {
+            InvalidRequest::HeaderLineTooLong => Ok(HeaderLineTooLong),
+            InvalidRequest::HeadersTooLarge => Ok(HeadersTooLarge),
             InvalidRequest::InvalidContentLength => Ok(InvalidContentLength),
             InvalidRequest::InvalidHeaderLine => Ok(InvalidHeaderLine),
             InvalidRequest::InvalidRequestLine => Ok(InvalidRequestLine),
             InvalidRequest::InvalidVersion => Ok(InvalidVersion),
+            InvalidRequest::RequestLineTooLong => Ok(RequestLineTooLong),
+            InvalidRequest::TooManyHeaders => Ok(TooManyHeaders),
             InvalidRequest::UnsupportedVersion => Ok(UnsupportedVersion),
             _ => Err(()),
         }
@@ -513,10 +678,14 @@ impl TryFrom<InvalidRequest> for IrrecoverableInvalidRequest {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum IrrecoverableInvalidResponse {
+    HeaderLineTooLong,
+    HeadersTooLarge,
     InvalidContentLength,
     InvalidHeaderLine,
     InvalidResponseLine,
     InvalidVersion,
+    ResponseLineTooLong,
+    TooManyHeaders,
     UnsupportedVersion,
 }
 
@@ -531,10 +700,14 @@ impl Error for IrrecoverableInvalidResponse {
         use self::IrrecoverableInvalidResponse::*;
 
         match self {
+            HeaderLineTooLong => "invalid RTSP response - header line too long",
+            HeadersTooLarge => "invalid RTSP response - headers too large",
             InvalidContentLength => "invalid RTSP response - invalid content length",
             InvalidHeaderLine => "invalid RTSP response - invalid header line",
             InvalidResponseLine => "invalid RTSP response - invalid response line",
             InvalidVersion => "invalid RTSP response - invalid version",
+            ResponseLineTooLong => "invalid RTSP response - response line too long",
+            TooManyHeaders => "invalid RTSP response - too many headers",
             UnsupportedVersion => "invalid RTSP response - unsupported version",
         }
     }
@@ -547,10 +720,14 @@ impl TryFrom<InvalidResponse> for IrrecoverableInvalidResponse {
         use self::IrrecoverableInvalidResponse::*;
 
         match value {
+            InvalidResponse::HeaderLineTooLong => Ok(HeaderLineTooLong),
+            InvalidResponse::HeadersTooLarge => Ok(HeadersTooLarge),
             InvalidResponse::InvalidContentLength => Ok(InvalidContentLength),
             InvalidResponse::InvalidHeaderLine => Ok(InvalidHeaderLine),
             InvalidResponse::InvalidResponseLine => Ok(InvalidResponseLine),
             InvalidResponse::InvalidVersion => Ok(InvalidVersion),
+            InvalidResponse::ResponseLineTooLong => Ok(ResponseLineTooLong),
+            InvalidResponse::TooManyHeaders => Ok(TooManyHeaders),
             InvalidResponse::UnsupportedVersion => Ok(UnsupportedVersion),
             _ => Err(()),
         }
@@ -586,6 +763,7 @@ impl Error for InvalidMessage {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum RecoverableInvalidRequest {
+    BodyTooLarge,
     InvalidHeaderName,
     InvalidHeaderValue,
     InvalidMethod,
@@ -603,6 +781,7 @@ impl Error for RecoverableInvalidRequest {
         use self::RecoverableInvalidRequest::*;
 
         match self {
+            BodyTooLarge => "invalid RTSP request - body too large",
             InvalidHeaderName => "invalid RTSP request - invalid header name",
             InvalidHeaderValue => "invalid RTSP request - invalid header value",
             InvalidMethod => "invalid RTSP request - invalid method",
@@ -618,6 +797,7 @@ impl TryFrom<InvalidRequest> for RecoverableInvalidRequest {
         use self::RecoverableInvalidRequest::*;
 
         match value {
+            InvalidRequest::BodyTooLarge => Ok(BodyTooLarge),
             InvalidRequest::InvalidHeaderName => Ok(InvalidHeaderName),
             InvalidRequest::InvalidHeaderValue => Ok(InvalidHeaderValue),
             InvalidRequest::InvalidMethod => Ok(InvalidMethod),
@@ -631,6 +811,7 @@ impl TryFrom<InvalidRequest> for RecoverableInvalidRequest {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum RecoverableInvalidResponse {
+    BodyTooLarge,
     InvalidHeaderName,
     InvalidHeaderValue,
     InvalidReasonPhrase,
@@ -648,6 +829,7 @@ impl Error for RecoverableInvalidResponse {
         use self::RecoverableInvalidResponse::*;
 
         match self {
+            BodyTooLarge => "invalid RTSP response - body too large",
             InvalidHeaderName => "invalid RTSP response - invalid header name",
             InvalidHeaderValue => "invalid RTSP response - invalid header value",
             InvalidReasonPhrase => "invalid RTSP response - invalid reason phrase",
@@ -663,6 +845,7 @@ impl TryFrom<InvalidResponse> for RecoverableInvalidResponse {
         use self::RecoverableInvalidResponse::*;
 
         match value {
+            InvalidResponse::BodyTooLarge => Ok(BodyTooLarge),
             InvalidResponse::InvalidHeaderName => Ok(InvalidHeaderName),
             InvalidResponse::InvalidHeaderValue => Ok(InvalidHeaderValue),
             InvalidResponse::InvalidReasonPhrase => Ok(InvalidReasonPhrase),
@@ -680,6 +863,7 @@ mod test {
 
     use super::*;
     use header::HeaderName;
+    use protocol::Deviation;
 
     #[test]
     fn test_codec_decoding() {
@@ -712,6 +896,49 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_codec_decoding_interleaved_data() {
+        let mut codec = Codec::new();
+        let mut buffer = BytesMut::from(&b"$\x01\x00"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+
+        buffer.extend_from_slice(b"\x04RT");
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+
+        buffer.extend_from_slice(b"P!$\x00\x00\x00OPTIONS * RTSP/2.0\r\n\r\n");
+        assert_eq!(
+            codec.decode(&mut buffer).unwrap().unwrap().unwrap(),
+            Message::InterleavedData(InterleavedData::new(1, BytesMut::from("RTP!")).unwrap())
+        );
+        assert_eq!(
+            codec.decode(&mut buffer).unwrap().unwrap().unwrap(),
+            Message::InterleavedData(InterleavedData::new(0, BytesMut::new()).unwrap())
+        );
+        assert_eq!(
+            codec.decode(&mut buffer).unwrap().unwrap().unwrap(),
+            Message::Request(
+                Request::builder()
+                    .method("OPTIONS")
+                    .uri("*")
+                    .build(BytesMut::new())
+                    .unwrap()
+            )
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_codec_encoding_interleaved_data() {
+        let mut codec = Codec::new();
+        let mut buffer = BytesMut::new();
+        let data = InterleavedData::new(2, BytesMut::from("RTCP")).unwrap();
+
+        codec
+            .encode(Message::InterleavedData(data), &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, BytesMut::from(&b"$\x02\x00\x04RTCP"[..]));
+    }
+
     #[test]
     fn test_codec_encoding() {
         let mut codec = Codec::new();
@@ -735,6 +962,227 @@ mod test {
         assert_eq!(buffer, expected_buffer);
     }
 
+    #[test]
+    fn test_codec_version_policy() {
+        let mut codec = Codec::new();
+        let mut buffer = BytesMut::from("OPTIONS * RTSP/1.0\r\nCSeq: 1\r\n\r\n");
+
+        match codec.decode(&mut buffer) {
+            Err(ProtocolError::DecodeError(DecodeError::InvalidRequest(error))) => {
+                assert_eq!(error, IrrecoverableInvalidRequest::UnsupportedVersion)
+            }
+            _ => panic!("expected unsupported version"),
+        }
+
+        let mut codec = Codec::new();
+        codec.set_version_policy(VersionPolicy::RTSP10Only);
+        let mut buffer = BytesMut::from("OPTIONS * RTSP/1.0\r\nCSeq: 1\r\n\r\n");
+
+        match codec.decode(&mut buffer).unwrap().unwrap().unwrap() {
+            Message::Request(request) => assert_eq!(request.version(), Version::RTSP10),
+            _ => panic!("expected request"),
+        }
+
+        let mut buffer = BytesMut::new();
+        let response = Response::builder().build(BytesMut::new()).unwrap();
+        codec
+            .encode(Message::Response(response), &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, BytesMut::from("RTSP/1.0 200 OK\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_codec_decoder_limits() {
+        let limits = DecoderLimits::builder()
+            .max_body_size(4)
+            .max_header_count(2)
+            .max_line_length(32)
+            .build()
+            .unwrap();
+
+        let mut codec = Codec::new();
+        codec.set_decoder_limits(limits);
+        let mut buffer = BytesMut::from(
+            "SETUP * RTSP/2.0\r\n\
+             Content-Length: 8\r\n\
+             \r\n\
+             BodyBody\
+             SETUP * RTSP/2.0\r\n\
+             Content-Length: 4\r\n\
+             \r\n\
+             Body",
+        );
+
+        match codec.decode(&mut buffer) {
+            Ok(Some(Err(InvalidMessage::InvalidRequest(error)))) => {
+                assert_eq!(error, RecoverableInvalidRequest::BodyTooLarge)
+            }
+            _ => panic!("expected body too large"),
+        }
+
+        match codec.decode(&mut buffer).unwrap().unwrap().unwrap() {
+            Message::Request(request) => assert_eq!(request.body(), &BytesMut::from("Body")),
+            _ => panic!("expected request"),
+        }
+
+        let mut codec = Codec::new();
+        codec.set_decoder_limits(limits);
+        let mut buffer = BytesMut::from(vec![b'A'; 64]);
+
+        match codec.decode(&mut buffer) {
+            Err(ProtocolError::DecodeError(DecodeError::InvalidRequest(error))) => {
+                assert_eq!(error, IrrecoverableInvalidRequest::RequestLineTooLong)
+            }
+            _ => panic!("expected request line too long"),
+        }
+
+        let mut codec = Codec::new();
+        codec.set_decoder_limits(limits);
+        let mut buffer = BytesMut::from(
+            "RTSP/2.0 200 OK\r\n\
+             Header: Value\r\n \
+             Folded over multiple lines\r\n\
+             \r\n",
+        );
+
+        match codec.decode(&mut buffer) {
+            Err(ProtocolError::DecodeError(DecodeError::InvalidResponse(error))) => {
+                assert_eq!(error, IrrecoverableInvalidResponse::HeaderLineTooLong)
+            }
+            _ => panic!("expected header line too long"),
+        }
+
+        let mut codec = Codec::new();
+        codec.set_decoder_limits(limits);
+        let mut buffer = BytesMut::from(
+            "OPTIONS * RTSP/2.0\r\n\
+             CSeq: 1\r\n\
+             Public: OPTIONS\r\n\
+             Supported: play.basic\r\n\
+             \r\n",
+        );
+
+        match codec.decode(&mut buffer) {
+            Err(ProtocolError::DecodeError(DecodeError::InvalidRequest(error))) => {
+                assert_eq!(error, IrrecoverableInvalidRequest::TooManyHeaders)
+            }
+            _ => panic!("expected too many headers"),
+        }
+    }
+
+    #[test]
+    fn test_codec_strictness() {
+        let mut codec = Codec::new();
+        let mut buffer = BytesMut::from(
+            "OPTIONS * rtsp/2.0\r\nCSeq: 1\r\n\r\n\
+             OPTIONS * RTSP/2.0 \r\nCSeq: 2\r\n\r\n",
+        );
+
+        match codec.decode(&mut buffer).unwrap().unwrap().unwrap() {
+            Message::Request(request) => {
+                assert_eq!(request.version(), Version::RTSP20);
+                assert!(request.deviations().is_empty());
+            }
+            _ => panic!("expected request"),
+        }
+
+        match codec.decode(&mut buffer) {
+            Err(ProtocolError::DecodeError(DecodeError::InvalidRequest(error))) => {
+                assert_eq!(error, IrrecoverableInvalidRequest::InvalidVersion)
+            }
+            _ => panic!("expected invalid version"),
+        }
+
+        let mut codec = Codec::new();
+        codec.set_strictness(Strictness::Lenient);
+        let mut buffer = BytesMut::from(
+            "\nOPTIONS * rtsp/2.0 \n\
+             CSeq: 1\n\
+             \n\
+             rtsp/2.0 200 \r\n\
+             Content-Length: 2\n\
+             Content-Length: 2\r\n\
+             \r\n\
+             OK\
+             RTSP/2.0 200\r\n\
+             Header: Folded\n over lines\r\n\
+             \r\n",
+        );
+
+        match codec.decode(&mut buffer).unwrap().unwrap().unwrap() {
+            Message::Request(request) => {
+                assert_eq!(request.version(), Version::RTSP20);
+                assert_eq!(
+                    request.deviations().iter().collect::<Vec<_>>(),
+                    vec![
+                        Deviation::BareLineFeed,
+                        Deviation::LowercaseVersion,
+                        Deviation::TrailingWhitespace,
+                    ]
+                );
+            }
+            _ => panic!("expected request"),
+        }
+
+        match codec.decode(&mut buffer).unwrap().unwrap().unwrap() {
+            Message::Response(response) => {
+                assert_eq!(response.body(), &BytesMut::from("OK"));
+                assert_eq!(
+                    response.deviations().iter().collect::<Vec<_>>(),
+                    vec![
+                        Deviation::BareLineFeed,
+                        Deviation::DuplicateContentLength,
+                        Deviation::LowercaseVersion,
+                        Deviation::MissingReasonPhrase,
+                        Deviation::TrailingWhitespace,
+                    ]
+                );
+            }
+            _ => panic!("expected response"),
+        }
+
+        match codec.decode(&mut buffer).unwrap().unwrap().unwrap() {
+            Message::Response(response) => {
+                assert_eq!(response.reason().as_str(), "OK");
+                assert_eq!(
+                    response.headers().get("Header").unwrap(),
+                    " Folded\r\n over lines"
+                );
+                assert_eq!(
+                    response.deviations().iter().collect::<Vec<_>>(),
+                    vec![Deviation::BareLineFeed, Deviation::MissingReasonPhrase]
+                );
+            }
+            _ => panic!("expected response"),
+        }
+    }
+
+    #[test]
+    fn test_codec_version_negotiation() {
+        let mut codec = Codec::new();
+        codec.set_version_policy(VersionPolicy::Negotiate);
+        assert_eq!(codec.version(), Version::RTSP20);
+
+        let mut buffer = BytesMut::from("RTSP/1.0 505 RTSP Version Not Supported\r\n\r\n");
+        assert!(codec.decode(&mut buffer).unwrap().unwrap().is_ok());
+        assert_eq!(codec.version(), Version::RTSP10);
+
+        let mut buffer = BytesMut::from("RTSP/2.0 200 OK\r\n\r\n");
+        assert!(codec.decode(&mut buffer).unwrap().unwrap().is_ok());
+        assert_eq!(codec.version(), Version::RTSP10);
+
+        let mut buffer = BytesMut::new();
+        let request = Request::builder()
+            .method("OPTIONS")
+            .uri("*")
+            .build(BytesMut::new())
+            .unwrap();
+        codec
+            .encode(Message::Request(request), &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, BytesMut::from("OPTIONS * RTSP/1.0\r\n\r\n"));
+    }
+
     #[test]
     fn test_codec_events() {
         let (tx_event, rx_event) = unbounded();
@@ -2,28 +2,72 @@ use bytes::BytesMut;
 use futures::sync::mpsc::Receiver;
 use futures::sync::oneshot;
 use futures::{Async, Future, Poll, Stream};
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 use tokio_timer::Delay;
 
 use super::SenderHandle;
-use header::types::CSeq;
-use header::{HeaderMap, HeaderName, TypedHeader};
+use header::types::{CSeq, PipelinedRequests, Session};
+use header::{HeaderMap, HeaderName, HeaderValue, TypedHeader};
 use protocol::{Message, Service};
 use request::Request;
 use response::Response;
+use session::SessionID;
 use status::StatusCode;
 
+/// Determines how many of the requests received on a connection are serviced at once.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RequestConcurrency {
+    /// Requests are serviced one at a time in the order they were received. This is the default.
+    Sequential,
+
+    /// Up to the given number of requests are serviced at once.
+    ///
+    /// Requests that belong to the same session, as given by their `"Session"` header, are still
+    /// serviced one at a time in the order they were received, and so are requests without a
+    /// session that belong to the same pipeline, as given by their `"Pipelined-Requests"` header.
+    /// All other requests are independent of each other and of every session. Responses are sent
+    /// as soon as they are ready, so they may not be in the same order as the requests.
+    Concurrent(usize),
+}
+
+impl RequestConcurrency {
+    /// Returns the maximum number of requests that are serviced at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::protocol::RequestConcurrency;
+    ///
+    /// assert_eq!(RequestConcurrency::Sequential.max_requests(), 1);
+    /// assert_eq!(RequestConcurrency::Concurrent(8).max_requests(), 8);
+    /// ```
+    pub fn max_requests(&self) -> usize {
+        match *self {
+            RequestConcurrency::Sequential => 1,
+            RequestConcurrency::Concurrent(max_requests) => max_requests,
+        }
+    }
+}
+
+impl Default for RequestConcurrency {
+    fn default() -> Self {
+        RequestConcurrency::Sequential
+    }
+}
+
 #[must_use = "futures do nothing unless polled"]
 pub struct RequestHandler<S>
 where
     S: Service,
 {
-    continue_timer: Option<Delay>,
+    concurrency: RequestConcurrency,
     continue_wait_duration: Option<Duration>,
-    rx_incoming_request: Receiver<(CSeq, Request<BytesMut>)>,
+    queued_requests: VecDeque<(CSeq, RequestContext, Request<BytesMut>)>,
+    rx_incoming_request: Option<Receiver<(CSeq, Request<BytesMut>)>>,
     sender_handle: SenderHandle,
     service: S,
-    serviced_request: Option<(CSeq, S::Future)>,
+    serviced_requests: Vec<ServicedRequest<S::Future>>,
     tx_shutdown_event: Option<oneshot::Sender<()>>,
 }
 
@@ -39,90 +83,134 @@ where
         sender_handle: SenderHandle,
         tx_shutdown_event: oneshot::Sender<()>,
         continue_wait_duration: Option<Duration>,
+        concurrency: RequestConcurrency,
     ) -> Self {
         RequestHandler {
-            continue_timer: None,
+            concurrency,
             continue_wait_duration,
-            rx_incoming_request,
+            queued_requests: VecDeque::new(),
+            rx_incoming_request: Some(rx_incoming_request),
             sender_handle,
             service,
-            serviced_request: None,
+            serviced_requests: Vec::new(),
             tx_shutdown_event: Some(tx_shutdown_event),
         }
     }
 
-    fn poll_continue_timer(&mut self, cseq: CSeq) {
-        while let Some(mut continue_timer) = self.continue_timer.take() {
-            match continue_timer
-                .poll()
-                .expect("polling `continue_timer` should not error")
-            {
-                Async::Ready(_) => {
-                    let response = Response::builder()
-                        .status_code(StatusCode::Continue)
-                        .build(BytesMut::new())
-                        .expect("continue response should not be invalid");
-                    self.send_response(cseq, response);
-                    self.reset_continue_timer();
+    fn is_at_capacity(&self) -> bool {
+        self.serviced_requests.len() + self.queued_requests.len() >= self.concurrency.max_requests()
+    }
+
+    fn is_context_serviced(&self, context: &RequestContext) -> bool {
+        self.serviced_requests
+            .iter()
+            .any(|serviced_request| serviced_request.context.as_ref() == Some(context))
+    }
+
+    fn poll_serviced_requests(&mut self) {
+        let mut index = 0;
+
+        while index < self.serviced_requests.len() {
+            let result = {
+                let serviced_request = &mut self.serviced_requests[index];
+
+                match serviced_request.future.poll() {
+                    Ok(Async::Ready(response)) => Some(response.into()),
+                    Ok(Async::NotReady) => {
+                        serviced_request.poll_continue_timer(
+                            &mut self.sender_handle,
+                            self.continue_wait_duration,
+                        );
+                        None
+                    }
+                    Err(_) => Some(
+                        Response::builder()
+                            .status_code(StatusCode::InternalServerError)
+                            .build(BytesMut::new())
+                            .expect("internal server error response should not be invalid"),
+                    ),
                 }
-                Async::NotReady => {
-                    self.continue_timer = Some(continue_timer);
-                    break;
+            };
+
+            match result {
+                Some(response) => {
+                    let serviced_request = self.serviced_requests.remove(index);
+                    send_response(&mut self.sender_handle, serviced_request.cseq, response);
                 }
+                None => index += 1,
             }
         }
     }
 
-    fn poll_serviced_request(
-        &mut self,
-        cseq: CSeq,
-        mut serviced_request: S::Future,
-    ) -> Poll<(), ()> {
-        match serviced_request.poll() {
-            Ok(Async::Ready(response)) => {
-                self.send_response(cseq, response.into());
-                self.continue_timer = None;
-                Ok(Async::Ready(()))
-            }
-            Ok(Async::NotReady) => {
-                self.poll_continue_timer(cseq);
-                self.serviced_request = Some((cseq, serviced_request));
-                Ok(Async::NotReady)
-            }
-            Err(_) => {
-                let response = Response::builder()
-                    .status_code(StatusCode::InternalServerError)
-                    .build(BytesMut::new())
-                    .expect("internal server error response should not be invalid");
-                self.send_response(cseq, response);
-                self.continue_timer = None;
-                Ok(Async::Ready(()))
+    /// Starts servicing the queued requests whose contexts are no longer being serviced, returning
+    /// whether or not any request was started.
+    fn process_queued_requests(&mut self) -> bool {
+        let mut index = 0;
+        let mut started = false;
+
+        while index < self.queued_requests.len() {
+            let is_blocked = {
+                let (_, ref context, _) = self.queued_requests[index];
+
+                self.is_context_serviced(context)
+                    || self
+                        .queued_requests
+                        .iter()
+                        .take(index)
+                        .any(|(_, other_context, _)| other_context == context)
+            };
+
+            if is_blocked {
+                index += 1;
+            } else {
+                let (cseq, context, request) = self
+                    .queued_requests
+                    .remove(index)
+                    .expect("queued request should exist");
+                self.service_request(cseq, Some(context), request);
+                started = true;
             }
         }
+
+        started
     }
 
     fn process_request(&mut self, cseq: CSeq, request: Request<BytesMut>) {
-        self.reset_continue_timer();
-        self.serviced_request = Some((cseq, self.service.call(request)))
-    }
+        let context = match self.concurrency {
+            RequestConcurrency::Sequential => None,
+            RequestConcurrency::Concurrent(_) => RequestContext::from_request(&request),
+        };
+
+        match context {
+            Some(context) => {
+                let is_blocked = self.is_context_serviced(&context)
+                    || self
+                        .queued_requests
+                        .iter()
+                        .any(|(_, other_context, _)| *other_context == context);
 
-    fn reset_continue_timer(&mut self) {
-        if let Some(duration) = self.continue_wait_duration {
-            let expire_time = Instant::now() + duration;
-            self.continue_timer = Some(Delay::new(expire_time));
+                if is_blocked {
+                    self.queued_requests.push_back((cseq, context, request));
+                } else {
+                    self.service_request(cseq, Some(context), request);
+                }
+            }
+            None => self.service_request(cseq, None, request),
         }
     }
 
-    fn send_response(&mut self, cseq: CSeq, mut response: Response<BytesMut>) {
-        let cseq = cseq.to_header_raw().remove(0);
-        response.headers_mut().insert(HeaderName::CSeq, cseq);
-
-        // We do not care if this fails. All requests that reach this handler will be processed
-        // regardless of whether or not a response can actually be sent.
-
-        self.sender_handle
-            .try_send_message(Message::Response(response))
-            .ok();
+    fn service_request(
+        &mut self,
+        cseq: CSeq,
+        context: Option<RequestContext>,
+        request: Request<BytesMut>,
+    ) {
+        self.serviced_requests.push(ServicedRequest {
+            context,
+            continue_timer: continue_timer(self.continue_wait_duration),
+            cseq,
+            future: self.service.call(request),
+        });
     }
 }
 
@@ -150,23 +238,131 @@ where
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
         loop {
-            if let Some((cseq, serviced_request)) = self.serviced_request.take() {
-                match self.poll_serviced_request(cseq, serviced_request) {
-                    Ok(Async::Ready(_)) => (),
-                    Ok(Async::NotReady) => return Ok(Async::NotReady),
-                    Err(_) => panic!("calling `poll_serviced_request` should not error"),
+            self.poll_serviced_requests();
+
+            // Newly started requests have to be polled so that the task is notified once they are
+            // ready.
+
+            while self.process_queued_requests() {
+                self.poll_serviced_requests();
+            }
+
+            if self.is_at_capacity() {
+                return Ok(Async::NotReady);
+            }
+
+            let incoming_request = match self.rx_incoming_request {
+                Some(ref mut rx_incoming_request) => rx_incoming_request
+                    .poll()
+                    .expect("receiver `rx_incoming_request` should not error"),
+                None => Async::Ready(None),
+            };
+
+            match incoming_request {
+                Async::Ready(Some((cseq, request))) => self.process_request(cseq, request),
+                Async::Ready(None) => {
+                    self.rx_incoming_request = None;
+
+                    if self.serviced_requests.is_empty() && self.queued_requests.is_empty() {
+                        return Ok(Async::Ready(()));
+                    }
+
+                    return Ok(Async::NotReady);
                 }
+                Async::NotReady => return Ok(Async::NotReady),
             }
+        }
+    }
+}
 
-            match self
-                .rx_incoming_request
+/// A request that is currently being serviced along with the timer used to send `100 Continue`
+/// responses while it is pending.
+struct ServicedRequest<F> {
+    context: Option<RequestContext>,
+    continue_timer: Option<Delay>,
+    cseq: CSeq,
+    future: F,
+}
+
+impl<F> ServicedRequest<F> {
+    fn poll_continue_timer(
+        &mut self,
+        sender_handle: &mut SenderHandle,
+        continue_wait_duration: Option<Duration>,
+    ) {
+        while let Some(mut timer) = self.continue_timer.take() {
+            match timer
                 .poll()
-                .expect("receiver `rx_incoming_request` should not error")
+                .expect("polling `continue_timer` should not error")
             {
-                Async::Ready(Some((cseq, request))) => self.process_request(cseq, request),
-                Async::Ready(None) => return Ok(Async::Ready(())),
-                Async::NotReady => return Ok(Async::NotReady),
+                Async::Ready(_) => {
+                    let response = Response::builder()
+                        .status_code(StatusCode::Continue)
+                        .build(BytesMut::new())
+                        .expect("continue response should not be invalid");
+                    send_response(sender_handle, self.cseq, response);
+                    self.continue_timer = continue_timer(continue_wait_duration);
+                }
+                Async::NotReady => {
+                    self.continue_timer = Some(timer);
+                    break;
+                }
             }
         }
     }
 }
+
+fn continue_timer(continue_wait_duration: Option<Duration>) -> Option<Delay> {
+    continue_wait_duration.map(|duration| Delay::new(Instant::now() + duration))
+}
+
+/// The context a request is executed in. Requests with the same context are serviced one at a time.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum RequestContext {
+    Pipeline(PipelinedRequests),
+    Session(SessionID),
+}
+
+impl RequestContext {
+    /// Returns the context given by the `"Session"` header of the request or, if there is none, by
+    /// its `"Pipelined-Requests"` header. Invalid headers are ignored.
+    fn from_request(request: &Request<BytesMut>) -> Option<Self> {
+        let header_values = |name| {
+            request
+                .headers()
+                .get_all(name)
+                .iter()
+                .cloned()
+                .collect::<Vec<HeaderValue>>()
+        };
+        let session = header_values(HeaderName::Session);
+
+        if !session.is_empty() {
+            return Session::try_from_header_raw(&session)
+                .ok()
+                .map(|session| RequestContext::Session(session.id().clone()));
+        }
+
+        let pipeline = header_values(HeaderName::PipelinedRequests);
+
+        if !pipeline.is_empty() {
+            return PipelinedRequests::try_from_header_raw(&pipeline)
+                .ok()
+                .map(RequestContext::Pipeline);
+        }
+
+        None
+    }
+}
+
+fn send_response(sender_handle: &mut SenderHandle, cseq: CSeq, mut response: Response<BytesMut>) {
+    let cseq = cseq.to_header_raw().remove(0);
+    response.headers_mut().insert(HeaderName::CSeq, cseq);
+
+    // We do not care if this fails. All requests that reach this handler will be processed
+    // regardless of whether or not a response can actually be sent.
+
+    sender_handle
+        .try_send_message(Message::Response(response))
+        .ok();
+}
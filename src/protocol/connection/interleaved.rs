@@ -0,0 +1,162 @@
+use bytes::BytesMut;
+use futures::stream::Fuse;
+use futures::sync::mpsc::{Receiver, Sender, UnboundedReceiver};
+use futures::{Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::SenderHandle;
+use protocol::InterleavedData;
+
+/// A handle to a single interleaved binary data channel on a connection.
+///
+/// As a [`Stream`], this yields the payloads of all interleaved binary data frames received on the
+/// channel. The stream ends if the connection stops receiving or if another [`InterleavedChannel`]
+/// is registered for the same channel identifier. If the payloads are not consumed quickly enough,
+/// frames received on this channel will be dropped, much like how they would be when using UDP.
+///
+/// As a [`Sink`], this sends payloads as interleaved binary data frames on the channel. Payloads
+/// that have not been written to the connection yet are buffered, and once the buffer is full,
+/// [`Sink::start_send`] will return the payload back until the connection has caught up. The
+/// buffer is shared by all channels on the connection and its size is given by the connection's
+/// interleaved buffer size.
+#[must_use = "streams do nothing unless polled"]
+pub struct InterleavedChannel {
+    channel: u8,
+    rx_data: Receiver<BytesMut>,
+    sender_handle: SenderHandle,
+}
+
+impl InterleavedChannel {
+    pub(crate) fn new(
+        channel: u8,
+        rx_data: Receiver<BytesMut>,
+        sender_handle: SenderHandle,
+    ) -> Self {
+        InterleavedChannel {
+            channel,
+            rx_data,
+            sender_handle,
+        }
+    }
+
+    /// Returns the channel identifier this handle is bound to.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+}
+
+impl Stream for InterleavedChannel {
+    type Item = BytesMut;
+    type Error = InterleavedChannelError;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        Ok(self
+            .rx_data
+            .poll()
+            .expect("interleaved data receiver should not error"))
+    }
+}
+
+impl Sink for InterleavedChannel {
+    type SinkItem = BytesMut;
+    type SinkError = InterleavedChannelError;
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        let data = InterleavedData::new(self.channel, item)
+            .map_err(|_| InterleavedChannelError::PayloadTooLarge)?;
+
+        match self.sender_handle.start_send_interleaved_data(data) {
+            Ok(AsyncSink::Ready) => Ok(AsyncSink::Ready),
+            Ok(AsyncSink::NotReady(data)) => Ok(AsyncSink::NotReady(data.into_payload())),
+            Err(_) => Err(InterleavedChannelError::Closed),
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.sender_handle
+            .poll_complete_interleaved_data()
+            .map_err(|_| InterleavedChannelError::Closed)
+    }
+}
+
+/// A possible error value when sending or receiving on an [`InterleavedChannel`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum InterleavedChannelError {
+    /// An attempt was made to send data when the connection no longer allows sending messages.
+    Closed,
+
+    /// An attempt was made to send a payload that is too large to fit in a single interleaved
+    /// binary data frame.
+    PayloadTooLarge,
+}
+
+impl fmt::Display for InterleavedChannelError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for InterleavedChannelError {
+    fn description(&self) -> &str {
+        use self::InterleavedChannelError::*;
+
+        match self {
+            Closed => "closed",
+            PayloadTooLarge => "payload too large",
+        }
+    }
+}
+
+pub enum InterleavedChannelUpdate {
+    AddChannel((u8, Sender<BytesMut>)),
+}
+
+pub struct InterleavedReceiver {
+    channels: HashMap<u8, Sender<BytesMut>>,
+    rx_interleaved_channel: Fuse<UnboundedReceiver<InterleavedChannelUpdate>>,
+}
+
+impl InterleavedReceiver {
+    pub fn new(rx_interleaved_channel: UnboundedReceiver<InterleavedChannelUpdate>) -> Self {
+        InterleavedReceiver {
+            channels: HashMap::new(),
+            rx_interleaved_channel: rx_interleaved_channel.fuse(),
+        }
+    }
+
+    fn handle_interleaved_channel_update(&mut self, update: InterleavedChannelUpdate) {
+        match update {
+            InterleavedChannelUpdate::AddChannel((channel, tx_data)) => {
+                self.channels.insert(channel, tx_data);
+            }
+        }
+    }
+
+    pub fn handle_interleaved_data(&mut self, data: InterleavedData) {
+        let channel = data.channel();
+        let mut remove_channel = false;
+
+        if let Some(tx_data) = self.channels.get_mut(&channel) {
+            if let Err(error) = tx_data.try_send(data.into_payload()) {
+                remove_channel = error.is_disconnected();
+            }
+        }
+
+        if remove_channel {
+            self.channels.remove(&channel);
+        }
+    }
+
+    pub fn poll_interleaved_channel_update(&mut self) {
+        while let Async::Ready(Some(update)) = self
+            .rx_interleaved_channel
+            .poll()
+            .expect("unbounded receiver `rx_interleaved_channel` should not error")
+        {
+            self.handle_interleaved_channel_update(update);
+        }
+    }
+}
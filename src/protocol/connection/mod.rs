@@ -1,28 +1,32 @@
 mod handler;
+mod interleaved;
 mod pending;
 mod receiver;
 mod sender;
 mod shutdown;
 
+use self::interleaved::{InterleavedChannelUpdate, InterleavedReceiver};
 use self::pending::{PendingRequestResponse, PendingRequestUpdate};
 use self::receiver::Receiver;
 use self::sender::{Sender, SenderHandle};
 use self::shutdown::{Shutdown, ShutdownState};
 
-pub use self::handler::RequestHandler;
+pub use self::handler::{RequestConcurrency, RequestHandler};
+pub use self::interleaved::{InterleavedChannel, InterleavedChannelError};
 pub use self::pending::{
-    RequestOptions, RequestOptionsBuilder, RequestOptionsBuilderError, SendRequestFuture,
+    RedirectPolicy, RequestOptions, RequestOptionsBuilder, RequestOptionsBuilderError,
+    SendRequestFuture,
 };
 pub use self::shutdown::ShutdownType;
 
 use bytes::BytesMut;
 use futures::future::Either;
-use futures::sync::mpsc::{channel, unbounded, UnboundedSender};
+use futures::sync::mpsc::{self, channel, unbounded, UnboundedSender};
 use futures::sync::oneshot;
 use futures::{future, Async, Future, Poll, Stream};
 use header::types::CSeq;
 use header::{HeaderMap, HeaderName, TypedHeader};
-use protocol::{Codec, Message, OperationError, Service};
+use protocol::{Codec, DecoderLimits, Message, OperationError, Service, Strictness};
 use request::Request;
 use response::Response;
 use std::convert::TryFrom;
@@ -32,10 +36,12 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio_io::{AsyncRead, AsyncWrite};
+use version::VersionPolicy;
 
 pub const DEFAULT_CONTINUE_WAIT_DURATION: Duration = Duration::from_secs(5);
 pub const DEFAULT_DECODE_TIMEOUT_DURATION: Duration = Duration::from_secs(10);
 pub const DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_DURATION: Duration = Duration::from_secs(10);
+pub const DEFAULT_INTERLEAVED_BUFFER_SIZE: usize = 64;
 pub const DEFAULT_REQUEST_BUFFER_SIZE: usize = 10;
 pub const DEFAULT_REQUEST_MAX_TIMEOUT_DURATION: Duration = Duration::from_secs(20);
 pub const DEFAULT_REQUEST_TIMEOUT_DURATION: Duration = Duration::from_secs(10);
@@ -77,20 +83,26 @@ impl Connection {
         let (tx_codec_event, rx_codec_event) = unbounded();
         let (tx_incoming_request, rx_incoming_request) = channel(config.request_buffer_size());
         let (tx_pending_request, rx_pending_request) = unbounded();
+        let (tx_interleaved_channel, rx_interleaved_channel) = unbounded();
         let (tx_initiate_shutdown, rx_initiate_shutdown) = oneshot::channel();
         let (tx_connection_shutdown_event, rx_connection_shutdown_event) = oneshot::channel();
         let (tx_handler_shutdown_event, rx_handler_shutdown_event) = oneshot::channel();
-        let (sink, stream) = transport.framed(Codec::with_events(tx_codec_event)).split();
+        let mut codec = Codec::with_events(tx_codec_event);
+        codec.set_decoder_limits(config.decoder_limits());
+        codec.set_strictness(config.strictness());
+        codec.set_version_policy(config.version_policy());
+        let (sink, stream) = transport.framed(codec).split();
 
         let receiver = Receiver::new(
             Box::new(stream),
             rx_pending_request,
+            rx_interleaved_channel,
             rx_codec_event,
             tx_incoming_request,
             config.decode_timeout_duration(),
             config.request_buffer_size(),
         );
-        let (sender, sender_handle) = Sender::new(Box::new(sink));
+        let (sender, sender_handle) = Sender::new(Box::new(sink), config.interleaved_buffer_size());
         let handler = if let Some(service) = service {
             Some(RequestHandler::new(
                 service,
@@ -98,6 +110,7 @@ impl Connection {
                 sender_handle.clone(),
                 tx_handler_shutdown_event,
                 config.continue_wait_duration(),
+                config.request_concurrency(),
             ))
         } else {
             None
@@ -118,12 +131,14 @@ impl Connection {
         let connection_handle = ConnectionHandle::new(
             connection.allow_requests.clone(),
             config.graceful_shutdown_default_timeout_duration(),
+            config.interleaved_buffer_size(),
             config.request_default_max_timeout_duration(),
             config.request_default_timeout_duration(),
             rx_connection_shutdown_event,
             rx_handler_shutdown_event,
             sender_handle,
             tx_pending_request,
+            tx_interleaved_channel,
             tx_initiate_shutdown,
         );
 
@@ -258,12 +273,14 @@ impl Future for Connection {
 #[derive(Clone)]
 pub struct ConnectionHandle {
     allow_requests: Arc<AtomicBool>,
+    interleaved_buffer_size: usize,
     request_default_max_timeout_duration: Option<Duration>,
     request_default_timeout_duration: Option<Duration>,
     sender_handle: SenderHandle,
     sequence_number: Arc<Mutex<CSeq>>,
     shutdown_receiver: Arc<Mutex<Option<ConnectionShutdownReceiver>>>,
     shutdown_sender: Arc<Mutex<ConnectionShutdownSender>>,
+    tx_interleaved_channel: UnboundedSender<InterleavedChannelUpdate>,
     tx_pending_request: UnboundedSender<PendingRequestUpdate>,
 }
 
@@ -271,12 +288,14 @@ impl ConnectionHandle {
     pub(self) fn new(
         allow_requests: Arc<AtomicBool>,
         graceful_shutdown_default_timeout_duration: Duration,
+        interleaved_buffer_size: usize,
         request_default_max_timeout_duration: Option<Duration>,
         request_default_timeout_duration: Option<Duration>,
         rx_connection_shutdown_event: oneshot::Receiver<()>,
         rx_handler_shutdown_event: Option<oneshot::Receiver<()>>,
         sender_handle: SenderHandle,
         tx_pending_request: UnboundedSender<PendingRequestUpdate>,
+        tx_interleaved_channel: UnboundedSender<InterleavedChannelUpdate>,
         tx_initiate_shutdown: oneshot::Sender<ShutdownType>,
     ) -> Self {
         let shutdown_receiver = ConnectionShutdownReceiver::new(
@@ -290,6 +309,7 @@ impl ConnectionHandle {
 
         ConnectionHandle {
             allow_requests,
+            interleaved_buffer_size,
             request_default_max_timeout_duration,
             request_default_timeout_duration,
             sender_handle,
@@ -298,10 +318,38 @@ impl ConnectionHandle {
             )),
             shutdown_receiver: Arc::new(Mutex::new(Some(shutdown_receiver))),
             shutdown_sender: Arc::new(Mutex::new(shutdown_sender)),
+            tx_interleaved_channel,
             tx_pending_request,
         }
     }
 
+    /// Registers an [`InterleavedChannel`] for the given channel identifier, through which
+    /// interleaved binary data frames can be received and sent on the connection.
+    ///
+    /// Registering a channel identifier that already has a channel ends the stream of the previous
+    /// channel, though it can still be used to send data.
+    ///
+    /// # Errors
+    ///
+    /// If the connection is no longer receiving, `OperationError::Closed` is returned.
+    pub fn interleaved_channel(
+        &mut self,
+        channel: u8,
+    ) -> Result<InterleavedChannel, OperationError> {
+        let (tx_data, rx_data) = mpsc::channel(self.interleaved_buffer_size);
+        let update = InterleavedChannelUpdate::AddChannel((channel, tx_data));
+
+        if let Err(_) = self.tx_interleaved_channel.unbounded_send(update) {
+            return Err(OperationError::Closed);
+        }
+
+        Ok(InterleavedChannel::new(
+            channel,
+            rx_data,
+            self.sender_handle.clone(),
+        ))
+    }
+
     pub fn send_request<R, B>(
         &mut self,
         request: R,
@@ -467,13 +515,19 @@ impl Drop for ConnectionShutdownSender {
     }
 }
 
+#[derive(Clone)]
 pub struct Config {
     continue_wait_duration: Option<Duration>,
     decode_timeout_duration: Duration,
+    decoder_limits: DecoderLimits,
     graceful_shutdown_default_timeout_duration: Duration,
+    interleaved_buffer_size: usize,
     request_buffer_size: usize,
+    request_concurrency: RequestConcurrency,
     request_default_max_timeout_duration: Option<Duration>,
     request_default_timeout_duration: Option<Duration>,
+    strictness: Strictness,
+    version_policy: VersionPolicy,
 }
 
 impl Config {
@@ -493,14 +547,26 @@ impl Config {
         self.decode_timeout_duration
     }
 
+    pub fn decoder_limits(&self) -> DecoderLimits {
+        self.decoder_limits
+    }
+
     pub fn graceful_shutdown_default_timeout_duration(&self) -> Duration {
         self.graceful_shutdown_default_timeout_duration
     }
 
+    pub fn interleaved_buffer_size(&self) -> usize {
+        self.interleaved_buffer_size
+    }
+
     pub fn request_buffer_size(&self) -> usize {
         self.request_buffer_size
     }
 
+    pub fn request_concurrency(&self) -> RequestConcurrency {
+        self.request_concurrency
+    }
+
     pub fn request_default_max_timeout_duration(&self) -> Option<Duration> {
         self.request_default_max_timeout_duration
     }
@@ -508,6 +574,14 @@ impl Config {
     pub fn request_default_timeout_duration(&self) -> Option<Duration> {
         self.request_default_timeout_duration
     }
+
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+
+    pub fn version_policy(&self) -> VersionPolicy {
+        self.version_policy
+    }
 }
 
 impl Default for Config {
@@ -521,10 +595,15 @@ impl Default for Config {
 pub struct ConfigBuilder {
     continue_wait_duration: Option<Duration>,
     decode_timeout_duration: Duration,
+    decoder_limits: DecoderLimits,
     graceful_shutdown_default_timeout_duration: Duration,
+    interleaved_buffer_size: usize,
     request_buffer_size: usize,
+    request_concurrency: RequestConcurrency,
     request_default_max_timeout_duration: Option<Duration>,
     request_default_timeout_duration: Option<Duration>,
+    strictness: Strictness,
+    version_policy: VersionPolicy,
 }
 
 impl ConfigBuilder {
@@ -543,10 +622,18 @@ impl ConfigBuilder {
             return Err(ConfigBuilderError::InvalidDecodeTimeoutDuration);
         }
 
+        if self.interleaved_buffer_size == 0 {
+            return Err(ConfigBuilderError::InvalidInterleavedBufferSize);
+        }
+
         if self.request_buffer_size == 0 {
             return Err(ConfigBuilderError::InvalidRequestBufferSize);
         }
 
+        if self.request_concurrency.max_requests() == 0 {
+            return Err(ConfigBuilderError::InvalidRequestConcurrency);
+        }
+
         if let Some(duration) = self.request_default_max_timeout_duration {
             if duration.as_secs() == 0 {
                 return Err(ConfigBuilderError::InvalidRequestDefaultMaxTimeoutDuration);
@@ -562,11 +649,16 @@ impl ConfigBuilder {
         Ok(Config {
             continue_wait_duration: self.continue_wait_duration,
             decode_timeout_duration: self.decode_timeout_duration,
+            decoder_limits: self.decoder_limits,
             graceful_shutdown_default_timeout_duration: self
                 .graceful_shutdown_default_timeout_duration,
+            interleaved_buffer_size: self.interleaved_buffer_size,
             request_buffer_size: self.request_buffer_size,
+            request_concurrency: self.request_concurrency,
             request_default_max_timeout_duration: self.request_default_max_timeout_duration,
             request_default_timeout_duration: self.request_default_timeout_duration,
+            strictness: self.strictness,
+            version_policy: self.version_policy,
         })
     }
 
@@ -580,16 +672,38 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets the limits enforced on the requests and responses received on the connection. By
+    /// default, the limits are given by `DecoderLimits::default()`.
+    pub fn decoder_limits(&mut self, limits: DecoderLimits) -> &mut Self {
+        self.decoder_limits = limits;
+        self
+    }
+
     pub fn graceful_shutdown_default_timeout_duration(&mut self, duration: Duration) -> &mut Self {
         self.graceful_shutdown_default_timeout_duration = duration;
         self
     }
 
+    /// Sets how many interleaved binary data payloads are buffered per channel when receiving, and
+    /// for the connection as a whole when sending. By default, the size is
+    /// `DEFAULT_INTERLEAVED_BUFFER_SIZE`.
+    pub fn interleaved_buffer_size(&mut self, size: usize) -> &mut Self {
+        self.interleaved_buffer_size = size;
+        self
+    }
+
     pub fn request_buffer_size(&mut self, size: usize) -> &mut Self {
         self.request_buffer_size = size;
         self
     }
 
+    /// Sets how many of the requests received on the connection are serviced at once. By default,
+    /// requests are serviced one at a time.
+    pub fn request_concurrency(&mut self, concurrency: RequestConcurrency) -> &mut Self {
+        self.request_concurrency = concurrency;
+        self
+    }
+
     pub fn request_default_max_timeout_duration(
         &mut self,
         duration: Option<Duration>,
@@ -602,6 +716,20 @@ impl ConfigBuilder {
         self.request_default_timeout_duration = duration;
         self
     }
+
+    /// Sets how strictly the syntax of the requests and responses received on the connection is
+    /// followed. By default, any deviation from the syntax is an error.
+    pub fn strictness(&mut self, strictness: Strictness) -> &mut Self {
+        self.strictness = strictness;
+        self
+    }
+
+    /// Sets the policy that determines which versions of RTSP are accepted and used. By default,
+    /// only RTSP 2.0 is accepted.
+    pub fn version_policy(&mut self, version_policy: VersionPolicy) -> &mut Self {
+        self.version_policy = version_policy;
+        self
+    }
 }
 
 impl Default for ConfigBuilder {
@@ -609,10 +737,15 @@ impl Default for ConfigBuilder {
         ConfigBuilder {
             continue_wait_duration: Some(DEFAULT_CONTINUE_WAIT_DURATION),
             decode_timeout_duration: DEFAULT_DECODE_TIMEOUT_DURATION,
+            decoder_limits: DecoderLimits::default(),
             graceful_shutdown_default_timeout_duration: DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_DURATION,
+            interleaved_buffer_size: DEFAULT_INTERLEAVED_BUFFER_SIZE,
             request_buffer_size: DEFAULT_REQUEST_BUFFER_SIZE,
+            request_concurrency: RequestConcurrency::default(),
             request_default_max_timeout_duration: Some(DEFAULT_REQUEST_MAX_TIMEOUT_DURATION),
             request_default_timeout_duration: Some(DEFAULT_REQUEST_TIMEOUT_DURATION),
+            strictness: Strictness::default(),
+            version_policy: VersionPolicy::default(),
         }
     }
 }
@@ -621,7 +754,9 @@ impl Default for ConfigBuilder {
 pub enum ConfigBuilderError {
     InvalidContinueWaitDuration,
     InvalidDecodeTimeoutDuration,
+    InvalidInterleavedBufferSize,
     InvalidRequestBufferSize,
+    InvalidRequestConcurrency,
     InvalidRequestDefaultMaxTimeoutDuration,
     InvalidRequestDefaultTimeoutDuration,
 }
@@ -639,7 +774,9 @@ impl Error for ConfigBuilderError {
         match self {
             InvalidContinueWaitDuration => "invalid continue wait duration",
             InvalidDecodeTimeoutDuration => "invalid decode timeout duration",
+            InvalidInterleavedBufferSize => "invalid interleaved buffer size",
             InvalidRequestBufferSize => "invalid request buffer size",
+            InvalidRequestConcurrency => "invalid request concurrency",
             InvalidRequestDefaultMaxTimeoutDuration => {
                 "invalid request default max timeout duration"
             }
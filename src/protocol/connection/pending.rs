@@ -58,7 +58,7 @@ impl Future for SendRequestFuture {
                     self.rx_response = rx_response;
                     self.timer = self
                         .timeout_duration
-                        .map(|duration| Delay::new(Instant::now() + duration));;
+                        .map(|duration| Delay::new(Instant::now() + duration));
                 }
                 PendingRequestResponse::None => {
                     self.rx_response.close();
@@ -102,6 +102,7 @@ impl Future for SendRequestFuture {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct RequestOptions {
     max_timeout_duration: Option<Duration>,
+    redirect_policy: RedirectPolicy,
     timeout_duration: Option<Duration>,
 }
 
@@ -118,6 +119,10 @@ impl RequestOptions {
         self.max_timeout_duration
     }
 
+    pub fn redirect_policy(&self) -> RedirectPolicy {
+        self.redirect_policy
+    }
+
     pub fn timeout_duration(&self) -> Option<Duration> {
         self.timeout_duration
     }
@@ -134,6 +139,7 @@ impl Default for RequestOptions {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct RequestOptionsBuilder {
     max_timeout_duration: Option<Duration>,
+    redirect_policy: RedirectPolicy,
     timeout_duration: Option<Duration>,
 }
 
@@ -163,6 +169,7 @@ impl RequestOptionsBuilder {
 
         Ok(RequestOptions {
             max_timeout_duration: self.max_timeout_duration,
+            redirect_policy: self.redirect_policy,
             timeout_duration: self.timeout_duration,
         })
     }
@@ -172,6 +179,12 @@ impl RequestOptionsBuilder {
         self
     }
 
+    /// Sets how redirection responses are handled. Redirects are not followed by default.
+    pub fn redirect_policy(&mut self, policy: RedirectPolicy) -> &mut Self {
+        self.redirect_policy = policy;
+        self
+    }
+
     pub fn timeout_duration(&mut self, duration: Option<Duration>) -> &mut Self {
         self.timeout_duration = duration;
         self
@@ -182,11 +195,36 @@ impl Default for RequestOptionsBuilder {
     fn default() -> Self {
         RequestOptionsBuilder {
             max_timeout_duration: None,
+            redirect_policy: RedirectPolicy::None,
             timeout_duration: None,
         }
     }
 }
 
+/// Determines whether redirection responses to a request are followed.
+///
+/// A [`ConnectionHandle`] is bound to a single connection and so always returns redirection
+/// responses as they are. Redirects are followed by the [`Client`], since doing so may require
+/// connecting to a different server.
+///
+/// [`ConnectionHandle`]: struct.ConnectionHandle.html
+/// [`Client`]: ../../client/struct.Client.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RedirectPolicy {
+    /// Redirection responses are returned as they are.
+    None,
+
+    /// Redirects are followed until a response that is not a redirect is received, up to the given
+    /// number of redirects.
+    Limited(usize),
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::None
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum RequestOptionsBuilderError {
     InvalidTimeoutDuration,
@@ -9,12 +9,15 @@ use std::mem;
 use std::time::{Duration, Instant};
 use tokio_timer::{Delay, Error as TimerError};
 
-use super::{PendingRequestResponse, PendingRequestUpdate, SenderHandle};
+use super::{
+    InterleavedChannelUpdate, InterleavedReceiver, PendingRequestResponse, PendingRequestUpdate,
+    SenderHandle,
+};
 use header::types::CSeq;
 use header::{HeaderName, HeaderValue, TypedHeader};
 use protocol::{
     CodecEvent, DecodeError, InvalidMessage, IrrecoverableInvalidRequest,
-    IrrecoverableInvalidResponse, Message, MessageResult, ProtocolError,
+    IrrecoverableInvalidResponse, Message, MessageResult, ProtocolError, RecoverableInvalidRequest,
 };
 use request::Request;
 use response::Response;
@@ -31,6 +34,16 @@ lazy_static! {
         .build(BytesMut::new())
         .expect("not enough bandwidth response should not be invalid");
 
+    static ref REQUEST_MESSAGE_BODY_TOO_LARGE_RESPONSE: Response<BytesMut> = Response::builder()
+        .status_code(StatusCode::RequestMessageBodyTooLarge)
+        .build(BytesMut::new())
+        .expect("request message body too large response should not be invalid");
+
+    static ref REQUEST_URI_TOO_LONG_RESPONSE: Response<BytesMut> = Response::builder()
+        .status_code(StatusCode::RequestURITooLong)
+        .build(BytesMut::new())
+        .expect("request-URI too long response should not be invalid");
+
     // TODO: As per specification, the "response SHOULD contain a message body describing why
     // that version is not supported and what other protocols are supported by that agent".
 
@@ -49,6 +62,7 @@ impl Receiver {
     pub fn new(
         stream: Box<Stream<Item = MessageResult, Error = ProtocolError> + Send + 'static>,
         rx_pending_request: UnboundedReceiver<PendingRequestUpdate>,
+        rx_interleaved_channel: UnboundedReceiver<InterleavedChannelUpdate>,
         rx_codec_event: UnboundedReceiver<CodecEvent>,
         tx_incoming_request: Sender<(CSeq, Request<BytesMut>)>,
         decode_timeout_duration: Duration,
@@ -63,6 +77,7 @@ impl Receiver {
                 stream,
                 rx_codec_event,
                 rx_pending_request,
+                rx_interleaved_channel,
                 decode_timeout_duration,
             )),
         }
@@ -139,6 +154,8 @@ impl Receiver {
             _ => (),
         }
 
+        inner.poll_interleaved_channel_update();
+
         match inner.poll_receive(&mut self.forwarding_receiver, sender_handle) {
             Ok(Async::Ready(_)) | Err(_) => return None,
             _ => (),
@@ -192,6 +209,7 @@ impl Receiver {
 struct ReceiverInner {
     decode_timeout_duration: Duration,
     decoding_timer: Either<Delay, future::Empty<(), TimerError>>,
+    interleaved_receiver: InterleavedReceiver,
     request_receiver: Option<RequestReceiver>,
     response_receiver: Option<ResponseReceiver>,
     rx_codec_event: UnboundedReceiver<CodecEvent>,
@@ -212,6 +230,10 @@ impl ReceiverInner {
                 let message = Message::Response(NOT_ENOUGH_BANDWIDTH_RESPONSE.clone());
                 ReceiverInner::send_message(message, sender_handle);
             }
+            RequestReceiverError::RequestMessageBodyTooLarge => {
+                let message = Message::Response(REQUEST_MESSAGE_BODY_TOO_LARGE_RESPONSE.clone());
+                ReceiverInner::send_message(message, sender_handle);
+            }
         }
     }
 
@@ -227,6 +249,12 @@ impl ReceiverInner {
                     let message = Message::Response(VERSION_NOT_SUPPORTED_RESPONSE.clone());
                     ReceiverInner::send_message(message, sender_handle);
                 }
+                ProtocolError::DecodeError(DecodeError::InvalidRequest(
+                    IrrecoverableInvalidRequest::RequestLineTooLong,
+                )) => {
+                    let message = Message::Response(REQUEST_URI_TOO_LONG_RESPONSE.clone());
+                    ReceiverInner::send_message(message, sender_handle);
+                }
                 ProtocolError::DecodeError(_) => {
                     let message = Message::Response(BAD_REQUEST_RESPONSE.clone());
                     ReceiverInner::send_message(message, sender_handle);
@@ -240,11 +268,13 @@ impl ReceiverInner {
         stream: Box<Stream<Item = MessageResult, Error = ProtocolError> + Send + 'static>,
         rx_codec_event: UnboundedReceiver<CodecEvent>,
         rx_pending_request: UnboundedReceiver<PendingRequestUpdate>,
+        rx_interleaved_channel: UnboundedReceiver<InterleavedChannelUpdate>,
         decode_timeout_duration: Duration,
     ) -> Self {
         ReceiverInner {
             decode_timeout_duration,
             decoding_timer: Either::B(future::empty()),
+            interleaved_receiver: InterleavedReceiver::new(rx_interleaved_channel),
             request_receiver: Some(RequestReceiver),
             response_receiver: Some(ResponseReceiver::new(rx_pending_request)),
             rx_codec_event,
@@ -277,6 +307,9 @@ impl ReceiverInner {
         message: MessageResult,
     ) -> Result<(), RequestReceiverError> {
         match message {
+            Ok(Message::InterleavedData(data)) => {
+                self.interleaved_receiver.handle_interleaved_data(data);
+            }
             Ok(Message::Request(request)) => {
                 if let Some(ref request_receiver) = self.request_receiver {
                     let forwarding_receiver = forwarding_receiver
@@ -294,6 +327,9 @@ impl ReceiverInner {
                     }
                 }
             }
+            Err(InvalidMessage::InvalidRequest(RecoverableInvalidRequest::BodyTooLarge)) => {
+                return Err(RequestReceiverError::RequestMessageBodyTooLarge)
+            }
             Err(InvalidMessage::InvalidRequest(_)) => return Err(RequestReceiverError::BadRequest),
             Err(InvalidMessage::InvalidResponse(_)) => {}
         }
@@ -325,6 +361,10 @@ impl ReceiverInner {
         }
     }
 
+    pub fn poll_interleaved_channel_update(&mut self) {
+        self.interleaved_receiver.poll_interleaved_channel_update();
+    }
+
     pub fn poll_codec_events(&mut self) {
         while let Async::Ready(item) = self
             .rx_codec_event
@@ -621,4 +661,5 @@ impl Drop for ResponseReceiver {
 enum RequestReceiverError {
     BadRequest,
     NotEnoughBandwidth,
+    RequestMessageBodyTooLarge,
 }
@@ -1,10 +1,11 @@
-use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
-use futures::{Async, AsyncSink, Poll, Sink, Stream};
+use futures::sync::mpsc::{self, channel, unbounded, Receiver, UnboundedReceiver, UnboundedSender};
+use futures::{Async, AsyncSink, Poll, Sink, StartSend, Stream};
 
-use protocol::{Message, ProtocolError};
+use protocol::{InterleavedData, Message, ProtocolError};
 
 pub struct Sender {
     buffered_message: Option<Message>,
+    rx_interleaved_data: Option<Receiver<InterleavedData>>,
     rx_outgoing_message: Option<UnboundedReceiver<Message>>,
     sink: Box<Sink<SinkItem = Message, SinkError = ProtocolError> + Send + 'static>,
 }
@@ -12,15 +13,22 @@ pub struct Sender {
 impl Sender {
     pub fn new(
         sink: Box<Sink<SinkItem = Message, SinkError = ProtocolError> + Send + 'static>,
+        interleaved_buffer_size: usize,
     ) -> (Self, SenderHandle) {
+        let (tx_interleaved_data, rx_interleaved_data) = channel(interleaved_buffer_size);
         let (tx_outgoing_message, rx_outgoing_message) = unbounded();
         let sender = Sender {
             buffered_message: None,
+            rx_interleaved_data: Some(rx_interleaved_data),
             rx_outgoing_message: Some(rx_outgoing_message),
             sink,
         };
+        let sender_handle = SenderHandle {
+            tx_interleaved_data,
+            tx_outgoing_message,
+        };
 
-        (sender, SenderHandle(tx_outgoing_message))
+        (sender, sender_handle)
     }
 
     pub fn poll(&mut self) -> Poll<(), ProtocolError> {
@@ -34,30 +42,71 @@ impl Sender {
         self.sink.close()
     }
 
-    fn poll_write(&mut self) -> Poll<(), ProtocolError> {
+    /// Returns the next message that should be written.
+    ///
+    /// Requests and responses are preferred over interleaved data, so that they are not delayed
+    /// behind the data that has been queued. Interleaved data is only taken from its bounded
+    /// channel as the sink accepts it, which is how senders of the data are kept from outpacing
+    /// the connection.
+    fn poll_next_message(&mut self) -> Async<Option<Message>> {
+        let mut not_ready = false;
+
         if let Some(mut rx_outgoing_message) = self.rx_outgoing_message.take() {
-            loop {
-                match rx_outgoing_message
-                    .poll()
-                    .expect("unbounded receiver `rx_outgoing_message` should not error")
-                {
-                    Async::Ready(Some(message)) => {
-                        if let Async::NotReady = self.try_send_message(message)? {
-                            self.rx_outgoing_message = Some(rx_outgoing_message);
-                            return Ok(Async::NotReady);
-                        }
-                    }
-                    Async::NotReady => {
-                        self.sink.poll_complete()?;
-                        self.rx_outgoing_message = Some(rx_outgoing_message);
+            match rx_outgoing_message
+                .poll()
+                .expect("unbounded receiver `rx_outgoing_message` should not error")
+            {
+                Async::Ready(Some(message)) => {
+                    self.rx_outgoing_message = Some(rx_outgoing_message);
+                    return Async::Ready(Some(message));
+                }
+                Async::NotReady => {
+                    self.rx_outgoing_message = Some(rx_outgoing_message);
+                    not_ready = true;
+                }
+                Async::Ready(None) => (),
+            }
+        }
+
+        if let Some(mut rx_interleaved_data) = self.rx_interleaved_data.take() {
+            match rx_interleaved_data
+                .poll()
+                .expect("receiver `rx_interleaved_data` should not error")
+            {
+                Async::Ready(Some(data)) => {
+                    self.rx_interleaved_data = Some(rx_interleaved_data);
+                    return Async::Ready(Some(Message::InterleavedData(data)));
+                }
+                Async::NotReady => {
+                    self.rx_interleaved_data = Some(rx_interleaved_data);
+                    not_ready = true;
+                }
+                Async::Ready(None) => (),
+            }
+        }
+
+        if not_ready {
+            Async::NotReady
+        } else {
+            Async::Ready(None)
+        }
+    }
+
+    fn poll_write(&mut self) -> Poll<(), ProtocolError> {
+        loop {
+            match self.poll_next_message() {
+                Async::Ready(Some(message)) => {
+                    if let Async::NotReady = self.try_send_message(message)? {
                         return Ok(Async::NotReady);
                     }
-                    Async::Ready(None) => break,
                 }
+                Async::NotReady => {
+                    self.sink.poll_complete()?;
+                    return Ok(Async::NotReady);
+                }
+                Async::Ready(None) => return Ok(Async::Ready(())),
             }
         }
-
-        Ok(Async::Ready(()))
     }
 
     fn try_send_message(&mut self, message: Message) -> Poll<(), ProtocolError> {
@@ -79,10 +128,30 @@ impl Drop for Sender {
 }
 
 #[derive(Clone)]
-pub struct SenderHandle(UnboundedSender<Message>);
+pub struct SenderHandle {
+    tx_interleaved_data: mpsc::Sender<InterleavedData>,
+    tx_outgoing_message: UnboundedSender<Message>,
+}
 
 impl SenderHandle {
+    /// Polls whether all interleaved data that was started to be sent has been queued.
+    pub fn poll_complete_interleaved_data(&mut self) -> Poll<(), ()> {
+        self.tx_interleaved_data.poll_complete().map_err(|_| ())
+    }
+
+    /// Starts sending interleaved data, returning it back if the buffer of interleaved data
+    /// waiting to be written is full. In that case, the current task is notified once there is
+    /// room again.
+    pub fn start_send_interleaved_data(
+        &mut self,
+        data: InterleavedData,
+    ) -> StartSend<InterleavedData, ()> {
+        self.tx_interleaved_data.start_send(data).map_err(|_| ())
+    }
+
     pub fn try_send_message(&mut self, message: Message) -> Result<(), ()> {
-        self.0.unbounded_send(message).map_err(|_| ())
+        self.tx_outgoing_message
+            .unbounded_send(message)
+            .map_err(|_| ())
     }
 }
@@ -67,6 +67,8 @@
 //! ```
 
 use bytes::BytesMut;
+use std::borrow::Cow;
+use std::cmp::min;
 use std::convert::TryFrom;
 use std::mem::replace;
 use std::{error, fmt};
@@ -75,6 +77,7 @@ use header::types::ContentLength;
 use header::{Entry, HeaderName, HeaderValue, TypedHeader};
 use request::{Builder as RequestBuilder, BuilderError as RequestBuilderError, Request};
 use response::{Builder as ResponseBuilder, BuilderError as ResponseBuilderError, Response};
+use version::{InvalidVersion, Version, VersionPolicy};
 
 /// The current state of the request/response parsing.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -309,6 +312,305 @@ impl<T, E> From<Result<T, E>> for ParseResult<T, E> {
     }
 }
 
+/// Determines how strictly a decoder follows the syntax of requests/responses.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Strictness {
+    /// Any deviation from the syntax is an error. This is the default.
+    Strict,
+
+    /// The following deviations from the syntax, which are common among devices that do not
+    /// conform to the specification, are tolerated:
+    ///
+    /// * [`Deviation::BareLineFeed`] - Lines that end with `"\n"` instead of `"\r\n"`.
+    /// * [`Deviation::DuplicateContentLength`] - Multiple `"Content-Length"` headers, as long as
+    ///   they all have the same value.
+    /// * [`Deviation::LowercaseVersion`] - Versions that are not in uppercase, such as
+    ///   `"rtsp/1.0"`. Since versions are case-insensitive, these are accepted by strict decoders
+    ///   as well, but only reported as a deviation by lenient ones.
+    /// * [`Deviation::MissingReasonPhrase`] - Response lines without a reason phrase, such as
+    ///   `"RTSP/1.0 200"`. The canonical reason phrase of the status code is used instead, so
+    ///   this is only tolerated for status codes that have one.
+    /// * [`Deviation::TrailingWhitespace`] - Spaces or tabs at the end of the request/response
+    ///   line.
+    ///
+    /// The deviations that were tolerated while decoding a request/response are given by its
+    /// `deviations` function.
+    ///
+    /// [`Deviation::BareLineFeed`]: enum.Deviation.html#variant.BareLineFeed
+    /// [`Deviation::DuplicateContentLength`]: enum.Deviation.html#variant.DuplicateContentLength
+    /// [`Deviation::LowercaseVersion`]: enum.Deviation.html#variant.LowercaseVersion
+    /// [`Deviation::MissingReasonPhrase`]: enum.Deviation.html#variant.MissingReasonPhrase
+    /// [`Deviation::TrailingWhitespace`]: enum.Deviation.html#variant.TrailingWhitespace
+    Lenient,
+}
+
+impl Default for Strictness {
+    fn default() -> Self {
+        Strictness::Strict
+    }
+}
+
+/// A deviation from the syntax of requests/responses that is tolerated by decoders with a
+/// [`Strictness::Lenient`] strictness.
+///
+/// [`Strictness::Lenient`]: enum.Strictness.html#variant.Lenient
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum Deviation {
+    BareLineFeed,
+    DuplicateContentLength,
+    LowercaseVersion,
+    MissingReasonPhrase,
+    TrailingWhitespace,
+}
+
+impl Deviation {
+    fn bit(&self) -> u8 {
+        use self::Deviation::*;
+
+        match *self {
+            BareLineFeed => 1,
+            DuplicateContentLength => 1 << 1,
+            LowercaseVersion => 1 << 2,
+            MissingReasonPhrase => 1 << 3,
+            TrailingWhitespace => 1 << 4,
+        }
+    }
+}
+
+/// All deviations in the order they are iterated over by [`Deviations::iter`].
+const DEVIATIONS: [Deviation; 5] = [
+    Deviation::BareLineFeed,
+    Deviation::DuplicateContentLength,
+    Deviation::LowercaseVersion,
+    Deviation::MissingReasonPhrase,
+    Deviation::TrailingWhitespace,
+];
+
+/// The set of deviations that were tolerated while decoding a request/response.
+///
+/// # Examples
+///
+/// ```
+/// use rtsp::protocol::{Deviation, Deviations};
+///
+/// let mut deviations = Deviations::new();
+/// assert!(deviations.is_empty());
+///
+/// deviations.insert(Deviation::BareLineFeed);
+/// assert!(deviations.contains(Deviation::BareLineFeed));
+/// assert!(!deviations.contains(Deviation::LowercaseVersion));
+/// assert_eq!(deviations.iter().collect::<Vec<_>>(), vec![Deviation::BareLineFeed]);
+/// ```
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct Deviations(u8);
+
+impl Deviations {
+    /// Constructs an empty set of deviations.
+    pub fn new() -> Self {
+        Deviations::default()
+    }
+
+    /// Returns whether or not the given deviation is in the set.
+    pub fn contains(&self, deviation: Deviation) -> bool {
+        self.0 & deviation.bit() != 0
+    }
+
+    /// Adds the given deviation to the set.
+    pub fn insert(&mut self, deviation: Deviation) {
+        self.0 |= deviation.bit();
+    }
+
+    /// Returns whether or not the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns an iterator over the deviations in the set.
+    pub fn iter(&self) -> impl Iterator<Item = Deviation> {
+        let deviations = *self;
+
+        DEVIATIONS
+            .iter()
+            .cloned()
+            .filter(move |&deviation| deviations.contains(deviation))
+    }
+}
+
+impl fmt::Debug for Deviations {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// The default maximum length in bytes of the request/response line and of each header line.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 8192;
+
+/// The default maximum number of headers in a request/response.
+pub const DEFAULT_MAX_HEADER_COUNT: usize = 100;
+
+/// The default maximum size in bytes of all headers of a request/response combined.
+pub const DEFAULT_MAX_HEADERS_SIZE: usize = 65_536;
+
+/// The default maximum size in bytes of a request/response body.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 16_777_216;
+
+/// The limits a decoder enforces on the requests/responses it decodes so that a peer cannot make it
+/// buffer an arbitrary amount of data.
+///
+/// Exceeding the body size limit is a recoverable error, since the body can be skipped without
+/// being buffered. Exceeding any of the other limits is an irrecoverable error.
+///
+/// # Examples
+///
+/// ```
+/// use rtsp::protocol::{DecoderLimits, DEFAULT_MAX_HEADER_COUNT};
+///
+/// let limits = DecoderLimits::builder().max_body_size(1024).build().unwrap();
+///
+/// assert_eq!(limits.max_body_size(), 1024);
+/// assert_eq!(limits.max_header_count(), DEFAULT_MAX_HEADER_COUNT);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DecoderLimits {
+    max_body_size: usize,
+    max_header_count: usize,
+    max_headers_size: usize,
+    max_line_length: usize,
+}
+
+impl DecoderLimits {
+    pub fn builder() -> DecoderLimitsBuilder {
+        DecoderLimitsBuilder::new()
+    }
+
+    pub fn new() -> Self {
+        DecoderLimits::default()
+    }
+
+    pub fn max_body_size(&self) -> usize {
+        self.max_body_size
+    }
+
+    pub fn max_header_count(&self) -> usize {
+        self.max_header_count
+    }
+
+    pub fn max_headers_size(&self) -> usize {
+        self.max_headers_size
+    }
+
+    pub fn max_line_length(&self) -> usize {
+        self.max_line_length
+    }
+}
+
+impl Default for DecoderLimits {
+    fn default() -> Self {
+        DecoderLimits::builder()
+            .build()
+            .expect("default decoder limits builder should be valid")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DecoderLimitsBuilder {
+    max_body_size: usize,
+    max_header_count: usize,
+    max_headers_size: usize,
+    max_line_length: usize,
+}
+
+impl DecoderLimitsBuilder {
+    pub fn new() -> Self {
+        DecoderLimitsBuilder::default()
+    }
+
+    pub fn build(self) -> Result<DecoderLimits, DecoderLimitsBuilderError> {
+        if self.max_header_count == 0 {
+            return Err(DecoderLimitsBuilderError::InvalidMaxHeaderCount);
+        }
+
+        if self.max_headers_size == 0 {
+            return Err(DecoderLimitsBuilderError::InvalidMaxHeadersSize);
+        }
+
+        if self.max_line_length == 0 {
+            return Err(DecoderLimitsBuilderError::InvalidMaxLineLength);
+        }
+
+        Ok(DecoderLimits {
+            max_body_size: self.max_body_size,
+            max_header_count: self.max_header_count,
+            max_headers_size: self.max_headers_size,
+            max_line_length: self.max_line_length,
+        })
+    }
+
+    /// Sets the maximum size of a body. A body of size `0` is always accepted.
+    pub fn max_body_size(&mut self, size: usize) -> &mut Self {
+        self.max_body_size = size;
+        self
+    }
+
+    pub fn max_header_count(&mut self, count: usize) -> &mut Self {
+        self.max_header_count = count;
+        self
+    }
+
+    /// Sets the maximum size of all header lines combined, including their line breaks.
+    pub fn max_headers_size(&mut self, size: usize) -> &mut Self {
+        self.max_headers_size = size;
+        self
+    }
+
+    /// Sets the maximum length of the request/response line and of each header line, excluding the
+    /// line break. For headers that span multiple lines, this is the length of all of their lines
+    /// combined.
+    pub fn max_line_length(&mut self, length: usize) -> &mut Self {
+        self.max_line_length = length;
+        self
+    }
+}
+
+impl Default for DecoderLimitsBuilder {
+    fn default() -> Self {
+        DecoderLimitsBuilder {
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            max_header_count: DEFAULT_MAX_HEADER_COUNT,
+            max_headers_size: DEFAULT_MAX_HEADERS_SIZE,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DecoderLimitsBuilderError {
+    InvalidMaxHeaderCount,
+    InvalidMaxHeadersSize,
+    InvalidMaxLineLength,
+}
+
+impl fmt::Display for DecoderLimitsBuilderError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        use std::error::Error;
+
+        formatter.write_str(self.description())
+    }
+}
+
+impl error::Error for DecoderLimitsBuilderError {
+    fn description(&self) -> &str {
+        use self::DecoderLimitsBuilderError::*;
+
+        match self {
+            InvalidMaxHeaderCount => "invalid max header count",
+            InvalidMaxHeadersSize => "invalid max headers size",
+            InvalidMaxLineLength => "invalid max line length",
+        }
+    }
+}
+
 /// An alias for the parse result that is returned from the request decoder.
 pub type RequestParseResult<T> = ParseResult<T, InvalidRequest>;
 
@@ -351,7 +653,14 @@ pub struct RequestDecoder {
     body: Option<BytesMut>,
     builder: RequestBuilder,
     content_length: ContentLength,
+    deviations: Deviations,
+    discarded_body_length: Option<usize>,
+    header_count: usize,
+    headers_size: usize,
+    limits: DecoderLimits,
     state: ParseState<InvalidRequest>,
+    strictness: Strictness,
+    version_policy: VersionPolicy,
 }
 
 impl RequestDecoder {
@@ -361,7 +670,14 @@ impl RequestDecoder {
             body: None,
             builder: RequestBuilder::new(),
             content_length: ContentLength::default(),
+            deviations: Deviations::new(),
+            discarded_body_length: None,
+            header_count: 0,
+            headers_size: 0,
+            limits: DecoderLimits::default(),
             state: ParseState::InfoLine,
+            strictness: Strictness::default(),
+            version_policy: VersionPolicy::default(),
         }
     }
 
@@ -488,9 +804,14 @@ impl RequestDecoder {
                 Header => self.parse_header(&mut buffer),
                 Body => self.parse_body(&mut buffer),
                 End => {
+                    let deviations = replace(&mut self.deviations, Deviations::new());
                     let request = self
                         .builder
                         .build(replace(&mut self.body, None).unwrap())
+                        .map(|mut request| {
+                            *request.deviations_mut() = deviations;
+                            request
+                        })
                         .map_err(|error| {
                             InvalidRequest::try_from(error).expect("unexpected `BuilderError`")
                         });
@@ -503,7 +824,12 @@ impl RequestDecoder {
             match parse_result {
                 Complete(_) => continue,
                 Error(error) => {
-                    self.state = ParseState::IrrecoverableError(error);
+                    self.deviations = Deviations::new();
+
+                    if !error.is_recoverable() {
+                        self.state = ParseState::IrrecoverableError(error);
+                    }
+
                     break (Error(error), buffer_size - buffer.len());
                 }
                 Incomplete => break (Incomplete, buffer_size - buffer.len()),
@@ -514,10 +840,26 @@ impl RequestDecoder {
     /// This function more so just extracts the body with a length determined by the content length
     /// than it does parse it. This decoder does not try to parse the body based on the content
     /// type, this should be done at a higher level.
+    ///
+    /// If the body is larger than allowed by the decoder limits, it is discarded as it arrives
+    /// instead and a `BodyTooLarge` error is returned once all of it has been discarded.
     fn parse_body<'a>(&mut self, buffer: &mut &'a [u8]) -> RequestParseResult<()> {
         use self::ParseResult::*;
 
-        if *self.content_length > buffer.len() {
+        if let Some(length) = self.discarded_body_length {
+            let discarded_length = min(length, buffer.len());
+            *buffer = &buffer[discarded_length..];
+
+            if discarded_length < length {
+                self.discarded_body_length = Some(length - discarded_length);
+                return Incomplete;
+            }
+
+            self.builder = RequestBuilder::new();
+            self.discarded_body_length = None;
+            self.state = ParseState::InfoLine;
+            Error(InvalidRequest::BodyTooLarge)
+        } else if *self.content_length > buffer.len() {
             Incomplete
         } else {
             let (body, unparsed_buffer) = buffer.split_at(*self.content_length);
@@ -534,8 +876,16 @@ impl RequestDecoder {
     fn parse_header<'a>(&mut self, buffer: &mut &'a [u8]) -> RequestParseResult<()> {
         use self::ParseResult::*;
 
-        match parse_header(buffer) {
-            Error(_) => Error(InvalidRequest::InvalidHeaderLine),
+        let buffer_size = buffer.len();
+
+        match parse_header(
+            buffer,
+            self.limits.max_line_length,
+            self.strictness,
+            &mut self.deviations,
+        ) {
+            Error(HeaderLineError::Invalid) => Error(InvalidRequest::InvalidHeaderLine),
+            Error(HeaderLineError::TooLong) => Error(InvalidRequest::HeaderLineTooLong),
             Incomplete => Incomplete,
             Complete(None) => {
                 self.state = ParseState::Body;
@@ -545,16 +895,31 @@ impl RequestDecoder {
                     .entry(HeaderName::ContentLength)
                     .expect("`ContentLength` should be a valid `HeaderName`");
 
-                match get_content_length(entry) {
+                match get_content_length(entry, self.strictness, &mut self.deviations) {
                     Ok(content_length) => self.content_length = content_length,
                     Err(_) => return Error(InvalidRequest::InvalidContentLength),
                 }
 
+                if *self.content_length > self.limits.max_body_size {
+                    self.discarded_body_length = Some(*self.content_length);
+                }
+
                 Complete(())
             }
             Complete(Some((name, value))) => {
+                self.header_count += 1;
+                self.headers_size += buffer_size - buffer.len();
+
+                if self.header_count > self.limits.max_header_count {
+                    return Error(InvalidRequest::TooManyHeaders);
+                }
+
+                if self.headers_size > self.limits.max_headers_size {
+                    return Error(InvalidRequest::HeadersTooLarge);
+                }
+
                 let name = trim_header(name);
-                self.builder.header(name, value);
+                self.builder.header(name, &*value);
                 Complete(())
             }
         }
@@ -568,28 +933,42 @@ impl RequestDecoder {
     /// continue any further.
     fn parse_request_line<'a>(&mut self, buffer: &mut &'a [u8]) -> RequestParseResult<()> {
         use self::ParseResult::*;
-        use self::RequestBuilderError as BuilderError;
 
         loop {
-            break match get_line(buffer) {
-                Some((_, 0)) => continue,
-                Some((line, _)) => match split_info_line(line) {
+            let line = get_line(
+                buffer,
+                self.limits.max_line_length,
+                self.strictness,
+                &mut self.deviations,
+            );
+
+            break match line {
+                Err(_) => Error(InvalidRequest::RequestLineTooLong),
+                Ok(Some(line)) if line.is_empty() => continue,
+                Ok(Some(line)) => match split_info_line(trim_info_line(
+                    line,
+                    self.strictness,
+                    &mut self.deviations,
+                )) {
                     Some((method, uri, version)) => {
+                        self.header_count = 0;
+                        self.headers_size = 0;
                         self.state = ParseState::Header;
-                        self.builder.version(version);
-
-                        if self.builder.error == Some(BuilderError::InvalidVersion) {
-                            Error(InvalidRequest::InvalidVersion)
-                        } else if self.builder.error == Some(BuilderError::UnsupportedVersion) {
-                            Error(InvalidRequest::UnsupportedVersion)
-                        } else {
-                            self.builder.method(method).uri(uri);
-                            Complete(())
+
+                        match parse_version(version, self.strictness, &mut self.deviations) {
+                            Ok(version) if self.version_policy.is_allowed(version) => {
+                                self.builder.version(version).method(method).uri(uri);
+                                Complete(())
+                            }
+                            Ok(_) | Err(InvalidVersion::Unknown) => {
+                                Error(InvalidRequest::UnsupportedVersion)
+                            }
+                            Err(InvalidVersion::Invalid) => Error(InvalidRequest::InvalidVersion),
                         }
                     }
                     None => Error(InvalidRequest::InvalidRequestLine),
                 },
-                None => Incomplete,
+                Ok(None) => Incomplete,
             };
         }
     }
@@ -669,8 +1048,52 @@ impl RequestDecoder {
         self.body = None;
         self.builder = RequestBuilder::new();
         self.content_length = ContentLength::default();
+        self.deviations = Deviations::new();
+        self.discarded_body_length = None;
+        self.header_count = 0;
+        self.headers_size = 0;
         self.state = ParseState::InfoLine;
     }
+
+    /// Sets the policy that determines which versions are accepted. A request with a version
+    /// that is not accepted results in an `UnsupportedVersion` error. By default, only RTSP 2.0 is
+    /// accepted.
+    ///
+    /// Resetting the decoder does not change its version policy.
+    pub fn set_version_policy(&mut self, version_policy: VersionPolicy) {
+        self.version_policy = version_policy;
+    }
+
+    /// Returns the policy that determines which versions are accepted.
+    pub fn version_policy(&self) -> VersionPolicy {
+        self.version_policy
+    }
+
+    /// Returns the limits enforced on the requests being decoded.
+    pub fn limits(&self) -> DecoderLimits {
+        self.limits
+    }
+
+    /// Sets how strictly the syntax of requests is followed. By default, any deviation from the
+    /// syntax is an error.
+    ///
+    /// Resetting the decoder does not change its strictness.
+    pub fn set_strictness(&mut self, strictness: Strictness) {
+        self.strictness = strictness;
+    }
+
+    /// Returns how strictly the syntax of requests is followed.
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+
+    /// Sets the limits enforced on the requests being decoded. By default, the limits are given
+    /// by `DecoderLimits::default()`.
+    ///
+    /// Resetting the decoder does not change its limits.
+    pub fn set_limits(&mut self, limits: DecoderLimits) {
+        self.limits = limits;
+    }
 }
 
 /// An error type for when the request was invalid. Within the set of errors exist two subsets:
@@ -678,6 +1101,9 @@ impl RequestDecoder {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum InvalidRequest {
+    BodyTooLarge,
+    HeaderLineTooLong,
+    HeadersTooLarge,
     InvalidContentLength,
     InvalidHeaderLine,
     InvalidHeaderName,
@@ -686,6 +1112,8 @@ pub enum InvalidRequest {
     InvalidRequestLine,
     InvalidRequestURI,
     InvalidVersion,
+    RequestLineTooLong,
+    TooManyHeaders,
     UnsupportedVersion,
 }
 
@@ -704,7 +1132,8 @@ impl InvalidRequest {
         use self::InvalidRequest::*;
 
         match *self {
-            InvalidContentLength | InvalidHeaderLine | InvalidRequestLine | InvalidVersion
+            HeaderLineTooLong | HeadersTooLarge | InvalidContentLength | InvalidHeaderLine
+            | InvalidRequestLine | InvalidVersion | RequestLineTooLong | TooManyHeaders
             | UnsupportedVersion => false,
             _ => true,
         }
@@ -724,6 +1153,9 @@ impl error::Error for InvalidRequest {
         use self::InvalidRequest::*;
 
         match *self {
+            BodyTooLarge => "invalid RTSP request - body too large",
+            HeaderLineTooLong => "invalid RTSP request - header line too long",
+            HeadersTooLarge => "invalid RTSP request - headers too large",
             InvalidContentLength => "invalid RTSP request - invalid content length",
             InvalidHeaderLine => "invalid RTSP request - invalid header line",
             InvalidHeaderName => "invalid RTSP request - invalid header name",
@@ -732,6 +1164,8 @@ impl error::Error for InvalidRequest {
             InvalidRequestLine => "invalid RTSP request - invalid request line",
             InvalidRequestURI => "invalid RTSP request - invalid request URI",
             InvalidVersion => "invalid RTSP request - invalid version",
+            RequestLineTooLong => "invalid RTSP request - request line too long",
+            TooManyHeaders => "invalid RTSP request - too many headers",
             UnsupportedVersion => "invalid RTSP request - unsupported version",
         }
     }
@@ -793,7 +1227,14 @@ pub struct ResponseDecoder {
     body: Option<BytesMut>,
     builder: ResponseBuilder,
     content_length: ContentLength,
+    deviations: Deviations,
+    discarded_body_length: Option<usize>,
+    header_count: usize,
+    headers_size: usize,
+    limits: DecoderLimits,
     state: ParseState<InvalidResponse>,
+    strictness: Strictness,
+    version_policy: VersionPolicy,
 }
 
 impl ResponseDecoder {
@@ -802,7 +1243,14 @@ impl ResponseDecoder {
             body: None,
             builder: ResponseBuilder::new(),
             content_length: ContentLength::default(),
+            deviations: Deviations::new(),
+            discarded_body_length: None,
+            header_count: 0,
+            headers_size: 0,
+            limits: DecoderLimits::default(),
             state: ParseState::InfoLine,
+            strictness: Strictness::default(),
+            version_policy: VersionPolicy::default(),
         }
     }
 
@@ -919,9 +1367,14 @@ impl ResponseDecoder {
                 Header => self.parse_header(&mut buffer),
                 Body => self.parse_body(&mut buffer),
                 End => {
+                    let deviations = replace(&mut self.deviations, Deviations::new());
                     let response = self
                         .builder
                         .build(replace(&mut self.body, None).unwrap())
+                        .map(|mut response| {
+                            *response.deviations_mut() = deviations;
+                            response
+                        })
                         .map_err(|error| {
                             InvalidResponse::try_from(error).expect("unexpected `BuilderError`")
                         });
@@ -934,7 +1387,12 @@ impl ResponseDecoder {
             match parse_result {
                 Complete(_) => continue,
                 Error(error) => {
-                    self.state = ParseState::IrrecoverableError(error);
+                    self.deviations = Deviations::new();
+
+                    if !error.is_recoverable() {
+                        self.state = ParseState::IrrecoverableError(error);
+                    }
+
                     break (Error(error), buffer_size - buffer.len());
                 }
                 Incomplete => break (Incomplete, buffer_size - buffer.len()),
@@ -945,10 +1403,26 @@ impl ResponseDecoder {
     /// This function more so just extracts the body with a length determined by the content length
     /// than it does parse it. This decoder does not try to parse the body based on the content
     /// type, this should be done at a higher level.
+    ///
+    /// If the body is larger than allowed by the decoder limits, it is discarded as it arrives
+    /// instead and a `BodyTooLarge` error is returned once all of it has been discarded.
     fn parse_body<'a>(&mut self, buffer: &mut &'a [u8]) -> ResponseParseResult<()> {
         use self::ParseResult::*;
 
-        if *self.content_length > buffer.len() {
+        if let Some(length) = self.discarded_body_length {
+            let discarded_length = min(length, buffer.len());
+            *buffer = &buffer[discarded_length..];
+
+            if discarded_length < length {
+                self.discarded_body_length = Some(length - discarded_length);
+                return Incomplete;
+            }
+
+            self.builder = ResponseBuilder::new();
+            self.discarded_body_length = None;
+            self.state = ParseState::InfoLine;
+            Error(InvalidResponse::BodyTooLarge)
+        } else if *self.content_length > buffer.len() {
             Incomplete
         } else {
             let (body, unparsed_buffer) = buffer.split_at(*self.content_length);
@@ -965,8 +1439,16 @@ impl ResponseDecoder {
     fn parse_header<'a>(&mut self, buffer: &mut &'a [u8]) -> ResponseParseResult<()> {
         use self::ParseResult::*;
 
-        match parse_header(buffer) {
-            Error(_) => Error(InvalidResponse::InvalidHeaderLine),
+        let buffer_size = buffer.len();
+
+        match parse_header(
+            buffer,
+            self.limits.max_line_length,
+            self.strictness,
+            &mut self.deviations,
+        ) {
+            Error(HeaderLineError::Invalid) => Error(InvalidResponse::InvalidHeaderLine),
+            Error(HeaderLineError::TooLong) => Error(InvalidResponse::HeaderLineTooLong),
             Incomplete => Incomplete,
             Complete(None) => {
                 self.state = ParseState::Body;
@@ -976,16 +1458,31 @@ impl ResponseDecoder {
                     .entry(HeaderName::ContentLength)
                     .expect("`ContentLength` should be a valid `HeaderName`");
 
-                match get_content_length(entry) {
+                match get_content_length(entry, self.strictness, &mut self.deviations) {
                     Ok(content_length) => self.content_length = content_length,
                     Err(_) => return Error(InvalidResponse::InvalidContentLength),
                 }
 
+                if *self.content_length > self.limits.max_body_size {
+                    self.discarded_body_length = Some(*self.content_length);
+                }
+
                 Complete(())
             }
             Complete(Some((name, value))) => {
+                self.header_count += 1;
+                self.headers_size += buffer_size - buffer.len();
+
+                if self.header_count > self.limits.max_header_count {
+                    return Error(InvalidResponse::TooManyHeaders);
+                }
+
+                if self.headers_size > self.limits.max_headers_size {
+                    return Error(InvalidResponse::HeadersTooLarge);
+                }
+
                 let name = trim_header(name);
-                self.builder.header(name, value);
+                self.builder.header(name, &*value);
                 Complete(())
             }
         }
@@ -999,34 +1496,67 @@ impl ResponseDecoder {
     /// is no way to recover, so decoding cannot continue any further.
     fn parse_response_line<'a>(&mut self, buffer: &mut &'a [u8]) -> ResponseParseResult<()> {
         use self::ParseResult::*;
-        use self::ResponseBuilderError as BuilderError;
 
         loop {
-            break match get_line(buffer) {
-                Some((_, 0)) => continue,
-                Some((line, _)) => match split_info_line(line) {
+            let line = get_line(
+                buffer,
+                self.limits.max_line_length,
+                self.strictness,
+                &mut self.deviations,
+            );
+
+            break match line {
+                Err(_) => Error(InvalidResponse::ResponseLineTooLong),
+                Ok(Some(line)) if line.is_empty() => continue,
+                Ok(Some(line)) => match self.split_response_line(line) {
                     Some((version, status_code, reason_phrase)) => {
+                        self.header_count = 0;
+                        self.headers_size = 0;
                         self.state = ParseState::Header;
-                        self.builder.version(version);
-
-                        if self.builder.error == Some(BuilderError::InvalidVersion) {
-                            Error(InvalidResponse::InvalidVersion)
-                        } else if self.builder.error == Some(BuilderError::UnsupportedVersion) {
-                            Error(InvalidResponse::UnsupportedVersion)
-                        } else {
-                            self.builder
-                                .status_code(status_code)
-                                .reason(Some(reason_phrase));
-                            Complete(())
+
+                        match parse_version(version, self.strictness, &mut self.deviations) {
+                            Ok(version) if self.version_policy.is_allowed(version) => {
+                                self.builder
+                                    .version(version)
+                                    .status_code(status_code)
+                                    .reason(reason_phrase);
+                                Complete(())
+                            }
+                            Ok(_) | Err(InvalidVersion::Unknown) => {
+                                Error(InvalidResponse::UnsupportedVersion)
+                            }
+                            Err(InvalidVersion::Invalid) => Error(InvalidResponse::InvalidVersion),
                         }
                     }
                     None => Error(InvalidResponse::InvalidResponseLine),
                 },
-                None => Incomplete,
+                Ok(None) => Incomplete,
             };
         }
     }
 
+    /// Splits the response line into its version, status code and reason phrase. If the
+    /// strictness is lenient, trailing whitespace and a missing reason phrase are tolerated.
+    fn split_response_line<'a>(
+        &mut self,
+        line: &'a [u8],
+    ) -> Option<(&'a [u8], &'a [u8], Option<&'a [u8]>)> {
+        let line = trim_info_line(line, self.strictness, &mut self.deviations);
+
+        if let Some((version, status_code, reason_phrase)) = split_info_line(line) {
+            return Some((version, status_code, Some(reason_phrase)));
+        }
+
+        if self.strictness == Strictness::Lenient {
+            if let Some(i) = line.iter().position(|&b| b == b' ') {
+                self.deviations.insert(Deviation::MissingReasonPhrase);
+                return Some((&line[..i], &line[i + 1..], None));
+            }
+        }
+
+        None
+    }
+
     /// Returns the current parsing state. If the parse state is the `IrrecoverableError` variant,
     /// it will include the error.
     ///
@@ -1100,8 +1630,52 @@ impl ResponseDecoder {
         self.body = None;
         self.builder = ResponseBuilder::new();
         self.content_length = ContentLength::default();
+        self.deviations = Deviations::new();
+        self.discarded_body_length = None;
+        self.header_count = 0;
+        self.headers_size = 0;
         self.state = ParseState::InfoLine;
     }
+
+    /// Sets the policy that determines which versions are accepted. A response with a version
+    /// that is not accepted results in an `UnsupportedVersion` error. By default, only RTSP 2.0 is
+    /// accepted.
+    ///
+    /// Resetting the decoder does not change its version policy.
+    pub fn set_version_policy(&mut self, version_policy: VersionPolicy) {
+        self.version_policy = version_policy;
+    }
+
+    /// Returns the policy that determines which versions are accepted.
+    pub fn version_policy(&self) -> VersionPolicy {
+        self.version_policy
+    }
+
+    /// Returns the limits enforced on the responses being decoded.
+    pub fn limits(&self) -> DecoderLimits {
+        self.limits
+    }
+
+    /// Sets how strictly the syntax of responses is followed. By default, any deviation from the
+    /// syntax is an error.
+    ///
+    /// Resetting the decoder does not change its strictness.
+    pub fn set_strictness(&mut self, strictness: Strictness) {
+        self.strictness = strictness;
+    }
+
+    /// Returns how strictly the syntax of responses is followed.
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+
+    /// Sets the limits enforced on the responses being decoded. By default, the limits are given
+    /// by `DecoderLimits::default()`.
+    ///
+    /// Resetting the decoder does not change its limits.
+    pub fn set_limits(&mut self, limits: DecoderLimits) {
+        self.limits = limits;
+    }
 }
 
 /// An error type for when the response was invalid. Within the set of errors exist two subsets:
@@ -1109,6 +1683,9 @@ impl ResponseDecoder {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum InvalidResponse {
+    BodyTooLarge,
+    HeaderLineTooLong,
+    HeadersTooLarge,
     InvalidContentLength,
     InvalidHeaderLine,
     InvalidHeaderName,
@@ -1117,6 +1694,8 @@ pub enum InvalidResponse {
     InvalidResponseLine,
     InvalidStatusCode,
     InvalidVersion,
+    ResponseLineTooLong,
+    TooManyHeaders,
     UnsupportedVersion,
 }
 
@@ -1135,7 +1714,8 @@ impl InvalidResponse {
         use self::InvalidResponse::*;
 
         match *self {
-            InvalidContentLength | InvalidHeaderLine | InvalidResponseLine | InvalidVersion
+            HeaderLineTooLong | HeadersTooLarge | InvalidContentLength | InvalidHeaderLine
+            | InvalidResponseLine | InvalidVersion | ResponseLineTooLong | TooManyHeaders
             | UnsupportedVersion => false,
             _ => true,
         }
@@ -1155,6 +1735,9 @@ impl error::Error for InvalidResponse {
         use self::InvalidResponse::*;
 
         match *self {
+            BodyTooLarge => "invalid RTSP response - body too large",
+            HeaderLineTooLong => "invalid RTSP response - header line too long",
+            HeadersTooLarge => "invalid RTSP response - headers too large",
             InvalidContentLength => "invalid RTSP response - invalid content length",
             InvalidHeaderLine => "invalid RTSP response - invalid header line",
             InvalidHeaderName => "invalid RTSP response - invalid header name",
@@ -1163,6 +1746,8 @@ impl error::Error for InvalidResponse {
             InvalidResponseLine => "invalid RTSP response - invalid response line",
             InvalidStatusCode => "invalid RTSP response - invalid status code",
             InvalidVersion => "invalid RTSP response - invalid version",
+            ResponseLineTooLong => "invalid RTSP response - response line too long",
+            TooManyHeaders => "invalid RTSP response - too many headers",
             UnsupportedVersion => "invalid RTSP response - unsupported version",
         }
     }
@@ -1178,94 +1763,241 @@ impl TryFrom<ResponseBuilderError> for InvalidResponse {
             ResponseBuilderError::InvalidHeaderName => Ok(InvalidHeaderName),
             ResponseBuilderError::InvalidHeaderValue => Ok(InvalidHeaderValue),
             ResponseBuilderError::InvalidReasonPhrase => Ok(InvalidReasonPhrase),
+            ResponseBuilderError::MissingReasonPhrase => Ok(InvalidReasonPhrase),
             ResponseBuilderError::InvalidStatusCode => Ok(InvalidStatusCode),
             _ => Err(()),
         }
     }
 }
 
-/// Consumes a line from the buffer, returning the line found and the index at which the `\r\n`
-/// started.
-fn get_line<'a>(buffer: &mut &'a [u8]) -> Option<(&'a [u8], usize)> {
-    if let Some(i) = buffer.windows(2).position(|b| b == b"\r\n") {
-        let (line, unparsed_buffer) = buffer.split_at(i);
-        *buffer = &unparsed_buffer[2..];
-        Some((line, i))
-    } else {
-        None
+/// The reasons a header line could not be parsed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum HeaderLineError {
+    Invalid,
+    TooLong,
+}
+
+/// Finds the first line break in the buffer, returning the index at which it starts and its
+/// length. If the strictness is lenient, a bare `\n` is accepted as a line break as well.
+fn find_line_break(buffer: &[u8], strictness: Strictness) -> Option<(usize, usize)> {
+    match strictness {
+        Strictness::Strict => buffer.windows(2).position(|b| b == b"\r\n").map(|i| (i, 2)),
+        Strictness::Lenient => buffer.iter().position(|&b| b == b'\n').map(|i| {
+            if i > 0 && buffer[i - 1] == b'\r' {
+                (i - 1, 2)
+            } else {
+                (i, 1)
+            }
+        }),
+    }
+}
+
+/// Consumes a line from the buffer, returning the line found without its line break. An error is
+/// returned if the line is known to be longer than `max_line_length`.
+fn get_line<'a>(
+    buffer: &mut &'a [u8],
+    max_line_length: usize,
+    strictness: Strictness,
+    deviations: &mut Deviations,
+) -> Result<Option<&'a [u8]>, ()> {
+    let limited_buffer = &buffer[..min(buffer.len(), max_line_length.saturating_add(2))];
+
+    match find_line_break(limited_buffer, strictness) {
+        Some((i, _)) if i > max_line_length => Err(()),
+        Some((i, line_break_length)) => {
+            if line_break_length == 1 {
+                deviations.insert(Deviation::BareLineFeed);
+            }
+
+            let (line, unparsed_buffer) = buffer.split_at(i);
+            *buffer = &unparsed_buffer[line_break_length..];
+            Ok(Some(line))
+        }
+        None if limited_buffer.len() < buffer.len() => Err(()),
+        None => Ok(None),
     }
 }
 
 /// Given the `Entry` for the `Content-Length` header (assuming use of `HeaderMap`), this function
 /// attempts to parse the given header and return the content length, defaulting to 0 if the header
-/// does not exist.
-fn get_content_length(header_entry: Entry<HeaderValue>) -> Result<ContentLength, ()> {
+/// does not exist. If the strictness is lenient, the header may be given multiple times as long as
+/// the content lengths are all the same.
+fn get_content_length(
+    header_entry: Entry<HeaderValue>,
+    strictness: Strictness,
+    deviations: &mut Deviations,
+) -> Result<ContentLength, ()> {
     match header_entry {
-        Entry::Occupied(entry) => if entry.iter().count() > 1 {
-            Err(())
-        } else {
-            let header_values = &entry.iter().cloned().collect::<Vec<HeaderValue>>();
-            ContentLength::try_from_header_raw(header_values).map_err(|_| ())
-        },
+        Entry::Occupied(entry) => {
+            if entry.iter().count() > 1 {
+                if strictness == Strictness::Strict {
+                    return Err(());
+                }
+
+                let mut content_lengths = entry.iter().map(|header_value| {
+                    ContentLength::try_from_header_raw(&[header_value.clone()]).map_err(|_| ())
+                });
+                let content_length = content_lengths.next().unwrap()?;
+
+                for other_content_length in content_lengths {
+                    if other_content_length? != content_length {
+                        return Err(());
+                    }
+                }
+
+                deviations.insert(Deviation::DuplicateContentLength);
+                Ok(content_length)
+            } else {
+                let header_values = &entry.iter().cloned().collect::<Vec<HeaderValue>>();
+                ContentLength::try_from_header_raw(header_values).map_err(|_| ())
+            }
+        }
         Entry::Vacant(_) => Ok(ContentLength::default()),
     }
 }
 
+/// Inserts a `\r` before every bare `\n` in a header value that was folded over lines ending with
+/// bare `\n` line breaks.
+fn normalize_line_breaks(value: &[u8]) -> Cow<[u8]> {
+    let is_bare_line_feed = |i: usize| value[i] == b'\n' && (i == 0 || value[i - 1] != b'\r');
+
+    if !(0..value.len()).any(is_bare_line_feed) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut normalized_value = Vec::with_capacity(value.len() + 1);
+
+    for (i, &b) in value.iter().enumerate() {
+        if is_bare_line_feed(i) {
+            normalized_value.push(b'\r');
+        }
+
+        normalized_value.push(b);
+    }
+
+    Cow::Owned(normalized_value)
+}
+
 /// A helper function to parse a header. This function will return two indices where one is
 /// optional. The first index will be the end of the header, while the second index is an
 /// `Option<usize>` of where the the header name and value are separated (the index of the `:`).
 /// RTSP allows for having multiline headers as long as newlines contained within header values
 /// start with a space or tab.
-fn parse_header_multiline(buffer: &[u8]) -> ParseResult<Option<(usize, usize)>, ()> {
+///
+/// Only the first `max_line_length` bytes of the header (plus the bytes needed to find its end) are
+/// searched, so that a header that is too long is detected without scanning the rest of the buffer.
+///
+/// Along with the indices, the index at which the next line starts is returned.
+fn parse_header_multiline(
+    buffer: &[u8],
+    max_line_length: usize,
+    strictness: Strictness,
+    deviations: &mut Deviations,
+) -> ParseResult<(usize, Option<(usize, usize)>), HeaderLineError> {
     use self::ParseResult::*;
 
-    let mut iter = buffer
-        .windows(2)
-        .enumerate()
-        .filter(|&(_, b)| b == b"\r\n")
-        .map(|(i, _)| i);
-
-    match iter.next() {
-        Some(0) => Complete(None),
-        Some(mut i) => if let Some(j) = buffer.iter().take(i).position(|&b| b == b':') {
-            loop {
-                match buffer.get(i + 2) {
-                    Some(&b) if b == b' ' || b == b'\t' => match iter.next() {
-                        Some(k) => i = k,
+    let limited_buffer = &buffer[..min(buffer.len(), max_line_length.saturating_add(3))];
+    let incomplete = || {
+        if limited_buffer.len() < buffer.len() {
+            Error(HeaderLineError::TooLong)
+        } else {
+            Incomplete
+        }
+    };
+    let next_line_break = |start: usize| {
+        find_line_break(&limited_buffer[start..], strictness)
+            .map(|(i, line_break_length)| (start + i, line_break_length))
+    };
+
+    match next_line_break(0) {
+        Some((0, line_break_length)) => {
+            if line_break_length == 1 {
+                deviations.insert(Deviation::BareLineFeed);
+            }
+
+            Complete((line_break_length, None))
+        }
+        Some((mut i, mut line_break_length)) => {
+            if let Some(j) = buffer.iter().take(i).position(|&b| b == b':') {
+                loop {
+                    if i > max_line_length {
+                        break Error(HeaderLineError::TooLong);
+                    }
+
+                    if line_break_length == 1 {
+                        deviations.insert(Deviation::BareLineFeed);
+                    }
+
+                    match buffer.get(i + line_break_length) {
+                        Some(&b) if b == b' ' || b == b'\t' => {
+                            match next_line_break(i + line_break_length) {
+                                Some((k, k_line_break_length)) => {
+                                    i = k;
+                                    line_break_length = k_line_break_length;
+                                }
+                                None => break incomplete(),
+                            }
+                        }
+                        Some(_) => break Complete((i + line_break_length, Some((i, j)))),
                         None => break Incomplete,
-                    },
-                    Some(_) => break Complete(Some((i, j))),
-                    None => break Incomplete,
+                    }
                 }
+            } else if i > max_line_length {
+                Error(HeaderLineError::TooLong)
+            } else {
+                Error(HeaderLineError::Invalid)
             }
-        } else {
-            Error(())
-        },
-        None => Incomplete,
+        }
+        None => incomplete(),
     }
 }
 
 /// Parses a header of the request/response.
-fn parse_header<'a>(buffer: &mut &'a [u8]) -> ParseResult<Option<(&'a [u8], &'a [u8])>, ()> {
+fn parse_header<'a>(
+    buffer: &mut &'a [u8],
+    max_line_length: usize,
+    strictness: Strictness,
+    deviations: &mut Deviations,
+) -> ParseResult<Option<(&'a [u8], Cow<'a, [u8]>)>, HeaderLineError> {
     use self::ParseResult::*;
 
-    match parse_header_multiline(*buffer) {
-        Error(_) => Error(()),
+    match parse_header_multiline(*buffer, max_line_length, strictness, deviations) {
+        Error(error) => Error(error),
         Incomplete => Incomplete,
-        Complete(None) => {
-            *buffer = &(*buffer)[2..];
+        Complete((k, None)) => {
+            *buffer = &(*buffer)[k..];
             Complete(None)
         }
-        Complete(Some((i, j))) => {
-            let (header, unparsed_buffer) = buffer.split_at(i);
-            *buffer = &unparsed_buffer[2..];
+        Complete((k, Some((i, j)))) => {
+            let header = &(*buffer)[..i];
+            *buffer = &(*buffer)[k..];
             let (name, mut value) = header.split_at(j);
             value = &value[1..];
-            Complete(Some((name, value)))
+
+            match strictness {
+                Strictness::Strict => Complete(Some((name, Cow::Borrowed(value)))),
+                Strictness::Lenient => Complete(Some((name, normalize_line_breaks(value)))),
+            }
         }
     }
 }
 
+/// Parses the version of the request/response line, which is case-insensitive. A version that is
+/// not in uppercase is reported as a deviation if the strictness is lenient.
+fn parse_version(
+    version: &[u8],
+    strictness: Strictness,
+    deviations: &mut Deviations,
+) -> Result<Version, InvalidVersion> {
+    let parsed_version = Version::try_from(version)?;
+
+    if strictness == Strictness::Lenient && !version.starts_with(b"RTSP/") {
+        deviations.insert(Deviation::LowercaseVersion);
+    }
+
+    Ok(parsed_version)
+}
+
 /// Splits the information line (either the request or response line) into three parts.
 fn split_info_line(line: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
     if let Some(i) = line.iter().position(|&b| b == b' ') {
@@ -1282,6 +2014,25 @@ fn split_info_line(line: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
     None
 }
 
+/// Trims the request/response line of any trailing spaces or tabs if the strictness is lenient.
+fn trim_info_line<'a>(
+    line: &'a [u8],
+    strictness: Strictness,
+    deviations: &mut Deviations,
+) -> &'a [u8] {
+    if strictness == Strictness::Strict {
+        return line;
+    }
+
+    let trimmed_line = trim_header(line);
+
+    if trimmed_line.len() < line.len() {
+        deviations.insert(Deviation::TrailingWhitespace);
+    }
+
+    trimmed_line
+}
+
 /// Trims the `&[u8]` of any trailing spaces or tabs. It is possible that the header is actually
 /// UTF-8 encoded and this function will create an invalid value, but since header names have to be
 /// ASCII-US encoded, it should not cause any problems.
@@ -1292,3 +2043,298 @@ fn trim_header(mut header: &[u8]) -> &[u8] {
 
     header
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use method::Method;
+
+    fn decode_request(
+        buffer: &str,
+        strictness: Strictness,
+    ) -> RequestParseResult<Request<BytesMut>> {
+        let mut decoder = RequestDecoder::new();
+        decoder.set_strictness(strictness);
+        decoder.decode(buffer).0
+    }
+
+    fn decode_response(
+        buffer: &str,
+        strictness: Strictness,
+    ) -> ResponseParseResult<Response<BytesMut>> {
+        let mut decoder = ResponseDecoder::new();
+        decoder.set_strictness(strictness);
+        decoder.decode(buffer).0
+    }
+
+    fn deviations(deviations: Deviations) -> Vec<Deviation> {
+        deviations.iter().collect()
+    }
+
+    #[test]
+    fn test_request_decoder_bare_line_feed() {
+        let buffer = "OPTIONS * RTSP/2.0\nCSeq: 1\n\n";
+        let request = decode_request(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(request.deviations()),
+            vec![Deviation::BareLineFeed]
+        );
+        assert_eq!(request.headers().get(HeaderName::CSeq).unwrap(), " 1");
+
+        assert!(decode_request(buffer, Strictness::Strict).is_incomplete());
+        assert_eq!(
+            decode_request("OPTIONS * RTSP/2.0\nCSeq: 1\r\n\r\n", Strictness::Strict),
+            ParseResult::Error(InvalidRequest::InvalidVersion)
+        );
+    }
+
+    #[test]
+    fn test_request_decoder_duplicate_content_length() {
+        let buffer = "OPTIONS * RTSP/2.0\r\n\
+                      Content-Length: 1\r\n\
+                      Content-Length: 1\r\n\
+                      \r\n\
+                      A";
+        let request = decode_request(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(request.deviations()),
+            vec![Deviation::DuplicateContentLength]
+        );
+        assert_eq!(request.body(), &BytesMut::from("A"));
+
+        assert_eq!(
+            decode_request(buffer, Strictness::Strict),
+            ParseResult::Error(InvalidRequest::InvalidContentLength)
+        );
+
+        let buffer = "OPTIONS * RTSP/2.0\r\n\
+                      Content-Length: 1\r\n\
+                      Content-Length: 2\r\n\
+                      \r\n\
+                      AB";
+
+        for strictness in &[Strictness::Strict, Strictness::Lenient] {
+            assert_eq!(
+                decode_request(buffer, *strictness),
+                ParseResult::Error(InvalidRequest::InvalidContentLength)
+            );
+        }
+    }
+
+    #[test]
+    fn test_request_decoder_lowercase_version() {
+        let buffer = "OPTIONS * rtsp/2.0\r\n\r\n";
+        let request = decode_request(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(request.deviations()),
+            vec![Deviation::LowercaseVersion]
+        );
+        assert_eq!(request.version(), Version::RTSP20);
+
+        let request = decode_request(buffer, Strictness::Strict).unwrap();
+        assert!(request.deviations().is_empty());
+        assert_eq!(request.version(), Version::RTSP20);
+    }
+
+    #[test]
+    fn test_request_decoder_trailing_whitespace() {
+        let buffer = "OPTIONS * RTSP/2.0 \t\r\n\r\n";
+        let request = decode_request(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(request.deviations()),
+            vec![Deviation::TrailingWhitespace]
+        );
+
+        assert_eq!(
+            decode_request(buffer, Strictness::Strict),
+            ParseResult::Error(InvalidRequest::InvalidVersion)
+        );
+    }
+
+    #[test]
+    fn test_request_decoder_folded_header_bare_line_feed() {
+        let buffer = "OPTIONS * RTSP/2.0\r\n\
+                      Header: a\n b\r\n\
+                      \r\n";
+        let request = decode_request(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(request.deviations()),
+            vec![Deviation::BareLineFeed]
+        );
+        assert_eq!(
+            request
+                .headers()
+                .get(HeaderName::try_from("Header").unwrap())
+                .unwrap(),
+            " a\r\n b"
+        );
+
+        assert_eq!(
+            decode_request(buffer, Strictness::Strict),
+            ParseResult::Error(InvalidRequest::InvalidHeaderValue)
+        );
+    }
+
+    #[test]
+    fn test_request_decoder_deviations_reset() {
+        let mut decoder = RequestDecoder::new();
+        decoder.set_strictness(Strictness::Lenient);
+
+        let request = decoder.decode("OPTIONS * rtsp/2.0\n\n").0.unwrap();
+        assert_eq!(
+            deviations(request.deviations()),
+            vec![Deviation::BareLineFeed, Deviation::LowercaseVersion]
+        );
+
+        let request = decoder.decode("OPTIONS * RTSP/2.0\r\n\r\n").0.unwrap();
+        assert!(request.deviations().is_empty());
+    }
+
+    #[test]
+    fn test_response_decoder_bare_line_feed() {
+        let buffer = "RTSP/2.0 200 OK\nCSeq: 1\n\n";
+        let response = decode_response(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(response.deviations()),
+            vec![Deviation::BareLineFeed]
+        );
+        assert_eq!(response.headers().get(HeaderName::CSeq).unwrap(), " 1");
+
+        assert!(decode_response(buffer, Strictness::Strict).is_incomplete());
+        assert_eq!(
+            decode_response("RTSP/2.0 200 OK\nCSeq: 1\r\n\r\n", Strictness::Strict),
+            ParseResult::Error(InvalidResponse::InvalidReasonPhrase)
+        );
+    }
+
+    #[test]
+    fn test_response_decoder_duplicate_content_length() {
+        let buffer = "RTSP/2.0 200 OK\r\n\
+                      Content-Length: 1\r\n\
+                      Content-Length: 1\r\n\
+                      \r\n\
+                      A";
+        let response = decode_response(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(response.deviations()),
+            vec![Deviation::DuplicateContentLength]
+        );
+        assert_eq!(response.body(), &BytesMut::from("A"));
+
+        assert_eq!(
+            decode_response(buffer, Strictness::Strict),
+            ParseResult::Error(InvalidResponse::InvalidContentLength)
+        );
+
+        let buffer = "RTSP/2.0 200 OK\r\n\
+                      Content-Length: 2\r\n\
+                      Content-Length: 1\r\n\
+                      \r\n\
+                      AB";
+
+        for strictness in &[Strictness::Strict, Strictness::Lenient] {
+            assert_eq!(
+                decode_response(buffer, *strictness),
+                ParseResult::Error(InvalidResponse::InvalidContentLength)
+            );
+        }
+    }
+
+    #[test]
+    fn test_response_decoder_lowercase_version() {
+        let buffer = "rtsp/2.0 200 OK\r\n\r\n";
+        let response = decode_response(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(response.deviations()),
+            vec![Deviation::LowercaseVersion]
+        );
+        assert_eq!(response.version(), Version::RTSP20);
+
+        let response = decode_response(buffer, Strictness::Strict).unwrap();
+        assert!(response.deviations().is_empty());
+        assert_eq!(response.version(), Version::RTSP20);
+    }
+
+    #[test]
+    fn test_response_decoder_missing_reason_phrase() {
+        let buffer = "RTSP/2.0 200\r\n\r\n";
+        let response = decode_response(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(response.deviations()),
+            vec![Deviation::MissingReasonPhrase]
+        );
+        assert_eq!(response.reason().as_str(), "OK");
+
+        assert_eq!(
+            decode_response(buffer, Strictness::Strict),
+            ParseResult::Error(InvalidResponse::InvalidResponseLine)
+        );
+
+        // Without a canonical reason phrase, there is nothing to fall back to.
+
+        assert_eq!(
+            decode_response("RTSP/2.0 299\r\n\r\n", Strictness::Lenient),
+            ParseResult::Error(InvalidResponse::InvalidReasonPhrase)
+        );
+    }
+
+    #[test]
+    fn test_response_decoder_trailing_whitespace() {
+        let buffer = "RTSP/2.0 200 OK \t\r\n\r\n";
+        let response = decode_response(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(response.deviations()),
+            vec![Deviation::TrailingWhitespace]
+        );
+        assert_eq!(response.reason().as_str(), "OK");
+
+        // The reason phrase may contain whitespace, so strict decoders accept it as part of the
+        // reason phrase, which is trimmed.
+
+        let response = decode_response(buffer, Strictness::Strict).unwrap();
+        assert!(response.deviations().is_empty());
+        assert_eq!(response.reason().as_str(), "OK");
+    }
+
+    #[test]
+    fn test_response_decoder_folded_header_bare_line_feed() {
+        let buffer = "RTSP/2.0 200 OK\r\n\
+                      Header: a\n b\r\n\
+                      \r\n";
+        let response = decode_response(buffer, Strictness::Lenient).unwrap();
+        assert_eq!(
+            deviations(response.deviations()),
+            vec![Deviation::BareLineFeed]
+        );
+        assert_eq!(
+            response
+                .headers()
+                .get(HeaderName::try_from("Header").unwrap())
+                .unwrap(),
+            " a\r\n b"
+        );
+
+        assert_eq!(
+            decode_response(buffer, Strictness::Strict),
+            ParseResult::Error(InvalidResponse::InvalidHeaderValue)
+        );
+    }
+
+    #[test]
+    fn test_deviations_equality() {
+        let request = decode_request("OPTIONS * rtsp/2.0\n\n", Strictness::Lenient).unwrap();
+        let expected_request = Request::builder()
+            .method(Method::Options)
+            .uri("*")
+            .build(BytesMut::new())
+            .unwrap();
+        assert!(!request.deviations().is_empty());
+        assert_eq!(request, expected_request);
+
+        let response = decode_response("RTSP/2.0 200\n\n", Strictness::Lenient).unwrap();
+        let expected_response = Response::builder().build(BytesMut::new()).unwrap();
+        assert!(!response.deviations().is_empty());
+        assert_eq!(response, expected_response);
+    }
+}
@@ -1,9 +1,25 @@
 use bytes::BytesMut;
 
 use header::HeaderName;
+use protocol::{InterleavedData, INTERLEAVED_DATA_MAGIC};
 use request::Request;
 use response::Response;
 
+/// Encodes the interleaved binary data frame `data` into `buffer`. The frame consists of the `'$'`
+/// character, the channel identifier, the length of the payload in network byte order, and then the
+/// payload itself.
+pub fn encode_interleaved_data(data: &InterleavedData, buffer: &mut BytesMut) {
+    let payload_size = data.payload().len();
+
+    buffer.extend(&[
+        INTERLEAVED_DATA_MAGIC,
+        data.channel(),
+        (payload_size >> 8) as u8,
+        payload_size as u8,
+    ]);
+    buffer.extend(data.payload());
+}
+
 /// Encodes `request` into `buffer`. This will set the `Content-Length` header to the size of the
 /// body but will not modify the original request. Even if a content length header was already set,
 /// it will be ignored.
@@ -0,0 +1,100 @@
+//! Interleaved Binary Data
+//!
+//! This module contains the [`InterleavedData`] type which represents a single frame of binary data
+//! that is interleaved with RTSP messages on the same connection as described by
+//! [[RFC7826, Section 14](https://tools.ietf.org/html/rfc7826#section-14)]. This is typically used
+//! to send RTP and RTCP packets over the RTSP control connection when the transport negotiated in
+//! a `SETUP` request uses the `interleaved` parameter.
+
+use bytes::BytesMut;
+use std::error::Error;
+use std::fmt;
+
+/// The byte that all interleaved binary data frames start with.
+pub const INTERLEAVED_DATA_MAGIC: u8 = b'$';
+
+/// The size of the frame header that precedes the payload of an interleaved binary data frame. The
+/// header consists of the `'$'` character, a one byte channel identifier, and a two byte payload
+/// length in network byte order.
+pub const INTERLEAVED_DATA_HEADER_SIZE: usize = 4;
+
+/// The maximum size of the payload of a single interleaved binary data frame.
+pub const MAX_INTERLEAVED_DATA_SIZE: usize = 0xFFFF;
+
+/// A single frame of interleaved binary data.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct InterleavedData {
+    /// The channel identifier that the payload was sent on.
+    channel: u8,
+
+    /// The payload of the frame.
+    payload: BytesMut,
+}
+
+impl InterleavedData {
+    /// Constructs a new interleaved binary data frame.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the payload is larger than [`MAX_INTERLEAVED_DATA_SIZE`] since
+    /// the length of the payload could not be encoded in the frame header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate bytes;
+    /// # extern crate rtsp;
+    /// #
+    /// use bytes::BytesMut;
+    ///
+    /// use rtsp::protocol::InterleavedData;
+    ///
+    /// # fn main() {
+    /// let data = InterleavedData::new(0, BytesMut::from("RTP")).unwrap();
+    /// assert_eq!(data.channel(), 0);
+    /// assert_eq!(data.payload(), &BytesMut::from("RTP"));
+    ///
+    /// assert!(InterleavedData::new(0, BytesMut::from(vec![0; 0x10000])).is_err());
+    /// # }
+    /// ```
+    pub fn new(channel: u8, payload: BytesMut) -> Result<Self, InvalidInterleavedData> {
+        if payload.len() > MAX_INTERLEAVED_DATA_SIZE {
+            return Err(InvalidInterleavedData);
+        }
+
+        Ok(InterleavedData { channel, payload })
+    }
+
+    /// Returns the channel identifier of this frame.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    /// Consumes the frame, returning the payload.
+    pub fn into_payload(self) -> BytesMut {
+        self.payload
+    }
+
+    /// Returns an immutable reference to the payload of this frame.
+    pub fn payload(&self) -> &BytesMut {
+        &self.payload
+    }
+}
+
+/// A possible error value when constructing an [`InterleavedData`].
+///
+/// This error indicates that the payload was larger than [`MAX_INTERLEAVED_DATA_SIZE`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InvalidInterleavedData;
+
+impl fmt::Display for InvalidInterleavedData {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for InvalidInterleavedData {
+    fn description(&self) -> &str {
+        "invalid interleaved data - payload too large"
+    }
+}
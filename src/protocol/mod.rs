@@ -2,6 +2,7 @@ pub mod codec;
 pub mod connection;
 pub mod decoder;
 pub mod encoder;
+pub mod interleaved;
 pub mod service;
 
 pub use self::codec::{
@@ -11,15 +12,22 @@ pub use self::codec::{
     ResponseResult,
 };
 pub use self::connection::{
-    Config, ConfigBuilder, ConfigBuilderError, Connection, ConnectionHandle, RequestOptions,
-    RequestOptionsBuilder, RequestOptionsBuilderError, SendRequestFuture, ShutdownType,
-    DEFAULT_DECODE_TIMEOUT_DURATION, DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_DURATION,
-    DEFAULT_REQUEST_BUFFER_SIZE, DEFAULT_REQUEST_MAX_TIMEOUT_DURATION,
-    DEFAULT_REQUEST_TIMEOUT_DURATION,
+    Config, ConfigBuilder, ConfigBuilderError, Connection, ConnectionHandle,
+    ConnectionShutdownReceiver, InterleavedChannel, RedirectPolicy, RequestConcurrency,
+    RequestOptions, RequestOptionsBuilder, RequestOptionsBuilderError, SendRequestFuture,
+    ShutdownType, DEFAULT_DECODE_TIMEOUT_DURATION, DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_DURATION,
+    DEFAULT_INTERLEAVED_BUFFER_SIZE, DEFAULT_REQUEST_BUFFER_SIZE,
+    DEFAULT_REQUEST_MAX_TIMEOUT_DURATION, DEFAULT_REQUEST_TIMEOUT_DURATION,
 };
 pub use self::decoder::{
+    DecoderLimits, DecoderLimitsBuilder, DecoderLimitsBuilderError, Deviation, Deviations,
     InvalidRequest, InvalidResponse, ParseResult, ParseState, RequestDecoder, RequestParseResult,
-    ResponseDecoder, ResponseParseResult,
+    ResponseDecoder, ResponseParseResult, Strictness, DEFAULT_MAX_BODY_SIZE,
+    DEFAULT_MAX_HEADERS_SIZE, DEFAULT_MAX_HEADER_COUNT, DEFAULT_MAX_LINE_LENGTH,
 };
-pub use self::encoder::{encode_request, encode_response};
-pub use self::service::{EmptyService, Service};
+pub use self::encoder::{encode_interleaved_data, encode_request, encode_response};
+pub use self::interleaved::{
+    InterleavedData, InvalidInterleavedData, INTERLEAVED_DATA_HEADER_SIZE, INTERLEAVED_DATA_MAGIC,
+    MAX_INTERLEAVED_DATA_SIZE,
+};
+pub use self::service::{EmptyService, NewService, Service};
@@ -27,3 +27,23 @@ impl Service for EmptyService {
         Box::new(future::empty())
     }
 }
+
+/// A factory for creating new instances of a [`Service`], such as one for every connection that is
+/// accepted by a server.
+pub trait NewService {
+    type Service: Service;
+
+    fn new_service(&self) -> io::Result<Self::Service>;
+}
+
+impl<F, S> NewService for F
+where
+    F: Fn() -> io::Result<S>,
+    S: Service,
+{
+    type Service = S;
+
+    fn new_service(&self) -> io::Result<Self::Service> {
+        self()
+    }
+}
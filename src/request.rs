@@ -11,6 +11,7 @@ use std::mem::replace;
 
 use header::{HeaderMap, HeaderName, HeaderValue, TypedHeader, TypedHeaderMap};
 use method::Method;
+use protocol::Deviations;
 use uri::RequestURIField;
 use version::Version;
 
@@ -21,11 +22,12 @@ use version::Version;
 ///
 /// This struct implements `PartialEq` but care should be taken when using it. Two requests can
 /// be semantically equivalent but not be byte by byte. This will mainly occur due to extra spaces
-/// in headers. Even when using a typed request, the same problem will occur.
+/// in headers. Even when using a typed request, the same problem will occur. The deviations that
+/// were tolerated while decoding the request are not compared.
 ///
 /// Note that it is not necessary to ever set the `Content-Length` header as it will be forcibly
 /// set during encoding even if it is already present.
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone)]
 pub struct Request<B, H = HeaderMap>
 where
     H: Default,
@@ -33,6 +35,9 @@ where
     /// The body component of the request. This is generic to support arbitrary content types.
     body: B,
 
+    /// The deviations from the syntax that were tolerated while decoding the request.
+    deviations: Deviations,
+
     /// A header map that will either be `HeaderMap` or `TypedHeaderMap`.
     headers: H,
 
@@ -63,6 +68,20 @@ impl Request<()> {
         Builder::new()
     }
 
+    /// A convenience function for quickly creating a new builder with the method set to
+    /// `"ANNOUNCE"` and the request URI field set to `uri`.
+    ///
+    /// This method is only used with RTSP 1.0, typically to publish a session description before
+    /// recording.
+    pub fn announce<T>(uri: T) -> Builder
+    where
+        RequestURIField: TryFrom<T>,
+    {
+        let mut b = Builder::new();
+        b.method(Method::Announce).uri(uri);
+        b
+    }
+
     /// A convenience function for quickly creating a new builder with the method set to
     /// `"DESCRIBE"` and the request URI field set to `uri`.
     pub fn describe<T>(uri: T) -> Builder
@@ -129,6 +148,19 @@ impl Request<()> {
         b
     }
 
+    /// A convenience function for quickly creating a new builder with the method set to `"RECORD"`
+    /// and the request URI set to `uri`.
+    ///
+    /// This method is only used with RTSP 1.0.
+    pub fn record<T>(uri: T) -> Builder
+    where
+        RequestURIField: TryFrom<T>,
+    {
+        let mut b = Builder::new();
+        b.method(Method::Record).uri(uri);
+        b
+    }
+
     /// A convenience function for quickly creating a new builder with the method set to
     /// `"REDIRECT"` and the request URI set to `uri`.
     pub fn redirect<T>(uri: T) -> Builder
@@ -189,6 +221,18 @@ where
         &mut self.body
     }
 
+    /// Returns a copy of the deviations from the syntax that were tolerated while decoding the
+    /// request. This is always empty for requests that were not decoded by a lenient decoder.
+    pub fn deviations(&self) -> Deviations {
+        self.deviations
+    }
+
+    /// Returns a mutable reference to the deviations from the syntax that were tolerated while
+    /// decoding the request.
+    pub fn deviations_mut(&mut self) -> &mut Deviations {
+        &mut self.deviations
+    }
+
     /// Returns an immutable reference to the request header map.
     pub fn headers(&self) -> &H {
         &self.headers
@@ -206,6 +250,7 @@ where
     {
         Request {
             body: mapper(self.body),
+            deviations: self.deviations,
             headers: self.headers,
             method: self.method,
             uri: self.uri,
@@ -249,6 +294,7 @@ impl<B> From<Request<B>> for Request<B, TypedHeaderMap> {
     fn from(value: Request<B>) -> Request<B, TypedHeaderMap> {
         Request {
             body: value.body,
+            deviations: value.deviations,
             headers: value.headers.into(),
             method: value.method,
             uri: value.uri,
@@ -262,6 +308,7 @@ impl<B> From<Request<B, TypedHeaderMap>> for Request<B> {
     fn from(value: Request<B, TypedHeaderMap>) -> Request<B> {
         Request {
             body: value.body,
+            deviations: value.deviations,
             headers: value.headers.into(),
             method: value.method,
             uri: value.uri,
@@ -270,6 +317,27 @@ impl<B> From<Request<B, TypedHeaderMap>> for Request<B> {
     }
 }
 
+impl<B, H> PartialEq for Request<B, H>
+where
+    B: PartialEq,
+    H: Default + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.body == other.body
+            && self.headers == other.headers
+            && self.method == other.method
+            && self.uri == other.uri
+            && self.version == other.version
+    }
+}
+
+impl<B, H> Eq for Request<B, H>
+where
+    B: Eq,
+    H: Default + Eq,
+{
+}
+
 impl<B, H> fmt::Debug for Request<B, H>
 where
     B: fmt::Debug,
@@ -357,6 +425,7 @@ where
             if let Some(uri) = replace(&mut self.uri, None) {
                 Ok(Request {
                     body,
+                    deviations: Deviations::new(),
                     headers: replace(&mut self.headers, H::default()),
                     method,
                     uri,
@@ -436,8 +505,8 @@ where
     ///
     /// # Errors
     ///
-    /// An error will be stored if the given version is an invalid or unsupported `Version`.
-    /// Currently the only supported version is RTSP/2.0.
+    /// An error will be stored if the given version is an invalid or unsupported `Version`. Both
+    /// RTSP/1.0 and RTSP/2.0 are supported.
     ///
     /// # Examples
     ///
@@ -456,8 +525,7 @@ where
         Version: TryFrom<T>,
     {
         match Version::try_from(version) {
-            Ok(version) if version == Version::RTSP20 => self.version = version,
-            Ok(_) => self.error = Some(BuilderError::UnsupportedVersion),
+            Ok(version) => self.version = version,
             Err(_) => self.error = Some(BuilderError::InvalidVersion),
         }
 
@@ -638,8 +706,7 @@ pub enum BuilderError {
     /// This error indicates that a request URI was not specified.
     MissingRequestURI,
 
-    /// This error indicates that the version was unsupported. The only supported version is
-    /// RTSP 2.0.
+    /// This error indicates that the version was unsupported.
     UnsupportedVersion,
 }
 
@@ -10,6 +10,7 @@ use std::fmt;
 use std::mem::replace;
 
 use header::{HeaderMap, HeaderName, HeaderValue, TypedHeader, TypedHeaderMap};
+use protocol::Deviations;
 use reason::ReasonPhrase;
 use status::StatusCode;
 use version::Version;
@@ -21,11 +22,12 @@ use version::Version;
 ///
 /// This struct implements `PartialEq` but care should be taken when using it. Two responses can
 /// be semantically equivalent but not be byte by byte. This will mainly occur due to extra spaces
-/// in headers. Even when using a typed response, the same problem will occur.
+/// in headers. Even when using a typed response, the same problem will occur. The deviations that
+/// were tolerated while decoding the response are not compared.
 ///
 /// Note that it is not necessary to ever set the `Content-Length` header as it will be forcibly
 /// set during encoding even if it is already present.
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone)]
 pub struct Response<B, H = HeaderMap>
 where
     H: Default,
@@ -33,6 +35,9 @@ where
     /// The body component of the response. This is generic to support arbitrary content types.
     body: B,
 
+    /// The deviations from the syntax that were tolerated while decoding the response.
+    deviations: Deviations,
+
     /// Specifies a reason phrase for the given status code. RTSP allows agents to give custom
     /// reason phrases and even recommends it in specific cases.
     reason_phrase: ReasonPhrase,
@@ -74,6 +79,18 @@ where
         &mut self.body
     }
 
+    /// Returns a copy of the deviations from the syntax that were tolerated while decoding the
+    /// response. This is always empty for responses that were not decoded by a lenient decoder.
+    pub fn deviations(&self) -> Deviations {
+        self.deviations
+    }
+
+    /// Returns a mutable reference to the deviations from the syntax that were tolerated while
+    /// decoding the response.
+    pub fn deviations_mut(&mut self) -> &mut Deviations {
+        &mut self.deviations
+    }
+
     /// Returns an immutable reference to the response header map.
     pub fn headers(&self) -> &H {
         &self.headers
@@ -91,6 +108,7 @@ where
     {
         Response {
             body: mapper(self.body),
+            deviations: self.deviations,
             headers: self.headers,
             reason_phrase: self.reason_phrase,
             status_code: self.status_code,
@@ -134,6 +152,7 @@ impl<B> From<Response<B>> for Response<B, TypedHeaderMap> {
     fn from(value: Response<B>) -> Response<B, TypedHeaderMap> {
         Response {
             body: value.body,
+            deviations: value.deviations,
             headers: value.headers.into(),
             reason_phrase: value.reason_phrase,
             status_code: value.status_code,
@@ -147,6 +166,7 @@ impl<B> From<Response<B, TypedHeaderMap>> for Response<B> {
     fn from(value: Response<B, TypedHeaderMap>) -> Response<B> {
         Response {
             body: value.body,
+            deviations: value.deviations,
             headers: value.headers.into(),
             reason_phrase: value.reason_phrase,
             status_code: value.status_code,
@@ -155,6 +175,27 @@ impl<B> From<Response<B, TypedHeaderMap>> for Response<B> {
     }
 }
 
+impl<B, H> PartialEq for Response<B, H>
+where
+    B: PartialEq,
+    H: Default + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.body == other.body
+            && self.headers == other.headers
+            && self.reason_phrase == other.reason_phrase
+            && self.status_code == other.status_code
+            && self.version == other.version
+    }
+}
+
+impl<B, H> Eq for Response<B, H>
+where
+    B: Eq,
+    H: Default + Eq,
+{
+}
+
 impl<B, H> fmt::Debug for Response<B, H>
 where
     B: fmt::Debug,
@@ -248,6 +289,7 @@ where
 
         Ok(Response {
             body,
+            deviations: Deviations::new(),
             headers: replace(&mut self.headers, H::default()),
             reason_phrase,
             status_code: self.status_code,
@@ -327,8 +369,8 @@ where
     ///
     /// # Errors
     ///
-    /// An error will be stored if the given version is an invalid or unsupported `Version`.
-    /// Currently the only supported version is RTSP/2.0.
+    /// An error will be stored if the given version is an invalid or unsupported `Version`. Both
+    /// RTSP/1.0 and RTSP/2.0 are supported.
     ///
     /// # Examples
     ///
@@ -345,8 +387,7 @@ where
         Version: TryFrom<T>,
     {
         match Version::try_from(version) {
-            Ok(version) if version == Version::RTSP20 => self.version = version,
-            Ok(_) => self.error = Some(BuilderError::UnsupportedVersion),
+            Ok(version) => self.version = version,
             Err(_) => self.error = Some(BuilderError::InvalidVersion),
         }
 
@@ -542,8 +583,7 @@ pub enum BuilderError {
     /// be specified.
     MissingReasonPhrase,
 
-    /// This error indicates that the version was unsupported. The only supported version is
-    /// RTSP 2.0.
+    /// This error indicates that the version was unsupported.
     UnsupportedVersion,
 }
 
@@ -0,0 +1,944 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str;
+
+use header::types::Range;
+use sdp::fields::is_text;
+use sdp::{
+    resolve_control, Attribute, Bandwidth, Connection, FormatParameters, InvalidField, Media,
+    Origin, ParseError, ParseErrorKind, RTPMap, Timing,
+};
+use uri::{InvalidRequestURI, RequestURI};
+
+/// A session description as described by [RFC8866](https://tools.ietf.org/html/rfc8866).
+///
+/// A session description consists of session-level fields followed by zero or more media
+/// descriptions. With RTSP, it is usually found in the body of a `DESCRIBE` response with the
+/// content type `"application/sdp"`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SessionDescription {
+    attributes: Vec<Attribute>,
+    bandwidths: Vec<Bandwidth>,
+    connection: Option<Connection>,
+    email_addresses: Vec<String>,
+    encryption_key: Option<String>,
+    information: Option<String>,
+    media_descriptions: Vec<MediaDescription>,
+    origin: Origin,
+    phone_numbers: Vec<String>,
+    session_name: String,
+    time_zones: Option<String>,
+    timings: Vec<Timing>,
+    uri: Option<String>,
+}
+
+impl SessionDescription {
+    /// Constructs a new session description with a single unbounded `t=0 0` timing field.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the session name is empty or contains a NUL, CR, or LF
+    /// character. A session without a meaningful name should use `" "`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::sdp::{Attribute, Origin, SessionDescription};
+    ///
+    /// let origin = Origin::new("-", "1", "1", "IN", "IP4", "127.0.0.1").unwrap();
+    /// let mut description = SessionDescription::new(origin, "Example").unwrap();
+    /// description
+    ///     .attributes_mut()
+    ///     .push(Attribute::new("control", Some("*")).unwrap());
+    ///
+    /// assert_eq!(
+    ///     description.to_string(),
+    ///     "v=0\r\no=- 1 1 IN IP4 127.0.0.1\r\ns=Example\r\nt=0 0\r\na=control:*\r\n"
+    /// );
+    /// ```
+    pub fn new(origin: Origin, session_name: &str) -> Result<Self, InvalidField> {
+        if !is_text(session_name) {
+            return Err(InvalidField);
+        }
+
+        Ok(SessionDescription {
+            attributes: Vec::new(),
+            bandwidths: Vec::new(),
+            connection: None,
+            email_addresses: Vec::new(),
+            encryption_key: None,
+            information: None,
+            media_descriptions: Vec::new(),
+            origin,
+            phone_numbers: Vec::new(),
+            session_name: session_name.to_string(),
+            time_zones: None,
+            timings: vec![Timing::new(0, 0)],
+            uri: None,
+        })
+    }
+
+    /// Adds the value of an `e=` field to the session description.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn add_email_address(&mut self, email_address: &str) -> Result<(), InvalidField> {
+        self.email_addresses.push(validate_text(email_address)?);
+        Ok(())
+    }
+
+    /// Adds the value of a `p=` field to the session description.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn add_phone_number(&mut self, phone_number: &str) -> Result<(), InvalidField> {
+        self.phone_numbers.push(validate_text(phone_number)?);
+        Ok(())
+    }
+
+    /// Returns the first session-level attribute with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        find_attribute(&self.attributes, name)
+    }
+
+    pub fn attributes(&self) -> &Vec<Attribute> {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        &mut self.attributes
+    }
+
+    pub fn bandwidths(&self) -> &Vec<Bandwidth> {
+        &self.bandwidths
+    }
+
+    pub fn bandwidths_mut(&mut self) -> &mut Vec<Bandwidth> {
+        &mut self.bandwidths
+    }
+
+    pub fn connection(&self) -> Option<&Connection> {
+        self.connection.as_ref()
+    }
+
+    pub fn connection_mut(&mut self) -> &mut Option<Connection> {
+        &mut self.connection
+    }
+
+    /// Returns the value of the session-level `a=control` attribute, if present.
+    pub fn control(&self) -> Option<&str> {
+        self.attribute("control")
+            .and_then(|attribute| attribute.value())
+    }
+
+    /// Returns the aggregate control URI of the session by resolving the session-level
+    /// `a=control` attribute against the given base URI. If there is no such attribute, the base
+    /// URI itself is used.
+    ///
+    /// The base URI is usually found using [`base_uri`].
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the resolved URI is not a valid request URI.
+    pub fn control_uri(&self, base: &RequestURI) -> Result<RequestURI, InvalidRequestURI> {
+        match self.control() {
+            Some(control) => resolve_control(base, control),
+            None => Ok(base.clone()),
+        }
+    }
+
+    pub fn email_addresses(&self) -> &[String] {
+        &self.email_addresses
+    }
+
+    pub fn encryption_key(&self) -> Option<&str> {
+        self.encryption_key.as_ref().map(|key| key.as_str())
+    }
+
+    pub fn information(&self) -> Option<&str> {
+        self.information
+            .as_ref()
+            .map(|information| information.as_str())
+    }
+
+    pub fn media_descriptions(&self) -> &Vec<MediaDescription> {
+        &self.media_descriptions
+    }
+
+    pub fn media_descriptions_mut(&mut self) -> &mut Vec<MediaDescription> {
+        &mut self.media_descriptions
+    }
+
+    pub fn origin(&self) -> &Origin {
+        &self.origin
+    }
+
+    pub fn origin_mut(&mut self) -> &mut Origin {
+        &mut self.origin
+    }
+
+    pub fn phone_numbers(&self) -> &[String] {
+        &self.phone_numbers
+    }
+
+    /// Returns the value of the session-level `a=range` attribute, if present and valid.
+    pub fn range(&self) -> Option<Range> {
+        parse_range(&self.attributes)
+    }
+
+    pub fn session_name(&self) -> &str {
+        &self.session_name
+    }
+
+    /// Sets the value of the `k=` field. This field is obsolete and should not be used by new
+    /// session descriptions.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn set_encryption_key(&mut self, encryption_key: Option<&str>) -> Result<(), InvalidField> {
+        self.encryption_key = validate_optional_text(encryption_key)?;
+        Ok(())
+    }
+
+    /// Sets the value of the `i=` field.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn set_information(&mut self, information: Option<&str>) -> Result<(), InvalidField> {
+        self.information = validate_optional_text(information)?;
+        Ok(())
+    }
+
+    /// Sets the value of the `s=` field.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn set_session_name(&mut self, session_name: &str) -> Result<(), InvalidField> {
+        self.session_name = validate_text(session_name)?;
+        Ok(())
+    }
+
+    /// Sets the value of the `z=` field.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn set_time_zones(&mut self, time_zones: Option<&str>) -> Result<(), InvalidField> {
+        self.time_zones = validate_optional_text(time_zones)?;
+        Ok(())
+    }
+
+    /// Sets the value of the `u=` field.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn set_uri(&mut self, uri: Option<&str>) -> Result<(), InvalidField> {
+        self.uri = validate_optional_text(uri)?;
+        Ok(())
+    }
+
+    pub fn time_zones(&self) -> Option<&str> {
+        self.time_zones
+            .as_ref()
+            .map(|time_zones| time_zones.as_str())
+    }
+
+    pub fn timings(&self) -> &Vec<Timing> {
+        &self.timings
+    }
+
+    /// Returns a mutable reference to the timing fields. Since at least one timing field is
+    /// required, an empty list is serialized as `t=0 0`.
+    pub fn timings_mut(&mut self) -> &mut Vec<Timing> {
+        &mut self.timings
+    }
+
+    pub fn uri(&self) -> Option<&str> {
+        self.uri.as_ref().map(|uri| uri.as_str())
+    }
+}
+
+impl fmt::Display for SessionDescription {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write_line(formatter, 'v', "0")?;
+        write_line(formatter, 'o', &self.origin)?;
+        write_line(formatter, 's', &self.session_name)?;
+
+        if let Some(ref information) = self.information {
+            write_line(formatter, 'i', information)?;
+        }
+
+        if let Some(ref uri) = self.uri {
+            write_line(formatter, 'u', uri)?;
+        }
+
+        for email_address in self.email_addresses.iter() {
+            write_line(formatter, 'e', email_address)?;
+        }
+
+        for phone_number in self.phone_numbers.iter() {
+            write_line(formatter, 'p', phone_number)?;
+        }
+
+        if let Some(ref connection) = self.connection {
+            write_line(formatter, 'c', connection)?;
+        }
+
+        for bandwidth in self.bandwidths.iter() {
+            write_line(formatter, 'b', bandwidth)?;
+        }
+
+        if self.timings.is_empty() {
+            write_line(formatter, 't', Timing::new(0, 0))?;
+        }
+
+        for timing in self.timings.iter() {
+            write_line(formatter, 't', timing)?;
+
+            for repeat_time in timing.repeat_times() {
+                write_line(formatter, 'r', repeat_time)?;
+            }
+        }
+
+        if let Some(ref time_zones) = self.time_zones {
+            write_line(formatter, 'z', time_zones)?;
+        }
+
+        if let Some(ref encryption_key) = self.encryption_key {
+            write_line(formatter, 'k', encryption_key)?;
+        }
+
+        for attribute in self.attributes.iter() {
+            write_line(formatter, 'a', attribute)?;
+        }
+
+        for media_description in self.media_descriptions.iter() {
+            write!(formatter, "{}", media_description)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for SessionDescription {
+    type Error = ParseError;
+
+    /// Parses a session description from the given bytes, such as the body of a `DESCRIBE`
+    /// response.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the bytes are not valid UTF-8 or if the session description is
+    /// invalid.
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let value = str::from_utf8(value)
+            .map_err(|_| ParseError::new(ParseErrorKind::InvalidEncoding, 1))?;
+        SessionDescription::try_from(value)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SessionDescription {
+    type Error = ParseError;
+
+    /// Parses a session description.
+    ///
+    /// The parser is strict in that all fields must appear in the order required by the
+    /// specification, and unknown field types are rejected. Lines may be terminated by either
+    /// CRLF or LF, and the terminator of the last line may be omitted.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the session description is invalid. The error contains the
+    /// line at which the problem was found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::sdp::{ParseErrorKind, SessionDescription};
+    ///
+    /// let description = SessionDescription::try_from(
+    ///     "v=0\r\n\
+    ///      o=- 1 1 IN IP4 127.0.0.1\r\n\
+    ///      s=Example\r\n\
+    ///      c=IN IP4 0.0.0.0\r\n\
+    ///      t=0 0\r\n\
+    ///      a=control:*\r\n\
+    ///      m=video 0 RTP/AVP 96\r\n\
+    ///      a=rtpmap:96 H264/90000\r\n\
+    ///      a=control:trackID=1\r\n",
+    /// ).unwrap();
+    /// assert_eq!(description.session_name(), "Example");
+    /// assert_eq!(description.media_descriptions()[0].control(), Some("trackID=1"));
+    ///
+    /// let error = SessionDescription::try_from("v=0\r\ns=Example\r\n").unwrap_err();
+    /// assert_eq!(error.kind(), ParseErrorKind::MissingField('o'));
+    /// assert_eq!(error.line(), 2);
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Parser::new(value)?.parse()
+    }
+}
+
+/// A media description within a session description, starting with an `m=` field.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MediaDescription {
+    attributes: Vec<Attribute>,
+    bandwidths: Vec<Bandwidth>,
+    connections: Vec<Connection>,
+    encryption_key: Option<String>,
+    information: Option<String>,
+    media: Media,
+}
+
+impl MediaDescription {
+    /// Constructs a new media description from the given media field.
+    pub fn new(media: Media) -> Self {
+        MediaDescription {
+            attributes: Vec::new(),
+            bandwidths: Vec::new(),
+            connections: Vec::new(),
+            encryption_key: None,
+            information: None,
+            media,
+        }
+    }
+
+    /// Returns the first media-level attribute with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        find_attribute(&self.attributes, name)
+    }
+
+    pub fn attributes(&self) -> &Vec<Attribute> {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        &mut self.attributes
+    }
+
+    pub fn bandwidths(&self) -> &Vec<Bandwidth> {
+        &self.bandwidths
+    }
+
+    pub fn bandwidths_mut(&mut self) -> &mut Vec<Bandwidth> {
+        &mut self.bandwidths
+    }
+
+    pub fn connections(&self) -> &Vec<Connection> {
+        &self.connections
+    }
+
+    pub fn connections_mut(&mut self) -> &mut Vec<Connection> {
+        &mut self.connections
+    }
+
+    /// Returns the value of the media-level `a=control` attribute, if present.
+    pub fn control(&self) -> Option<&str> {
+        self.attribute("control")
+            .and_then(|attribute| attribute.value())
+    }
+
+    /// Returns the control URI of the media by resolving the media-level `a=control` attribute
+    /// against the given base URI. If there is no such attribute, `None` is returned, in which
+    /// case the media can only be controlled using the aggregate control URI of the session.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the resolved URI is not a valid request URI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::RequestURI;
+    /// use rtsp::sdp::{Attribute, Media, MediaDescription};
+    ///
+    /// let base = RequestURI::try_from("rtsp://example.com/movie/").unwrap();
+    /// let mut media_description =
+    ///     MediaDescription::new(Media::new("audio", 0, "RTP/AVP", &["0"]).unwrap());
+    /// assert_eq!(media_description.control_uri(&base), Ok(None));
+    ///
+    /// media_description
+    ///     .attributes_mut()
+    ///     .push(Attribute::new("control", Some("audio")).unwrap());
+    /// assert_eq!(
+    ///     media_description.control_uri(&base).unwrap().unwrap().as_str(),
+    ///     "rtsp://example.com/movie/audio"
+    /// );
+    /// ```
+    pub fn control_uri(&self, base: &RequestURI) -> Result<Option<RequestURI>, InvalidRequestURI> {
+        match self.control() {
+            Some(control) => resolve_control(base, control).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn encryption_key(&self) -> Option<&str> {
+        self.encryption_key.as_ref().map(|key| key.as_str())
+    }
+
+    /// Returns the format parameters of the `a=fmtp` attribute for the given format, if present
+    /// and valid.
+    pub fn format_parameters(&self, format: &str) -> Option<FormatParameters> {
+        self.attributes
+            .iter()
+            .filter(|attribute| attribute.name() == "fmtp")
+            .filter_map(|attribute| attribute.value())
+            .filter_map(|value| FormatParameters::try_from(value).ok())
+            .find(|parameters| parameters.format() == format)
+    }
+
+    pub fn information(&self) -> Option<&str> {
+        self.information
+            .as_ref()
+            .map(|information| information.as_str())
+    }
+
+    pub fn media(&self) -> &Media {
+        &self.media
+    }
+
+    pub fn media_mut(&mut self) -> &mut Media {
+        &mut self.media
+    }
+
+    /// Returns the value of the media-level `a=range` attribute, if present and valid.
+    pub fn range(&self) -> Option<Range> {
+        parse_range(&self.attributes)
+    }
+
+    /// Returns the RTP map of the `a=rtpmap` attribute for the given payload type, if present and
+    /// valid.
+    pub fn rtp_map(&self, payload_type: u8) -> Option<RTPMap> {
+        self.rtp_maps()
+            .into_iter()
+            .find(|rtp_map| rtp_map.payload_type() == payload_type)
+    }
+
+    /// Returns the RTP maps of all valid `a=rtpmap` attributes.
+    pub fn rtp_maps(&self) -> Vec<RTPMap> {
+        self.attributes
+            .iter()
+            .filter(|attribute| attribute.name() == "rtpmap")
+            .filter_map(|attribute| attribute.value())
+            .filter_map(|value| RTPMap::try_from(value).ok())
+            .collect()
+    }
+
+    /// Sets the value of the `k=` field. This field is obsolete and should not be used by new
+    /// session descriptions.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn set_encryption_key(&mut self, encryption_key: Option<&str>) -> Result<(), InvalidField> {
+        self.encryption_key = validate_optional_text(encryption_key)?;
+        Ok(())
+    }
+
+    /// Sets the value of the `i=` field.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value is empty or contains a NUL, CR, or LF character.
+    pub fn set_information(&mut self, information: Option<&str>) -> Result<(), InvalidField> {
+        self.information = validate_optional_text(information)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for MediaDescription {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write_line(formatter, 'm', &self.media)?;
+
+        if let Some(ref information) = self.information {
+            write_line(formatter, 'i', information)?;
+        }
+
+        for connection in self.connections.iter() {
+            write_line(formatter, 'c', connection)?;
+        }
+
+        for bandwidth in self.bandwidths.iter() {
+            write_line(formatter, 'b', bandwidth)?;
+        }
+
+        if let Some(ref encryption_key) = self.encryption_key {
+            write_line(formatter, 'k', encryption_key)?;
+        }
+
+        for attribute in self.attributes.iter() {
+            write_line(formatter, 'a', attribute)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A parser over the lines of a session description that enforces the field order given by the
+/// specification.
+struct Parser<'a> {
+    lines: Vec<(char, &'a str)>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Splits the session description into its `<type>=<value>` lines.
+    fn new(value: &'a str) -> Result<Self, ParseError> {
+        let value = if value.ends_with('\n') {
+            &value[..value.len() - 1]
+        } else {
+            value
+        };
+        let mut lines = Vec::new();
+
+        for (index, line) in value.split('\n').enumerate() {
+            let line = if line.ends_with('\r') {
+                &line[..line.len() - 1]
+            } else {
+                line
+            };
+            let bytes = line.as_bytes();
+
+            if bytes.len() < 2 || !bytes[0].is_ascii_lowercase() || bytes[1] != b'=' {
+                return Err(ParseError::new(ParseErrorKind::InvalidLine, index + 1));
+            }
+
+            lines.push((bytes[0] as char, &line[2..]));
+        }
+
+        Ok(Parser { lines, position: 0 })
+    }
+
+    /// Returns an error of the given kind for the current line.
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(kind, self.position + 1)
+    }
+
+    /// Consumes the current line if it is of the given type, returning its value.
+    fn next_if(&mut self, field_type: char) -> Option<&'a str> {
+        match self.lines.get(self.position) {
+            Some(&(current_type, value)) if current_type == field_type => {
+                self.position += 1;
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Consumes the current line if it is of the given type, converting its value using the given
+    /// function.
+    fn next_field_if<F, T>(&mut self, field_type: char, f: F) -> Result<Option<T>, ParseError>
+    where
+        F: FnOnce(&'a str) -> Result<T, InvalidField>,
+    {
+        match self.next_if(field_type) {
+            Some(value) => f(value).map(Some).map_err(|_| {
+                ParseError::new(ParseErrorKind::InvalidField(field_type), self.position)
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Consumes all consecutive lines of the given type, converting their values using the given
+    /// function.
+    fn next_fields<F, T>(&mut self, field_type: char, mut f: F) -> Result<Vec<T>, ParseError>
+    where
+        F: FnMut(&'a str) -> Result<T, InvalidField>,
+    {
+        let mut fields = Vec::new();
+
+        while let Some(field) = self.next_field_if(field_type, &mut f)? {
+            fields.push(field);
+        }
+
+        Ok(fields)
+    }
+
+    /// Consumes the current line which must be of the given type, converting its value using the
+    /// given function.
+    fn next_required_field<F, T>(&mut self, field_type: char, f: F) -> Result<T, ParseError>
+    where
+        F: FnOnce(&'a str) -> Result<T, InvalidField>,
+    {
+        self.next_field_if(field_type, f)?
+            .ok_or_else(|| self.error(ParseErrorKind::MissingField(field_type)))
+    }
+
+    fn parse(mut self) -> Result<SessionDescription, ParseError> {
+        match self.next_if('v') {
+            Some("0") => (),
+            Some(_) => return Err(ParseError::new(ParseErrorKind::InvalidVersion, 1)),
+            None => return Err(self.error(ParseErrorKind::MissingField('v'))),
+        }
+
+        let origin = self.next_required_field('o', Origin::try_from)?;
+        let session_name = self.next_required_field('s', validate_text)?;
+        let information = self.next_field_if('i', validate_text)?;
+        let uri = self.next_field_if('u', validate_text)?;
+        let email_addresses = self.next_fields('e', validate_text)?;
+        let phone_numbers = self.next_fields('p', validate_text)?;
+        let connection = self.next_field_if('c', Connection::try_from)?;
+        let bandwidths = self.next_fields('b', Bandwidth::try_from)?;
+        let mut timings = Vec::new();
+
+        while let Some(mut timing) = self.next_field_if('t', Timing::try_from)? {
+            while let Some(repeat_time) = self.next_if('r') {
+                timing.add_repeat_time(repeat_time).map_err(|_| {
+                    ParseError::new(ParseErrorKind::InvalidField('r'), self.position)
+                })?;
+            }
+
+            timings.push(timing);
+        }
+
+        if timings.is_empty() {
+            return Err(self.error(ParseErrorKind::MissingField('t')));
+        }
+
+        let time_zones = self.next_field_if('z', validate_text)?;
+        let encryption_key = self.next_field_if('k', validate_text)?;
+        let attributes = self.next_fields('a', Attribute::try_from)?;
+        let mut media_descriptions = Vec::new();
+
+        while let Some(media) = self.next_field_if('m', Media::try_from)? {
+            let media_line = self.position;
+            let media_description = MediaDescription {
+                information: self.next_field_if('i', validate_text)?,
+                connections: self.next_fields('c', Connection::try_from)?,
+                bandwidths: self.next_fields('b', Bandwidth::try_from)?,
+                encryption_key: self.next_field_if('k', validate_text)?,
+                attributes: self.next_fields('a', Attribute::try_from)?,
+                media,
+            };
+
+            // Connection data is required either at the session level or in every media
+            // description.
+
+            if connection.is_none() && media_description.connections.is_empty() {
+                return Err(ParseError::new(
+                    ParseErrorKind::MissingField('c'),
+                    media_line,
+                ));
+            }
+
+            media_descriptions.push(media_description);
+        }
+
+        if let Some(&(field_type, _)) = self.lines.get(self.position) {
+            return Err(self.error(ParseErrorKind::UnexpectedField(field_type)));
+        }
+
+        Ok(SessionDescription {
+            attributes,
+            bandwidths,
+            connection,
+            email_addresses,
+            encryption_key,
+            information,
+            media_descriptions,
+            origin,
+            phone_numbers,
+            session_name,
+            time_zones,
+            timings,
+            uri,
+        })
+    }
+}
+
+fn find_attribute<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    attributes.iter().find(|attribute| attribute.name() == name)
+}
+
+fn parse_range(attributes: &[Attribute]) -> Option<Range> {
+    find_attribute(attributes, "range")
+        .and_then(|attribute| attribute.value())
+        .and_then(|value| Range::try_from(value).ok())
+}
+
+fn validate_optional_text(value: Option<&str>) -> Result<Option<String>, InvalidField> {
+    match value {
+        Some(value) => validate_text(value).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn validate_text(value: &str) -> Result<String, InvalidField> {
+    if is_text(value) {
+        Ok(value.to_string())
+    } else {
+        Err(InvalidField)
+    }
+}
+
+fn write_line<T>(formatter: &mut fmt::Formatter, field_type: char, value: T) -> fmt::Result
+where
+    T: fmt::Display,
+{
+    write!(formatter, "{}={}\r\n", field_type, value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DESCRIPTION: &str = "v=0\r\n\
+                               o=- 2890844526 2890842807 IN IP4 192.16.24.202\r\n\
+                               s=RTSP Session\r\n\
+                               i=An Example\r\n\
+                               e=user@example.com\r\n\
+                               c=IN IP4 0.0.0.0\r\n\
+                               b=AS:1000\r\n\
+                               t=0 0\r\n\
+                               r=7d 1h 0 25h\r\n\
+                               a=control:*\r\n\
+                               a=range:npt=0-34.57\r\n\
+                               m=audio 0 RTP/AVP 0\r\n\
+                               a=control:audio\r\n\
+                               m=video 0 RTP/AVP 96 97\r\n\
+                               b=AS:900\r\n\
+                               a=rtpmap:96 H264/90000\r\n\
+                               a=fmtp:96 packetization-mode=1;profile-level-id=42e01f\r\n\
+                               a=rtpmap:97 MPEG4-GENERIC/44100/2\r\n\
+                               a=control:rtsp://example.com/movie/video\r\n";
+
+    fn parse_error(value: &str) -> (ParseErrorKind, usize) {
+        let error = SessionDescription::try_from(value).unwrap_err();
+        (error.kind(), error.line())
+    }
+
+    #[test]
+    fn test_session_description_round_trip() {
+        let description = SessionDescription::try_from(DESCRIPTION).unwrap();
+        assert_eq!(description.to_string(), DESCRIPTION);
+        assert_eq!(
+            SessionDescription::try_from(description.to_string().as_bytes()).unwrap(),
+            description
+        );
+    }
+
+    #[test]
+    fn test_session_description_fields() {
+        let description =
+            SessionDescription::try_from(DESCRIPTION.replace("\r\n", "\n").trim()).unwrap();
+        assert_eq!(description.origin().session_id(), "2890844526");
+        assert_eq!(description.information(), Some("An Example"));
+        assert_eq!(
+            description.email_addresses(),
+            &["user@example.com".to_string()]
+        );
+        assert_eq!(description.bandwidths()[0].bandwidth(), 1000);
+        assert_eq!(
+            description.timings()[0].repeat_times(),
+            &["7d 1h 0 25h".to_string()]
+        );
+        assert_eq!(description.control(), Some("*"));
+        assert!(description.range().is_some());
+
+        let media_descriptions = description.media_descriptions();
+        assert_eq!(media_descriptions.len(), 2);
+        assert_eq!(media_descriptions[0].media().media_type(), "audio");
+        assert!(media_descriptions[0].rtp_maps().is_empty());
+
+        let video = &media_descriptions[1];
+        assert_eq!(
+            video.media().formats(),
+            &["96".to_string(), "97".to_string()]
+        );
+        assert_eq!(video.rtp_map(96).unwrap().encoding_name(), "H264");
+        assert_eq!(video.rtp_map(97).unwrap().encoding_parameters(), Some("2"));
+        assert_eq!(
+            video
+                .format_parameters("96")
+                .unwrap()
+                .parameter("packetization-mode"),
+            Some("1")
+        );
+        assert!(video.format_parameters("97").is_none());
+    }
+
+    #[test]
+    fn test_session_description_control_uri() {
+        let description = SessionDescription::try_from(DESCRIPTION).unwrap();
+        let base = RequestURI::try_from("rtsp://example.com/movie/").unwrap();
+        assert_eq!(description.control_uri(&base).unwrap(), base);
+
+        let media_descriptions = description.media_descriptions();
+        assert_eq!(
+            media_descriptions[0]
+                .control_uri(&base)
+                .unwrap()
+                .unwrap()
+                .as_str(),
+            "rtsp://example.com/movie/audio"
+        );
+        assert_eq!(
+            media_descriptions[1]
+                .control_uri(&base)
+                .unwrap()
+                .unwrap()
+                .as_str(),
+            "rtsp://example.com/movie/video"
+        );
+    }
+
+    #[test]
+    fn test_session_description_errors() {
+        assert_eq!(
+            SessionDescription::try_from(&b"v=0\r\n\xFF"[..])
+                .unwrap_err()
+                .kind(),
+            ParseErrorKind::InvalidEncoding
+        );
+        assert_eq!(parse_error(""), (ParseErrorKind::InvalidLine, 1));
+        assert_eq!(parse_error("v=1\r\n"), (ParseErrorKind::InvalidVersion, 1));
+        assert_eq!(
+            parse_error("o=- 1 1 IN IP4 0.0.0.0\r\n"),
+            (ParseErrorKind::MissingField('v'), 1)
+        );
+        assert_eq!(
+            parse_error("v=0\r\no=- a 1 IN IP4 0.0.0.0\r\n"),
+            (ParseErrorKind::InvalidField('o'), 2)
+        );
+        assert_eq!(
+            parse_error("v=0\r\no=- 1 1 IN IP4 0.0.0.0\r\ns=Name\r\n\r\nt=0 0\r\n"),
+            (ParseErrorKind::InvalidLine, 4)
+        );
+        assert_eq!(
+            parse_error("v=0\r\no=- 1 1 IN IP4 0.0.0.0\r\ns=Name\r\na=control:*\r\n"),
+            (ParseErrorKind::MissingField('t'), 4)
+        );
+        assert_eq!(
+            parse_error("v=0\r\no=- 1 1 IN IP4 0.0.0.0\r\ns=Name\r\nt=0 0\r\nr=1d\r\n"),
+            (ParseErrorKind::InvalidField('r'), 5)
+        );
+        assert_eq!(
+            parse_error("v=0\r\no=- 1 1 IN IP4 0.0.0.0\r\ns=Name\r\nt=0 0\r\nc=IN IP4 0.0.0.0\r\n"),
+            (ParseErrorKind::UnexpectedField('c'), 5)
+        );
+        assert_eq!(
+            parse_error("v=0\r\no=- 1 1 IN IP4 0.0.0.0\r\ns=Name\r\nt=0 0\r\nx=unknown\r\n"),
+            (ParseErrorKind::UnexpectedField('x'), 5)
+        );
+        assert_eq!(
+            parse_error(
+                "v=0\r\no=- 1 1 IN IP4 0.0.0.0\r\ns=Name\r\nt=0 0\r\nm=audio 0 RTP/AVP 0\r\n"
+            ),
+            (ParseErrorKind::MissingField('c'), 5)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::fmt;
+
+/// An error indicating that a session description could not be parsed, along with the line at
+/// which the problem was found.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    line: usize,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: ParseErrorKind, line: usize) -> Self {
+        ParseError { kind, line }
+    }
+
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Returns the one-based number of the line at which the error occurred. If the description
+    /// ended before a required line was found, this is one past the last line.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        use self::ParseErrorKind::*;
+
+        match self.kind {
+            InvalidField(field) | MissingField(field) | UnexpectedField(field) => write!(
+                formatter,
+                "{} `{}=` on line {}",
+                self.description(),
+                field,
+                self.line
+            ),
+            _ => write!(formatter, "{} on line {}", self.description(), self.line),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        self.kind.description()
+    }
+}
+
+/// The different kinds of errors that can occur while parsing a session description.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The description is not valid UTF-8.
+    InvalidEncoding,
+
+    /// The value of a line of the given type is malformed.
+    InvalidField(char),
+
+    /// The line is not of the form `<type>=<value>`.
+    InvalidLine,
+
+    /// The protocol version is not `0`.
+    InvalidVersion,
+
+    /// A required line of the given type was not found where it was expected.
+    MissingField(char),
+
+    /// A line of the given type was found where it is not allowed, either because the type is
+    /// unknown, the line is out of order, or the line is repeated.
+    UnexpectedField(char),
+}
+
+impl ParseErrorKind {
+    fn description(&self) -> &str {
+        use self::ParseErrorKind::*;
+
+        match self {
+            InvalidEncoding => "invalid session description encoding",
+            InvalidField(_) => "invalid session description field",
+            InvalidLine => "invalid session description line",
+            InvalidVersion => "invalid session description version",
+            MissingField(_) => "missing session description field",
+            UnexpectedField(_) => "unexpected session description field",
+        }
+    }
+}
+
+/// A generic error type indicating that a value cannot be used for a field of a session
+/// description.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InvalidField;
+
+impl fmt::Display for InvalidField {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for InvalidField {
+    fn description(&self) -> &str {
+        "invalid session description field"
+    }
+}
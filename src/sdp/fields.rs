@@ -0,0 +1,750 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use sdp::InvalidField;
+use syntax::is_token;
+
+/// The `o=` field identifying the originator of the session along with a session identifier and
+/// version number.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Origin {
+    address_type: String,
+    network_type: String,
+    session_id: String,
+    session_version: String,
+    unicast_address: String,
+    username: String,
+}
+
+impl Origin {
+    /// Constructs a new origin field. The username should be `"-"` if the originating host does
+    /// not support the concept of user IDs.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the session ID or version are not numeric, if the network or
+    /// address types are not tokens, or if the username or address contain whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::sdp::Origin;
+    ///
+    /// let origin = Origin::new("-", "2890844526", "2890842807", "IN", "IP4", "10.47.16.5")
+    ///     .unwrap();
+    /// assert_eq!(origin.to_string(), "- 2890844526 2890842807 IN IP4 10.47.16.5");
+    ///
+    /// assert!(Origin::new("-", "session", "1", "IN", "IP4", "10.47.16.5").is_err());
+    /// ```
+    pub fn new(
+        username: &str,
+        session_id: &str,
+        session_version: &str,
+        network_type: &str,
+        address_type: &str,
+        unicast_address: &str,
+    ) -> Result<Self, InvalidField> {
+        if !is_non_whitespace_string(username)
+            || !is_digits(session_id)
+            || !is_digits(session_version)
+            || !is_token(network_type)
+            || !is_token(address_type)
+            || !is_non_whitespace_string(unicast_address)
+        {
+            return Err(InvalidField);
+        }
+
+        Ok(Origin {
+            address_type: address_type.to_string(),
+            network_type: network_type.to_string(),
+            session_id: session_id.to_string(),
+            session_version: session_version.to_string(),
+            unicast_address: unicast_address.to_string(),
+            username: username.to_string(),
+        })
+    }
+
+    pub fn address_type(&self) -> &str {
+        &self.address_type
+    }
+
+    pub fn network_type(&self) -> &str {
+        &self.network_type
+    }
+
+    /// Returns the numeric session identifier. Since its size is not limited, it is kept as a
+    /// string.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Returns the numeric version of the session description. Since its size is not limited, it
+    /// is kept as a string.
+    pub fn session_version(&self) -> &str {
+        &self.session_version
+    }
+
+    pub fn unicast_address(&self) -> &str {
+        &self.unicast_address
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} {} {} {} {} {}",
+            self.username,
+            self.session_id,
+            self.session_version,
+            self.network_type,
+            self.address_type,
+            self.unicast_address
+        )
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Origin {
+    type Error = InvalidField;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let parts = split_fields(value, 6)?;
+        Origin::new(parts[0], parts[1], parts[2], parts[3], parts[4], parts[5])
+    }
+}
+
+/// The `c=` field containing the connection data of the session or of a media description.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Connection {
+    address_type: String,
+    connection_address: String,
+    network_type: String,
+}
+
+impl Connection {
+    /// Constructs a new connection field. For multicast addresses, the connection address may
+    /// include the TTL and number of addresses, e.g. `"224.2.36.42/127/3"`.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the network or address types are not tokens or if the
+    /// connection address contains whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::sdp::Connection;
+    ///
+    /// let connection = Connection::new("IN", "IP4", "224.2.36.42/127").unwrap();
+    /// assert_eq!(connection.to_string(), "IN IP4 224.2.36.42/127");
+    /// ```
+    pub fn new(
+        network_type: &str,
+        address_type: &str,
+        connection_address: &str,
+    ) -> Result<Self, InvalidField> {
+        if !is_token(network_type)
+            || !is_token(address_type)
+            || !is_non_whitespace_string(connection_address)
+        {
+            return Err(InvalidField);
+        }
+
+        Ok(Connection {
+            address_type: address_type.to_string(),
+            connection_address: connection_address.to_string(),
+            network_type: network_type.to_string(),
+        })
+    }
+
+    pub fn address_type(&self) -> &str {
+        &self.address_type
+    }
+
+    pub fn connection_address(&self) -> &str {
+        &self.connection_address
+    }
+
+    pub fn network_type(&self) -> &str {
+        &self.network_type
+    }
+}
+
+impl fmt::Display for Connection {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} {} {}",
+            self.network_type, self.address_type, self.connection_address
+        )
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Connection {
+    type Error = InvalidField;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let parts = split_fields(value, 3)?;
+        Connection::new(parts[0], parts[1], parts[2])
+    }
+}
+
+/// The `b=` field containing the proposed bandwidth of the session or of a media description.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Bandwidth {
+    bandwidth: u64,
+    bandwidth_type: String,
+}
+
+impl Bandwidth {
+    /// Constructs a new bandwidth field. Common bandwidth types are `"AS"` and `"CT"`, for which
+    /// the bandwidth is given in kilobits per second.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the bandwidth type is not a token.
+    pub fn new(bandwidth_type: &str, bandwidth: u64) -> Result<Self, InvalidField> {
+        if !is_token(bandwidth_type) {
+            return Err(InvalidField);
+        }
+
+        Ok(Bandwidth {
+            bandwidth,
+            bandwidth_type: bandwidth_type.to_string(),
+        })
+    }
+
+    pub fn bandwidth(&self) -> u64 {
+        self.bandwidth
+    }
+
+    pub fn bandwidth_type(&self) -> &str {
+        &self.bandwidth_type
+    }
+}
+
+impl fmt::Display for Bandwidth {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}:{}", self.bandwidth_type, self.bandwidth)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Bandwidth {
+    type Error = InvalidField;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut parts = value.splitn(2, ':');
+        let bandwidth_type = parts.next().unwrap();
+        let bandwidth = parse_integer(parts.next().ok_or(InvalidField)?)?;
+        Bandwidth::new(bandwidth_type, bandwidth)
+    }
+}
+
+/// The `t=` field containing the start and stop times of the session as NTP timestamps in seconds,
+/// along with any `r=` repeat times that follow it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Timing {
+    repeat_times: Vec<String>,
+    start_time: u64,
+    stop_time: u64,
+}
+
+impl Timing {
+    /// Constructs a new timing field without any repeat times. A stop time of `0` indicates that
+    /// the session is not bounded, and if the start time is also `0`, the session is permanent.
+    pub fn new(start_time: u64, stop_time: u64) -> Self {
+        Timing {
+            repeat_times: Vec::new(),
+            start_time,
+            stop_time,
+        }
+    }
+
+    /// Adds the value of an `r=` field to the timing field.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the value does not consist of at least three whitespace
+    /// separated typed times, e.g. `"7d 1h 0 25h"`.
+    pub fn add_repeat_time(&mut self, repeat_time: &str) -> Result<(), InvalidField> {
+        let parts = repeat_time.split(' ').collect::<Vec<&str>>();
+
+        if parts.len() < 3 || !parts.iter().all(|part| is_typed_time(part)) {
+            return Err(InvalidField);
+        }
+
+        self.repeat_times.push(repeat_time.to_string());
+        Ok(())
+    }
+
+    pub fn repeat_times(&self) -> &[String] {
+        &self.repeat_times
+    }
+
+    pub fn start_time(&self) -> u64 {
+        self.start_time
+    }
+
+    pub fn stop_time(&self) -> u64 {
+        self.stop_time
+    }
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} {}", self.start_time, self.stop_time)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Timing {
+    type Error = InvalidField;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let parts = split_fields(value, 2)?;
+        Ok(Timing::new(
+            parse_integer(parts[0])?,
+            parse_integer(parts[1])?,
+        ))
+    }
+}
+
+/// The `m=` field starting a media description.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Media {
+    formats: Vec<String>,
+    media_type: String,
+    number_of_ports: Option<u16>,
+    port: u16,
+    protocol: String,
+}
+
+impl Media {
+    /// Constructs a new media field. For RTP based protocols, the formats are the RTP payload
+    /// types.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the media type is not a token, if the protocol is not a `'/'`
+    /// separated list of tokens, if a format contains whitespace, or if no formats are given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::sdp::Media;
+    ///
+    /// let media = Media::new("video", 0, "RTP/AVP", &["96", "97"]).unwrap();
+    /// assert_eq!(media.to_string(), "video 0 RTP/AVP 96 97");
+    ///
+    /// assert!(Media::new("video", 0, "RTP/AVP", &[]).is_err());
+    /// ```
+    pub fn new(
+        media_type: &str,
+        port: u16,
+        protocol: &str,
+        formats: &[&str],
+    ) -> Result<Self, InvalidField> {
+        if !is_token(media_type)
+            || !protocol.split('/').all(is_token)
+            || formats.is_empty()
+            || !formats
+                .iter()
+                .all(|format| is_non_whitespace_string(format))
+        {
+            return Err(InvalidField);
+        }
+
+        Ok(Media {
+            formats: formats.iter().map(|format| format.to_string()).collect(),
+            media_type: media_type.to_string(),
+            number_of_ports: None,
+            port,
+            protocol: protocol.to_string(),
+        })
+    }
+
+    pub fn formats(&self) -> &[String] {
+        &self.formats
+    }
+
+    /// Returns the type of the media, such as `"audio"`, `"video"`, or `"application"`.
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    pub fn number_of_ports(&self) -> Option<u16> {
+        self.number_of_ports
+    }
+
+    pub fn number_of_ports_mut(&mut self) -> &mut Option<u16> {
+        &mut self.number_of_ports
+    }
+
+    /// Returns the transport port. With RTSP, this is usually `0` since the transport is
+    /// negotiated using `SETUP` instead.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn port_mut(&mut self) -> &mut u16 {
+        &mut self.port
+    }
+
+    pub fn protocol(&self) -> &str {
+        &self.protocol
+    }
+}
+
+impl fmt::Display for Media {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} {}", self.media_type, self.port)?;
+
+        if let Some(number_of_ports) = self.number_of_ports {
+            write!(formatter, "/{}", number_of_ports)?;
+        }
+
+        write!(formatter, " {} {}", self.protocol, self.formats.join(" "))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Media {
+    type Error = InvalidField;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let parts = value.split(' ').collect::<Vec<&str>>();
+
+        if parts.len() < 4 {
+            return Err(InvalidField);
+        }
+
+        let mut ports = parts[1].splitn(2, '/');
+        let port = parse_integer(ports.next().unwrap())?;
+        let mut media = Media::new(parts[0], port, parts[2], &parts[3..])?;
+
+        if let Some(number_of_ports) = ports.next() {
+            media.number_of_ports = Some(parse_integer(number_of_ports)?);
+        }
+
+        Ok(media)
+    }
+}
+
+/// The `a=` field containing either a property attribute of the form `a=<name>` or a value
+/// attribute of the form `a=<name>:<value>`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Attribute {
+    name: String,
+    value: Option<String>,
+}
+
+impl Attribute {
+    /// Constructs a new attribute field.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the name is not a token or if the value contains a NUL, CR,
+    /// or LF character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::sdp::Attribute;
+    ///
+    /// let attribute = Attribute::new("control", Some("trackID=1")).unwrap();
+    /// assert_eq!(attribute.to_string(), "control:trackID=1");
+    ///
+    /// let attribute = Attribute::new("recvonly", None).unwrap();
+    /// assert_eq!(attribute.to_string(), "recvonly");
+    /// ```
+    pub fn new(name: &str, value: Option<&str>) -> Result<Self, InvalidField> {
+        if !is_token(name) || !value.map_or(true, is_text) {
+            return Err(InvalidField);
+        }
+
+        Ok(Attribute {
+            name: name.to_string(),
+            value: value.map(|value| value.to_string()),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_ref().map(|value| value.as_str())
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            Some(ref value) => write!(formatter, "{}:{}", self.name, value),
+            None => formatter.write_str(&self.name),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Attribute {
+    type Error = InvalidField;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut parts = value.splitn(2, ':');
+        Attribute::new(parts.next().unwrap(), parts.next())
+    }
+}
+
+impl From<RTPMap> for Attribute {
+    fn from(value: RTPMap) -> Self {
+        Attribute {
+            name: "rtpmap".to_string(),
+            value: Some(value.to_string()),
+        }
+    }
+}
+
+impl From<FormatParameters> for Attribute {
+    fn from(value: FormatParameters) -> Self {
+        Attribute {
+            name: "fmtp".to_string(),
+            value: Some(value.to_string()),
+        }
+    }
+}
+
+/// The value of an `a=rtpmap` attribute mapping an RTP payload type to an encoding.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RTPMap {
+    clock_rate: u32,
+    encoding_name: String,
+    encoding_parameters: Option<String>,
+    payload_type: u8,
+}
+
+impl RTPMap {
+    /// Constructs a new RTP map. For audio encodings, the encoding parameters are the number of
+    /// audio channels.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the payload type is larger than `127`, if the encoding name
+    /// is not a token, or if the encoding parameters contain whitespace or `'/'`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::sdp::RTPMap;
+    ///
+    /// let rtp_map = RTPMap::new(97, "MPEG4-GENERIC", 44100, Some("2")).unwrap();
+    /// assert_eq!(rtp_map.to_string(), "97 MPEG4-GENERIC/44100/2");
+    /// assert_eq!(RTPMap::try_from("97 MPEG4-GENERIC/44100/2").unwrap(), rtp_map);
+    /// ```
+    pub fn new(
+        payload_type: u8,
+        encoding_name: &str,
+        clock_rate: u32,
+        encoding_parameters: Option<&str>,
+    ) -> Result<Self, InvalidField> {
+        if payload_type > 127
+            || !is_token(encoding_name)
+            || !encoding_parameters.map_or(true, |parameters| {
+                is_non_whitespace_string(parameters) && !parameters.contains('/')
+            })
+        {
+            return Err(InvalidField);
+        }
+
+        Ok(RTPMap {
+            clock_rate,
+            encoding_name: encoding_name.to_string(),
+            encoding_parameters: encoding_parameters.map(|parameters| parameters.to_string()),
+            payload_type,
+        })
+    }
+
+    pub fn clock_rate(&self) -> u32 {
+        self.clock_rate
+    }
+
+    pub fn encoding_name(&self) -> &str {
+        &self.encoding_name
+    }
+
+    pub fn encoding_parameters(&self) -> Option<&str> {
+        self.encoding_parameters
+            .as_ref()
+            .map(|parameters| parameters.as_str())
+    }
+
+    pub fn payload_type(&self) -> u8 {
+        self.payload_type
+    }
+}
+
+impl fmt::Display for RTPMap {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} {}/{}",
+            self.payload_type, self.encoding_name, self.clock_rate
+        )?;
+
+        if let Some(ref parameters) = self.encoding_parameters {
+            write!(formatter, "/{}", parameters)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for RTPMap {
+    type Error = InvalidField;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let parts = split_fields(value, 2)?;
+        let payload_type = parse_integer(parts[0])?;
+        let mut encoding = parts[1].splitn(3, '/');
+        let encoding_name = encoding.next().unwrap();
+        let clock_rate = parse_integer(encoding.next().ok_or(InvalidField)?)?;
+
+        RTPMap::new(payload_type, encoding_name, clock_rate, encoding.next())
+    }
+}
+
+/// The value of an `a=fmtp` attribute containing format specific parameters.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct FormatParameters {
+    format: String,
+    parameters: String,
+}
+
+impl FormatParameters {
+    /// Constructs new format parameters for the given format.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the format contains whitespace or if the parameters are empty
+    /// or contain a NUL, CR, or LF character.
+    pub fn new(format: &str, parameters: &str) -> Result<Self, InvalidField> {
+        if !is_non_whitespace_string(format) || !is_text(parameters) {
+            return Err(InvalidField);
+        }
+
+        Ok(FormatParameters {
+            format: format.to_string(),
+            parameters: parameters.to_string(),
+        })
+    }
+
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+
+    /// Returns the value of the parameter with the given name, assuming the common syntax of
+    /// `';'` separated `<name>=<value>` pairs. Parameter names are compared case insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::sdp::FormatParameters;
+    ///
+    /// let parameters =
+    ///     FormatParameters::try_from("96 packetization-mode=1; profile-level-id=42e01f").unwrap();
+    /// assert_eq!(parameters.parameter("profile-level-id"), Some("42e01f"));
+    /// assert_eq!(parameters.parameter("sprop-parameter-sets"), None);
+    /// ```
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .split(';')
+            .filter_map(|parameter| {
+                let mut parts = parameter.trim().splitn(2, '=');
+                let parameter_name = parts.next().unwrap().trim();
+
+                if parameter_name.eq_ignore_ascii_case(name) {
+                    Some(parts.next().unwrap_or("").trim())
+                } else {
+                    None
+                }
+            })
+            .next()
+    }
+
+    pub fn parameters(&self) -> &str {
+        &self.parameters
+    }
+}
+
+impl fmt::Display for FormatParameters {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} {}", self.format, self.parameters)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for FormatParameters {
+    type Error = InvalidField;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut parts = value.splitn(2, ' ');
+        let format = parts.next().unwrap();
+        FormatParameters::new(format, parts.next().ok_or(InvalidField)?)
+    }
+}
+
+/// Determines whether the given value is valid text as it is used by fields such as the session
+/// name, that is, a nonempty string without NUL, CR, or LF characters.
+pub(crate) fn is_text(value: &str) -> bool {
+    !value.is_empty() && !value.contains(|c| c == '\0' || c == '\r' || c == '\n')
+}
+
+fn is_digits(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_non_whitespace_string(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b > b' ' && b != 0x7F)
+}
+
+/// Determines whether the given value is a typed time as used by `r=` fields, e.g. `"7d"`.
+fn is_typed_time(value: &str) -> bool {
+    let value = if value.ends_with(|c| c == 'd' || c == 'h' || c == 'm' || c == 's') {
+        &value[..value.len() - 1]
+    } else {
+        value
+    };
+
+    is_digits(value)
+}
+
+fn parse_integer<T>(value: &str) -> Result<T, InvalidField>
+where
+    T: FromStr,
+{
+    if !is_digits(value) {
+        return Err(InvalidField);
+    }
+
+    value.parse().map_err(|_| InvalidField)
+}
+
+/// Splits the value of a field into exactly the given number of single space separated parts.
+fn split_fields(value: &str, count: usize) -> Result<Vec<&str>, InvalidField> {
+    let parts = value.split(' ').collect::<Vec<&str>>();
+
+    if parts.len() != count {
+        return Err(InvalidField);
+    }
+
+    Ok(parts)
+}
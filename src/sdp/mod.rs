@@ -0,0 +1,101 @@
+//! Session Description Protocol
+//!
+//! This module contains types for parsing and serializing session descriptions as described by
+//! [RFC8866](https://tools.ietf.org/html/rfc8866). These are usually found in the body of
+//! `DESCRIBE` responses with the content type `"application/sdp"`, and describe the media streams
+//! that make up a presentation along with the URIs used to control them.
+
+mod description;
+mod error;
+mod fields;
+
+pub use self::description::{MediaDescription, SessionDescription};
+pub use self::error::{InvalidField, ParseError, ParseErrorKind};
+pub use self::fields::{
+    Attribute, Bandwidth, Connection, FormatParameters, Media, Origin, RTPMap, Timing,
+};
+
+use header::{HeaderMap, HeaderName};
+use uri::{InvalidRequestURI, RequestURI};
+
+/// Determines the base URI that relative `a=control` attributes of a session description are
+/// resolved against, as described by
+/// [RFC7826](https://tools.ietf.org/html/rfc7826#appendix-C.1.1).
+///
+/// The `"Content-Base"` header of the response is used if present, followed by the
+/// `"Content-Location"` header. Relative values of either are resolved against the request URI of
+/// the `DESCRIBE` request, which is also used if neither header is present.
+///
+/// # Return Value
+///
+/// An error will be returned if the header value does not resolve to a valid request URI.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(try_from)]
+/// #
+/// use std::convert::TryFrom;
+///
+/// use rtsp::*;
+/// use rtsp::sdp::base_uri;
+///
+/// let request_uri = RequestURI::try_from("rtsp://example.com/movie").unwrap();
+/// let mut headers = HeaderMap::new();
+/// assert_eq!(base_uri(&request_uri, &headers).unwrap(), request_uri);
+///
+/// headers.insert(
+///     HeaderName::ContentBase,
+///     HeaderValue::try_from("rtsp://example.com/movie/").unwrap(),
+/// );
+/// assert_eq!(
+///     base_uri(&request_uri, &headers).unwrap().as_str(),
+///     "rtsp://example.com/movie/"
+/// );
+/// ```
+pub fn base_uri(
+    request_uri: &RequestURI,
+    headers: &HeaderMap,
+) -> Result<RequestURI, InvalidRequestURI> {
+    match headers
+        .get(HeaderName::ContentBase)
+        .or_else(|| headers.get(HeaderName::ContentLocation))
+    {
+        Some(value) => request_uri.join(value.as_str()),
+        None => Ok(request_uri.clone()),
+    }
+}
+
+/// Resolves the value of an `a=control` attribute against the given base URI.
+///
+/// A value of `"*"` refers to the base URI itself, while any other value is treated as a URI
+/// reference that may be relative.
+///
+/// # Return Value
+///
+/// An error will be returned if the resolved URI is not a valid request URI.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(try_from)]
+/// #
+/// use std::convert::TryFrom;
+///
+/// use rtsp::RequestURI;
+/// use rtsp::sdp::resolve_control;
+///
+/// let base = RequestURI::try_from("rtsp://example.com/movie/").unwrap();
+/// assert_eq!(resolve_control(&base, "*").unwrap(), base);
+/// assert_eq!(
+///     resolve_control(&base, "trackID=1").unwrap().as_str(),
+///     "rtsp://example.com/movie/trackID=1"
+/// );
+/// ```
+pub fn resolve_control(base: &RequestURI, control: &str) -> Result<RequestURI, InvalidRequestURI> {
+    if control == "*" {
+        Ok(base.clone())
+    } else {
+        base.join(control)
+    }
+}
@@ -0,0 +1,353 @@
+//! Media Server
+//!
+//! This module contains the [`MediaServer`] trait, a higher-level alternative to implementing
+//! [`Service`] directly. Rather than matching on the method of every request, an implementation
+//! provides one method per RTSP method that it handles, and [`MediaService`] takes care of the
+//! dispatching along with answering `OPTIONS` requests.
+
+use bytes::BytesMut;
+use futures::{future, Future};
+use std::error::Error;
+
+use header::types::Public;
+use method::Method;
+use protocol::Service;
+use request::{Request, TypedRequest};
+use response::{Response, TypedResponse};
+use status::StatusCode;
+
+use super::SUPPORTED_METHODS;
+
+/// The future returned by the methods of a [`MediaServer`].
+pub type MediaFuture<E> = Box<Future<Item = TypedResponse<BytesMut>, Error = E> + Send + 'static>;
+
+/// A server that handles RTSP requests on a per-method basis.
+///
+/// Every method has a default implementation that responds with `501 Not Implemented`, so an
+/// implementation only needs to override the methods that it supports. A [`MediaServer`] is
+/// turned into a [`Service`] using a [`MediaService`].
+///
+/// Since there is no way to determine which provided methods an implementation overrides, the
+/// overridden methods must also be marked in [`MediaServer::IMPLEMENTED`]. Only the marked methods
+/// are dispatched to, and they are also used to build the `"Public"` header that is sent in
+/// response to `OPTIONS` requests, so the advertised methods are always the ones that are handled.
+pub trait MediaServer {
+    type Error: Into<Box<Error + Send + Sync + 'static>> + Send + 'static;
+
+    /// The methods that this implementation handles, not including `OPTIONS` which is always
+    /// handled by the [`MediaService`]. No method is implemented by default.
+    const IMPLEMENTED: ImplementedMethods = ImplementedMethods::NONE;
+
+    fn announce(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn describe(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn get_parameter(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn pause(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn play(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn play_notify(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn record(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn redirect(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn set_parameter(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn setup(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+
+    fn teardown(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        not_implemented()
+    }
+}
+
+/// The methods that have a method of their own in a [`MediaServer`], in alphabetical order.
+const MEDIA_METHODS: [Method; 11] = [
+    Method::Announce,
+    Method::Describe,
+    Method::GetParameter,
+    Method::Pause,
+    Method::Play,
+    Method::PlayNotify,
+    Method::Record,
+    Method::Redirect,
+    Method::SetParameter,
+    Method::Setup,
+    Method::Teardown,
+];
+
+/// The methods that a [`MediaServer`] implements, one flag per method.
+///
+/// Since the struct can be built in a constant context, an implementation only needs to set the
+/// flags of the methods it overrides:
+///
+/// ```
+/// # use rtsp::server::ImplementedMethods;
+/// #
+/// const IMPLEMENTED: ImplementedMethods = ImplementedMethods {
+///     describe: true,
+///     ..ImplementedMethods::NONE
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ImplementedMethods {
+    pub announce: bool,
+    pub describe: bool,
+    pub get_parameter: bool,
+    pub pause: bool,
+    pub play: bool,
+    pub play_notify: bool,
+    pub record: bool,
+    pub redirect: bool,
+    pub set_parameter: bool,
+    pub setup: bool,
+    pub teardown: bool,
+}
+
+impl ImplementedMethods {
+    /// No method is implemented.
+    pub const NONE: ImplementedMethods = ImplementedMethods {
+        announce: false,
+        describe: false,
+        get_parameter: false,
+        pause: false,
+        play: false,
+        play_notify: false,
+        record: false,
+        redirect: false,
+        set_parameter: false,
+        setup: false,
+        teardown: false,
+    };
+
+    /// Returns whether the given method is implemented. Since `OPTIONS` and extension methods do
+    /// not have a method of their own in a [`MediaServer`], they are never implemented.
+    pub fn contains(&self, method: &Method) -> bool {
+        match *method {
+            Method::Announce => self.announce,
+            Method::Describe => self.describe,
+            Method::GetParameter => self.get_parameter,
+            Method::Pause => self.pause,
+            Method::Play => self.play,
+            Method::PlayNotify => self.play_notify,
+            Method::Record => self.record,
+            Method::Redirect => self.redirect,
+            Method::SetParameter => self.set_parameter,
+            Method::Setup => self.setup,
+            Method::Teardown => self.teardown,
+            _ => false,
+        }
+    }
+
+    /// Returns an iterator over the implemented methods in a fixed order.
+    pub fn iter(&self) -> impl Iterator<Item = &'static Method> {
+        let implemented = *self;
+        MEDIA_METHODS
+            .iter()
+            .filter(move |method| implemented.contains(method))
+    }
+}
+
+/// An adapter that turns a [`MediaServer`] into a [`Service`].
+///
+/// Requests are dispatched to the method of the media server that corresponds to the request
+/// method if it is marked in [`MediaServer::IMPLEMENTED`]. `OPTIONS` requests are answered directly
+/// with a `"Public"` header listing the implemented methods, and requests with any other method are
+/// answered with `501 Not Implemented`.
+pub struct MediaService<S> {
+    public: Public,
+    server: S,
+}
+
+impl<S> MediaService<S>
+where
+    S: MediaServer,
+{
+    pub fn new(server: S) -> Self {
+        let public = SUPPORTED_METHODS
+            .iter()
+            .chain(S::IMPLEMENTED.iter())
+            .cloned()
+            .collect();
+
+        MediaService { public, server }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.server
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.server
+    }
+
+    pub fn into_inner(self) -> S {
+        self.server
+    }
+
+    /// Returns the `"Public"` header that is sent in response to `OPTIONS` requests.
+    pub fn public(&self) -> &Public {
+        &self.public
+    }
+
+    fn handle_method_options(&self) -> TypedResponse<BytesMut> {
+        Response::typed_builder()
+            .header(self.public.clone())
+            .build(BytesMut::new())
+            .expect("options response should not be invalid")
+    }
+}
+
+impl<S> Service for MediaService<S>
+where
+    S: MediaServer,
+{
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = S::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let request: TypedRequest<BytesMut> = request.into();
+
+        let method = request.method().clone();
+
+        if method != Method::Options && !S::IMPLEMENTED.contains(&method) {
+            return Box::new(not_implemented().map(Into::into));
+        }
+
+        let future = match method {
+            Method::Announce => self.server.announce(request),
+            Method::Describe => self.server.describe(request),
+            Method::GetParameter => self.server.get_parameter(request),
+            Method::Options => Box::new(future::ok(self.handle_method_options())),
+            Method::Pause => self.server.pause(request),
+            Method::Play => self.server.play(request),
+            Method::PlayNotify => self.server.play_notify(request),
+            Method::Record => self.server.record(request),
+            Method::Redirect => self.server.redirect(request),
+            Method::SetParameter => self.server.set_parameter(request),
+            Method::Setup => self.server.setup(request),
+            Method::Teardown => self.server.teardown(request),
+            _ => not_implemented(),
+        };
+
+        Box::new(future.map(Into::into))
+    }
+}
+
+/// Returns a future that resolves to a `501 Not Implemented` response.
+fn not_implemented<E>() -> MediaFuture<E>
+where
+    E: Send + 'static,
+{
+    let response = Response::typed_builder()
+        .status_code(StatusCode::NotImplemented)
+        .build(BytesMut::new())
+        .expect("not implemented response should not be invalid");
+    Box::new(future::ok(response))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io;
+
+    use header::{HeaderName, HeaderValue, TypedHeader};
+
+    struct Application;
+
+    impl MediaServer for Application {
+        type Error = io::Error;
+
+        const IMPLEMENTED: ImplementedMethods = ImplementedMethods {
+            describe: true,
+            ..ImplementedMethods::NONE
+        };
+
+        fn describe(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+            let response = Response::typed_builder()
+                .build(BytesMut::from("v=0"))
+                .unwrap();
+            Box::new(future::ok(response))
+        }
+
+        /// Not marked as implemented, so it is never dispatched to.
+        fn play(&mut self, _request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+            panic!("`PLAY` is not implemented")
+        }
+    }
+
+    fn call(method: Method) -> Response<BytesMut> {
+        let request = Request::builder()
+            .method(method)
+            .uri("rtsp://example.com/movie")
+            .build(BytesMut::new())
+            .unwrap();
+        MediaService::new(Application).call(request).wait().unwrap()
+    }
+
+    #[test]
+    fn test_media_service_dispatch() {
+        let response = call(Method::Describe);
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.body(), &BytesMut::from("v=0"));
+
+        let extension = Method::try_from("X-CUSTOM").unwrap();
+
+        for method in vec![Method::Setup, Method::Play, extension] {
+            let response = call(method);
+            assert_eq!(response.status_code(), StatusCode::NotImplemented);
+        }
+    }
+
+    #[test]
+    fn test_media_service_options() {
+        let response = call(Method::Options);
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let public = Public::try_from_header_raw(
+            &response
+                .headers()
+                .get_all(HeaderName::Public)
+                .iter()
+                .cloned()
+                .collect::<Vec<HeaderValue>>(),
+        )
+        .unwrap();
+        assert_eq!(
+            public,
+            vec![Method::Describe, Method::Options]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            response.headers().get(HeaderName::Public),
+            Some(&HeaderValue::try_from("DESCRIBE, OPTIONS").unwrap())
+        );
+    }
+}
@@ -0,0 +1,481 @@
+mod media;
+mod router;
+mod session;
+
+pub use self::media::{ImplementedMethods, MediaFuture, MediaServer, MediaService};
+pub use self::router::{
+    InvalidRoutePattern, Params, RoutePattern, RouteTarget, RoutedRequest, Router,
+};
+pub use self::session::{
+    ServerSession, SessionManager, SessionReaper, SessionService,
+    DEFAULT_SESSION_REAP_INTERVAL_DURATION,
+};
+
+use bytes::BytesMut;
+use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::{Async, Future, Poll, Stream};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_executor::{DefaultExecutor, Executor};
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_tcp::{TcpListener, TcpStream};
+use tokio_timer::Delay;
+
+use header::HeaderMap;
+use method::Method;
+use protocol::{
+    Config, Connection, ConnectionHandle, ConnectionShutdownReceiver, NewService, Service,
+    ShutdownType,
+};
+use request::Request;
+use response::Response;
+use session::DEFAULT_SESSION_TIMEOUT;
+#[cfg(feature = "tls")]
+use tls::{Handshakes, TlsAcceptor, DEFAULT_TLS_HANDSHAKE_TIMEOUT_DURATION};
+use tunnel::TunnelAcceptor;
+
+/// The duration that the server waits before accepting connections again after the listener
+/// encountered an error that is not specific to a single connection, such as running out of file
+/// descriptors.
+pub const ACCEPT_ERROR_DELAY_DURATION: Duration = Duration::from_secs(1);
+
+/// The methods that are handled by the server itself rather than the application.
+pub const SUPPORTED_METHODS: [Method; 1] = [Method::Options];
+
+/// A server that accepts TCP connections and serves requests on each of them using a service
+/// created by a service factory.
+///
+/// The server is a future that must be spawned onto an executor. It completes once it has been
+/// shut down through a [`ServerHandle`] and all of its connections have finished.
+#[must_use = "futures do nothing unless polled"]
+pub struct Server<F> {
+    /// The delay after which accepting is resumed, if the listener recently encountered an error.
+    accept_delay: Option<Delay>,
+
+    /// The configuration that is applied to every accepted connection.
+    config: Config,
+
+    /// The connections that are currently alive, along with the receivers used to determine when
+    /// they have shut down.
+    connections: Vec<(ConnectionHandle, ConnectionShutdownReceiver)>,
+
+    /// The TLS handshakes of accepted connections, if the server only accepts TLS connections.
+    /// This is set to `None` once a shutdown has been initiated.
+    #[cfg(feature = "tls")]
+    handshakes: Option<Handshakes>,
+
+    /// The listener on which connections are accepted. This is set to `None` once a shutdown has
+    /// been initiated.
+    listener: Option<TcpListener>,
+
+    /// The number of connections that are currently alive, shared with all server handles.
+    number_of_connections: Arc<AtomicUsize>,
+
+    /// The timer task that tears down expired sessions. It is driven by the server so that it
+    /// stops once the server has finished, regardless of who else holds the session manager. This
+    /// is set to `None` once the task has finished, such as when the timer has shut down.
+    reaper: Option<SessionReaper>,
+
+    /// A receiver of shutdown requests from server handles.
+    rx_shutdown: UnboundedReceiver<ShutdownType>,
+
+    /// The factory used to create a service for every accepted connection.
+    service_factory: F,
+
+    /// The registry of sessions shared by all connections.
+    session_manager: SessionManager,
+
+    /// The detector of HTTP tunnel halves among accepted connections, if HTTP tunneling is
+    /// enabled. This is set to `None` once a shutdown has been initiated.
+    tunnels: Option<TunnelAcceptor<TcpStream>>,
+
+    /// A sender of shutdown requests that is cloned for new server handles.
+    tx_shutdown: UnboundedSender<ShutdownType>,
+}
+
+impl Server<()> {
+    /// Returns a builder for a server listening on the given address.
+    pub fn bind(address: &SocketAddr) -> Builder {
+        Builder::new(address)
+    }
+}
+
+impl<F> Server<F>
+where
+    F: NewService,
+    F::Service: Service<Request = Request<BytesMut>> + Send + 'static,
+    <F::Service as Service>::Error: Send + 'static,
+    <F::Service as Service>::Future: Send + 'static,
+    <F::Service as Service>::Response: Into<Response<BytesMut, HeaderMap>> + 'static,
+{
+    /// Returns a handle that can be used to inspect the server and shut it down.
+    pub fn handle(&self) -> ServerHandle {
+        ServerHandle {
+            number_of_connections: self.number_of_connections.clone(),
+            tx_shutdown: self.tx_shutdown.clone(),
+        }
+    }
+
+    /// Returns the local address that the server is listening on. After a shutdown has been
+    /// initiated, an error will be returned.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self.listener {
+            Some(ref listener) => listener.local_addr(),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "server has been shut down",
+            )),
+        }
+    }
+
+    /// Returns the registry of sessions shared by all connections of the server.
+    pub fn session_manager(&self) -> &SessionManager {
+        &self.session_manager
+    }
+
+    /// Serves a newly accepted socket, first performing a TLS handshake if the server was
+    /// configured with a TLS acceptor, or detecting tunnel halves if HTTP tunneling is enabled.
+    fn accept_connection(&mut self, tcp_stream: TcpStream) {
+        #[cfg(feature = "tls")]
+        {
+            if let Some(ref handshakes) = self.handshakes {
+                handshakes.start(tcp_stream);
+                return;
+            }
+        }
+
+        match self.tunnels {
+            Some(ref tunnels) => tunnels.accept(tcp_stream),
+            None => self.serve_connection(tcp_stream),
+        }
+    }
+
+    /// Spawns a connection along with its request handler for the given transport and starts
+    /// tracking it.
+    ///
+    /// If the service factory fails to create a service, the transport is dropped.
+    fn serve_connection<T>(&mut self, transport: T)
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let service = match self.service_factory.new_service() {
+            Ok(service) => service,
+            Err(_) => return,
+        };
+
+        let service = SessionService::new(service, self.session_manager.clone());
+        let mut executor = DefaultExecutor::current();
+        let (connection, handler, mut handle) =
+            Connection::with_config(transport, Some(service), self.config.clone());
+
+        if executor.spawn(Box::new(connection)).is_err() {
+            return;
+        }
+
+        if let Some(handler) = handler {
+            if executor.spawn(Box::new(handler)).is_err() {
+                handle.shutdown(ShutdownType::Immediate);
+                return;
+            }
+        }
+
+        let shutdown = handle
+            .take_shutdown()
+            .expect("shutdown receiver should not have been taken");
+        self.connections.push((handle, shutdown));
+    }
+
+    /// Initiates a shutdown of the server. No more connections will be accepted, and all existing
+    /// connections will be shut down with the given shutdown type.
+    fn shutdown(&mut self, shutdown_type: ShutdownType) {
+        self.listener = None;
+
+        self.tunnels = None;
+
+        #[cfg(feature = "tls")]
+        {
+            self.handshakes = None;
+        }
+
+        for &mut (ref mut handle, _) in self.connections.iter_mut() {
+            handle.shutdown(shutdown_type);
+        }
+    }
+
+    /// Accepts all pending connections on the listener.
+    ///
+    /// If the listener encounters an error that is not specific to a single connection, such as
+    /// running out of file descriptors, the error is logged and accepting is paused for
+    /// [`ACCEPT_ERROR_DELAY_DURATION`] rather than ending the server, since the condition is
+    /// usually temporary and the existing connections must still be served.
+    fn poll_accept(&mut self) {
+        loop {
+            if let Some(ref mut delay) = self.accept_delay {
+                if let Ok(Async::NotReady) = delay.poll() {
+                    return;
+                }
+            }
+
+            self.accept_delay = None;
+
+            let result = match self.listener {
+                Some(ref mut listener) => listener.poll_accept(),
+                None => return,
+            };
+
+            match result {
+                Ok(Async::Ready((tcp_stream, _))) => self.accept_connection(tcp_stream),
+                Ok(Async::NotReady) => return,
+                Err(ref error) if is_connection_error(error) => continue,
+                Err(error) => {
+                    error!("accepting connection failed: {}", error);
+                    self.accept_delay =
+                        Some(Delay::new(Instant::now() + ACCEPT_ERROR_DELAY_DURATION));
+                }
+            }
+        }
+    }
+
+    /// Serves all connections whose TLS handshakes have completed.
+    #[cfg(feature = "tls")]
+    fn poll_handshakes(&mut self) {
+        loop {
+            let stream = match self.handshakes {
+                Some(ref mut handshakes) => handshakes.poll_stream(),
+                None => return,
+            };
+
+            match stream {
+                Some(stream) => self.serve_connection(stream),
+                None => return,
+            }
+        }
+    }
+
+    /// Serves all connections that have been detected or joined by the tunnel acceptor.
+    fn poll_tunnels(&mut self) {
+        loop {
+            let stream = match self.tunnels {
+                Some(ref mut tunnels) => tunnels.poll(),
+                None => return,
+            };
+
+            match stream {
+                Ok(Async::Ready(Some(stream))) => self.serve_connection(stream),
+                _ => return,
+            }
+        }
+    }
+
+    /// Stops tracking all connections that have finished shutting down.
+    fn poll_connections(&mut self) {
+        let mut index = 0;
+
+        while index < self.connections.len() {
+            match self.connections[index].1.poll() {
+                Ok(Async::NotReady) => index += 1,
+                _ => {
+                    self.connections.swap_remove(index);
+                }
+            }
+        }
+
+        self.number_of_connections
+            .store(self.connections.len(), Ordering::SeqCst);
+    }
+
+    /// Drives the task that tears down expired sessions, dropping it once it has finished.
+    fn poll_reaper(&mut self) {
+        let finished = match self.reaper {
+            Some(ref mut reaper) => match reaper.poll() {
+                Ok(Async::NotReady) => false,
+                Ok(Async::Ready(())) | Err(()) => true,
+            },
+            None => false,
+        };
+
+        if finished {
+            debug!("session reaper finished, expired sessions will only be removed on use");
+            self.reaper = None;
+        }
+    }
+
+    /// Checks whether a shutdown has been requested through a server handle.
+    fn poll_shutdown(&mut self) {
+        while let Ok(Async::Ready(Some(shutdown_type))) = self.rx_shutdown.poll() {
+            self.shutdown(shutdown_type);
+        }
+    }
+}
+
+impl<F> Future for Server<F>
+where
+    F: NewService,
+    F::Service: Service<Request = Request<BytesMut>> + Send + 'static,
+    <F::Service as Service>::Error: Send + 'static,
+    <F::Service as Service>::Future: Send + 'static,
+    <F::Service as Service>::Response: Into<Response<BytesMut, HeaderMap>> + 'static,
+{
+    type Item = ();
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        self.poll_reaper();
+        self.poll_shutdown();
+        self.poll_accept();
+        #[cfg(feature = "tls")]
+        self.poll_handshakes();
+        self.poll_tunnels();
+        self.poll_connections();
+
+        if self.listener.is_none() && self.connections.is_empty() {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+/// A builder for a [`Server`].
+pub struct Builder {
+    address: SocketAddr,
+    config: Config,
+    http_tunneling: bool,
+    session_timeout_duration: Duration,
+    #[cfg(feature = "tls")]
+    tls_acceptor: Option<TlsAcceptor>,
+    #[cfg(feature = "tls")]
+    tls_handshake_timeout_duration: Duration,
+}
+
+impl Builder {
+    /// Constructs a new builder for a server listening on the given address.
+    pub fn new(address: &SocketAddr) -> Self {
+        Builder {
+            address: *address,
+            config: Config::default(),
+            http_tunneling: false,
+            session_timeout_duration: DEFAULT_SESSION_TIMEOUT,
+            #[cfg(feature = "tls")]
+            tls_acceptor: None,
+            #[cfg(feature = "tls")]
+            tls_handshake_timeout_duration: DEFAULT_TLS_HANDSHAKE_TIMEOUT_DURATION,
+        }
+    }
+
+    /// Sets the configuration that is applied to every accepted connection.
+    pub fn config(&mut self, config: Config) -> &mut Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets whether RTSP over HTTP tunneling is accepted in addition to plain RTSP connections. See
+    /// the [`tunnel`] module for details. Tunneling is not used for TLS connections.
+    ///
+    /// [`tunnel`]: ../tunnel/index.html
+    pub fn http_tunneling(&mut self, enabled: bool) -> &mut Self {
+        self.http_tunneling = enabled;
+        self
+    }
+
+    /// Sets the duration after which sessions expire if they are not refreshed by a request.
+    pub fn session_timeout_duration(&mut self, duration: Duration) -> &mut Self {
+        self.session_timeout_duration = duration;
+        self
+    }
+
+    /// Sets the acceptor used to perform a TLS handshake on every accepted connection, in which
+    /// case the server will only serve TLS connections. Connections whose handshake fails are
+    /// dropped.
+    #[cfg(feature = "tls")]
+    pub fn tls_acceptor(&mut self, acceptor: TlsAcceptor) -> &mut Self {
+        self.tls_acceptor = Some(acceptor);
+        self
+    }
+
+    /// Sets the duration within which the TLS handshake of an accepted connection must complete.
+    /// Connections whose handshake takes longer are dropped.
+    #[cfg(feature = "tls")]
+    pub fn tls_handshake_timeout_duration(&mut self, duration: Duration) -> &mut Self {
+        self.tls_handshake_timeout_duration = duration;
+        self
+    }
+
+    /// Binds to the address and constructs a server that will serve every accepted connection
+    /// with a new service created by the given factory.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if binding to the address failed.
+    pub fn serve<F>(&self, service_factory: F) -> io::Result<Server<F>>
+    where
+        F: NewService,
+        F::Service: Service<Request = Request<BytesMut>> + Send + 'static,
+        <F::Service as Service>::Error: Send + 'static,
+        <F::Service as Service>::Future: Send + 'static,
+        <F::Service as Service>::Response: Into<Response<BytesMut, HeaderMap>> + 'static,
+    {
+        let listener = TcpListener::bind(&self.address)?;
+        let (tx_shutdown, rx_shutdown) = unbounded();
+        let session_manager = SessionManager::new(self.session_timeout_duration);
+
+        Ok(Server {
+            accept_delay: None,
+            config: self.config.clone(),
+            connections: Vec::new(),
+            #[cfg(feature = "tls")]
+            handshakes: self
+                .tls_acceptor
+                .clone()
+                .map(|acceptor| Handshakes::new(acceptor, self.tls_handshake_timeout_duration)),
+            listener: Some(listener),
+            number_of_connections: Arc::new(AtomicUsize::new(0)),
+            reaper: Some(session_manager.reaper(DEFAULT_SESSION_REAP_INTERVAL_DURATION)),
+            rx_shutdown,
+            service_factory,
+            session_manager,
+            tunnels: if self.http_tunneling {
+                Some(TunnelAcceptor::new())
+            } else {
+                None
+            },
+            tx_shutdown,
+        })
+    }
+}
+
+/// A handle to a running [`Server`].
+#[derive(Clone)]
+pub struct ServerHandle {
+    number_of_connections: Arc<AtomicUsize>,
+    tx_shutdown: UnboundedSender<ShutdownType>,
+}
+
+impl ServerHandle {
+    /// Returns the number of connections that are currently alive.
+    pub fn number_of_connections(&self) -> usize {
+        self.number_of_connections.load(Ordering::SeqCst)
+    }
+
+    /// Initiates a shutdown of the server. The server will stop accepting connections and all
+    /// existing connections will be shut down with the given shutdown type. The server future will
+    /// complete once all connections have finished.
+    pub fn shutdown(&mut self, shutdown_type: ShutdownType) {
+        self.tx_shutdown.unbounded_send(shutdown_type).ok();
+    }
+}
+
+/// Determines whether the given error from accepting a connection only affects that connection,
+/// in which case the server should continue accepting.
+fn is_connection_error(error: &io::Error) -> bool {
+    match error.kind() {
+        io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::Interrupted => true,
+        _ => false,
+    }
+}
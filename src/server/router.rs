@@ -0,0 +1,537 @@
+//! URI Router
+//!
+//! This module contains a [`Router`] that dispatches requests to one of several services based on
+//! the path of the request URI. Patterns consist of literal segments (`"live"`), named captures
+//! that match exactly one segment (`":stream"`), and an optional named wildcard as the final
+//! segment that matches the remainder of the path (`"*path"`).
+//!
+//! A route also matches the per-track control URIs of the presentation it describes, which are
+//! assumed to consist of the aggregate URI followed by one additional segment, such as
+//! `"rtsp://example.com/live/camera/trackID=1"` for the route `"/live/:stream"`. Since a wildcard
+//! matches any number of segments, routes with a wildcard only ever match aggregate URIs.
+
+use bytes::BytesMut;
+use futures::{future, Future};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use protocol::Service;
+use request::Request;
+use response::Response;
+use status::StatusCode;
+use uri::RequestURIField;
+
+type BoxedService<E> = Box<
+    Service<
+            Request = RoutedRequest,
+            Response = Response<BytesMut>,
+            Error = E,
+            Future = Box<Future<Item = Response<BytesMut>, Error = E> + Send + 'static>,
+        > + Send,
+>;
+
+/// A service that dispatches requests to other services based on the path of the request URI.
+///
+/// Routes are tried in the order in which they were added, with matches of aggregate URIs taking
+/// precedence over matches of per-track control URIs. Requests with the request URI `"*"` are
+/// dispatched to the service set by [`Router::any`]. All other requests are answered with
+/// `404 Not Found`.
+pub struct Router<E> {
+    any: Option<BoxedService<E>>,
+    routes: Vec<(RoutePattern, BoxedService<E>)>,
+}
+
+impl<E> Router<E>
+where
+    E: Into<Box<Error + Send + Sync + 'static>> + Send + 'static,
+{
+    pub fn new() -> Self {
+        Router {
+            any: None,
+            routes: Vec::new(),
+        }
+    }
+
+    /// Sets the service that handles requests with the request URI `"*"`, such as
+    /// `OPTIONS * RTSP/2.0`.
+    pub fn any<S>(&mut self, service: S) -> &mut Self
+    where
+        S: Service<Request = RoutedRequest, Response = Response<BytesMut>, Error = E>
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        self.any = Some(Box::new(BoxService(service)));
+        self
+    }
+
+    /// Adds a route that dispatches requests whose path matches the given pattern to the given
+    /// service.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the pattern is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate bytes;
+    /// # extern crate futures;
+    /// # extern crate rtsp;
+    /// #
+    /// use bytes::BytesMut;
+    /// use futures::{future, Future};
+    /// use rtsp::server::{RoutedRequest, Router};
+    /// use rtsp::{Response, Service};
+    /// use std::io;
+    ///
+    /// struct Stream;
+    ///
+    /// impl Service for Stream {
+    ///     type Request = RoutedRequest;
+    ///     type Response = Response<BytesMut>;
+    ///     type Error = io::Error;
+    ///     type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+    ///
+    ///     fn call(&mut self, request: Self::Request) -> Self::Future {
+    ///         let stream = request.params().get("stream").unwrap();
+    ///         let response = Response::builder().build(BytesMut::from(stream)).unwrap();
+    ///         Box::new(future::ok(response))
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut router = Router::new();
+    /// router.route("/live/:stream", Stream).unwrap();
+    /// assert!(router.route("/vod/*path/extra", Stream).is_err());
+    /// # }
+    /// ```
+    pub fn route<S>(&mut self, pattern: &str, service: S) -> Result<&mut Self, InvalidRoutePattern>
+    where
+        S: Service<Request = RoutedRequest, Response = Response<BytesMut>, Error = E>
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        let pattern = RoutePattern::try_from(pattern)?;
+        self.routes.push((pattern, Box::new(BoxService(service))));
+        Ok(self)
+    }
+
+    /// Finds the index of the route matching the given path segments, along with the matched
+    /// parameters and target.
+    fn find(&self, segments: &[String]) -> Option<(usize, Params, RouteTarget)> {
+        for (index, &(ref pattern, _)) in self.routes.iter().enumerate() {
+            if let Some(params) = pattern.matches(segments) {
+                return Some((index, params, RouteTarget::Aggregate));
+            }
+        }
+
+        let (control, segments) = segments.split_last()?;
+
+        for (index, &(ref pattern, _)) in self.routes.iter().enumerate() {
+            if pattern.has_wildcard() {
+                continue;
+            }
+
+            if let Some(params) = pattern.matches(segments) {
+                return Some((index, params, RouteTarget::Track(control.clone())));
+            }
+        }
+
+        None
+    }
+}
+
+impl<E> Default for Router<E>
+where
+    E: Into<Box<Error + Send + Sync + 'static>> + Send + 'static,
+{
+    fn default() -> Self {
+        Router::new()
+    }
+}
+
+impl<E> Service for Router<E>
+where
+    E: Into<Box<Error + Send + Sync + 'static>> + Send + 'static,
+{
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = E;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let segments = match request.uri() {
+            RequestURIField::Any => {
+                return match self.any {
+                    Some(ref mut service) => service.call(RoutedRequest {
+                        params: Params::default(),
+                        request,
+                        target: RouteTarget::Any,
+                    }),
+                    None => Box::new(future::ok(not_found_response())),
+                };
+            }
+            RequestURIField::URI(uri) => uri
+                .path_segments()
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| segment.to_string())
+                .collect::<Vec<_>>(),
+        };
+
+        match self.find(&segments) {
+            Some((index, params, target)) => self.routes[index].1.call(RoutedRequest {
+                params,
+                request,
+                target,
+            }),
+            None => Box::new(future::ok(not_found_response())),
+        }
+    }
+}
+
+/// A request that has been matched to a route, along with the parameters captured by the route
+/// pattern.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoutedRequest {
+    params: Params,
+    request: Request<BytesMut>,
+    target: RouteTarget,
+}
+
+impl RoutedRequest {
+    pub fn into_request(self) -> Request<BytesMut> {
+        self.request
+    }
+
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    pub fn request(&self) -> &Request<BytesMut> {
+        &self.request
+    }
+
+    pub fn request_mut(&mut self) -> &mut Request<BytesMut> {
+        &mut self.request
+    }
+
+    pub fn target(&self) -> &RouteTarget {
+        &self.target
+    }
+}
+
+/// The resource that a routed request refers to.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RouteTarget {
+    /// The request URI was `"*"`.
+    Any,
+
+    /// The request URI matched the route pattern itself and refers to the aggregate control URI of
+    /// the presentation.
+    Aggregate,
+
+    /// The request URI matched the route pattern followed by one additional segment and refers to
+    /// the control URI of a single track. The additional segment is included.
+    Track(String),
+}
+
+/// The parameters captured by the named captures and wildcard of a route pattern.
+///
+/// The values are percent-encoded just as they appear in the request URI. The value of a wildcard
+/// consists of the remaining segments joined by `'/'`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Params(Vec<(String, String)>);
+
+impl Params {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|&&(ref key, _)| key == name)
+            .map(|&(_, ref value)| value.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|&(ref key, ref value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum PatternSegment {
+    Capture(String),
+    Literal(String),
+    Wildcard(String),
+}
+
+/// A parsed route pattern, such as `"/live/:stream"` or `"/vod/*path"`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RoutePattern {
+    segments: Vec<PatternSegment>,
+}
+
+impl RoutePattern {
+    /// Returns whether the pattern ends in a wildcard.
+    pub fn has_wildcard(&self) -> bool {
+        match self.segments.last() {
+            Some(PatternSegment::Wildcard(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Matches the given non-empty path segments against the pattern, returning the captured
+    /// parameters if they match.
+    fn matches(&self, segments: &[String]) -> Option<Params> {
+        let mut params = Vec::new();
+        let mut segments = segments.iter();
+
+        for pattern_segment in self.segments.iter() {
+            match pattern_segment {
+                PatternSegment::Capture(name) => {
+                    params.push((name.clone(), segments.next()?.clone()));
+                }
+                PatternSegment::Literal(literal) => {
+                    if segments.next()? != literal {
+                        return None;
+                    }
+                }
+                PatternSegment::Wildcard(name) => {
+                    let tail = segments.by_ref().map(String::as_str).collect::<Vec<_>>();
+                    params.push((name.clone(), tail.join("/")));
+                }
+            }
+        }
+
+        if segments.next().is_some() {
+            None
+        } else {
+            Some(Params(params))
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for RoutePattern {
+    type Error = InvalidRoutePattern;
+
+    /// Parses a route pattern. Empty segments are ignored, so `"/live/:stream/"` and
+    /// `"live/:stream"` are equivalent. Capture and wildcard names must not be empty or repeated,
+    /// and a wildcard may only appear as the final segment.
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut names: Vec<&str> = Vec::new();
+        let mut segments = Vec::new();
+
+        for segment in value.split('/').filter(|segment| !segment.is_empty()) {
+            if segments.last().map_or(false, |segment| match segment {
+                PatternSegment::Wildcard(_) => true,
+                _ => false,
+            }) {
+                return Err(InvalidRoutePattern);
+            }
+
+            let (name, segment) = if segment.starts_with(':') {
+                let name = &segment[1..];
+                (name, PatternSegment::Capture(name.to_string()))
+            } else if segment.starts_with('*') {
+                let name = &segment[1..];
+                (name, PatternSegment::Wildcard(name.to_string()))
+            } else {
+                segments.push(PatternSegment::Literal(segment.to_string()));
+                continue;
+            };
+
+            if name.is_empty() || names.contains(&name) {
+                return Err(InvalidRoutePattern);
+            }
+
+            names.push(name);
+            segments.push(segment);
+        }
+
+        Ok(RoutePattern { segments })
+    }
+}
+
+/// A possible error value when converting to a [`RoutePattern`] from a `&str`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InvalidRoutePattern;
+
+impl fmt::Display for InvalidRoutePattern {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for InvalidRoutePattern {
+    fn description(&self) -> &str {
+        "invalid route pattern"
+    }
+}
+
+/// Boxes the future of the wrapped service so that services of different types can be stored
+/// together.
+struct BoxService<S>(S);
+
+impl<S> Service for BoxService<S>
+where
+    S: Service<Request = RoutedRequest, Response = Response<BytesMut>>,
+    S::Future: Send + 'static,
+{
+    type Request = RoutedRequest;
+    type Response = Response<BytesMut>;
+    type Error = S::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        Box::new(self.0.call(request))
+    }
+}
+
+fn not_found_response() -> Response<BytesMut> {
+    Response::builder()
+        .status_code(StatusCode::NotFound)
+        .build(BytesMut::new())
+        .expect("not found response should not be invalid")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io;
+
+    use method::Method;
+
+    /// Responds with the route target and parameters in the body.
+    struct Echo(&'static str);
+
+    impl Service for Echo {
+        type Request = RoutedRequest;
+        type Response = Response<BytesMut>;
+        type Error = io::Error;
+        type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+        fn call(&mut self, request: Self::Request) -> Self::Future {
+            let mut body = format!("{} {:?}", self.0, request.target());
+
+            for (name, value) in request.params().iter() {
+                body.push_str(&format!(" {}={}", name, value));
+            }
+
+            let response = Response::builder().build(BytesMut::from(body)).unwrap();
+            Box::new(future::ok(response))
+        }
+    }
+
+    fn router() -> Router<io::Error> {
+        let mut router = Router::new();
+        router
+            .route("/live/:stream", Echo("live"))
+            .unwrap()
+            .route("/live/camera", Echo("camera"))
+            .unwrap()
+            .route("/vod/*path", Echo("vod"))
+            .unwrap()
+            .any(Echo("any"));
+        router
+    }
+
+    fn call(router: &mut Router<io::Error>, uri: &str) -> Response<BytesMut> {
+        let request = Request::builder()
+            .method(Method::Options)
+            .uri(uri)
+            .build(BytesMut::new())
+            .unwrap();
+        router.call(request).wait().unwrap()
+    }
+
+    #[test]
+    fn test_route_pattern_parse() {
+        assert!(RoutePattern::try_from("/live/:stream").is_ok());
+        assert!(RoutePattern::try_from("vod/*path/").is_ok());
+        assert!(RoutePattern::try_from("/").is_ok());
+        assert_eq!(
+            RoutePattern::try_from("/vod/*path/extra"),
+            Err(InvalidRoutePattern)
+        );
+        assert_eq!(RoutePattern::try_from("/live/:"), Err(InvalidRoutePattern));
+        assert_eq!(
+            RoutePattern::try_from("/:name/:name"),
+            Err(InvalidRoutePattern)
+        );
+    }
+
+    #[test]
+    fn test_router_dispatch() {
+        let mut router = router();
+
+        let response = call(&mut router, "rtsp://example.com/live/movie");
+        assert_eq!(
+            response.body(),
+            &BytesMut::from("live Aggregate stream=movie")
+        );
+
+        let response = call(&mut router, "rtsp://example.com/live/movie/");
+        assert_eq!(
+            response.body(),
+            &BytesMut::from("live Aggregate stream=movie")
+        );
+
+        let response = call(&mut router, "rtsp://example.com/live/movie/trackID=1");
+        assert_eq!(
+            response.body(),
+            &BytesMut::from("live Track(\"trackID=1\") stream=movie")
+        );
+
+        let response = call(&mut router, "rtsp://example.com/vod/a/b/trackID=1");
+        assert_eq!(
+            response.body(),
+            &BytesMut::from("vod Aggregate path=a/b/trackID=1")
+        );
+
+        let response = call(&mut router, "*");
+        assert_eq!(response.body(), &BytesMut::from("any Any"));
+
+        let response = call(&mut router, "rtsp://example.com/other");
+        assert_eq!(response.status_code(), StatusCode::NotFound);
+
+        let response = call(&mut router, "rtsp://example.com/live/movie/track/extra");
+        assert_eq!(response.status_code(), StatusCode::NotFound);
+    }
+
+    #[test]
+    fn test_router_precedence() {
+        let mut router = router();
+
+        // The first matching route wins, so the literal route added after the capture route is
+        // never matched.
+
+        let response = call(&mut router, "rtsp://example.com/live/camera");
+        assert_eq!(
+            response.body(),
+            &BytesMut::from("live Aggregate stream=camera")
+        );
+
+        // An aggregate match takes precedence over a track match.
+
+        let response = call(&mut router, "rtsp://example.com/vod");
+        assert_eq!(response.body(), &BytesMut::from("vod Aggregate path="));
+    }
+
+    #[test]
+    fn test_router_any_not_found() {
+        let mut router = Router::<io::Error>::new();
+        let response = call(&mut router, "*");
+        assert_eq!(response.status_code(), StatusCode::NotFound);
+    }
+}
@@ -0,0 +1,730 @@
+//! Server Session Management
+//!
+//! This module contains the registry of sessions that is owned by a [`super::Server`]. Sessions are
+//! created when a `SETUP` request succeeds, refreshed by every request that carries a `"Session"`
+//! header, and torn down either by a `TEARDOWN` request or by a background task once they expire.
+//! Each session also tracks its [`StateMachine`] so that requests that are not valid in the current
+//! state are rejected.
+
+use bytes::BytesMut;
+use chrono::{self, offset, DateTime, Utc};
+use fnv::FnvHashMap;
+use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::{future, Async, Future, Poll, Stream};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::{Duration, Instant};
+use tokio_timer::Interval;
+
+use header::types::{PipelinedRequests, Session as SessionHeader};
+use header::{HeaderMap, HeaderName, HeaderValue, InvalidTypedHeader, TypedHeader};
+use method::Method;
+use protocol::Service;
+use request::Request;
+use response::Response;
+use session::{Session, SessionID, DEFAULT_SESSION_TIMEOUT};
+use state::StateMachine;
+use status::StatusCode;
+
+/// The interval at which the background task checks for expired sessions.
+pub const DEFAULT_SESSION_REAP_INTERVAL_DURATION: Duration = Duration::from_secs(1);
+
+/// A session as it is tracked by the server.
+#[derive(Clone, Debug)]
+pub struct ServerSession {
+    expire_time: DateTime<Utc>,
+    id: SessionID,
+    state_machine: StateMachine,
+    timeout_duration: Duration,
+}
+
+impl ServerSession {
+    /// Constructs a new session with the given ID that expires after the given timeout unless it
+    /// is refreshed.
+    pub fn new(id: SessionID, timeout_duration: Duration) -> Self {
+        let mut session = ServerSession {
+            expire_time: offset::Utc::now(),
+            id,
+            state_machine: StateMachine::new(),
+            timeout_duration,
+        };
+        session.touch();
+        session
+    }
+
+    pub fn state_machine(&self) -> &StateMachine {
+        &self.state_machine
+    }
+
+    pub fn state_machine_mut(&mut self) -> &mut StateMachine {
+        &mut self.state_machine
+    }
+
+    /// Returns the duration after which the session expires if it is not refreshed.
+    pub fn timeout_duration(&self) -> Duration {
+        self.timeout_duration
+    }
+
+    /// Refreshes the session so that it expires one timeout duration from now.
+    pub fn touch(&mut self) {
+        let timeout_duration = self.timeout_duration;
+
+        if self.set_timeout(timeout_duration).is_err() {
+            self.expire_time = chrono::MAX_DATE.and_hms(0, 0, 0);
+        }
+    }
+}
+
+impl Session for ServerSession {
+    fn expire_time(&self) -> DateTime<Utc> {
+        self.expire_time
+    }
+
+    fn id(&self) -> &SessionID {
+        &self.id
+    }
+
+    fn set_expire_time(&mut self, expire_time: DateTime<Utc>) {
+        self.expire_time = expire_time;
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), ()> {
+        let timeout = chrono::Duration::from_std(timeout).map_err(|_| ())?;
+        self.expire_time = offset::Utc::now().checked_add_signed(timeout).ok_or(())?;
+        Ok(())
+    }
+}
+
+/// The sessions bound to the identifiers of `"Pipelined-Requests"` headers on a single connection.
+type Pipelines = FnvHashMap<PipelinedRequests, SessionID>;
+
+struct Sessions {
+    /// Senders that are notified of the IDs of sessions that have expired.
+    expired_senders: Vec<UnboundedSender<SessionID>>,
+
+    /// The pipelines of every [`SessionService`] using this registry, so that they can be evicted
+    /// along with the sessions they are bound to.
+    pipelines: Vec<Weak<Mutex<Pipelines>>>,
+
+    sessions: FnvHashMap<SessionID, ServerSession>,
+}
+
+impl Sessions {
+    /// Removes the session with the given ID along with any pipelines bound to it, returning
+    /// whether the session existed.
+    fn remove(&mut self, id: &SessionID) -> bool {
+        self.pipelines
+            .retain(|pipelines| match pipelines.upgrade() {
+                Some(pipelines) => {
+                    lock_pipelines(&pipelines).retain(|_, pipeline_id| pipeline_id != id);
+                    true
+                }
+                None => false,
+            });
+
+        self.sessions.remove(id).is_some()
+    }
+
+    fn notify_expired(&mut self, id: &SessionID) {
+        self.expired_senders
+            .retain(|sender| sender.unbounded_send(id.clone()).is_ok());
+    }
+}
+
+/// A registry of the sessions that are active on a server.
+///
+/// Cloning the manager results in a new handle to the same registry.
+#[derive(Clone)]
+pub struct SessionManager {
+    inner: Arc<Mutex<Sessions>>,
+    timeout_duration: Duration,
+}
+
+impl SessionManager {
+    /// Constructs a new session manager whose sessions expire after the given duration unless
+    /// they are refreshed.
+    pub fn new(timeout_duration: Duration) -> Self {
+        SessionManager {
+            inner: Arc::new(Mutex::new(Sessions {
+                expired_senders: Vec::new(),
+                pipelines: Vec::new(),
+                sessions: FnvHashMap::default(),
+            })),
+            timeout_duration,
+        }
+    }
+
+    /// Returns whether a session with the given ID exists and has not expired.
+    pub fn contains_session(&self, id: &SessionID) -> bool {
+        self.lock()
+            .sessions
+            .get(id)
+            .map_or(false, |session| !session.is_expired())
+    }
+
+    /// Creates a new session with a randomly generated ID, returning the ID.
+    pub fn create_session(&self) -> SessionID {
+        let mut inner = self.lock();
+
+        loop {
+            let id = SessionID::random();
+
+            if !inner.sessions.contains_key(&id) {
+                let session = ServerSession::new(id.clone(), self.timeout_duration);
+                inner.sessions.insert(id.clone(), session);
+                return id;
+            }
+        }
+    }
+
+    /// Returns a stream of the IDs of sessions that have expired from now on. Sessions that are
+    /// removed explicitly are not included.
+    pub fn expired_sessions(&self) -> UnboundedReceiver<SessionID> {
+        let (tx_expired, rx_expired) = unbounded();
+        self.lock().expired_senders.push(tx_expired);
+        rx_expired
+    }
+
+    /// Registers a session with the given ID. This is useful when the ID is chosen by the
+    /// application rather than the manager.
+    ///
+    /// # Return Value
+    ///
+    /// If a session with the given ID already exists, `false` is returned and the existing session
+    /// is left untouched.
+    pub fn insert_session(&self, id: SessionID) -> bool {
+        let mut inner = self.lock();
+
+        if inner.sessions.contains_key(&id) {
+            return false;
+        }
+
+        let session = ServerSession::new(id.clone(), self.timeout_duration);
+        inner.sessions.insert(id, session);
+        true
+    }
+
+    /// Returns the number of sessions that are currently tracked.
+    pub fn number_of_sessions(&self) -> usize {
+        self.lock().sessions.len()
+    }
+
+    /// Refreshes the session with the given ID so that it expires one timeout duration from now.
+    ///
+    /// # Return Value
+    ///
+    /// If the session does not exist or has already expired, `false` is returned.
+    pub fn refresh_session(&self, id: &SessionID) -> bool {
+        let mut inner = self.lock();
+        let expired = match inner.sessions.get_mut(id) {
+            Some(ref session) if session.is_expired() => true,
+            Some(session) => {
+                session.touch();
+                return true;
+            }
+            None => return false,
+        };
+
+        if expired {
+            inner.remove(id);
+            inner.notify_expired(id);
+        }
+
+        false
+    }
+
+    /// Removes all sessions that have expired, notifying any expired session streams.
+    ///
+    /// # Return Value
+    ///
+    /// The IDs of the removed sessions are returned.
+    pub fn remove_expired_sessions(&self) -> Vec<SessionID> {
+        let mut inner = self.lock();
+        let expired = inner
+            .sessions
+            .values()
+            .filter(|session| session.is_expired())
+            .map(|session| session.id().clone())
+            .collect::<Vec<_>>();
+
+        for id in expired.iter() {
+            inner.remove(id);
+            inner.notify_expired(id);
+        }
+
+        expired
+    }
+
+    /// Removes the session with the given ID, returning whether it existed.
+    pub fn remove_session(&self, id: &SessionID) -> bool {
+        self.lock().remove(id)
+    }
+
+    /// Returns a future that periodically removes expired sessions. The future completes once all
+    /// handles to the session manager have been dropped.
+    pub fn reaper(&self, interval_duration: Duration) -> SessionReaper {
+        SessionReaper {
+            interval: Interval::new(Instant::now() + interval_duration, interval_duration),
+            manager: Arc::downgrade(&self.inner),
+            timeout_duration: self.timeout_duration,
+        }
+    }
+
+    /// Returns the duration after which sessions expire if they are not refreshed.
+    pub fn timeout_duration(&self) -> Duration {
+        self.timeout_duration
+    }
+
+    /// Calls the given function with the session with the given ID, returning its result. If the
+    /// session does not exist, `None` is returned.
+    pub fn with_session<F, T>(&self, id: &SessionID, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut ServerSession) -> T,
+    {
+        self.lock().sessions.get_mut(id).map(f)
+    }
+
+    fn lock(&self) -> MutexGuard<Sessions> {
+        self.inner.lock().expect("locking `inner` should not error")
+    }
+
+    /// Registers the pipelines of a [`SessionService`] so that they are evicted whenever the
+    /// session they are bound to is removed.
+    fn register_pipelines(&self, pipelines: &Arc<Mutex<Pipelines>>) {
+        let mut inner = self.lock();
+        inner
+            .pipelines
+            .retain(|pipelines| pipelines.upgrade().is_some());
+        inner.pipelines.push(Arc::downgrade(pipelines));
+    }
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        SessionManager::new(DEFAULT_SESSION_TIMEOUT)
+    }
+}
+
+/// A background task that periodically tears down expired sessions.
+#[must_use = "futures do nothing unless polled"]
+pub struct SessionReaper {
+    interval: Interval,
+    manager: Weak<Mutex<Sessions>>,
+    timeout_duration: Duration,
+}
+
+impl Future for SessionReaper {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            match self.interval.poll() {
+                Ok(Async::Ready(Some(_))) => {
+                    let inner = match self.manager.upgrade() {
+                        Some(inner) => inner,
+                        None => return Ok(Async::Ready(())),
+                    };
+                    let manager = SessionManager {
+                        inner,
+                        timeout_duration: self.timeout_duration,
+                    };
+                    manager.remove_expired_sessions();
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(None)) | Err(_) => return Ok(Async::Ready(())),
+            }
+        }
+    }
+}
+
+/// A service that manages the sessions of requests before forwarding them to the inner service.
+///
+/// * A successful `SETUP` request without a `"Session"` header creates a new session. If the
+///   response of the inner service does not contain a `"Session"` header, one is added with a
+///   randomly generated ID, otherwise the ID chosen by the inner service is registered.
+/// * Any request with a `"Session"` header refreshes the session. If the session does not exist,
+///   the request is answered with `454 Session Not Found` without being forwarded.
+/// * Any request that is not valid in the state of its session, or in the initial state if it has
+///   no `"Session"` header, is answered with `455 Method Not Valid in This State` along with an
+///   `"Allow"` header without being forwarded. Successful responses drive the state transitions.
+/// * A successful `TEARDOWN` request whose response does not contain a `"Session"` header removes
+///   the session.
+/// * Requests with a `"Pipelined-Requests"` header but without a `"Session"` header are bound to
+///   the session created by the first successful `SETUP` request with the same identifier. If
+///   there is no such session, for example because that `SETUP` request failed, any request other
+///   than `SETUP` is answered with `454 Session Not Found` without being forwarded. Identifiers are
+///   tracked per service, that is, per connection, and are forgotten once their session is removed.
+#[derive(Clone)]
+pub struct SessionService<S> {
+    manager: SessionManager,
+    pipelines: Arc<Mutex<Pipelines>>,
+    service: S,
+}
+
+impl<S> SessionService<S> {
+    pub fn new(service: S, manager: SessionManager) -> Self {
+        let pipelines = Arc::new(Mutex::new(FnvHashMap::default()));
+        manager.register_pipelines(&pipelines);
+
+        SessionService {
+            manager,
+            pipelines,
+            service,
+        }
+    }
+
+    pub fn manager(&self) -> &SessionManager {
+        &self.manager
+    }
+}
+
+impl<S> Service for SessionService<S>
+where
+    S: Service<Request = Request<BytesMut>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Into<Response<BytesMut, HeaderMap>> + 'static,
+{
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = S::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, mut request: Self::Request) -> Self::Future {
+        let mut header_values = request
+            .headers()
+            .get_all(HeaderName::Session)
+            .iter()
+            .cloned()
+            .collect::<Vec<HeaderValue>>();
+        let manager = self.manager.clone();
+        let method = request.method().clone();
+        let pipeline = if header_values.is_empty() {
+            match pipelined_requests(&request) {
+                Ok(pipeline) => pipeline,
+                Err(_) => return Box::new(future::ok(status_response(StatusCode::BadRequest))),
+            }
+        } else {
+            None
+        };
+
+        // A pipelined request is handled as if it had the `"Session"` header of the session that
+        // was created for the pipeline. Only a `SETUP` request can create that session.
+
+        if let Some(pipeline) = pipeline {
+            match self.lock_pipelines().get(&pipeline) {
+                Some(id) => {
+                    let session = SessionHeader::without_timeout(id.as_str())
+                        .expect("session ID should be valid");
+                    header_values = session.to_header_raw();
+                    request
+                        .headers_mut()
+                        .insert(HeaderName::Session, header_values[0].clone());
+                }
+                None if method != Method::Setup => {
+                    return Box::new(future::ok(status_response(StatusCode::SessionNotFound)))
+                }
+                None => (),
+            }
+        }
+
+        if header_values.is_empty() {
+            let state_machine = StateMachine::new();
+
+            if !state_machine.is_method_allowed(&method) {
+                return Box::new(future::ok(state_machine.method_not_valid_response()));
+            }
+
+            if method != Method::Setup {
+                return Box::new(self.service.call(request).map(Into::into));
+            }
+
+            let pipelines = self.pipelines.clone();
+
+            return Box::new(self.service.call(request).map(move |response| {
+                let mut response = response.into();
+                let id = if response.status_code().is_success() {
+                    register_session(&manager, &mut response)
+                } else {
+                    None
+                };
+
+                if let (Some(pipeline), Some(id)) = (pipeline, id) {
+                    lock_pipelines(&pipelines).insert(pipeline, id);
+                }
+
+                response
+            }));
+        }
+
+        let id = match SessionHeader::try_from_header_raw(&header_values) {
+            Ok(session) => session.id().clone(),
+            Err(_) => return Box::new(future::ok(status_response(StatusCode::BadRequest))),
+        };
+
+        let state_machine = if manager.refresh_session(&id) {
+            manager.with_session(&id, |session| session.state_machine().clone())
+        } else {
+            None
+        };
+
+        match state_machine {
+            Some(ref state_machine) if !state_machine.is_method_allowed(&method) => {
+                return Box::new(future::ok(state_machine.method_not_valid_response()));
+            }
+            Some(_) => (),
+            None => return Box::new(future::ok(status_response(StatusCode::SessionNotFound))),
+        }
+
+        Box::new(self.service.call(request).map(move |response| {
+            let mut response = response.into();
+            let status_code = response.status_code();
+
+            // A `TEARDOWN` response with a `"Session"` header indicates that only part of an
+            // aggregate session was torn down, in which case the session remains as it is.
+
+            if method == Method::Teardown {
+                if status_code.is_success() && !response.headers().contains_key(HeaderName::Session)
+                {
+                    manager.remove_session(&id);
+                }
+            } else {
+                manager.with_session(&id, |session| {
+                    session
+                        .state_machine_mut()
+                        .handle_response(&method, status_code)
+                });
+            }
+
+            if method != Method::Teardown && !response.headers().contains_key(HeaderName::Session) {
+                let session = SessionHeader::without_timeout(id.as_str())
+                    .expect("session ID should be valid");
+                response
+                    .headers_mut()
+                    .insert(HeaderName::Session, session.to_header_raw().remove(0));
+            }
+
+            response
+        }))
+    }
+}
+
+impl<S> SessionService<S> {
+    fn lock_pipelines(&self) -> MutexGuard<Pipelines> {
+        lock_pipelines(&self.pipelines)
+    }
+}
+
+fn lock_pipelines(pipelines: &Mutex<Pipelines>) -> MutexGuard<Pipelines> {
+    pipelines
+        .lock()
+        .expect("locking `pipelines` should not error")
+}
+
+/// Returns the identifier given by the `"Pipelined-Requests"` header of the request, if present.
+fn pipelined_requests(
+    request: &Request<BytesMut>,
+) -> Result<Option<PipelinedRequests>, InvalidTypedHeader> {
+    let header_values = request
+        .headers()
+        .get_all(HeaderName::PipelinedRequests)
+        .iter()
+        .cloned()
+        .collect::<Vec<HeaderValue>>();
+
+    if header_values.is_empty() {
+        return Ok(None);
+    }
+
+    PipelinedRequests::try_from_header_raw(&header_values).map(Some)
+}
+
+/// Registers the session of a successful `SETUP` response, adding a `"Session"` header with a
+/// newly created session if the response does not already have one.
+///
+/// # Return Value
+///
+/// The ID of the registered session is returned, or `None` if the `"Session"` header of the
+/// response is invalid.
+fn register_session(
+    manager: &SessionManager,
+    response: &mut Response<BytesMut>,
+) -> Option<SessionID> {
+    let header_values = response
+        .headers()
+        .get_all(HeaderName::Session)
+        .iter()
+        .cloned()
+        .collect::<Vec<HeaderValue>>();
+
+    let id = if header_values.is_empty() {
+        let id = manager.create_session();
+        let session = SessionHeader::with_timeout(id.as_str(), manager.timeout_duration())
+            .expect("generated session ID should be valid");
+        response
+            .headers_mut()
+            .insert(HeaderName::Session, session.to_header_raw().remove(0));
+        id
+    } else {
+        match SessionHeader::try_from_header_raw(&header_values) {
+            Ok(session) => {
+                manager.insert_session(session.id().clone());
+                session.id().clone()
+            }
+            Err(_) => return None,
+        }
+    };
+
+    manager.with_session(&id, |session| {
+        session
+            .state_machine_mut()
+            .handle_response(&Method::Setup, response.status_code())
+    });
+
+    Some(id)
+}
+
+fn status_response(status_code: StatusCode) -> Response<BytesMut> {
+    Response::builder()
+        .status_code(status_code)
+        .build(BytesMut::new())
+        .expect("status response should not be invalid")
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use super::*;
+
+    /// A service that fails requests whose URI ends with `"fail"` and succeeds otherwise.
+    struct FailService;
+
+    impl Service for FailService {
+        type Request = Request<BytesMut>;
+        type Response = Response<BytesMut>;
+        type Error = io::Error;
+        type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+        fn call(&mut self, request: Self::Request) -> Self::Future {
+            let mut builder = Response::builder();
+
+            if request.uri().to_string().ends_with("fail") {
+                builder.status_code(StatusCode::ServiceUnavailable);
+            }
+
+            Box::new(future::ok(builder.build(BytesMut::new()).unwrap()))
+        }
+    }
+
+    fn pipelined_request(method: Method, path: &str, pipeline: u32) -> Request<BytesMut> {
+        Request::builder()
+            .method(method)
+            .uri(format!("rtsp://example.com/{}", path).as_str())
+            .header(HeaderName::PipelinedRequests, pipeline.to_string().as_str())
+            .build(BytesMut::new())
+            .unwrap()
+    }
+
+    fn session_id(response: &Response<BytesMut>) -> SessionID {
+        let header_values = response
+            .headers()
+            .get_all(HeaderName::Session)
+            .iter()
+            .cloned()
+            .collect::<Vec<HeaderValue>>();
+        SessionHeader::try_from_header_raw(&header_values)
+            .unwrap()
+            .id()
+            .clone()
+    }
+
+    #[test]
+    fn test_session_manager_lifecycle() {
+        let manager = SessionManager::new(Duration::from_secs(60));
+        let id = manager.create_session();
+        assert!(manager.contains_session(&id));
+        assert!(manager.refresh_session(&id));
+        assert!(!manager.insert_session(id.clone()));
+        assert_eq!(manager.number_of_sessions(), 1);
+
+        assert!(manager.remove_session(&id));
+        assert!(!manager.refresh_session(&id));
+        assert_eq!(manager.number_of_sessions(), 0);
+    }
+
+    #[test]
+    fn test_session_manager_expired_sessions() {
+        let manager = SessionManager::new(Duration::from_secs(0));
+        let expired_sessions = manager.expired_sessions();
+        let id = manager.create_session();
+
+        ::std::thread::sleep(Duration::from_millis(10));
+        assert!(!manager.contains_session(&id));
+        assert_eq!(manager.remove_expired_sessions(), vec![id.clone()]);
+        assert_eq!(manager.number_of_sessions(), 0);
+        assert_eq!(expired_sessions.wait().next(), Some(Ok(id)));
+    }
+
+    #[test]
+    fn test_session_service_pipelines() {
+        let manager = SessionManager::new(Duration::from_secs(60));
+        let mut service = SessionService::new(FailService, manager.clone());
+
+        // A failed `SETUP` request does not bind the pipeline, so the rest of it fails but the
+        // identifier can be used again.
+
+        let response = service
+            .call(pipelined_request(Method::Setup, "fail", 1))
+            .wait()
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::ServiceUnavailable);
+        let response = service
+            .call(pipelined_request(Method::Play, "fail", 1))
+            .wait()
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::SessionNotFound);
+        assert!(service.lock_pipelines().is_empty());
+
+        let response = service
+            .call(pipelined_request(Method::Setup, "movie", 1))
+            .wait()
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let id = session_id(&response);
+        let response = service
+            .call(pipelined_request(Method::Play, "movie", 1))
+            .wait()
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(session_id(&response), id);
+
+        assert!(manager.remove_session(&id));
+        assert!(service.lock_pipelines().is_empty());
+    }
+
+    #[test]
+    fn test_session_service_pipelines_expired() {
+        let manager = SessionManager::new(Duration::from_secs(0));
+        let mut service = SessionService::new(FailService, manager.clone());
+        let response = service
+            .call(pipelined_request(Method::Setup, "movie", 1))
+            .wait()
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(service.lock_pipelines().len(), 1);
+
+        ::std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(
+            manager.remove_expired_sessions(),
+            vec![session_id(&response)]
+        );
+        assert!(service.lock_pipelines().is_empty());
+
+        // The pipelines of dropped services are no longer tracked.
+
+        drop(service);
+        let _service = SessionService::new(FailService, manager.clone());
+        assert_eq!(manager.lock().pipelines.len(), 1);
+    }
+}
@@ -0,0 +1,337 @@
+//! Session State Machine
+//!
+//! This module contains the per-session state machine described by
+//! [RFC7826](https://tools.ietf.org/html/rfc7826#appendix-B). A session starts out in the
+//! [`SessionState::Init`] state, moves to [`SessionState::Ready`] once a `SETUP` request succeeds,
+//! and to [`SessionState::Play`] once a `PLAY` request succeeds. Sessions used to publish media
+//! instead move to [`SessionState::Record`] once a `RECORD` request succeeds, as described by
+//! [RFC2326](https://tools.ietf.org/html/rfc2326#appendix-A.2).
+//!
+//! The same state machine is used by both sides of a connection. The client validates the requests
+//! it is about to send and the server validates the requests it receives, and both apply the
+//! transition once the response is known.
+
+use bytes::BytesMut;
+use std::error::Error;
+use std::fmt;
+
+use header::types::Allow;
+use method::Method;
+use response::Response;
+use status::StatusCode;
+
+/// The state of a session.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SessionState {
+    /// The initial state in which no session exists yet, or after the session has been torn down.
+    Init,
+
+    /// At least one media stream has been set up, but the session is not playing.
+    Ready,
+
+    /// The session is playing.
+    Play,
+
+    /// The session is recording media sent by the client.
+    Record,
+}
+
+impl SessionState {
+    /// Returns the methods of requests sent from the client to the server that are valid in this
+    /// state. Extension methods are not included but are always considered valid.
+    pub fn allowed_methods(&self) -> Vec<Method> {
+        use self::SessionState::*;
+
+        let mut methods = vec![
+            Method::Announce,
+            Method::Describe,
+            Method::GetParameter,
+            Method::Options,
+            Method::SetParameter,
+            Method::Setup,
+            Method::Teardown,
+        ];
+
+        match self {
+            Init => (),
+            Ready => {
+                methods.push(Method::Pause);
+                methods.push(Method::Play);
+                methods.push(Method::Record);
+            }
+            Play => {
+                methods.push(Method::Pause);
+                methods.push(Method::Play);
+            }
+            Record => {
+                methods.push(Method::Pause);
+                methods.push(Method::Record);
+            }
+        }
+
+        methods
+    }
+
+    /// Returns whether a request with the given method sent from the client to the server is
+    /// valid in this state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::Method;
+    /// use rtsp::state::SessionState;
+    ///
+    /// assert!(SessionState::Init.is_method_allowed(&Method::Setup));
+    /// assert!(!SessionState::Init.is_method_allowed(&Method::Play));
+    /// assert!(SessionState::Ready.is_method_allowed(&Method::Play));
+    /// assert!(!SessionState::Play.is_method_allowed(&Method::Record));
+    /// ```
+    pub fn is_method_allowed(&self, method: &Method) -> bool {
+        match method {
+            Method::Extension(_) => true,
+            method => self.allowed_methods().contains(method),
+        }
+    }
+}
+
+/// A state machine tracking the state of a single session as requests are made.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct StateMachine {
+    state: SessionState,
+}
+
+impl StateMachine {
+    /// Constructs a new state machine in the [`SessionState::Init`] state.
+    pub fn new() -> Self {
+        StateMachine::default()
+    }
+
+    /// Returns the `"Allow"` header listing the methods that are valid in the current state.
+    pub fn allow(&self) -> Allow {
+        self.state.allowed_methods().into_iter().collect()
+    }
+
+    /// Applies the transition for a request sent from the client to the server with the given
+    /// method, based on the status code of its response. Only successful responses cause a
+    /// transition.
+    ///
+    /// A successful `TEARDOWN` always moves the session back to the [`SessionState::Init`] state.
+    /// If only part of an aggregate session was torn down, the transition should not be applied.
+    ///
+    /// # Return Value
+    ///
+    /// The new state is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::{Method, StatusCode};
+    /// use rtsp::state::{SessionState, StateMachine};
+    ///
+    /// let mut state_machine = StateMachine::new();
+    /// state_machine.handle_response(&Method::Setup, StatusCode::OK);
+    /// assert_eq!(state_machine.state(), SessionState::Ready);
+    ///
+    /// state_machine.handle_response(&Method::Play, StatusCode::SessionNotFound);
+    /// assert_eq!(state_machine.state(), SessionState::Ready);
+    ///
+    /// state_machine.handle_response(&Method::Play, StatusCode::OK);
+    /// assert_eq!(state_machine.state(), SessionState::Play);
+    /// ```
+    pub fn handle_response(&mut self, method: &Method, status_code: StatusCode) -> SessionState {
+        use self::SessionState::*;
+
+        if !status_code.is_success() || !self.state.is_method_allowed(method) {
+            return self.state;
+        }
+
+        self.state = match (self.state, method) {
+            (Init, Method::Setup) => Ready,
+            (Ready, Method::Play) => Play,
+            (Ready, Method::Record) => Record,
+            (Play, Method::Pause) | (Record, Method::Pause) => Ready,
+            (_, Method::Teardown) => Init,
+            (state, _) => state,
+        };
+        self.state
+    }
+
+    /// Applies the transition for a request sent from the server to the client with the given
+    /// method. A `TEARDOWN` request from the server ends the session, while `PLAY_NOTIFY` and
+    /// `REDIRECT` requests do not change the state by themselves.
+    ///
+    /// # Return Value
+    ///
+    /// The new state is returned.
+    pub fn handle_server_request(&mut self, method: &Method) -> SessionState {
+        if *method == Method::Teardown {
+            self.state = SessionState::Init;
+        }
+
+        self.state
+    }
+
+    /// Returns whether a request with the given method sent from the client to the server is
+    /// valid in the current state.
+    pub fn is_method_allowed(&self, method: &Method) -> bool {
+        self.state.is_method_allowed(method)
+    }
+
+    /// Returns a `455 Method Not Valid in This State` response with an `"Allow"` header listing
+    /// the methods that are valid in the current state.
+    pub fn method_not_valid_response(&self) -> Response<BytesMut> {
+        Response::typed_builder()
+            .status_code(StatusCode::MethodNotValidInThisState)
+            .header(self.allow())
+            .build(BytesMut::new())
+            .expect("method not valid in this state response should not be invalid")
+            .into()
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    /// Validates that a request with the given method sent from the client to the server is
+    /// valid in the current state.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the method is not valid in the current state.
+    pub fn validate_request(&self, method: &Method) -> Result<(), MethodNotValidInThisState> {
+        if self.is_method_allowed(method) {
+            Ok(())
+        } else {
+            Err(MethodNotValidInThisState(self.state))
+        }
+    }
+}
+
+impl Default for StateMachine {
+    fn default() -> Self {
+        StateMachine {
+            state: SessionState::Init,
+        }
+    }
+}
+
+/// An error indicating that a method is not valid in the given session state.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MethodNotValidInThisState(pub SessionState);
+
+impl fmt::Display for MethodNotValidInThisState {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.description())
+    }
+}
+
+impl Error for MethodNotValidInThisState {
+    fn description(&self) -> &str {
+        "method not valid in this state"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use header::{HeaderName, TypedHeader};
+
+    #[test]
+    fn test_state_machine_transitions() {
+        let mut state_machine = StateMachine::new();
+        assert_eq!(
+            state_machine.validate_request(&Method::Play),
+            Err(MethodNotValidInThisState(SessionState::Init))
+        );
+        assert_eq!(
+            state_machine.handle_response(&Method::Play, StatusCode::OK),
+            SessionState::Init
+        );
+        assert_eq!(
+            state_machine.handle_response(&Method::Setup, StatusCode::OK),
+            SessionState::Ready
+        );
+        assert_eq!(
+            state_machine.handle_response(&Method::Pause, StatusCode::OK),
+            SessionState::Ready
+        );
+        assert_eq!(
+            state_machine.handle_response(&Method::Play, StatusCode::OK),
+            SessionState::Play
+        );
+        assert_eq!(
+            state_machine.handle_response(&Method::Setup, StatusCode::OK),
+            SessionState::Play
+        );
+        assert_eq!(
+            state_machine.handle_response(&Method::Pause, StatusCode::OK),
+            SessionState::Ready
+        );
+        assert_eq!(
+            state_machine.handle_response(&Method::Teardown, StatusCode::OK),
+            SessionState::Init
+        );
+
+        state_machine.handle_response(&Method::Setup, StatusCode::OK);
+        assert_eq!(
+            state_machine.handle_server_request(&Method::PlayNotify),
+            SessionState::Ready
+        );
+        assert_eq!(
+            state_machine.handle_server_request(&Method::Teardown),
+            SessionState::Init
+        );
+    }
+
+    #[test]
+    fn test_state_machine_record_transitions() {
+        let mut state_machine = StateMachine::new();
+        assert!(state_machine.is_method_allowed(&Method::Announce));
+        assert_eq!(
+            state_machine.handle_response(&Method::Record, StatusCode::OK),
+            SessionState::Init
+        );
+
+        state_machine.handle_response(&Method::Setup, StatusCode::OK);
+        assert_eq!(
+            state_machine.handle_response(&Method::Record, StatusCode::OK),
+            SessionState::Record
+        );
+        assert_eq!(
+            state_machine.validate_request(&Method::Play),
+            Err(MethodNotValidInThisState(SessionState::Record))
+        );
+        assert_eq!(
+            state_machine.handle_response(&Method::Pause, StatusCode::OK),
+            SessionState::Ready
+        );
+
+        state_machine.handle_response(&Method::Play, StatusCode::OK);
+        assert_eq!(
+            state_machine.handle_response(&Method::Record, StatusCode::OK),
+            SessionState::Play
+        );
+    }
+
+    #[test]
+    fn test_state_machine_method_not_valid_response() {
+        let response = StateMachine::new().method_not_valid_response();
+        assert_eq!(
+            response.status_code(),
+            StatusCode::MethodNotValidInThisState
+        );
+
+        let allow = Allow::try_from_header_raw(
+            &response
+                .headers()
+                .get_all(HeaderName::Allow)
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert!(allow.contains(&Method::Setup));
+        assert!(!allow.contains(&Method::Play));
+    }
+}
@@ -65,6 +65,80 @@ pub fn extract_quoted_string(string: &str) -> Option<&str> {
         .map(|m| &string[m.start() + 1..m.end() - 1])
 }
 
+/// A helper function used to determine whether the entirety of the given string is a valid quoted
+/// string (including the surrounding double quotes).
+pub fn is_quoted_string(string: &str) -> bool {
+    string.starts_with('"')
+        && extract_quoted_string(string).map_or(false, |inner| inner.len() + 2 == string.len())
+}
+
+/// A helper function used to serialize the given string as a quoted string. Any `'\\'` and `'"'`
+/// characters are escaped with a preceding `'\\'`.
+pub fn quote_string(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len() + 2);
+    quoted.push('"');
+
+    for c in string.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+
+        quoted.push(c);
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// A helper function used to split a string on the given separator character while ignoring any
+/// occurrences of the separator within quoted strings. Within a quoted string, a `'\\'` escapes the
+/// character following it.
+pub fn split_unquoted(string: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut escaped = false;
+    let mut quoted = false;
+    let mut start = 0;
+
+    for (index, c) in string.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if !quoted && c == separator {
+            parts.push(&string[start..index]);
+            start = index + c.len_utf8();
+        }
+    }
+
+    parts.push(&string[start..]);
+    parts
+}
+
+/// A helper function used to get the unescaped inner text out of a string that in its entirety is
+/// a valid quoted string. If the given string is not a valid quoted string, the return value will
+/// be `None`.
+pub fn unquote_string(string: &str) -> Option<String> {
+    if !is_quoted_string(string) {
+        return None;
+    }
+
+    let mut unquoted = String::with_capacity(string.len() - 2);
+    let mut escaped = false;
+
+    for c in string[1..string.len() - 1].chars() {
+        if !escaped && c == '\\' {
+            escaped = true;
+        } else {
+            escaped = false;
+            unquoted.push(c);
+        }
+    }
+
+    Some(unquoted)
+}
+
 /// A helper function used to trim whitespace as it is used in
 /// [[RFC7826](https://tools.ietf.org/html/rfc782)]. Specifically, whitespace includes `' '`,
 /// `'\t'`, and `"\r\n"`. The trim functions defined on the `str` slice do not seem to be enough to
@@ -119,6 +193,46 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_is_quoted_string() {
+        assert_eq!(true, is_quoted_string(r#""""#));
+        assert_eq!(true, is_quoted_string(r#""a \" b""#));
+        assert_eq!(false, is_quoted_string(r#"a"b""#));
+        assert_eq!(false, is_quoted_string(r#""a" b"#));
+    }
+
+    #[test]
+    fn test_quote_string() {
+        assert_eq!(r#""""#, quote_string(""));
+        assert_eq!(r#""a \" b \\ c""#, quote_string(r#"a " b \ c"#));
+    }
+
+    #[test]
+    fn test_split_unquoted() {
+        assert_eq!(vec![""], split_unquoted("", ','));
+        assert_eq!(vec!["a", " b", ""], split_unquoted("a, b,", ','));
+        assert_eq!(
+            vec!["mode=\"PLAY,RECORD\"", "ttl=4"],
+            split_unquoted("mode=\"PLAY,RECORD\";ttl=4", ';')
+        );
+        assert_eq!(
+            vec!["mode=\"PLAY,RECORD\";ttl=4"],
+            split_unquoted("mode=\"PLAY,RECORD\";ttl=4", ',')
+        );
+        assert_eq!(vec![r#""a\",b""#, "c"], split_unquoted(r#""a\",b",c"#, ','));
+    }
+
+    #[test]
+    fn test_unquote_string() {
+        assert_eq!(Some(String::new()), unquote_string(r#""""#));
+        assert_eq!(
+            Some(r#"a " b \ c"#.to_string()),
+            unquote_string(r#""a \" b \\ c""#)
+        );
+        assert_eq!(None, unquote_string(r#""a" b"#));
+        assert_eq!(None, unquote_string("a"));
+    }
+
     #[test]
     fn test_trim_whitespace() {
         assert_eq!(
@@ -0,0 +1,169 @@
+//! TLS Support
+//!
+//! This module is only available when the `tls` feature is enabled. It provides a [`TlsConnector`]
+//! that is used by the client to connect to `rtsps` URIs and a [`TlsAcceptor`] that can be given
+//! to a server so that it only accepts TLS connections. Both use the platform's native TLS
+//! implementation.
+//!
+//! The DER-encoded certificate of the peer is available through [`peer_certificate`], which can be
+//! used along with the `"Accept-Credentials"` and `"Connection-Credentials"` headers to decide
+//! which servers are trusted when going through a proxy.
+
+use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::{Async, Future, Stream};
+use native_tls;
+use std::io;
+use std::time::Duration;
+use tokio_executor::{DefaultExecutor, Executor};
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_tcp::TcpStream;
+use tokio_timer::Timeout;
+use tokio_tls;
+
+pub use native_tls::{Certificate, Identity};
+pub use tokio_tls::TlsStream;
+
+/// The default duration within which the TLS handshake of a connection accepted by a server must
+/// complete before the connection is dropped.
+pub const DEFAULT_TLS_HANDSHAKE_TIMEOUT_DURATION: Duration = Duration::from_secs(10);
+
+/// Establishes TLS connections on the client side.
+#[derive(Clone)]
+pub struct TlsConnector(tokio_tls::TlsConnector);
+
+impl TlsConnector {
+    /// Constructs a connector that trusts the platform's root certificates.
+    pub fn new() -> io::Result<Self> {
+        native_tls::TlsConnector::new()
+            .map(TlsConnector::from)
+            .map_err(to_io_error)
+    }
+
+    /// Constructs a connector that trusts the given certificates in addition to the platform's
+    /// root certificates. This is useful for servers using self-signed certificates.
+    pub fn with_root_certificates<I>(certificates: I) -> io::Result<Self>
+    where
+        I: IntoIterator<Item = Certificate>,
+    {
+        let mut builder = native_tls::TlsConnector::builder();
+
+        for certificate in certificates {
+            builder.add_root_certificate(certificate);
+        }
+
+        builder.build().map(TlsConnector::from).map_err(to_io_error)
+    }
+
+    /// Performs a TLS handshake over the given stream, verifying that the server's certificate is
+    /// valid for the given domain.
+    pub fn connect<T>(
+        &self,
+        domain: &str,
+        stream: T,
+    ) -> impl Future<Item = TlsStream<T>, Error = io::Error>
+    where
+        T: AsyncRead + AsyncWrite,
+    {
+        self.0.connect(domain, stream).map_err(to_io_error)
+    }
+}
+
+impl From<native_tls::TlsConnector> for TlsConnector {
+    fn from(connector: native_tls::TlsConnector) -> Self {
+        TlsConnector(tokio_tls::TlsConnector::from(connector))
+    }
+}
+
+/// Accepts TLS connections on the server side.
+#[derive(Clone)]
+pub struct TlsAcceptor(tokio_tls::TlsAcceptor);
+
+impl TlsAcceptor {
+    /// Constructs an acceptor that presents the given identity to clients.
+    pub fn new(identity: Identity) -> io::Result<Self> {
+        native_tls::TlsAcceptor::new(identity)
+            .map(TlsAcceptor::from)
+            .map_err(to_io_error)
+    }
+
+    /// Constructs an acceptor from a PEM-encoded certificate chain and a PEM-encoded PKCS #8
+    /// private key.
+    pub fn from_pem(certificate: &[u8], key: &[u8]) -> io::Result<Self> {
+        let identity = Identity::from_pkcs8(certificate, key).map_err(to_io_error)?;
+        TlsAcceptor::new(identity)
+    }
+
+    /// Performs a TLS handshake over the given stream.
+    pub fn accept<T>(&self, stream: T) -> impl Future<Item = TlsStream<T>, Error = io::Error>
+    where
+        T: AsyncRead + AsyncWrite,
+    {
+        self.0.accept(stream).map_err(to_io_error)
+    }
+}
+
+impl From<native_tls::TlsAcceptor> for TlsAcceptor {
+    fn from(acceptor: native_tls::TlsAcceptor) -> Self {
+        TlsAcceptor(tokio_tls::TlsAcceptor::from(acceptor))
+    }
+}
+
+/// Returns the DER-encoded certificate that the peer presented during the handshake, if any.
+pub fn peer_certificate<T>(stream: &TlsStream<T>) -> io::Result<Option<Vec<u8>>>
+where
+    T: io::Read + io::Write,
+{
+    match stream.get_ref().peer_certificate().map_err(to_io_error)? {
+        Some(certificate) => certificate.to_der().map(Some).map_err(to_io_error),
+        None => Ok(None),
+    }
+}
+
+/// Performs TLS handshakes for the connections accepted by a server. Each handshake is spawned as
+/// its own task so that a slow client does not hold up the server, and completed handshakes are
+/// passed back to the server through a channel. Handshakes that do not complete within the timeout
+/// are abandoned, so that clients that stall cannot hold connections open indefinitely.
+pub(crate) struct Handshakes {
+    acceptor: TlsAcceptor,
+    rx_stream: UnboundedReceiver<TlsStream<TcpStream>>,
+    timeout_duration: Duration,
+    tx_stream: UnboundedSender<TlsStream<TcpStream>>,
+}
+
+impl Handshakes {
+    pub fn new(acceptor: TlsAcceptor, timeout_duration: Duration) -> Self {
+        let (tx_stream, rx_stream) = unbounded();
+
+        Handshakes {
+            acceptor,
+            rx_stream,
+            timeout_duration,
+            tx_stream,
+        }
+    }
+
+    /// Starts a handshake for the given socket. The socket is dropped if the handshake fails or
+    /// times out.
+    pub fn start(&self, tcp_stream: TcpStream) {
+        let tx_stream = self.tx_stream.clone();
+        let handshake = Timeout::new(self.acceptor.accept(tcp_stream), self.timeout_duration)
+            .map(move |stream| {
+                tx_stream.unbounded_send(stream).ok();
+            })
+            .map_err(|error| debug!("TLS handshake failed: {}", error));
+
+        DefaultExecutor::current().spawn(Box::new(handshake)).ok();
+    }
+
+    /// Returns the next stream whose handshake has completed, if any.
+    pub fn poll_stream(&mut self) -> Option<TlsStream<TcpStream>> {
+        match self.rx_stream.poll() {
+            Ok(Async::Ready(stream)) => stream,
+            _ => None,
+        }
+    }
+}
+
+fn to_io_error(error: native_tls::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
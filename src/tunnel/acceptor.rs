@@ -0,0 +1,450 @@
+use bytes::BytesMut;
+use futures::future::{self, Either};
+use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::sync::oneshot;
+use futures::{Async, Future, Poll, Stream};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_executor::{DefaultExecutor, Executor};
+use tokio_io::io::write_all;
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_timer::{timeout, Delay, Timeout};
+
+use super::{Head, ReadHead, Tunnel, READ_BUFFER_SIZE, TUNNEL_CONTENT_TYPE};
+
+/// The default duration within which an accepted connection must send its HTTP request head, or
+/// enough data to tell that it is not a tunnel half, before it is closed.
+pub const DEFAULT_TUNNEL_HEAD_TIMEOUT_DURATION: Duration = Duration::from_secs(10);
+
+/// The default duration that the `GET` half of a tunnel waits for its `POST` half before it is
+/// closed.
+pub const DEFAULT_TUNNEL_JOIN_TIMEOUT_DURATION: Duration = Duration::from_secs(30);
+
+/// The `GET` halves that are waiting for their `POST` halves, keyed by session cookie. Each is
+/// stored along with a sender that is dropped once the half is removed, which stops the timer that
+/// would otherwise close it.
+type PendingTunnels<T> = HashMap<String, (T, oneshot::Sender<()>)>;
+
+/// The methods of the HTTP requests that start a tunnel half, followed by a space. Since RTSP
+/// methods such as `GET_PARAMETER` share a prefix with them, the space is needed to tell them
+/// apart.
+const TUNNEL_METHODS: [&[u8]; 2] = [b"GET ", b"POST "];
+
+/// Detects and joins the halves of tunnels among accepted connections.
+///
+/// Every accepted connection is given to [`TunnelAcceptor::accept`], which determines whether it
+/// begins with an HTTP `GET` or `POST` request. If it does not, it is passed through as a direct
+/// connection. Otherwise, a `GET` request is answered and kept until the `POST` request with the
+/// same `"x-sessioncookie"` arrives, at which point the two are joined into a [`Tunnel`]. The
+/// resulting streams are retrieved by polling the acceptor as a stream.
+///
+/// Connections that do not send their request head in time are closed, so that idle connections
+/// cannot be held open indefinitely before they are detected.
+#[must_use = "streams do nothing unless polled"]
+pub struct TunnelAcceptor<T> {
+    head_timeout_duration: Duration,
+
+    join_timeout_duration: Duration,
+
+    pending: Arc<Mutex<PendingTunnels<T>>>,
+
+    rx_stream: UnboundedReceiver<AcceptedStream<T>>,
+    tx_stream: UnboundedSender<AcceptedStream<T>>,
+}
+
+impl<T> TunnelAcceptor<T>
+where
+    T: AsyncRead + AsyncWrite + Send + 'static,
+{
+    pub fn new() -> Self {
+        let (tx_stream, rx_stream) = unbounded();
+
+        TunnelAcceptor {
+            head_timeout_duration: DEFAULT_TUNNEL_HEAD_TIMEOUT_DURATION,
+            join_timeout_duration: DEFAULT_TUNNEL_JOIN_TIMEOUT_DURATION,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            rx_stream,
+            tx_stream,
+        }
+    }
+
+    /// Sets the duration within which an accepted connection must send its HTTP request head, or
+    /// enough data to tell that it is not a tunnel half, before it is closed.
+    pub fn with_head_timeout_duration(mut self, duration: Duration) -> Self {
+        self.head_timeout_duration = duration;
+        self
+    }
+
+    /// Sets the duration that the `GET` half of a tunnel waits for its `POST` half before it is
+    /// closed.
+    pub fn with_join_timeout_duration(mut self, duration: Duration) -> Self {
+        self.join_timeout_duration = duration;
+        self
+    }
+
+    /// Starts detecting whether the given connection is a tunnel half. The connection is dropped if
+    /// it closes, sends an invalid request or does not send its request head in time before it can
+    /// be used.
+    pub fn accept(&self, stream: T) {
+        let deadline = Instant::now() + self.head_timeout_duration;
+        let join_timeout_duration = self.join_timeout_duration;
+        let pending = self.pending.clone();
+        let tx_stream = self.tx_stream.clone();
+
+        let task = Timeout::new_at(Detect::new(stream), deadline)
+            .map_err(timeout_error)
+            .and_then(move |(stream, buffer, is_http)| {
+                if !is_http {
+                    let stream = AcceptedStream::Direct(PrefixedStream::new(buffer, stream));
+                    tx_stream.unbounded_send(stream).ok();
+                    return Either::A(future::ok(()));
+                }
+
+                Either::B(
+                    Timeout::new_at(ReadHead::new(stream, buffer), deadline)
+                        .map_err(timeout_error)
+                        .and_then(move |(stream, head)| {
+                            join(stream, head, pending, tx_stream, join_timeout_duration)
+                        }),
+                )
+            })
+            .map_err(|error| debug!("tunnel detection failed: {}", error));
+
+        DefaultExecutor::current().spawn(Box::new(task)).ok();
+    }
+}
+
+impl<T> Stream for TunnelAcceptor<T> {
+    type Item = AcceptedStream<T>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.rx_stream.poll()
+    }
+}
+
+/// Handles a connection that began with an HTTP request. A `GET` request is answered and stored
+/// until its `POST` half arrives, while a `POST` request is joined with its stored `GET` half.
+fn join<T>(
+    stream: T,
+    head: Head,
+    pending: Arc<Mutex<PendingTunnels<T>>>,
+    tx_stream: UnboundedSender<AcceptedStream<T>>,
+    join_timeout_duration: Duration,
+) -> impl Future<Item = (), Error = io::Error>
+where
+    T: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let cookie = match head.header("x-sessioncookie") {
+        Some(cookie) if !cookie.is_empty() => cookie.to_string(),
+        _ => {
+            let response = "HTTP/1.0 400 Bad Request\r\nConnection: close\r\n\r\n";
+            return Either::A(write_all(stream, response).map(|_| ()));
+        }
+    };
+
+    if head.start_line.starts_with("POST ") {
+        let get = pending
+            .lock()
+            .expect("locking pending tunnels should not error")
+            .remove(&cookie);
+
+        if let Some((get, _)) = get {
+            let tunnel = Tunnel::server(get, stream, &head.remaining).map(|tunnel| {
+                tx_stream
+                    .unbounded_send(AcceptedStream::Tunnel(tunnel))
+                    .ok();
+            });
+            return Either::B(Either::A(future::result(tunnel)));
+        }
+
+        return Either::B(Either::A(future::err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no tunnel `GET` request with matching session cookie",
+        ))));
+    }
+
+    let response = format!(
+        "HTTP/1.0 200 OK\r\n\
+         Connection: close\r\n\
+         Cache-Control: no-store\r\n\
+         Pragma: no-cache\r\n\
+         Content-Type: {}\r\n\
+         \r\n",
+        TUNNEL_CONTENT_TYPE
+    );
+
+    Either::B(Either::B(write_all(stream, response).map(
+        move |(stream, _)| {
+            let (tx_joined, rx_joined) = oneshot::channel();
+            pending
+                .lock()
+                .expect("locking pending tunnels should not error")
+                .insert(cookie.clone(), (stream, tx_joined));
+
+            // Only a weak reference is kept, so that the timer does not keep the `GET` halves alive
+            // once the acceptor has been dropped.
+
+            let pending = Arc::downgrade(&pending);
+            let expire = Delay::new(Instant::now() + join_timeout_duration)
+                .select2(rx_joined)
+                .then(move |result| {
+                    if let (Ok(Either::A(_)), Some(pending)) = (result, pending.upgrade()) {
+                        pending
+                            .lock()
+                            .expect("locking pending tunnels should not error")
+                            .remove(&cookie);
+                    }
+
+                    Ok(())
+                });
+
+            DefaultExecutor::current().spawn(Box::new(expire)).ok();
+        },
+    )))
+}
+
+/// Converts the error of a future with a deadline into an I/O error.
+fn timeout_error(error: timeout::Error<io::Error>) -> io::Error {
+    if error.is_elapsed() {
+        io::Error::new(
+            io::ErrorKind::TimedOut,
+            "request head was not received in time",
+        )
+    } else if error.is_timer() {
+        let error = error.into_timer().expect("error should be a timer error");
+        io::Error::new(io::ErrorKind::Other, error)
+    } else {
+        error.into_inner().expect("error should be an inner error")
+    }
+}
+
+/// A future that reads from a connection until it can be determined whether it begins with an
+/// HTTP request that starts a tunnel half.
+struct Detect<T> {
+    buffer: BytesMut,
+    stream: Option<T>,
+}
+
+impl<T> Detect<T> {
+    fn new(stream: T) -> Self {
+        Detect {
+            buffer: BytesMut::new(),
+            stream: Some(stream),
+        }
+    }
+}
+
+impl<T> Future for Detect<T>
+where
+    T: AsyncRead,
+{
+    type Item = (T, BytesMut, bool);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let mut data = [0; READ_BUFFER_SIZE];
+
+        loop {
+            if let Some(is_http) = is_tunnel_request(&self.buffer) {
+                let stream = self
+                    .stream
+                    .take()
+                    .expect("`Detect` polled after completion");
+                return Ok(Async::Ready((stream, self.buffer.take(), is_http)));
+            }
+
+            let n = try_ready!(self
+                .stream
+                .as_mut()
+                .expect("`Detect` polled after completion")
+                .poll_read(&mut data));
+
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed before any request was received",
+                ));
+            }
+
+            self.buffer.extend_from_slice(&data[..n]);
+        }
+    }
+}
+
+/// Determines whether the given data begins with an HTTP request that starts a tunnel half, or
+/// `None` if more data is needed to tell.
+fn is_tunnel_request(data: &[u8]) -> Option<bool> {
+    if TUNNEL_METHODS.iter().any(|method| data.starts_with(method)) {
+        Some(true)
+    } else if TUNNEL_METHODS.iter().any(|method| method.starts_with(data)) {
+        None
+    } else {
+        Some(false)
+    }
+}
+
+/// A connection accepted by a [`TunnelAcceptor`].
+pub enum AcceptedStream<T> {
+    /// A connection that did not begin with an HTTP request, and is used directly.
+    Direct(PrefixedStream<T>),
+
+    /// Two connections that were joined into a tunnel.
+    Tunnel(Tunnel<T>),
+}
+
+impl<T> Read for AcceptedStream<T>
+where
+    T: Read,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self {
+            AcceptedStream::Direct(stream) => stream.read(buffer),
+            AcceptedStream::Tunnel(tunnel) => tunnel.read(buffer),
+        }
+    }
+}
+
+impl<T> Write for AcceptedStream<T>
+where
+    T: Write,
+{
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        match self {
+            AcceptedStream::Direct(stream) => stream.write(buffer),
+            AcceptedStream::Tunnel(tunnel) => tunnel.write(buffer),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            AcceptedStream::Direct(stream) => stream.flush(),
+            AcceptedStream::Tunnel(tunnel) => tunnel.flush(),
+        }
+    }
+}
+
+impl<T> AsyncRead for AcceptedStream<T> where T: AsyncRead {}
+
+impl<T> AsyncWrite for AcceptedStream<T>
+where
+    T: AsyncWrite,
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match self {
+            AcceptedStream::Direct(stream) => stream.shutdown(),
+            AcceptedStream::Tunnel(tunnel) => tunnel.shutdown(),
+        }
+    }
+}
+
+/// A stream whose first bytes have already been read, and are returned before any data that is
+/// read from the stream itself.
+pub struct PrefixedStream<T> {
+    prefix: BytesMut,
+    stream: T,
+}
+
+impl<T> PrefixedStream<T> {
+    pub fn new(prefix: BytesMut, stream: T) -> Self {
+        PrefixedStream { prefix, stream }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.stream
+    }
+}
+
+impl<T> Read for PrefixedStream<T>
+where
+    T: Read,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.prefix.is_empty() {
+            return self.stream.read(buffer);
+        }
+
+        let n = buffer.len().min(self.prefix.len());
+        buffer[..n].copy_from_slice(&self.prefix.split_to(n));
+        Ok(n)
+    }
+}
+
+impl<T> Write for PrefixedStream<T>
+where
+    T: Write,
+{
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.stream.write(buffer)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<T> AsyncRead for PrefixedStream<T> where T: AsyncRead {}
+
+impl<T> AsyncWrite for PrefixedStream<T>
+where
+    T: AsyncWrite,
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.stream.shutdown()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::net::TcpStream as StdTcpStream;
+    use tokio::runtime::current_thread::Runtime;
+    use tokio_tcp::TcpListener;
+
+    #[test]
+    fn test_accept_head_timeout() {
+        let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let mut client = StdTcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(b"GET / HTTP/1.0\r\n").unwrap();
+
+        // The detection task is the only one left once the connection has been accepted, so the
+        // runtime only finishes running once the connection has been closed.
+
+        let acceptor = TunnelAcceptor::new().with_head_timeout_duration(Duration::from_millis(50));
+        let mut runtime = Runtime::new().unwrap();
+        runtime.spawn(
+            listener
+                .incoming()
+                .into_future()
+                .map(move |(stream, _)| acceptor.accept(stream.unwrap()))
+                .map_err(|_| ()),
+        );
+        runtime.run().unwrap();
+
+        let mut data = Vec::new();
+        assert_eq!(client.read_to_end(&mut data).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_is_tunnel_request() {
+        assert_eq!(is_tunnel_request(b""), None);
+        assert_eq!(is_tunnel_request(b"GE"), None);
+        assert_eq!(is_tunnel_request(b"POST"), None);
+        assert_eq!(is_tunnel_request(b"GET / HTTP/1.0"), Some(true));
+        assert_eq!(is_tunnel_request(b"POST "), Some(true));
+        assert_eq!(is_tunnel_request(b"GET_PARAMETER"), Some(false));
+        assert_eq!(is_tunnel_request(b"OPTIONS * RTSP/2.0"), Some(false));
+    }
+
+    #[test]
+    fn test_prefixed_stream() {
+        let mut stream = PrefixedStream::new(BytesMut::from("OPT"), io::Cursor::new(b"IONS"));
+        let mut data = String::new();
+        stream.read_to_string(&mut data).unwrap();
+
+        assert_eq!(data, "OPTIONS");
+    }
+}
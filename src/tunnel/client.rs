@@ -0,0 +1,157 @@
+use bytes::BytesMut;
+use futures::Future;
+use std::io;
+use std::net::SocketAddr;
+use tokio_io::io::write_all;
+use tokio_tcp::TcpStream;
+
+use session::SessionID;
+
+use super::{ReadHead, Tunnel, TUNNEL_CONTENT_TYPE};
+
+/// Establishes a tunnel to the HTTP server at the given address, using the given path on the given
+/// host as the target of both requests. The host, which may include a port, is sent in the
+/// `"Host"` header, and the target is sent in absolute form so that the requests can pass through
+/// HTTP proxies.
+///
+/// The `GET` connection is established first, and once the server has responded successfully, the
+/// `POST` connection is established. The session cookie joining them is chosen randomly.
+pub fn connect(
+    address: &SocketAddr,
+    host: &str,
+    path: &str,
+) -> impl Future<Item = Tunnel<TcpStream>, Error = io::Error> {
+    let address = *address;
+    let cookie = SessionID::random();
+    let target = absolute_target(host, path);
+    let get = get_request(&target, host, &cookie);
+    let post = post_request(&target, host, &cookie);
+
+    TcpStream::connect(&address)
+        .and_then(move |stream| write_all(stream, get))
+        .and_then(|(stream, _)| ReadHead::new(stream, BytesMut::new()))
+        .and_then(|(stream, head)| {
+            if is_tunnel_accepted(&head.start_line) {
+                Ok((stream, head.remaining))
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    "server did not accept tunnel",
+                ))
+            }
+        })
+        .and_then(move |(get, received)| {
+            TcpStream::connect(&address)
+                .and_then(move |stream| write_all(stream, post))
+                .map(move |(post, _)| Tunnel::client(get, received, post))
+        })
+}
+
+/// Returns the absolute form of the request target for the given path on the given host.
+fn absolute_target(host: &str, path: &str) -> String {
+    if path.starts_with('/') {
+        format!("http://{}{}", host, path)
+    } else {
+        format!("http://{}/{}", host, path)
+    }
+}
+
+/// Returns the head of the `GET` request that establishes the connection carrying the data sent by
+/// the server.
+fn get_request(target: &str, host: &str, cookie: &SessionID) -> String {
+    format!(
+        "GET {} HTTP/1.0\r\n\
+         Host: {}\r\n\
+         x-sessioncookie: {}\r\n\
+         Accept: {}\r\n\
+         Pragma: no-cache\r\n\
+         Cache-Control: no-cache\r\n\
+         \r\n",
+        target,
+        host,
+        cookie.as_str(),
+        TUNNEL_CONTENT_TYPE
+    )
+}
+
+/// Returns the head of the `POST` request that establishes the connection carrying the data sent
+/// by the client.
+fn post_request(target: &str, host: &str, cookie: &SessionID) -> String {
+    // The content length of the `POST` request is not known in advance, so a large length is given
+    // as is common practice. The server ignores it and reads until the connection is closed.
+
+    format!(
+        "POST {} HTTP/1.0\r\n\
+         Host: {}\r\n\
+         x-sessioncookie: {}\r\n\
+         Content-Type: {}\r\n\
+         Pragma: no-cache\r\n\
+         Cache-Control: no-cache\r\n\
+         Content-Length: 32767\r\n\
+         Expires: Sun, 9 Jan 1972 00:00:00 GMT\r\n\
+         \r\n",
+        target,
+        host,
+        cookie.as_str(),
+        TUNNEL_CONTENT_TYPE
+    )
+}
+
+/// Determines whether the given status line of the response to the `GET` request indicates that
+/// the server accepted the tunnel.
+fn is_tunnel_accepted(status_line: &str) -> bool {
+    let mut parts = status_line.split(' ');
+
+    match (parts.next(), parts.next()) {
+        (Some(version), Some("200")) => version.starts_with("HTTP/"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn test_absolute_target() {
+        assert_eq!(
+            absolute_target("example.com", "/movie"),
+            "http://example.com/movie"
+        );
+        assert_eq!(
+            absolute_target("example.com:8080", ""),
+            "http://example.com:8080/"
+        );
+        assert_eq!(absolute_target("[::1]", "movie"), "http://[::1]/movie");
+    }
+
+    #[test]
+    fn test_requests() {
+        let cookie = SessionID::try_from("abcdefgh").unwrap();
+        let get = get_request("http://example.com/movie", "example.com", &cookie);
+        let post = post_request("http://example.com/movie", "example.com", &cookie);
+
+        assert!(get.starts_with("GET http://example.com/movie HTTP/1.0\r\n"));
+        assert!(post.starts_with("POST http://example.com/movie HTTP/1.0\r\n"));
+
+        for head in &[get, post] {
+            assert!(head.contains("\r\nHost: example.com\r\n"));
+            assert!(head.contains("\r\nx-sessioncookie: abcdefgh\r\n"));
+            assert!(head.contains(TUNNEL_CONTENT_TYPE));
+            assert!(head.ends_with("\r\n\r\n"));
+            assert_eq!(head.matches("\r\n\r\n").count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_is_tunnel_accepted() {
+        assert!(is_tunnel_accepted("HTTP/1.0 200 OK"));
+        assert!(is_tunnel_accepted("HTTP/1.1 200"));
+        assert!(!is_tunnel_accepted("HTTP/1.0 403 Forbidden"));
+        assert!(!is_tunnel_accepted("RTSP/1.0 200 OK"));
+        assert!(!is_tunnel_accepted("HTTP/1.0  200 OK"));
+        assert!(!is_tunnel_accepted(""));
+    }
+}
@@ -0,0 +1,440 @@
+//! RTSP over HTTP Tunneling
+//!
+//! Clients that can only reach a server through HTTP proxies can tunnel RTSP through a pair of
+//! HTTP connections. The client first sends a `GET` request whose response body carries all
+//! traffic from the server to the client. It then sends a `POST` request whose body carries all
+//! traffic from the client to the server, encoded using base64. The two requests are joined by the
+//! server using the value of their `"x-sessioncookie"` header, which the client chooses randomly.
+//!
+//! A joined pair of connections is represented by a [`Tunnel`], which can be used with
+//! `Connection::new` like any other transport. On the client side, a tunnel is created with
+//! [`connect`], and on the server side, tunnel halves are detected and joined by a
+//! [`TunnelAcceptor`].
+
+mod acceptor;
+mod client;
+
+pub use self::acceptor::{
+    AcceptedStream, PrefixedStream, TunnelAcceptor, DEFAULT_TUNNEL_HEAD_TIMEOUT_DURATION,
+    DEFAULT_TUNNEL_JOIN_TIMEOUT_DURATION,
+};
+pub use self::client::connect;
+
+use base64;
+use bytes::BytesMut;
+use futures::{Async, Future, Poll};
+use std::io::{self, Read, Write};
+use tokio_io::{AsyncRead, AsyncWrite};
+
+/// The content type of both the `GET` and `POST` requests of a tunnel.
+pub const TUNNEL_CONTENT_TYPE: &str = "application/x-rtsp-tunnelled";
+
+/// The maximum size of the head of an HTTP request or response that establishes a tunnel.
+pub const MAX_TUNNEL_HEAD_SIZE: usize = 8192;
+
+/// The size of the buffer that data is read into from the underlying connections.
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// A pair of HTTP connections that are joined together into a single bidirectional stream.
+///
+/// On the client side, data is read as is from the `GET` connection and written using base64 to
+/// the `POST` connection. On the server side, data is read using base64 from the `POST` connection
+/// and written as is to the `GET` connection.
+pub struct Tunnel<T> {
+    /// Base64 characters that have been read but not yet decoded, because they do not make up a
+    /// whole group of four.
+    pending_base64: BytesMut,
+
+    /// Data that has been read from the incoming connection but not yet returned.
+    read_buffer: BytesMut,
+
+    /// The connection that data is read from.
+    reader: T,
+
+    /// The side of the tunnel that this is.
+    side: Side,
+
+    /// Base64-encoded data that has been accepted but not yet written.
+    write_buffer: BytesMut,
+
+    /// The connection that data is written to.
+    writer: T,
+}
+
+/// The side of a [`Tunnel`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Side {
+    Client,
+    Server,
+}
+
+impl<T> Tunnel<T> {
+    /// Constructs the client side of a tunnel from the `GET` connection, any data that was
+    /// received on it after the head of the response, and the `POST` connection.
+    pub fn client(get: T, received: BytesMut, post: T) -> Self {
+        Tunnel {
+            pending_base64: BytesMut::new(),
+            read_buffer: received,
+            reader: get,
+            side: Side::Client,
+            write_buffer: BytesMut::new(),
+            writer: post,
+        }
+    }
+
+    /// Constructs the server side of a tunnel from the `GET` connection, the `POST` connection,
+    /// and any data that was received on the latter after the head of the request.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the received data is not valid base64.
+    pub fn server(get: T, post: T, received: &[u8]) -> io::Result<Self> {
+        let mut tunnel = Tunnel {
+            pending_base64: BytesMut::new(),
+            read_buffer: BytesMut::new(),
+            reader: post,
+            side: Side::Server,
+            write_buffer: BytesMut::new(),
+            writer: get,
+        };
+        tunnel.decode(received)?;
+        Ok(tunnel)
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// Decodes as much of the given base64 data as possible into the read buffer. Whitespace is
+    /// ignored, and any characters that do not make up a whole group of four are kept until more
+    /// data is received. Since each group is decoded separately, padding may appear in between
+    /// groups, as is the case when a client encodes every message separately.
+    fn decode(&mut self, data: &[u8]) -> io::Result<()> {
+        // `BytesMut::extend` only reserves the lower bound of the iterator's size hint, which is
+        // zero after filtering, so the space is reserved up front.
+
+        self.pending_base64.reserve(data.len());
+        self.pending_base64
+            .extend(data.iter().filter(|b| !b.is_ascii_whitespace()).cloned());
+
+        let length = self.pending_base64.len() - self.pending_base64.len() % 4;
+        let groups = self.pending_base64.split_to(length);
+
+        for group in groups.chunks(4) {
+            let decoded = base64::decode(group)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            self.read_buffer.extend_from_slice(&decoded);
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Tunnel<T>
+where
+    T: Write,
+{
+    /// Writes as much of the write buffer as possible.
+    fn write_buffered(&mut self) -> io::Result<()> {
+        while !self.write_buffer.is_empty() {
+            match self.writer.write(&self.write_buffer)? {
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write to tunnel",
+                    ))
+                }
+                n => {
+                    self.write_buffer.advance(n);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Read for Tunnel<T>
+where
+    T: Read,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let mut data = [0; READ_BUFFER_SIZE];
+
+        while self.read_buffer.is_empty() {
+            let n = self.reader.read(&mut data)?;
+
+            if n == 0 {
+                return Ok(0);
+            }
+
+            match self.side {
+                Side::Client => self.read_buffer.extend_from_slice(&data[..n]),
+                Side::Server => self.decode(&data[..n])?,
+            }
+        }
+
+        let n = buffer.len().min(self.read_buffer.len());
+        buffer[..n].copy_from_slice(&self.read_buffer.split_to(n));
+        Ok(n)
+    }
+}
+
+impl<T> Write for Tunnel<T>
+where
+    T: Write,
+{
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        if self.side == Side::Server {
+            return self.writer.write(buffer);
+        }
+
+        // New data is only accepted once everything that was previously accepted has been
+        // written, so that the buffer does not grow without bound.
+
+        self.write_buffered()?;
+        self.write_buffer
+            .extend_from_slice(base64::encode(buffer).as_bytes());
+
+        match self.write_buffered() {
+            Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => (),
+            Err(error) => return Err(error),
+            Ok(()) => (),
+        }
+
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buffered()?;
+        self.writer.flush()
+    }
+}
+
+impl<T> AsyncRead for Tunnel<T> where T: AsyncRead {}
+
+impl<T> AsyncWrite for Tunnel<T>
+where
+    T: AsyncWrite,
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match self.flush() {
+            Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => {
+                return Ok(Async::NotReady)
+            }
+            Err(error) => return Err(error),
+            Ok(()) => (),
+        }
+
+        self.writer.shutdown()
+    }
+}
+
+/// The head of an HTTP request or response, along with any data that was received after it.
+struct Head {
+    /// The first line of the head.
+    start_line: String,
+
+    /// The header fields of the head, with lowercase names.
+    headers: Vec<(String, String)>,
+
+    /// The data that was received after the head.
+    remaining: BytesMut,
+}
+
+impl Head {
+    /// Returns the value of the first header with the given lowercase name.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|&&(ref header_name, _)| header_name == name)
+            .map(|&(_, ref value)| value.as_str())
+    }
+
+    /// Parses the head from the given data if it is complete, which is the case once it contains
+    /// an empty line.
+    fn parse(data: &mut BytesMut) -> io::Result<Option<Head>> {
+        let end = match data.windows(4).position(|window| window == b"\r\n\r\n") {
+            Some(end) => end,
+            None if data.len() > MAX_TUNNEL_HEAD_SIZE => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "tunnel head is too large",
+                ))
+            }
+            None => return Ok(None),
+        };
+
+        let head = data.split_to(end + 4);
+        let head = String::from_utf8_lossy(&head[..end]);
+        let mut lines = head.split("\r\n");
+        let start_line = lines.next().unwrap_or("").to_string();
+        let headers = lines
+            .filter_map(|line| {
+                line.find(':').map(|index| {
+                    (
+                        line[..index].trim().to_ascii_lowercase(),
+                        line[index + 1..].trim().to_string(),
+                    )
+                })
+            })
+            .collect();
+
+        Ok(Some(Head {
+            headers,
+            remaining: data.take(),
+            start_line,
+        }))
+    }
+}
+
+/// A future that reads the head of an HTTP request or response from a stream.
+struct ReadHead<T> {
+    buffer: BytesMut,
+    stream: Option<T>,
+}
+
+impl<T> ReadHead<T> {
+    /// Starts reading a head from the stream, where the given data has already been read.
+    fn new(stream: T, buffer: BytesMut) -> Self {
+        ReadHead {
+            buffer,
+            stream: Some(stream),
+        }
+    }
+}
+
+impl<T> Future for ReadHead<T>
+where
+    T: AsyncRead,
+{
+    type Item = (T, Head);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let mut data = [0; READ_BUFFER_SIZE];
+
+        loop {
+            if let Some(head) = Head::parse(&mut self.buffer)? {
+                let stream = self
+                    .stream
+                    .take()
+                    .expect("`ReadHead` polled after completion");
+                return Ok(Async::Ready((stream, head)));
+            }
+
+            let n = try_ready!(self
+                .stream
+                .as_mut()
+                .expect("`ReadHead` polled after completion")
+                .poll_read(&mut data));
+
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed before tunnel head was received",
+                ));
+            }
+
+            self.buffer.extend_from_slice(&data[..n]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A connection that reads from and writes to in-memory buffers.
+    struct Memory {
+        read: io::Cursor<Vec<u8>>,
+        written: Vec<u8>,
+    }
+
+    impl Memory {
+        fn new(read: &[u8]) -> Self {
+            Memory {
+                read: io::Cursor::new(read.to_vec()),
+                written: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for Memory {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            self.read.read(buffer)
+        }
+    }
+
+    impl Write for Memory {
+        fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+            self.written.write(buffer)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_tunnel_client() {
+        let mut tunnel = Tunnel::client(
+            Memory::new(b" RTSP/2.0 200 OK\r\n"),
+            BytesMut::from("from server:"),
+            Memory::new(b""),
+        );
+        let mut data = String::new();
+        tunnel.read_to_string(&mut data).unwrap();
+        tunnel.write_all(b"OPTIONS * RTSP/2.0\r\n").unwrap();
+
+        assert_eq!(data, "from server: RTSP/2.0 200 OK\r\n");
+        assert_eq!(tunnel.writer.written, b"T1BUSU9OUyAqIFJUU1AvMi4wDQo=");
+    }
+
+    #[test]
+    fn test_tunnel_server() {
+        let mut tunnel = Tunnel::server(
+            Memory::new(b""),
+            Memory::new(b"1DQoNCg==\r\nT1BUSU9OUyAqIFJU\r\nU1AvMi4wDQo="),
+            b"T1BUSU9OUyAqIFJUU1AvMi4wDQpDU2VxOiA",
+        )
+        .unwrap();
+        let mut data = String::new();
+        tunnel.read_to_string(&mut data).unwrap();
+        tunnel.write_all(b"RTSP/2.0 200 OK\r\n").unwrap();
+
+        assert_eq!(
+            data,
+            "OPTIONS * RTSP/2.0\r\nCSeq: 5\r\n\r\nOPTIONS * RTSP/2.0\r\n"
+        );
+        assert_eq!(tunnel.writer.written, b"RTSP/2.0 200 OK\r\n");
+    }
+
+    #[test]
+    fn test_tunnel_server_invalid_base64() {
+        let mut tunnel = Tunnel::server(Memory::new(b""), Memory::new(b"*!*!"), b"").unwrap();
+        let mut data = [0; 16];
+
+        assert_eq!(
+            tunnel.read(&mut data).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_head_parse() {
+        let mut data = BytesMut::from("GET /movie HTTP/1.0\r\nx-sessioncookie: abc\r\n");
+        assert!(Head::parse(&mut data).unwrap().is_none());
+
+        data.extend_from_slice(b"Accept: application/x-rtsp-tunnelled\r\n\r\nrest");
+        let head = Head::parse(&mut data).unwrap().unwrap();
+
+        assert_eq!(head.start_line, "GET /movie HTTP/1.0");
+        assert_eq!(head.header("x-sessioncookie"), Some("abc"));
+        assert_eq!(head.header("accept"), Some(TUNNEL_CONTENT_TYPE));
+        assert_eq!(&head.remaining[..], b"rest");
+
+        let mut data = BytesMut::from(vec![b'a'; MAX_TUNNEL_HEAD_SIZE + 1]);
+        assert!(Head::parse(&mut data).is_err());
+    }
+}
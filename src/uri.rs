@@ -238,6 +238,43 @@ impl RequestURI {
             .expect("request URI should have a base")
     }
 
+    /// Resolves the given URI reference against this URI, as is done for `Content-Base` headers or
+    /// `a=control` attributes.
+    ///
+    /// # Return Value
+    ///
+    /// An error will be returned if the resolved URI is not a valid request URI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(try_from)]
+    /// #
+    /// use std::convert::TryFrom;
+    ///
+    /// use rtsp::RequestURI;
+    ///
+    /// let base = RequestURI::try_from("rtsp://example.com/movie/").unwrap();
+    /// assert_eq!(
+    ///     base.join("trackID=1").unwrap().as_str(),
+    ///     "rtsp://example.com/movie/trackID=1"
+    /// );
+    /// assert_eq!(
+    ///     base.join("rtsp://example.org/audio").unwrap().as_str(),
+    ///     "rtsp://example.org/audio"
+    /// );
+    /// ```
+    pub fn join(&self, reference: &str) -> Result<RequestURI, InvalidRequestURI> {
+        let mut uri = self.inner.join(reference).map_err(|_| InvalidRequestURI)?;
+        uri.set_fragment(None);
+
+        if uri.has_authority() {
+            Ok(RequestURI { inner: uri })
+        } else {
+            Err(InvalidRequestURI)
+        }
+    }
+
     pub fn origin(&self) -> (String, Host<String>, u16) {
         match self.inner.origin() {
             Origin::Tuple(scheme, host, port) => (scheme, host, port),
@@ -4,8 +4,8 @@
 //! accessed through the root of the crate ([`Version`]) rather than this module.
 //!
 //! The [`Version`] enum type contains variants that represent the various versions of the the RTSP
-//! protocol. Note that although RTSP 1.0 and RTSP 2.0 are listed here, RTSP 2.0 obseletes RTSP 1.0
-//! and thus RTSP 1.0 is not actually supported by this library.
+//! protocol. Although RTSP 2.0 obseletes RTSP 1.0, many devices still only speak RTSP 1.0, so which
+//! versions a connection accepts is determined by its [`VersionPolicy`].
 //!
 //! # Examples
 //!
@@ -31,7 +31,8 @@ pub enum Version {
     /// `RTSP/1.0`
     /// [[RFC2326](https://tools.ietf.org/html/rfc2326)]
     ///
-    /// This is only listed here for completeness. RTSP 2.0 obseletes RTSP 1.0.
+    /// RTSP 2.0 obseletes RTSP 1.0, so this is only used by connections whose [`VersionPolicy`]
+    /// allows it.
     RTSP10,
 
     /// `RTSP/2.0`
@@ -241,3 +242,65 @@ impl Error for InvalidVersion {
         }
     }
 }
+
+/// Determines which versions of RTSP a connection accepts and which version it uses for the
+/// messages it sends.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum VersionPolicy {
+    /// Both versions are accepted. Until a message has been received, messages are sent using
+    /// RTSP 2.0, after which the version of the first received message is used for the rest of the
+    /// connection.
+    Negotiate,
+
+    /// Only RTSP 1.0 is accepted and used.
+    RTSP10Only,
+
+    /// Only RTSP 2.0 is accepted and used. This is the default.
+    RTSP20Only,
+}
+
+impl VersionPolicy {
+    /// Returns whether or not messages with the given version are accepted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::version::{Version, VersionPolicy};
+    ///
+    /// assert!(VersionPolicy::Negotiate.is_allowed(Version::RTSP10));
+    /// assert!(VersionPolicy::RTSP10Only.is_allowed(Version::RTSP10));
+    /// assert!(!VersionPolicy::RTSP20Only.is_allowed(Version::RTSP10));
+    /// ```
+    pub fn is_allowed(&self, version: Version) -> bool {
+        use self::VersionPolicy::*;
+
+        match *self {
+            Negotiate => true,
+            RTSP10Only => version == Version::RTSP10,
+            RTSP20Only => version == Version::RTSP20,
+        }
+    }
+
+    /// Returns the version that is used before any version has been negotiated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtsp::version::{Version, VersionPolicy};
+    ///
+    /// assert_eq!(VersionPolicy::Negotiate.preferred_version(), Version::RTSP20);
+    /// assert_eq!(VersionPolicy::RTSP10Only.preferred_version(), Version::RTSP10);
+    /// ```
+    pub fn preferred_version(&self) -> Version {
+        match *self {
+            VersionPolicy::RTSP10Only => Version::RTSP10,
+            _ => Version::RTSP20,
+        }
+    }
+}
+
+impl Default for VersionPolicy {
+    fn default() -> Self {
+        VersionPolicy::RTSP20Only
+    }
+}
@@ -0,0 +1,138 @@
+extern crate bytes;
+extern crate futures;
+extern crate rtsp;
+extern crate tokio;
+extern crate tokio_tcp;
+
+use bytes::BytesMut;
+use futures::{future, Future, Stream};
+use rtsp::auth::{AuthenticationService, ClientAuthenticator};
+use rtsp::header::types::DigestAlgorithm;
+use rtsp::protocol::{Connection, EmptyService};
+use rtsp::{Request, Response, Service, StatusCode};
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokio_tcp::{TcpListener, TcpStream};
+
+#[derive(Clone)]
+struct OkService;
+
+impl Service for OkService {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, _: Self::Request) -> Self::Future {
+        let response = Response::builder().build(BytesMut::new()).unwrap();
+        Box::new(future::ok(response))
+    }
+}
+
+/// Sends the given number of requests through a [`ClientAuthenticator`] to a server requiring
+/// authentication, returning the status code of each response.
+fn authenticate(
+    password: &'static str,
+    digest_algorithms: Vec<DigestAlgorithm>,
+    requests: usize,
+) -> Vec<StatusCode> {
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let address = listener.local_addr().unwrap();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+
+    let mut credential_store = HashMap::new();
+    credential_store.insert("user".to_string(), "pass".to_string());
+    let service = AuthenticationService::builder()
+        .allow_basic(digest_algorithms.is_empty())
+        .digest_algorithms(digest_algorithms)
+        .build(OkService, credential_store)
+        .unwrap();
+
+    let server = listener
+        .incoming()
+        .take(1)
+        .for_each(move |tcp_stream| {
+            let (connection, handler, mut handle) =
+                Connection::new(tcp_stream, Some(service.clone()));
+            tokio::spawn(connection);
+            tokio::spawn(handler.unwrap());
+
+            // Dropping the last handle would shut the connection down, so it is kept alive until
+            // the client closes the connection.
+
+            let shutdown = handle.take_shutdown().unwrap();
+            tokio::spawn(shutdown.map(move |_| drop(handle)));
+            Ok(())
+        })
+        .map_err(|_| ());
+
+    let client = TcpStream::connect(&address)
+        .map_err(|_| ())
+        .and_then(move |tcp_stream| {
+            let (connection, _, handle) = Connection::new::<_, EmptyService>(tcp_stream, None);
+            tokio::spawn(connection);
+
+            let authenticator = ClientAuthenticator::new(handle, "user", password);
+
+            future::loop_fn(0, move |count| {
+                let results = results_clone.clone();
+                let mut authenticator = authenticator.clone();
+                let request = Request::options("rtsp://example.com/media")
+                    .build(BytesMut::new())
+                    .unwrap();
+
+                authenticator
+                    .send_request(request)
+                    .map_err(|_| ())
+                    .map(move |response| {
+                        results.lock().unwrap().push(response.status_code());
+
+                        if count + 1 == requests {
+                            future::Loop::Break(())
+                        } else {
+                            future::Loop::Continue(count + 1)
+                        }
+                    })
+            })
+        });
+
+    tokio::run(server.join(client).map(|_| ()));
+
+    let results = results.lock().unwrap().clone();
+    results
+}
+
+#[test]
+fn test_authentication_digest() {
+    assert_eq!(
+        authenticate(
+            "pass",
+            vec![DigestAlgorithm::SHA256, DigestAlgorithm::MD5],
+            3
+        ),
+        vec![StatusCode::OK; 3]
+    );
+    assert_eq!(
+        authenticate("pass", vec![DigestAlgorithm::MD5], 2),
+        vec![StatusCode::OK; 2]
+    );
+}
+
+#[test]
+fn test_authentication_basic() {
+    assert_eq!(authenticate("pass", vec![], 2), vec![StatusCode::OK; 2]);
+}
+
+#[test]
+fn test_authentication_invalid_password() {
+    assert_eq!(
+        authenticate("wrong", vec![DigestAlgorithm::SHA256], 2),
+        vec![StatusCode::Unauthorized; 2]
+    );
+    assert_eq!(
+        authenticate("wrong", vec![], 1),
+        vec![StatusCode::Unauthorized]
+    );
+}
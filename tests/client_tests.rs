@@ -0,0 +1,867 @@
+#![feature(try_from)]
+
+extern crate bytes;
+extern crate futures;
+extern crate rtsp;
+extern crate tokio;
+
+use bytes::BytesMut;
+use futures::{future, Future, Stream};
+use rtsp::client::{
+    incoming_requests, ClientSession, DefaultService, KeepAliveEvent, RequestError,
+};
+use rtsp::header::types::{NPTTime, NotifyReason, Range, TimeRange, Transport, TransportSpec};
+use rtsp::header::TypedHeader;
+use rtsp::protocol::{Config, RedirectPolicy, RequestOptions, ShutdownType};
+use rtsp::request::TypedRequest;
+use rtsp::sdp::SessionDescription;
+use rtsp::server::{ImplementedMethods, MediaFuture, MediaServer, MediaService};
+use rtsp::state::SessionState;
+use rtsp::version::VersionPolicy;
+use rtsp::{
+    Client, HeaderName, HeaderValue, Method, Request, RequestURI, Response, Server, Service,
+    StatusCode,
+};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const DESCRIPTION: &str = "v=0\r\n\
+                           o=- 0 0 IN IP4 127.0.0.1\r\n\
+                           s=Movie\r\n\
+                           c=IN IP4 0.0.0.0\r\n\
+                           t=0 0\r\n\
+                           a=control:*\r\n\
+                           m=video 0 RTP/AVP 96\r\n\
+                           a=control:trackID=0\r\n\
+                           m=audio 0 RTP/AVP 97\r\n\
+                           a=control:trackID=1\r\n";
+
+/// Records the request line of every request it receives.
+struct Application(Arc<Mutex<Vec<String>>>);
+
+impl Application {
+    fn respond(&self, request: &TypedRequest<BytesMut>, body: &str) -> MediaFuture<io::Error> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", request.method(), request.uri()));
+        let response = Response::typed_builder()
+            .build(BytesMut::from(body))
+            .unwrap();
+        Box::new(future::ok(response))
+    }
+}
+
+impl MediaServer for Application {
+    type Error = io::Error;
+
+    const IMPLEMENTED: ImplementedMethods = ImplementedMethods {
+        announce: true,
+        describe: true,
+        get_parameter: true,
+        pause: true,
+        play: true,
+        record: true,
+        setup: true,
+        teardown: true,
+        ..ImplementedMethods::NONE
+    };
+
+    fn announce(&mut self, request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        assert_eq!(request.body(), &BytesMut::from(DESCRIPTION));
+        self.respond(&request, "")
+    }
+
+    fn describe(&mut self, request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        self.respond(&request, DESCRIPTION)
+    }
+
+    fn get_parameter(&mut self, request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        self.respond(&request, "")
+    }
+
+    fn pause(&mut self, request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        self.respond(&request, "")
+    }
+
+    fn play(&mut self, request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        assert!(request.headers().has_raw(&HeaderName::Range));
+        assert!(request.headers().has_raw(&HeaderName::Scale));
+        self.respond(&request, "")
+    }
+
+    fn record(&mut self, request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        self.respond(&request, "")
+    }
+
+    fn setup(&mut self, request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        let transport = request
+            .headers()
+            .get::<Transport>()
+            .unwrap()
+            .unwrap()
+            .clone();
+        let future = self.respond(&request, "");
+
+        Box::new(future.map(move |mut response| {
+            response.headers_mut().set(transport);
+            response
+        }))
+    }
+
+    fn teardown(&mut self, request: TypedRequest<BytesMut>) -> MediaFuture<Self::Error> {
+        self.respond(&request, "")
+    }
+}
+
+/// Redirects every request to the given location, or responds with `200 OK` if there is none.
+struct Redirector(Option<String>);
+
+impl Service for Redirector {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, _: Self::Request) -> Self::Future {
+        let mut builder = Response::builder();
+
+        if let Some(ref location) = self.0 {
+            builder.status_code(StatusCode::MovedPermanently).header(
+                HeaderName::Location,
+                HeaderValue::try_from(location.as_str()).unwrap(),
+            );
+        }
+
+        Box::new(future::ok(builder.build(BytesMut::new()).unwrap()))
+    }
+}
+
+#[test]
+fn test_client_session() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let requests_clone = requests.clone();
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(move || Ok(MediaService::new(Application(requests_clone.clone()))))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let session_manager = server.session_manager().clone();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+    let uri = RequestURI::try_from(format!("rtsp://{}/movie/", address).as_str()).unwrap();
+
+    let client = Client::connect(address)
+        .map_err(|_| ())
+        .and_then(move |client| {
+            ClientSession::describe(client.clone(), uri)
+                .map_err(|_| ())
+                .map(move |session| (client, session))
+        })
+        .and_then(|(client, mut session)| {
+            let transport = Transport(vec![TransportSpec::try_from(
+                "RTP/AVP/TCP;unicast;interleaved=0-1",
+            )
+            .unwrap()]);
+
+            session
+                .setup(move |_| transport.clone())
+                .map_err(|_| ())
+                .map(move |_| (client, session))
+        })
+        .and_then(|(client, mut session)| {
+            let range = Range::NPT(Some(TimeRange::From(NPTTime::Now)));
+
+            session
+                .play(Some(range), Some(2.0))
+                .map_err(|_| ())
+                .map(move |_| (client, session))
+        })
+        .and_then(|(client, mut session)| {
+            session
+                .pause()
+                .map_err(|_| ())
+                .map(move |_| (client, session))
+        })
+        .and_then(move |(mut client, session)| {
+            {
+                let mut results = results_clone.lock().unwrap();
+                results.push(session.state() == SessionState::Ready);
+                results.push(session.id().is_some());
+                results.push(session.timeout_duration().is_some());
+                results.push(
+                    session
+                        .tracks()
+                        .iter()
+                        .all(|track| track.transport().is_some()),
+                );
+                results.push(session_manager.number_of_sessions() == 1);
+            }
+
+            // Dropping the session tears it down, which the server handles before the following
+            // request since they are sent over the same connection.
+
+            drop(session);
+
+            let request = Request::builder()
+                .method(Method::Options)
+                .uri("*")
+                .build(BytesMut::new())
+                .unwrap();
+
+            client.send_request(request).map_err(|_| ()).map(move |_| {
+                results_clone
+                    .lock()
+                    .unwrap()
+                    .push(session_manager.number_of_sessions() == 0);
+            })
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(*results.lock().unwrap(), vec![true; 6]);
+
+    let base = format!("rtsp://{}/movie/", address);
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![
+            format!("DESCRIBE {}", base),
+            format!("SETUP {}trackID=0", base),
+            format!("SETUP {}trackID=1", base),
+            format!("PLAY {}", base),
+            format!("PAUSE {}", base),
+            format!("TEARDOWN {}", base),
+        ]
+    );
+}
+
+#[test]
+fn test_client_session_record() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let requests_clone = requests.clone();
+    let mut config = Config::builder();
+    config.version_policy(VersionPolicy::RTSP10Only);
+    let config = config.build().unwrap();
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .config(config.clone())
+        .serve(move || Ok(MediaService::new(Application(requests_clone.clone()))))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+    let uri = RequestURI::try_from(format!("rtsp://{}/live/", address).as_str()).unwrap();
+    let description = SessionDescription::try_from(DESCRIPTION).unwrap();
+
+    let client = Client::connect_url_with_config(uri.clone(), config, DefaultService)
+        .map_err(|_| ())
+        .and_then(move |client| ClientSession::announce(client, uri, description).map_err(|_| ()))
+        .and_then(|mut session| {
+            let transport = Transport(vec![TransportSpec::try_from(
+                "RTP/AVP/TCP;unicast;interleaved=0-1;mode=record",
+            )
+            .unwrap()]);
+
+            session
+                .setup(move |_| transport.clone())
+                .map_err(|_| ())
+                .map(move |_| session)
+        })
+        .and_then(|mut session| session.record(None).map_err(|_| ()).map(|_| session))
+        .and_then(move |mut session| {
+            results_clone
+                .lock()
+                .unwrap()
+                .push(session.state() == SessionState::Record);
+
+            session.play(None, None).then(move |result| {
+                results_clone.lock().unwrap().push(result.is_err());
+                session.teardown().map_err(|_| ())
+            })
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(*results.lock().unwrap(), vec![true; 2]);
+
+    let base = format!("rtsp://{}/live/", address);
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![
+            format!("ANNOUNCE {}", base),
+            format!("SETUP {}trackID=0", base),
+            format!("SETUP {}trackID=1", base),
+            format!("RECORD {}", base),
+            format!("TEARDOWN {}", base),
+        ]
+    );
+}
+
+#[test]
+fn test_client_session_setup_and_play() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let requests_clone = requests.clone();
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(move || Ok(MediaService::new(Application(requests_clone.clone()))))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let session_manager = server.session_manager().clone();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+    let uri = RequestURI::try_from(format!("rtsp://{}/movie/", address).as_str()).unwrap();
+
+    let client = Client::connect(address)
+        .map_err(|_| ())
+        .and_then(move |client| ClientSession::describe(client, uri).map_err(|_| ()))
+        .and_then(|mut session| {
+            let transport = Transport(vec![TransportSpec::try_from(
+                "RTP/AVP/TCP;unicast;interleaved=0-1",
+            )
+            .unwrap()]);
+
+            let range = Range::NPT(Some(TimeRange::From(NPTTime::Now)));
+
+            session
+                .setup_and_play(move |_| transport.clone(), Some(range), Some(2.0))
+                .map_err(|_| ())
+                .map(move |_| session)
+        })
+        .and_then(move |session| {
+            let mut results = results_clone.lock().unwrap();
+            results.push(session.state() == SessionState::Play);
+            results.push(session.id().is_some());
+            results.push(
+                session
+                    .tracks()
+                    .iter()
+                    .all(|track| track.transport().is_some()),
+            );
+            results.push(session_manager.number_of_sessions() == 1);
+            session.teardown().map_err(|_| ())
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(*results.lock().unwrap(), vec![true; 4]);
+
+    let base = format!("rtsp://{}/movie/", address);
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![
+            format!("DESCRIBE {}", base),
+            format!("SETUP {}trackID=0", base),
+            format!("SETUP {}trackID=1", base),
+            format!("PLAY {}", base),
+            format!("TEARDOWN {}", base),
+        ]
+    );
+}
+
+#[test]
+fn test_client_session_method_not_valid_in_this_state() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(move || Ok(MediaService::new(Application(requests.clone()))))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+    let uri = RequestURI::try_from(format!("rtsp://{}/movie/", address).as_str()).unwrap();
+
+    let client = Client::connect(address)
+        .map_err(|_| ())
+        .and_then(move |client| ClientSession::describe(client, uri).map_err(|_| ()))
+        .and_then(move |mut session| {
+            session.play(None, None).then(move |result| {
+                results_clone
+                    .lock()
+                    .unwrap()
+                    .push(format!("{:?}", result.err()));
+                drop(session);
+                Ok(())
+            })
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec!["Some(MethodNotValidInThisState(Init))".to_string()]
+    );
+}
+
+#[test]
+fn test_client_connect_url() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let requests_clone = requests.clone();
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(move || Ok(MediaService::new(Application(requests_clone.clone()))))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let uri = RequestURI::try_from(format!("rtsp://localhost:{}/movie/", address.port()).as_str())
+        .unwrap();
+
+    let client = Client::connect_url(uri)
+        .map_err(|_| ())
+        .and_then(|mut client| {
+            let request = Request::builder()
+                .method(Method::Describe)
+                .uri(client.resolve_uri("trackID=0").unwrap().as_str())
+                .build(BytesMut::new())
+                .unwrap();
+
+            client.send_request(request).map_err(|_| ())
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![format!(
+            "DESCRIBE rtsp://localhost:{}/movie/trackID=0",
+            address.port()
+        )]
+    );
+}
+
+#[test]
+fn test_client_session_keep_alive() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .session_timeout_duration(Duration::from_secs(1))
+        .serve(move || Ok(MediaService::new(Application(requests.clone()))))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let session_manager = server.session_manager().clone();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_clone = events.clone();
+    let number_of_sessions = Arc::new(Mutex::new(0));
+    let number_of_sessions_clone = number_of_sessions.clone();
+    let uri = RequestURI::try_from(format!("rtsp://{}/movie/", address).as_str()).unwrap();
+
+    let client = Client::connect(address)
+        .map_err(|_| ())
+        .and_then(move |client| ClientSession::describe(client, uri).map_err(|_| ()))
+        .and_then(|mut session| {
+            let transport = Transport(vec![TransportSpec::try_from(
+                "RTP/AVP/TCP;unicast;interleaved=0-1",
+            )
+            .unwrap()]);
+
+            session
+                .setup(move |_| transport.clone())
+                .map_err(|_| ())
+                .map(move |_| session)
+        })
+        .and_then(move |session| {
+            // The session would expire after one second if it were not kept alive.
+
+            let keep_alive = session.keep_alive(0.25).unwrap().unwrap();
+
+            keep_alive.take(6).collect().and_then(move |events| {
+                *events_clone.lock().unwrap() = events;
+                *number_of_sessions_clone.lock().unwrap() = session_manager.number_of_sessions();
+                session.teardown().map(|_| ()).map_err(|_| ())
+            })
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    let mut expected_events = vec![KeepAliveEvent::Refreshed(Method::GetParameter); 6];
+    expected_events[0] = KeepAliveEvent::Refreshed(Method::Options);
+
+    assert_eq!(*events.lock().unwrap(), expected_events);
+    assert_eq!(*number_of_sessions.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_client_redirect() {
+    let target_server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(|| Ok(Redirector(None)))
+        .unwrap();
+    let target_address = target_server.local_addr().unwrap();
+    let mut target_server_handle = target_server.handle();
+    let target_uri = format!("rtsp://{}/movie", target_address);
+
+    let location = target_uri.clone();
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(move || Ok(Redirector(Some(location.clone()))))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let uri = RequestURI::try_from(format!("rtsp://{}/movie", address).as_str()).unwrap();
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+    let options = RequestOptions::builder()
+        .redirect_policy(RedirectPolicy::Limited(5))
+        .build()
+        .unwrap();
+    let request = Request::builder()
+        .method(Method::Describe)
+        .uri(uri.as_str())
+        .build(BytesMut::new())
+        .unwrap();
+
+    let client = Client::connect_url(uri.clone())
+        .map_err(|_| ())
+        .and_then(move |mut client| {
+            client
+                .send_request_with_options(request, options)
+                .map_err(|_| ())
+                .map(move |(response, redirected_client)| {
+                    let mut results = results_clone.lock().unwrap();
+                    results.push(response.status_code().to_string());
+                    results.push(redirected_client.uri().unwrap().to_string());
+                    results.push(client.uri().unwrap().to_string());
+                })
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            target_server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        tokio::spawn(target_server.map_err(|_| ()));
+        client
+    }));
+
+    // The original client stays connected to the server that sent the redirect.
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec!["200".to_string(), target_uri, uri.to_string()]
+    );
+}
+
+#[test]
+fn test_client_redirect_loop() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(|| Ok(Redirector(Some("/movie".to_string()))))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let uri = RequestURI::try_from(format!("rtsp://{}/movie", address).as_str()).unwrap();
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+    let options = RequestOptions::builder()
+        .redirect_policy(RedirectPolicy::Limited(5))
+        .build()
+        .unwrap();
+    let request = Request::builder()
+        .method(Method::Describe)
+        .uri(uri.as_str())
+        .build(BytesMut::new())
+        .unwrap();
+
+    let client = Client::connect(address)
+        .map_err(|_| ())
+        .and_then(move |mut client| {
+            client
+                .send_request_with_options(request, options)
+                .then(move |result| {
+                    results_clone.lock().unwrap().push(result.err());
+                    Ok(())
+                })
+        })
+        .then(move |_: Result<(), ()>| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec![Some(RequestError::RedirectLoop)]
+    );
+}
+
+#[test]
+fn test_client_incoming_requests() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // The server sends a `PLAY_NOTIFY` request, which the application responds to, followed by a
+    // `SET_PARAMETER` request, which the application drops and is answered by default.
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut status_lines = Vec::new();
+
+        for request in &[
+            "PLAY_NOTIFY rtsp://127.0.0.1/movie RTSP/2.0\r\n\
+             CSeq: 1\r\n\
+             Notify-Reason: end-of-stream\r\n\
+             \r\n",
+            "SET_PARAMETER rtsp://127.0.0.1/movie RTSP/2.0\r\n\
+             CSeq: 2\r\n\
+             \r\n",
+        ] {
+            stream.write_all(request.as_bytes()).unwrap();
+
+            let mut response = Vec::new();
+            let mut byte = [0];
+
+            while !response.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).unwrap();
+                response.push(byte[0]);
+            }
+
+            let response = String::from_utf8(response).unwrap();
+            status_lines.push(response.lines().next().unwrap().to_string());
+        }
+
+        status_lines
+    });
+
+    let reasons = Arc::new(Mutex::new(Vec::new()));
+    let reasons_clone = reasons.clone();
+    let (service, requests) = incoming_requests();
+
+    let client = Client::connect_with_service(address, service)
+        .map_err(|_| ())
+        .and_then(move |client| {
+            requests
+                .take(2)
+                .for_each(move |incoming| {
+                    let reason = NotifyReason::try_from_header_raw(
+                        &incoming
+                            .request()
+                            .headers()
+                            .get_all(HeaderName::NotifyReason)
+                            .iter()
+                            .cloned()
+                            .collect::<Vec<_>>(),
+                    )
+                    .ok();
+                    reasons_clone.lock().unwrap().push(reason.clone());
+
+                    if reason.is_some() {
+                        incoming.respond(Response::builder().build(BytesMut::new()).unwrap());
+                    }
+
+                    Ok(())
+                })
+                .then(move |_| {
+                    drop(client);
+                    Ok(())
+                })
+        });
+
+    tokio::run(client);
+
+    assert_eq!(
+        *reasons.lock().unwrap(),
+        vec![Some(NotifyReason::EndOfStream), None]
+    );
+    assert_eq!(
+        server.join().unwrap(),
+        vec!["RTSP/2.0 200 OK", "RTSP/2.0 501 Not Implemented"]
+    );
+}
+
+#[test]
+fn test_client_server_teardown() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let uri = RequestURI::try_from(format!("rtsp://{}/movie/", address).as_str()).unwrap();
+
+    // The server sets up a session, tears it down, and then records the request line of the next
+    // request, which must not be a second `TEARDOWN` request from the dropped session.
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let request = read_head(&mut stream);
+        assert!(request.starts_with("DESCRIBE "));
+        write_response(&mut stream, &request, "", DESCRIPTION);
+
+        for _ in 0..2 {
+            let request = read_head(&mut stream);
+            assert!(request.starts_with("SETUP "));
+            write_response(
+                &mut stream,
+                &request,
+                "Session: 12345678;timeout=60\r\n\
+                 Transport: RTP/AVP/TCP;unicast;interleaved=0-1\r\n",
+                "",
+            );
+        }
+
+        stream
+            .write_all(
+                format!(
+                    "TEARDOWN rtsp://{}/movie/ RTSP/2.0\r\n\
+                     CSeq: 1\r\n\
+                     Session: 12345678\r\n\
+                     Terminate-Reason: Server-Admin\r\n\
+                     \r\n",
+                    address
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        // The response to the `TEARDOWN` request and the following request may arrive in either
+        // order.
+
+        let mut heads = vec![read_head(&mut stream), read_head(&mut stream)];
+        heads.sort();
+        write_response(&mut stream, &heads[0], "", "");
+
+        heads
+            .iter()
+            .map(|head| head.lines().next().unwrap().to_string())
+            .collect::<Vec<_>>()
+    });
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+    let (service, requests) = incoming_requests();
+
+    let client = Client::connect_with_service(address, service)
+        .map_err(|_| ())
+        .and_then(move |client| {
+            ClientSession::describe(client.clone(), uri)
+                .map_err(|_| ())
+                .map(move |session| (client, session))
+        })
+        .and_then(|(client, mut session)| {
+            let transport = Transport(vec![TransportSpec::try_from(
+                "RTP/AVP/TCP;unicast;interleaved=0-1",
+            )
+            .unwrap()]);
+
+            session
+                .setup(move |_| transport.clone())
+                .map_err(|_| ())
+                .map(move |_| (client, session))
+        })
+        .and_then(move |(client, session)| {
+            requests
+                .into_future()
+                .map_err(|_| ())
+                .and_then(move |(incoming, _)| {
+                    let incoming = incoming.unwrap();
+                    {
+                        let mut results = results_clone.lock().unwrap();
+                        results.push(format!("{}", incoming.request().method()));
+                        results.push(format!("{:?}", session.state()));
+                        results.push(format!("{:?}", session.id()));
+                    }
+
+                    incoming.respond(Response::builder().build(BytesMut::new()).unwrap());
+                    drop(session);
+                    Ok(client)
+                })
+        })
+        .and_then(|mut client| {
+            let request = Request::builder()
+                .method(Method::Options)
+                .uri("*")
+                .build(BytesMut::new())
+                .unwrap();
+
+            client.send_request(request).map(|_| ()).map_err(|_| ())
+        });
+
+    tokio::run(client);
+
+    assert_eq!(*results.lock().unwrap(), vec!["TEARDOWN", "Init", "None"]);
+    assert_eq!(
+        server.join().unwrap(),
+        vec!["OPTIONS * RTSP/2.0", "RTSP/2.0 200 OK"]
+    );
+}
+
+/// Reads the request or response line and the headers of a message without a body.
+fn read_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut byte = [0];
+
+    while !head.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+
+    String::from_utf8(head).unwrap()
+}
+
+/// Writes a `200 OK` response to the given request with the given additional headers and body.
+fn write_response(stream: &mut TcpStream, request: &str, headers: &str, body: &str) {
+    let cseq = request
+        .lines()
+        .find(|line| line.to_ascii_lowercase().starts_with("cseq:"))
+        .unwrap()[5..]
+        .trim();
+
+    stream
+        .write_all(
+            format!(
+                "RTSP/2.0 200 OK\r\nCSeq: {}\r\n{}Content-Length: {}\r\n\r\n{}",
+                cseq,
+                headers,
+                body.len(),
+                body
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+}
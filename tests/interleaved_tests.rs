@@ -0,0 +1,65 @@
+extern crate bytes;
+extern crate futures;
+extern crate rtsp;
+extern crate tokio;
+extern crate tokio_tcp;
+extern crate tokio_timer;
+
+use bytes::BytesMut;
+use futures::{lazy, stream, Future, Sink};
+use rtsp::protocol::{Config, Connection, EmptyService};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::current_thread::Runtime;
+use tokio_tcp::TcpStream;
+use tokio_timer::Timeout;
+
+/// Test to make sure that sending on an interleaved channel stalls once the connection cannot
+/// write any more because the peer is not reading, rather than queueing data without bound.
+#[test]
+fn test_interleaved_channel_backpressure() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+
+    let (tx_close, rx_close) = mpsc::channel::<()>();
+
+    thread::spawn(move || {
+        let (_stream, _) = server.accept().unwrap();
+        let _ = rx_close.recv();
+    });
+
+    let tcp_stream = TcpStream::connect(&address).wait().unwrap();
+    let mut config = Config::builder();
+    config.interleaved_buffer_size(4);
+    let config = config.build().unwrap();
+    let number_sent = Arc::new(AtomicUsize::new(0));
+    let payloads = {
+        let number_sent = number_sent.clone();
+        stream::iter_ok((0..1_000_000).map(move |_| {
+            number_sent.fetch_add(1, Ordering::SeqCst);
+            BytesMut::from(&[0; 1000][..])
+        }))
+    };
+
+    let mut runtime = Runtime::new().unwrap();
+    let elapsed = runtime.block_on(lazy(move || {
+        let (connection, _, mut handle) =
+            Connection::with_config::<_, EmptyService>(tcp_stream, None, config);
+        tokio::spawn(connection);
+
+        let channel = handle.interleaved_channel(0).unwrap();
+        Timeout::new(channel.send_all(payloads), Duration::from_millis(500)).then(move |result| {
+            drop(handle);
+            Ok::<_, ()>(result.err().map_or(false, |error| error.is_elapsed()))
+        })
+    }));
+
+    assert!(elapsed.unwrap());
+    assert!(number_sent.load(Ordering::SeqCst) < 100_000);
+
+    drop(tx_close);
+    runtime.run().unwrap();
+}
@@ -0,0 +1,548 @@
+extern crate bytes;
+extern crate futures;
+extern crate rtsp;
+extern crate tokio;
+extern crate tokio_tcp;
+extern crate tokio_timer;
+
+use bytes::BytesMut;
+use futures::sync::oneshot;
+use futures::{future, Future};
+use rtsp::header::types::Allow;
+use rtsp::protocol::{
+    Config, Connection, ConnectionHandle, DecoderLimits, EmptyService, RequestConcurrency,
+    ShutdownType,
+};
+use rtsp::{HeaderName, Method, Request, Response, Server, Service, StatusCode, TypedHeader};
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream as StdTcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio_tcp::TcpStream;
+use tokio_timer::Delay;
+
+struct OkService;
+
+impl Service for OkService {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, _: Self::Request) -> Self::Future {
+        let response = Response::builder().build(BytesMut::new()).unwrap();
+        Box::new(future::ok(response))
+    }
+}
+
+/// A service that delays its response to requests whose URI ends with `"slow"`.
+struct DelayService;
+
+impl Service for DelayService {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let delay = if request.uri().to_string().ends_with("slow") {
+            Duration::from_millis(200)
+        } else {
+            Duration::from_millis(0)
+        };
+
+        Box::new(
+            Delay::new(Instant::now() + delay)
+                .map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+                .map(|_| Response::builder().build(BytesMut::new()).unwrap()),
+        )
+    }
+}
+
+/// Responds with `503 Service Unavailable` to requests whose URI ends with `"fail"`.
+struct FailService;
+
+impl Service for FailService {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let mut builder = Response::builder();
+
+        if request.uri().to_string().ends_with("fail") {
+            builder.status_code(StatusCode::ServiceUnavailable);
+        }
+
+        Box::new(future::ok(builder.build(BytesMut::new()).unwrap()))
+    }
+}
+
+/// Connects a client to the given server and sends a single request with the given method,
+/// returning the response along with the handle of the connection.
+fn send_request(
+    address: SocketAddr,
+    method: Method,
+) -> impl Future<Item = (Response<BytesMut>, ConnectionHandle), Error = ()> {
+    TcpStream::connect(&address)
+        .map_err(|_| ())
+        .and_then(|tcp_stream| {
+            let (connection, _, mut handle) = Connection::new::<_, EmptyService>(tcp_stream, None);
+            tokio::spawn(connection);
+
+            let request = Request::builder()
+                .method(method)
+                .uri("rtsp://example.com/")
+                .build(BytesMut::new())
+                .unwrap();
+
+            handle
+                .send_request(request)
+                .map_err(|_| ())
+                .map(move |response| (response, handle))
+        })
+}
+
+#[test]
+fn test_server_serves_and_shuts_down_connections() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(|| Ok(OkService))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+
+    let client = send_request(address, Method::Options)
+        .join(send_request(address, Method::Options))
+        .and_then(
+            move |((first_response, mut first_handle), (second_response, mut second_handle))| {
+                let mut results = results_clone.lock().unwrap();
+                results.push(first_response.status_code());
+                results.push(second_response.status_code());
+                results.push(if server_handle.number_of_connections() == 2 {
+                    StatusCode::OK
+                } else {
+                    StatusCode::InternalServerError
+                });
+
+                server_handle.shutdown(ShutdownType::Graceful(Duration::from_secs(1)));
+
+                // The client handles are kept alive until the server has closed the connections.
+
+                first_handle
+                    .take_shutdown()
+                    .unwrap()
+                    .join(second_handle.take_shutdown().unwrap())
+                    .map(move |_| drop((first_handle, second_handle)))
+            },
+        );
+
+    tokio::run(server.map_err(|_| ()).join(client).map(|_| ()));
+
+    assert_eq!(*results.lock().unwrap(), vec![StatusCode::OK; 3]);
+}
+
+#[test]
+fn test_server_shutdown_without_connections() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(|| Ok(OkService))
+        .unwrap();
+    let mut server_handle = server.handle();
+
+    server_handle.shutdown(ShutdownType::Immediate);
+    tokio::run(server.map_err(|_| ()));
+
+    assert_eq!(server_handle.number_of_connections(), 0);
+}
+
+#[test]
+fn test_server_sessions() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(|| Ok(OkService))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let session_manager = server.session_manager().clone();
+    let server_handle = server.handle();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+
+    let client = TcpStream::connect(&address)
+        .map_err(|_| ())
+        .and_then(move |tcp_stream| {
+            let (connection, _, mut handle) = Connection::new::<_, EmptyService>(tcp_stream, None);
+            tokio::spawn(connection);
+
+            let request = Request::setup("rtsp://example.com/movie")
+                .build(BytesMut::new())
+                .unwrap();
+
+            handle
+                .send_request(request)
+                .map_err(|_| ())
+                .and_then(move |response| {
+                    let session = response
+                        .headers()
+                        .get(HeaderName::Session)
+                        .unwrap()
+                        .as_str()
+                        .split(';')
+                        .next()
+                        .unwrap()
+                        .to_string();
+                    let requests = vec![
+                        (Method::Play, Some(session.clone())),
+                        (Method::Play, Some("UnknownSession".to_string())),
+                        (Method::Teardown, Some(session.clone())),
+                        (Method::Play, Some(session)),
+                    ];
+
+                    future::loop_fn(
+                        (handle, requests.into_iter()),
+                        move |(mut handle, mut requests)| {
+                            let results = results_clone.clone();
+                            let mut server_handle = server_handle.clone();
+                            let (method, session) = requests.next().unwrap();
+                            let mut builder = Request::builder();
+                            builder.method(method).uri("rtsp://example.com/movie");
+
+                            if let Some(session) = session {
+                                builder.header(HeaderName::Session, session.as_str());
+                            }
+
+                            let request = builder.build(BytesMut::new()).unwrap();
+
+                            handle
+                                .send_request(request)
+                                .map_err(|_| ())
+                                .map(move |response| {
+                                    let mut results = results.lock().unwrap();
+                                    results.push(response.status_code());
+
+                                    if results.len() == 4 {
+                                        server_handle.shutdown(ShutdownType::Immediate);
+                                        future::Loop::Break(())
+                                    } else {
+                                        future::Loop::Continue((handle, requests))
+                                    }
+                                })
+                        },
+                    )
+                })
+        });
+
+    tokio::run(server.map_err(|_| ()).join(client).map(|_| ()));
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec![
+            StatusCode::OK,
+            StatusCode::SessionNotFound,
+            StatusCode::OK,
+            StatusCode::SessionNotFound,
+        ]
+    );
+    assert_eq!(session_manager.number_of_sessions(), 0);
+}
+
+#[test]
+fn test_server_method_not_valid_in_this_state() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(|| Ok(OkService))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+
+    let client = send_request(address, Method::Play).map(move |(response, _)| {
+        let allow = Allow::try_from_header_raw(
+            &response
+                .headers()
+                .get_all(HeaderName::Allow)
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let mut results = results_clone.lock().unwrap();
+        results.push(response.status_code());
+        results.push(
+            if allow.contains(&Method::Setup) && !allow.contains(&Method::Play) {
+                StatusCode::OK
+            } else {
+                StatusCode::InternalServerError
+            },
+        );
+        server_handle.shutdown(ShutdownType::Immediate);
+    });
+
+    tokio::run(server.map_err(|_| ()).join(client).map(|_| ()));
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec![StatusCode::MethodNotValidInThisState, StatusCode::OK]
+    );
+}
+
+/// Sets up a session and then sends a slow and a fast request for the session followed by a request
+/// without a session, returning the order in which the responses were received.
+fn request_completion_order(concurrency: RequestConcurrency) -> Vec<String> {
+    let mut config = Config::builder();
+    config.request_concurrency(concurrency);
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .config(config.build().unwrap())
+        .serve(|| Ok(DelayService))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+
+    let client = TcpStream::connect(&address)
+        .map_err(|_| ())
+        .and_then(move |tcp_stream| {
+            let (connection, _, mut handle) = Connection::new::<_, EmptyService>(tcp_stream, None);
+            tokio::spawn(connection);
+
+            let request = Request::setup("rtsp://example.com/movie")
+                .build(BytesMut::new())
+                .unwrap();
+
+            handle
+                .send_request(request)
+                .map_err(|_| ())
+                .and_then(move |response| {
+                    let session = response
+                        .headers()
+                        .get(HeaderName::Session)
+                        .unwrap()
+                        .as_str()
+                        .split(';')
+                        .next()
+                        .unwrap()
+                        .to_string();
+                    let requests = vec![
+                        (Method::GetParameter, "slow", Some(session.clone())),
+                        (Method::GetParameter, "fast", Some(session)),
+                        (Method::Options, "fast", None),
+                    ];
+                    let futures = requests
+                        .into_iter()
+                        .map(|(method, path, session)| {
+                            let results = results_clone.clone();
+                            let label = format!("{} {}", method, path);
+                            let mut builder = Request::builder();
+                            builder
+                                .method(method)
+                                .uri(format!("rtsp://example.com/{}", path).as_str());
+
+                            if let Some(session) = session {
+                                builder.header(HeaderName::Session, session.as_str());
+                            }
+
+                            handle
+                                .send_request(builder.build(BytesMut::new()).unwrap())
+                                .map_err(|_| ())
+                                .map(move |_| results.lock().unwrap().push(label))
+                        })
+                        .collect::<Vec<_>>();
+
+                    future::join_all(futures).map(move |_| drop(handle))
+                })
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(server.map_err(|_| ()).join(client).map(|_| ()));
+
+    let results = results.lock().unwrap();
+    results.clone()
+}
+
+#[test]
+fn test_server_sequential_requests() {
+    assert_eq!(
+        request_completion_order(RequestConcurrency::Sequential),
+        vec!["GET_PARAMETER slow", "GET_PARAMETER fast", "OPTIONS fast"]
+    );
+}
+
+#[test]
+fn test_server_concurrent_requests() {
+    assert_eq!(
+        request_completion_order(RequestConcurrency::Concurrent(4)),
+        vec!["OPTIONS fast", "GET_PARAMETER slow", "GET_PARAMETER fast"]
+    );
+}
+
+#[test]
+fn test_server_pipelined_requests() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(|| Ok(FailService))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+
+    let client = TcpStream::connect(&address)
+        .map_err(|_| ())
+        .and_then(move |tcp_stream| {
+            let (connection, _, mut handle) = Connection::new::<_, EmptyService>(tcp_stream, None);
+            tokio::spawn(connection);
+
+            // The first pipeline succeeds, so its `PLAY` request is bound to the new session. The
+            // `SETUP` request of the second pipeline fails, so its `PLAY` request fails as well.
+
+            let requests = vec![
+                (Method::Setup, "movie", 1),
+                (Method::Play, "movie", 1),
+                (Method::Setup, "fail", 2),
+                (Method::Play, "fail", 2),
+            ];
+            let futures = requests
+                .into_iter()
+                .map(|(method, path, pipeline)| {
+                    let request = Request::builder()
+                        .method(method)
+                        .uri(format!("rtsp://example.com/{}", path).as_str())
+                        .header(HeaderName::PipelinedRequests, pipeline.to_string().as_str())
+                        .build(BytesMut::new())
+                        .unwrap();
+
+                    handle.send_request(request).map_err(|_| ())
+                })
+                .collect::<Vec<_>>();
+
+            future::join_all(futures).map(move |responses| {
+                let mut results = results_clone.lock().unwrap();
+                let session_ids = responses
+                    .iter()
+                    .take(2)
+                    .map(|response| {
+                        response
+                            .headers()
+                            .get(HeaderName::Session)
+                            .unwrap()
+                            .as_str()
+                            .split(';')
+                            .next()
+                            .unwrap()
+                            .to_string()
+                    })
+                    .collect::<Vec<_>>();
+
+                for response in responses.iter() {
+                    results.push(response.status_code());
+                }
+
+                results.push(if session_ids[0] == session_ids[1] {
+                    StatusCode::OK
+                } else {
+                    StatusCode::InternalServerError
+                });
+
+                drop(handle);
+            })
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(server.map_err(|_| ()).join(client).map(|_| ()));
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec![
+            StatusCode::OK,
+            StatusCode::OK,
+            StatusCode::ServiceUnavailable,
+            StatusCode::SessionNotFound,
+            StatusCode::OK,
+        ]
+    );
+}
+
+/// Writes the given bytes to the stream and reads back the status line of the response.
+fn write_and_read_status_line(stream: &mut StdTcpStream, bytes: &[u8]) -> String {
+    let mut head = Vec::new();
+    let mut byte = [0];
+    stream.write_all(bytes).unwrap();
+
+    while !head.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+
+    String::from_utf8(head)
+        .unwrap()
+        .lines()
+        .next()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_server_decoder_limits() {
+    let limits = DecoderLimits::builder()
+        .max_body_size(4)
+        .max_line_length(64)
+        .build()
+        .unwrap();
+    let mut config = Config::builder();
+    config.decoder_limits(limits);
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .config(config.build().unwrap())
+        .serve(|| Ok(OkService))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let status_lines = Arc::new(Mutex::new(Vec::new()));
+    let status_lines_clone = status_lines.clone();
+    let (tx_done, rx_done) = oneshot::channel();
+
+    thread::spawn(move || {
+        let mut status_lines = status_lines_clone.lock().unwrap();
+
+        // The body that is too large is skipped, so the connection can still be used afterwards.
+
+        let mut stream = StdTcpStream::connect(address).unwrap();
+        status_lines.push(write_and_read_status_line(
+            &mut stream,
+            b"SET_PARAMETER * RTSP/2.0\r\nCSeq: 1\r\nContent-Length: 8\r\n\r\nBodyBody",
+        ));
+        status_lines.push(write_and_read_status_line(
+            &mut stream,
+            b"OPTIONS * RTSP/2.0\r\nCSeq: 2\r\n\r\n",
+        ));
+
+        let mut stream = StdTcpStream::connect(address).unwrap();
+        let request = format!("OPTIONS rtsp://example.com/{} RTSP/2.0\r\n", "a".repeat(64));
+        status_lines.push(write_and_read_status_line(&mut stream, request.as_bytes()));
+
+        tx_done.send(()).unwrap();
+    });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        rx_done.then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        })
+    }));
+
+    assert_eq!(
+        *status_lines.lock().unwrap(),
+        vec![
+            "RTSP/2.0 413 Request Message Body Too Large",
+            "RTSP/2.0 200 OK",
+            "RTSP/2.0 414 Request-URI Too Long",
+        ]
+    );
+}
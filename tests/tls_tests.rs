@@ -0,0 +1,180 @@
+#![cfg(feature = "tls")]
+
+extern crate bytes;
+extern crate futures;
+extern crate rtsp;
+extern crate tokio;
+extern crate tokio_tcp;
+
+use bytes::BytesMut;
+use futures::{future, Future};
+use rtsp::client::DefaultService;
+use rtsp::header::types::ConnectionCredentials;
+use rtsp::protocol::ShutdownType;
+use rtsp::tls::{peer_certificate, Certificate, TlsAcceptor, TlsConnector};
+use rtsp::{Client, Method, Request, RequestURI, Response, Server, Service, StatusCode};
+use std::convert::TryFrom;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::timer::Timeout;
+use tokio_tcp::TcpStream;
+
+const CERTIFICATE: &[u8] = include_bytes!("tls/certificate.pem");
+const KEY: &[u8] = include_bytes!("tls/key.pem");
+
+struct OkService;
+
+impl Service for OkService {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, _: Self::Request) -> Self::Future {
+        let response = Response::builder().build(BytesMut::new()).unwrap();
+        Box::new(future::ok(response))
+    }
+}
+
+fn serve() -> (Server<fn() -> io::Result<OkService>>, RequestURI) {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .tls_acceptor(TlsAcceptor::from_pem(CERTIFICATE, KEY).unwrap())
+        .serve((|| Ok(OkService)) as fn() -> io::Result<OkService>)
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let uri =
+        RequestURI::try_from(format!("rtsps://localhost:{}/", address.port()).as_str()).unwrap();
+
+    (server, uri)
+}
+
+fn connector() -> TlsConnector {
+    TlsConnector::with_root_certificates(vec![Certificate::from_pem(CERTIFICATE).unwrap()]).unwrap()
+}
+
+#[test]
+fn test_tls_client_server() {
+    let (server, uri) = serve();
+    let mut server_handle = server.handle();
+    let status_codes = Arc::new(Mutex::new(Vec::new()));
+    let status_codes_clone = status_codes.clone();
+    let request = Request::builder()
+        .method(Method::Options)
+        .uri(uri.as_str())
+        .build(BytesMut::new())
+        .unwrap();
+
+    let client = Client::connect_url_with_connector(uri, connector(), DefaultService)
+        .map_err(|_| ())
+        .and_then(move |mut client| client.send_request(request).map_err(|_| ()))
+        .then(move |result| {
+            status_codes_clone
+                .lock()
+                .unwrap()
+                .push(result.map(|response| response.status_code()));
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(*status_codes.lock().unwrap(), vec![Ok(StatusCode::OK)]);
+}
+
+#[test]
+fn test_tls_untrusted_certificate() {
+    let (server, uri) = serve();
+    let mut server_handle = server.handle();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+
+    let client = Client::connect_url(uri).then(move |result| {
+        results_clone.lock().unwrap().push(result.is_ok());
+        server_handle.shutdown(ShutdownType::Immediate);
+        Ok(())
+    });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(*results.lock().unwrap(), vec![false]);
+}
+
+#[test]
+fn test_tls_connection_credentials() {
+    let (server, uri) = serve();
+    let mut server_handle = server.handle();
+    let address = server.local_addr().unwrap();
+    let credentials = Arc::new(Mutex::new(Vec::new()));
+    let credentials_clone = credentials.clone();
+
+    let client = TcpStream::connect(&address)
+        .and_then(|tcp_stream| connector().connect("localhost", tcp_stream))
+        .map_err(|_| ())
+        .and_then(move |tls_stream| {
+            let certificate = peer_certificate(&tls_stream).unwrap().unwrap();
+            let connection_credentials =
+                ConnectionCredentials::new(uri.as_str(), certificate).unwrap();
+            credentials_clone
+                .lock()
+                .unwrap()
+                .push(connection_credentials);
+            Ok(())
+        })
+        .then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    let credentials = credentials.lock().unwrap();
+    let expected = Certificate::from_pem(CERTIFICATE)
+        .unwrap()
+        .to_der()
+        .unwrap();
+
+    assert_eq!(credentials.len(), 1);
+    assert!(credentials[0].credential_info().matches(&expected));
+}
+
+#[test]
+fn test_tls_handshake_timeout() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .tls_acceptor(TlsAcceptor::from_pem(CERTIFICATE, KEY).unwrap())
+        .tls_handshake_timeout_duration(Duration::from_millis(100))
+        .serve(|| Ok(OkService))
+        .unwrap();
+    let mut server_handle = server.handle();
+    let address = server.local_addr().unwrap();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results_clone = results.clone();
+
+    // The client never starts the handshake, so the server closes the connection once the
+    // handshake times out. The outer timeout only keeps the test from hanging if it does not.
+
+    let client = TcpStream::connect(&address)
+        .and_then(|tcp_stream| tokio::io::read_to_end(tcp_stream, Vec::new()))
+        .map(|(_, data)| data.len());
+    let client = Timeout::new(client, Duration::from_secs(5)).then(move |result| {
+        results_clone.lock().unwrap().push(result.ok());
+        server_handle.shutdown(ShutdownType::Immediate);
+        Ok(())
+    });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        client
+    }));
+
+    assert_eq!(*results.lock().unwrap(), vec![Some(0)]);
+}
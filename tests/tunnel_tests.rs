@@ -0,0 +1,175 @@
+extern crate base64;
+extern crate bytes;
+extern crate futures;
+extern crate rtsp;
+extern crate tokio;
+
+use bytes::BytesMut;
+use futures::sync::oneshot;
+use futures::{future, Future};
+use rtsp::protocol::ShutdownType;
+use rtsp::{Client, Method, Request, RequestURI, Response, Server, Service, StatusCode};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+struct OkService;
+
+impl Service for OkService {
+    type Request = Request<BytesMut>;
+    type Response = Response<BytesMut>;
+    type Error = io::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error> + Send + 'static>;
+
+    fn call(&mut self, _: Self::Request) -> Self::Future {
+        let response = Response::builder().build(BytesMut::new()).unwrap();
+        Box::new(future::ok(response))
+    }
+}
+
+fn options_request(uri: &RequestURI) -> Request<BytesMut> {
+    Request::builder()
+        .method(Method::Options)
+        .uri(uri.as_str())
+        .build(BytesMut::new())
+        .unwrap()
+}
+
+/// Reads from the stream until an empty line has been received, returning everything read.
+fn read_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut byte = [0];
+
+    while !head.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+
+    String::from_utf8(head).unwrap()
+}
+
+#[test]
+fn test_tunnel_client_server() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .http_tunneling(true)
+        .serve(|| Ok(OkService))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+    let uri = RequestURI::try_from(format!("rtsp://{}/movie", address).as_str()).unwrap();
+    let status_codes = Arc::new(Mutex::new(Vec::new()));
+    let status_codes_tunnel = status_codes.clone();
+    let status_codes_direct = status_codes.clone();
+    let request = options_request(&uri);
+    let second_request = options_request(&uri);
+    let direct_request = options_request(&uri);
+
+    let tunnel = Client::connect_tunnel(address, uri.clone())
+        .map_err(|_| ())
+        .and_then(move |mut client| {
+            client
+                .send_request(request)
+                .and_then(move |response| {
+                    status_codes_tunnel
+                        .lock()
+                        .unwrap()
+                        .push(response.status_code());
+                    client.send_request(second_request).map(move |response| {
+                        status_codes_tunnel
+                            .lock()
+                            .unwrap()
+                            .push(response.status_code());
+                    })
+                })
+                .map_err(|_| ())
+        });
+    let direct = Client::connect(address)
+        .map_err(|_| ())
+        .and_then(move |mut client| client.send_request(direct_request).map_err(|_| ()))
+        .map(move |response| {
+            status_codes_direct
+                .lock()
+                .unwrap()
+                .push(response.status_code());
+        });
+    let clients = tunnel.join(direct).then(move |_| {
+        server_handle.shutdown(ShutdownType::Immediate);
+        Ok(())
+    });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        clients
+    }));
+
+    assert_eq!(
+        *status_codes.lock().unwrap(),
+        vec![StatusCode::OK, StatusCode::OK, StatusCode::OK]
+    );
+}
+
+#[test]
+fn test_tunnel_wire_format() {
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .http_tunneling(true)
+        .serve(|| Ok(OkService))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let mut server_handle = server.handle();
+
+    let heads = Arc::new(Mutex::new(Vec::new()));
+    let heads_clone = heads.clone();
+    let (tx_done, rx_done) = oneshot::channel();
+
+    thread::spawn(move || {
+        let mut get = TcpStream::connect(address).unwrap();
+        get.write_all(
+            b"GET /movie HTTP/1.0\r\n\
+              x-sessioncookie: cookie\r\n\
+              Accept: application/x-rtsp-tunnelled\r\n\
+              \r\n",
+        )
+        .unwrap();
+        heads_clone.lock().unwrap().push(read_head(&mut get));
+
+        // The request is encoded in one piece but written in two, with the second piece including
+        // a line break, to check that the server decodes across reads.
+
+        let encoded = base64::encode("OPTIONS * RTSP/2.0\r\nCSeq: 1\r\n\r\n");
+        let mut post = TcpStream::connect(address).unwrap();
+        post.write_all(
+            format!(
+                "POST /movie HTTP/1.0\r\n\
+                 x-sessioncookie: cookie\r\n\
+                 Content-Type: application/x-rtsp-tunnelled\r\n\
+                 Content-Length: 32767\r\n\
+                 \r\n\
+                 {}",
+                &encoded[..10]
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        post.write_all(format!("\r\n{}", &encoded[10..]).as_bytes())
+            .unwrap();
+        heads_clone.lock().unwrap().push(read_head(&mut get));
+        tx_done.send(()).unwrap();
+    });
+
+    tokio::run(future::lazy(move || {
+        tokio::spawn(server.map_err(|_| ()));
+        rx_done.then(move |_| {
+            server_handle.shutdown(ShutdownType::Immediate);
+            Ok(())
+        })
+    }));
+
+    let heads = heads.lock().unwrap();
+    assert_eq!(heads.len(), 2);
+    assert!(heads[0].starts_with("HTTP/1.0 200 OK\r\n"));
+    assert!(heads[0].contains("Content-Type: application/x-rtsp-tunnelled\r\n"));
+    assert!(heads[1].starts_with("RTSP/2.0 200 OK\r\n"));
+    assert!(heads[1].contains("CSeq: 1\r\n"));
+}